@@ -0,0 +1,35 @@
+#![no_main]
+#![no_std]
+
+extern crate panic_semihosting;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::dbg;
+
+use lpc55_hal as hal;
+
+#[entry]
+fn main() -> ! {
+    let hal = hal::new();
+
+    let mut syscon = hal.syscon;
+    let mut crc = hal.crc.enabled(&mut syscon);
+
+    let data = b"123456789";
+
+    let mut crc32 = crc.crc32();
+    crc32.update(&data[..4]);
+    crc32.update(&data[4..]);
+    // expected: 0xCBF43926
+    dbg!(crc32.sum());
+
+    // expected: 0x29B1
+    dbg!(crc.crc16_ccitt().checksum(data));
+
+    // expected: 0xBB3D
+    dbg!(crc.crc16_ibm().checksum(data));
+
+    loop {
+        asm::wfi();
+    }
+}
@@ -39,9 +39,9 @@ fn main() -> ! {
     heprintln!("looping 1 Hz").unwrap();
     let mut c = 0;
     loop {
-        cdriver.start(1_000_000.microseconds());
+        cdriver.start(1_000_000.micros());
         dbg!(c * 1_000_000);
-        dbg!(cdriver.elapsed().0);
+        dbg!(cdriver.elapsed().ticks());
         c += 1;
         block!(cdriver.wait()).unwrap(); // blocks for 1 second
     }
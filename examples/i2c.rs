@@ -1,7 +1,6 @@
 #![no_main]
 #![no_std]
 
-use core::convert::TryFrom;
 // extern crate panic_semihosting;
 extern crate panic_halt;
 use cortex_m_rt::entry;
@@ -15,7 +14,6 @@ use hal::{
         Pins,
         I2cMaster,
     },
-    time::Hertz,
 };
 
 use ssd1306;
@@ -49,7 +47,7 @@ fn main() -> ! {
     let sda = pins.pio1_21.into_i2c4_sda_pin(&mut iocon);
 
     // let i2c = I2cMaster::new(i2c, (scl, sda), 400.khz());
-    let i2c = I2cMaster::new(i2c, (scl, sda), Hertz::try_from(1_u32.MHz()).unwrap());
+    let i2c = I2cMaster::new(i2c, (scl, sda), 1.MHz());
 
     // OLED
     let mut display: TerminalMode<_> = ssd1306::Builder::new()
@@ -50,11 +50,11 @@ fn main() -> ! {
 
     loop {
 
-        timer.start(1_000_000.microseconds());
+        timer.start(1_000_000.micros());
 
         delay_cycles(10_000_000);
 
-        let us = timer.elapsed().0;
+        let us = timer.elapsed().ticks();
         timer.cancel().ok();
 
         heprintln!("{} MHz", 10_000_000 / us).ok();
@@ -87,7 +87,7 @@ fn main() -> ! {
 
     loop {
 
-        delay_timer.start(5_000.microseconds());
+        delay_timer.start(5_000.micros());
         block!(delay_timer.wait()).unwrap();
 
         for i in 0..3 {
@@ -4,7 +4,7 @@
 // extern crate panic_semihosting;
 extern crate panic_halt;
 use cortex_m_rt::entry;
-use core::{convert::TryFrom, fmt::Write};
+use core::fmt::Write;
 
 use lpc55_hal as hal;
 
@@ -19,7 +19,7 @@ use hal::{
             NoCs,
         },
     },
-    time::{Hertz, RateExtensions},
+    time::RateExtensions,
     traits::wg::spi::{
         Mode,
         Phase,
@@ -74,7 +74,7 @@ fn main() -> ! {
         phase: Phase::CaptureOnFirstTransition,
     };
 
-    let spi = SpiMaster::new(spi, spi_pins, Hertz::try_from(100_u32.kHz()).unwrap(), spi_mode);
+    let spi = SpiMaster::new(spi, spi_pins, 100.kHz(), spi_mode);
 
     let dc = pins.pio1_5.into_gpio_pin(&mut iocon, &mut gpio).into_output_high();
 
@@ -102,7 +102,7 @@ fn main() -> ! {
         }
     }
 
-    delay_timer.start(300_000.microseconds());
+    delay_timer.start(300_000.micros());
     block!(delay_timer.wait()).unwrap();
 
     loop {
@@ -142,8 +142,8 @@ for PIN, alt, FUNCTION in data:
                 assert KINDS[0] == "SCK", KINDS
                 PERIPHERALS = ["USART"]
 
-                PERIPHERALS = ["USART", "SPI"]
-                KINDS = ["SCLK", "SCK"]
+                PERIPHERALS = ["USART", "SPI", "I2S"]
+                KINDS = ["SCLK", "SCK", "SCK"]
         else:
             PERIPHERALS = ["USART", "I2C", "SPI"]
             if l == 4:
@@ -0,0 +1,179 @@
+//! One-call initialization of the HAL.
+//!
+//! ```ignore
+//! let mut board = hal::init(hal::board::Config::default().system_frequency(96.MHz()));
+//!
+//! let mut red = board.pins.pio1_6
+//!     .into_gpio_pin(&mut board.iocon, &mut board.gpio)
+//!     .into_output(Level::High);
+//! ```
+
+use crate::{
+    drivers::{
+        clocks::Clocks,
+        ClockRequirements,
+        Pins,
+    },
+    peripherals::{
+        adc::Adc,
+        anactrl::Anactrl,
+        crc::Crc,
+        ctimer::Ctimers,
+        dma::Dma,
+        flash::Flash,
+        flexcomm::Flexcomm,
+        gint::Gint,
+        gpio::Gpio,
+        inputmux::InputMux,
+        iocon::Iocon,
+        ostimer::OsTimer,
+        pint::{Pint, SecPint},
+        plu::Plu,
+        pmc::Pmc,
+        rng::Rng,
+        rtc::Rtc,
+        secgpio::SecGpio,
+        syscon::{FlashAcceleration, Syscon},
+        utick::Utick,
+    },
+    raw,
+    time::Megahertz,
+    typestates::init_state::Enabled,
+    Peripherals,
+};
+#[cfg(feature = "_casper")]
+use crate::peripherals::casper::Casper;
+#[cfg(feature = "_hashcrypt")]
+use crate::peripherals::hashcrypt::Hashcrypt;
+#[cfg(feature = "_pfr")]
+use crate::peripherals::pfr::Pfr;
+#[cfg(feature = "_prince")]
+use crate::peripherals::prince::Prince;
+#[cfg(feature = "_usb")]
+use crate::peripherals::usbfs::Usbfs;
+#[cfg(feature = "_usb")]
+use crate::peripherals::usbhs::Usbhs;
+
+/// Clock and power profile applied by `hal::init`
+#[derive(Default)]
+pub struct Config {
+    pub clocks: ClockRequirements,
+    /// If not set, chosen to match the system frequency
+    pub flash_acceleration: Option<FlashAcceleration>,
+}
+
+impl Config {
+    pub fn system_frequency(mut self, freq: Megahertz) -> Self {
+        self.clocks = self.clocks.system_frequency(freq);
+        self
+    }
+
+    pub fn flash_acceleration(mut self, flash_acceleration: FlashAcceleration) -> Self {
+        self.flash_acceleration = Some(flash_acceleration);
+        self
+    }
+}
+
+/// Ready-to-use peripherals, as returned by `hal::init`.
+///
+/// Same as `Peripherals`, with the clocks configured, GPIO and IOCON enabled,
+/// and the pins taken.
+#[allow(non_snake_case)]
+pub struct Board {
+    pub clocks: Clocks,
+    pub pins: Pins,
+
+    pub adc: Adc,
+    pub anactrl: Anactrl,
+    #[cfg(feature = "_casper")]
+    pub casper: Casper,
+    pub crc: Crc,
+    pub ctimer: Ctimers,
+    pub dma: Dma,
+    pub flash: Flash,
+    pub flexcomm: Flexcomm,
+    pub gint: Gint,
+    pub gpio: Gpio<Enabled>,
+    #[cfg(feature = "_hashcrypt")]
+    pub hashcrypt: Hashcrypt,
+    pub inputmux: InputMux,
+    pub iocon: Iocon<Enabled>,
+    pub ostimer: OsTimer,
+    pub pint: Pint,
+    pub plu: Plu,
+    #[cfg(feature = "_pfr")]
+    pub pfr: Pfr,
+    pub pmc: Pmc,
+    #[cfg(feature = "_prince")]
+    pub prince: Prince,
+    pub rng: Rng,
+    pub rtc: Rtc,
+    pub secgpio: SecGpio,
+    pub secpint: SecPint,
+    pub syscon: Syscon,
+    #[cfg(feature = "_usb")]
+    pub usbfs: Usbfs,
+    #[cfg(feature = "_usb")]
+    pub usbhs: Usbhs,
+    pub utick: Utick,
+
+    pub FLASH_CMPA: raw::FLASH_CMPA,
+    pub FLASH_CFPA0: raw::FLASH_CFPA0,
+    pub SCT0: raw::SCT0,
+    pub SAU: raw::SAU,
+    pub AHB_SECURE_CTRL: raw::AHB_SECURE_CTRL,
+    pub CPUID: raw::CPUID,
+    pub DCB: raw::DCB,
+    pub DWT: raw::DWT,
+    pub MPU: raw::MPU,
+    pub NVIC: raw::NVIC,
+    pub SCB: raw::SCB,
+    #[cfg(not(feature = "rtic-peripherals"))]
+    pub SYST: raw::SYST,
+}
+
+/// Take the peripherals and pins, and apply `config`.
+///
+/// Panics if the peripherals or pins were already taken, or the clock
+/// requirements can't be met.
+pub fn init(config: Config) -> Board {
+    let Peripherals {
+        adc, mut anactrl, crc, ctimer, dma, flash, flexcomm, gint, gpio,
+        inputmux, iocon, ostimer, pint, plu, mut pmc, rng, rtc, secgpio, secpint, mut syscon, utick,
+        #[cfg(feature = "_casper")] casper,
+        #[cfg(feature = "_hashcrypt")] hashcrypt,
+        #[cfg(feature = "_pfr")] pfr,
+        #[cfg(feature = "_prince")] prince,
+        #[cfg(feature = "_usb")] usbfs,
+        #[cfg(feature = "_usb")] usbhs,
+        FLASH_CMPA, FLASH_CFPA0, SCT0, SAU, AHB_SECURE_CTRL, CPUID, DCB, DWT, MPU, NVIC, SCB,
+        #[cfg(not(feature = "rtic-peripherals"))]
+        SYST,
+    } = crate::take().expect("peripherals already taken");
+    let pins = Pins::take().expect("pins already taken");
+
+    let clocks = config.clocks
+        .configure(&mut anactrl, &mut pmc, &mut syscon)
+        .expect("clock requirements not feasible");
+    let system_frequency: Megahertz = clocks.system_frequency.convert();
+    syscon.set_flash_acceleration(config.flash_acceleration
+        .unwrap_or_else(|| FlashAcceleration::for_frequency(system_frequency)));
+
+    let gpio = gpio.enabled(&mut syscon);
+    let iocon = iocon.enabled(&mut syscon);
+
+    Board {
+        clocks, pins,
+        adc, anactrl, crc, ctimer, dma, flash, flexcomm, gint, gpio,
+        inputmux, iocon, ostimer, pint, plu, pmc, rng, rtc, secgpio, secpint, syscon, utick,
+        #[cfg(feature = "_casper")] casper,
+        #[cfg(feature = "_hashcrypt")] hashcrypt,
+        #[cfg(feature = "_pfr")] pfr,
+        #[cfg(feature = "_prince")] prince,
+        #[cfg(feature = "_usb")] usbfs,
+        #[cfg(feature = "_usb")] usbhs,
+        FLASH_CMPA, FLASH_CFPA0, SCT0, SAU, AHB_SECURE_CTRL, CPUID, DCB, DWT, MPU, NVIC, SCB,
+        #[cfg(not(feature = "rtic-peripherals"))]
+        SYST,
+    }
+}
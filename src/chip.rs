@@ -0,0 +1,74 @@
+//! Properties of the chip selected via Cargo feature (`lpc55s69`, `lpc55s28`, `lpc55s16`, ...).
+//!
+//! Peripherals the chip lacks are not part of `Peripherals`, and their drivers
+//! don't exist. Note that the register definitions are those of the LPC55S6x
+//! for all chips.
+
+macro_rules! chips {
+    ($($feature:literal => $name:literal {
+        flash: $flash:expr,
+        user_flash_end: $user_flash_end:expr,
+        ram: $ram:expr,
+        sram_end: $sram_end:expr,
+        cores: $cores:expr,
+    })*) => {
+        const SELECTED: usize = 0 $(+ cfg!(feature = $feature) as usize)*;
+        const _: () = assert!(SELECTED == 1, "enable exactly one chip feature, e.g. `lpc55s69`");
+
+        $(
+            #[cfg(feature = $feature)]
+            mod selected {
+                pub const NAME: &str = $name;
+                pub const FLASH_SIZE: usize = $flash;
+                pub const USER_FLASH_END: usize = $user_flash_end;
+                pub const RAM_SIZE: usize = $ram;
+                pub const SRAM_END: usize = $sram_end;
+                pub const CORES: usize = $cores;
+            }
+        )*
+    }
+}
+
+chips! {
+    "lpc55s69" => "LPC55S69" { flash: 640 * 1024, user_flash_end: 0x9_DE00, ram: 320 * 1024, sram_end: 0x2004_4000, cores: 2, }
+    "lpc55s66" => "LPC55S66" { flash: 256 * 1024, user_flash_end: 0x4_0000, ram: 144 * 1024, sram_end: 0x2001_0000, cores: 2, }
+    "lpc55s28" => "LPC55S28" { flash: 512 * 1024, user_flash_end: 0x8_0000, ram: 256 * 1024, sram_end: 0x2003_0000, cores: 1, }
+    "lpc55s26" => "LPC55S26" { flash: 256 * 1024, user_flash_end: 0x4_0000, ram: 160 * 1024, sram_end: 0x2001_0000, cores: 1, }
+    "lpc5528" => "LPC5528" { flash: 512 * 1024, user_flash_end: 0x8_0000, ram: 256 * 1024, sram_end: 0x2003_0000, cores: 1, }
+    "lpc5526" => "LPC5526" { flash: 256 * 1024, user_flash_end: 0x4_0000, ram: 160 * 1024, sram_end: 0x2001_0000, cores: 1, }
+    "lpc55s16" => "LPC55S16" { flash: 256 * 1024, user_flash_end: 0x3_DC00, ram: 96 * 1024, sram_end: 0x2001_0000, cores: 1, }
+    "lpc55s14" => "LPC55S14" { flash: 128 * 1024, user_flash_end: 0x2_0000, ram: 96 * 1024, sram_end: 0x2001_0000, cores: 1, }
+    "lpc55s06" => "LPC55S06" { flash: 256 * 1024, user_flash_end: 0x3_DC00, ram: 96 * 1024, sram_end: 0x2001_0000, cores: 1, }
+    "lpc55s04" => "LPC55S04" { flash: 128 * 1024, user_flash_end: 0x2_0000, ram: 96 * 1024, sram_end: 0x2001_0000, cores: 1, }
+}
+
+/// Part number, e.g. "LPC55S69"
+pub const NAME: &str = selected::NAME;
+/// Total flash, in bytes
+pub const FLASH_SIZE: usize = selected::FLASH_SIZE;
+/// End of the flash available to applications, where the protected flash region (PFR) or the flash ends
+pub const USER_FLASH_END: usize = selected::USER_FLASH_END;
+/// Total SRAM, in bytes
+pub const RAM_SIZE: usize = selected::RAM_SIZE;
+/// End of the SRAM contiguous from 0x2000_0000, so without SRAMX and USB SRAM
+pub const SRAM_END: usize = selected::SRAM_END;
+/// Number of Cortex-M33 cores
+pub const CORES: usize = selected::CORES;
+
+/// Cryptographic accelerators and security features of a part
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SecurityFeatures {
+    pub casper: bool,
+    pub hashcrypt: bool,
+    pub prince: bool,
+    pub puf: bool,
+}
+
+/// Security features of the part
+pub const SECURITY: SecurityFeatures = SecurityFeatures {
+    casper: cfg!(feature = "_casper"),
+    hashcrypt: cfg!(feature = "_hashcrypt"),
+    prince: cfg!(feature = "_prince"),
+    puf: cfg!(feature = "_puf"),
+};
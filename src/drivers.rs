@@ -2,19 +2,47 @@
 //!
 //! Typically, these drivers take ownership of one or more HAL peripherals,
 //! and expose functionality defined in a separate trait.
+//!
+//! Dropping the Flexcomm drivers masks the interrupts and gates the clock of
+//! their peripheral, and dropping `Timer`, `Pwm` or `Counter` stops and gates
+//! their CTIMER; to keep it running, `release` the driver instead (or
+//! `core::mem::forget` it). Dropping a DMA `Transfer` before it is done aborts
+//! it, disabling its channel. The other way round, the unsafe `steal`
+//! constructors wrap a peripheral which was configured by `new` before,
+//! without touching the hardware: fault handlers and panic paths use them
+//! when the driver owned by the application is out of reach.
+//!
+//! `raw` and `raw_mut` give access to the peripheral owned by a driver, for
+//! registers it does not model. Changes made behind the driver's back may
+//! invalidate its assumptions.
 
 pub mod prelude {
     pub use super::i2c::prelude::*;
     pub use super::spi::prelude::*;
+    #[cfg(all(feature = "usb", feature = "_usb"))]
     pub use super::usbd::prelude::*;
 }
 
+#[cfg(all(feature = "crypto", feature = "_hashcrypt"))]
 pub mod aes;
+#[cfg(all(feature = "crypto", feature = "_hashcrypt"))]
 pub use aes::{Aes, Key as AesKey};
 
+#[cfg(feature = "async")]
+pub mod asynch;
+
 pub mod clocks;
 pub use clocks::ClockRequirements;
 
+pub mod erased;
+pub use erased::{ErasedI2c, ErasedSerial, ErasedSpi};
+
+pub mod crc;
+pub use crc::Checksum;
+
+pub mod counter;
+pub use counter::Counter;
+
 pub mod pins;
 pub use pins::{
     Pin,
@@ -30,6 +58,9 @@ pub use gint::GroupInterrupt;
 pub mod i2c;
 pub use i2c::I2cMaster;
 
+pub mod parallel;
+pub use parallel::ParallelBus;
+
 pub mod pwm;
 pub use pwm::Pwm;
 
@@ -41,10 +72,14 @@ pub use serial::Serial;
 
 pub mod rng;
 
+#[cfg(all(feature = "crypto", feature = "_hashcrypt"))]
 pub mod sha;
+#[cfg(all(feature = "crypto", feature = "_hashcrypt"))]
 pub use sha::{Sha1, Sha256};
 
+#[cfg(all(feature = "usb", feature = "_usb"))]
 pub mod usbd;
+#[cfg(all(feature = "usb", feature = "_usb"))]
 pub use usbd::UsbBus;
 
 pub mod timer;
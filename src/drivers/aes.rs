@@ -32,6 +32,7 @@ pub enum Key<Size: KeySize> {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Mode {
     Encrypt,
     Decrypt,
@@ -0,0 +1,281 @@
+//! Waker plumbing for the interrupt-driven futures of the drivers (feature `async`).
+//!
+//! The futures enable the interrupt sources they wait for, and register their waker.
+//! The interrupt handlers must call into this module, which masks the sources again
+//! and wakes the futures. This is done by binding the handlers of this module:
+//!
+//! ```ignore
+//! hal::bind_interrupts!(struct Irqs {
+//!     FLEXCOMM2 => hal::drivers::asynch::FlexcommInterruptHandler<2>;
+//! });
+//!
+//! hal::interrupt::unmask::<FlexcommInterruptHandler<2>>(Irqs);
+//! ```
+
+use core::cell::RefCell;
+use core::future::poll_fn;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Poll, Waker};
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::{
+    interrupt::Handler,
+    peripherals::flexcomm::dispatch,
+    raw::{self, Interrupt},
+};
+
+/// Storage for the waker of one pending future
+pub struct WakerSlot {
+    waker: Mutex<RefCell<Option<Waker>>>,
+}
+
+impl WakerSlot {
+    pub const fn new() -> Self {
+        Self { waker: Mutex::new(RefCell::new(None)) }
+    }
+
+    pub fn register(&self, waker: &Waker) {
+        interrupt::free(|cs| {
+            let mut slot = self.waker.borrow(cs).borrow_mut();
+            match slot.as_ref() {
+                Some(registered) if registered.will_wake(waker) => {}
+                _ => *slot = Some(waker.clone()),
+            }
+        })
+    }
+
+    pub fn wake(&self) {
+        if let Some(waker) = interrupt::free(|cs| self.waker.borrow(cs).borrow_mut().take()) {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for WakerSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Storage for the wakers of up to `N` tasks waiting for the same thing, all
+/// woken together
+pub(crate) struct WakerList<const N: usize> {
+    wakers: Mutex<RefCell<[Option<Waker>; N]>>,
+}
+
+impl<const N: usize> WakerList<N> {
+    pub const fn new() -> Self {
+        Self { wakers: Mutex::new(RefCell::new([const { None }; N])) }
+    }
+
+    /// Add `waker`, unless it is in the list already.
+    ///
+    /// If the list is full, the wakers in it are woken to make room, their
+    /// tasks registering again when polled.
+    pub fn register(&self, waker: &Waker) {
+        let full = interrupt::free(|cs| {
+            let mut wakers = self.wakers.borrow(cs).borrow_mut();
+            if wakers.iter().flatten().any(|registered| registered.will_wake(waker)) {
+                return None;
+            }
+            if let Some(free) = wakers.iter_mut().find(|slot| slot.is_none()) {
+                *free = Some(waker.clone());
+                return None;
+            }
+            let full = core::mem::replace(&mut *wakers, [const { None }; N]);
+            wakers[0] = Some(waker.clone());
+            Some(full)
+        });
+        if let Some(mut full) = full {
+            for waker in full.iter_mut().filter_map(Option::take) {
+                waker.wake();
+            }
+        }
+    }
+
+    pub fn wake_all(&self) {
+        let mut wakers = interrupt::free(|cs| {
+            core::mem::replace(&mut *self.wakers.borrow(cs).borrow_mut(), [const { None }; N])
+        });
+        for waker in wakers.iter_mut().filter_map(Option::take) {
+            waker.wake();
+        }
+    }
+}
+
+pub(crate) static FLEXCOMM_WAKERS: [WakerSlot; 9] = [const { WakerSlot::new() }; 9];
+/// Separate slots for the receive side of a split USART
+pub(crate) static FLEXCOMM_RX_WAKERS: [WakerSlot; 9] = [const { WakerSlot::new() }; 9];
+pub(crate) static CTIMER_WAKERS: [WakerSlot; 5] = [const { WakerSlot::new() }; 5];
+/// Set by the CTIMER interrupt handler for a match 0 it cleared in IR
+pub(crate) static CTIMER_MATCHED: [AtomicBool; 5] = [const { AtomicBool::new(false) }; 5];
+pub(crate) static ADC_WAKER: WakerSlot = WakerSlot::new();
+pub(crate) static PINT_WAKERS: [WakerSlot; 8] = [const { WakerSlot::new() }; 8];
+
+/// Resolves once `ready` returns true.
+///
+/// Until then, `enable_interrupt` is called after registering the waker on every poll,
+/// the interrupt handler disabling the interrupt source again before waking.
+pub(crate) async fn wait_for(
+    slot: &WakerSlot,
+    mut ready: impl FnMut() -> bool,
+    mut enable_interrupt: impl FnMut(),
+) {
+    poll_fn(|cx| {
+        if ready() {
+            return Poll::Ready(());
+        }
+        slot.register(cx.waker());
+        enable_interrupt();
+        // the condition may have become true before the interrupt was enabled
+        if ready() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }).await
+}
+
+pub(crate) use crate::peripherals::flexcomm::dispatch::index_of as flexcomm_index;
+
+/// Index of the CTIMER, given the address of its register block
+pub(crate) fn ctimer_index(block: *const raw::ctimer0::RegisterBlock) -> usize {
+    [
+        raw::CTIMER0::ptr(),
+        raw::CTIMER1::ptr(),
+        raw::CTIMER2::ptr(),
+        raw::CTIMER3::ptr(),
+        raw::CTIMER4::ptr(),
+    ].iter().position(|&ptr| core::ptr::eq(ptr, block)).unwrap()
+}
+
+/// To be called from the FLEXCOMM`index` interrupt handler, or registered with
+/// `flexcomm::dispatch` to share the interrupt with other drivers
+pub fn on_flexcomm_interrupt(index: usize) {
+    let base = dispatch::base(index);
+    let flexcomm = unsafe { &*(base as *const raw::flexcomm0::RegisterBlock) };
+    let persel = flexcomm.pselid.read().persel();
+    if persel.is_i2c() {
+        let i2c = unsafe { &*(base as *const raw::i2c0::RegisterBlock) };
+        i2c.intenclr.write(|w| w
+            .mstpendingclr().set_bit()
+            .mstarblossclr().set_bit()
+            .mstststperrclr().set_bit()
+        );
+    } else if persel.is_spi() {
+        let spi = unsafe { &*(base as *const raw::spi0::RegisterBlock) };
+        spi.fifointenclr.write(|w| w
+            .txlvl().set_bit()
+            .rxlvl().set_bit()
+        );
+    } else if persel.is_usart() {
+        let usart = unsafe { &*(base as *const raw::usart0::RegisterBlock) };
+        usart.fifointenclr.write(|w| w
+            .txlvl().set_bit()
+            .rxlvl().set_bit()
+        );
+    }
+    FLEXCOMM_WAKERS[index].wake();
+    FLEXCOMM_RX_WAKERS[index].wake();
+}
+
+/// To be called from the CTIMER`index` interrupt handler
+pub fn on_ctimer_interrupt(index: usize) {
+    let ptr = [
+        raw::CTIMER0::ptr(),
+        raw::CTIMER1::ptr(),
+        raw::CTIMER2::ptr(),
+        raw::CTIMER3::ptr(),
+        raw::CTIMER4::ptr(),
+    ][index];
+    let ctimer = unsafe { &*ptr };
+    ctimer.mcr.modify(|_, w| w.mr0i().clear_bit());
+    // the latched flag keeps the interrupt pending, so it is recorded and cleared
+    if ctimer.ir.read().mr0int().bit_is_set() {
+        CTIMER_MATCHED[index].store(true, Ordering::Release);
+        ctimer.ir.write(|w| w.mr0int().set_bit());
+    }
+    CTIMER_WAKERS[index].wake();
+}
+
+/// To be called from the ADC0 interrupt handler
+pub fn on_adc_interrupt() {
+    let adc = unsafe { &*raw::ADC0::ptr() };
+    adc.ie.modify(|_, w| w.fwmie0().clear_bit());
+    ADC_WAKER.wake();
+}
+
+/// To be called from the PIN_INT`slot` interrupt handler
+pub fn on_pint_interrupt(slot: usize) {
+    let pint = unsafe { &*raw::PINT::ptr() };
+    let bit = 1 << slot;
+    // in level mode, CIENF would change the active level instead
+    let edge = pint.isel.read().pmode().bits() & bit == 0;
+    pint.cienr.write(|w| unsafe { w.cenrl().bits(bit) });
+    if edge {
+        pint.cienf.write(|w| unsafe { w.cenaf().bits(bit) });
+    }
+    PINT_WAKERS[slot].wake();
+}
+
+/// Interrupt handler for Flexcomm interface `N` (0-8)
+pub struct FlexcommInterruptHandler<const N: usize>;
+
+impl<const N: usize> Handler for FlexcommInterruptHandler<N> {
+    const INTERRUPT: Interrupt = dispatch::interrupt(N);
+
+    unsafe fn on_interrupt() {
+        on_flexcomm_interrupt(N);
+    }
+}
+
+/// Interrupt handler for CTIMER `N` (0-4)
+pub struct CtimerInterruptHandler<const N: usize>;
+
+impl<const N: usize> Handler for CtimerInterruptHandler<N> {
+    const INTERRUPT: Interrupt = match N {
+        0 => Interrupt::CTIMER0,
+        1 => Interrupt::CTIMER1,
+        2 => Interrupt::CTIMER2,
+        3 => Interrupt::CTIMER3,
+        4 => Interrupt::CTIMER4,
+        _ => panic!("there are only CTIMERs 0-4"),
+    };
+
+    unsafe fn on_interrupt() {
+        on_ctimer_interrupt(N);
+    }
+}
+
+/// Interrupt handler for the ADC
+pub struct AdcInterruptHandler;
+
+impl Handler for AdcInterruptHandler {
+    const INTERRUPT: Interrupt = Interrupt::ADC0;
+
+    unsafe fn on_interrupt() {
+        on_adc_interrupt();
+    }
+}
+
+/// Interrupt handler for pin interrupt slot `N` (0-7)
+pub struct PintInterruptHandler<const N: usize>;
+
+impl<const N: usize> Handler for PintInterruptHandler<N> {
+    const INTERRUPT: Interrupt = match N {
+        0 => Interrupt::PIN_INT0,
+        1 => Interrupt::PIN_INT1,
+        2 => Interrupt::PIN_INT2,
+        3 => Interrupt::PIN_INT3,
+        4 => Interrupt::PIN_INT4,
+        5 => Interrupt::PIN_INT5,
+        6 => Interrupt::PIN_INT6,
+        7 => Interrupt::PIN_INT7,
+        _ => panic!("there are only pin interrupts 0-7"),
+    };
+
+    unsafe fn on_interrupt() {
+        on_pint_interrupt(N);
+    }
+}
@@ -5,8 +5,7 @@
 ///!
 ///! It is currently used to prepare for using the USBFSD and
 ///! Flexcomm peripherals.
-use core::{cmp::min, convert::TryFrom};
-use embedded_time::rate::Extensions;
+use core::cmp::min;
 
 use crate::typestates::{
     main_clock::MainClock,
@@ -36,17 +35,20 @@ use crate::{
 // TODO: It seems even Fro12Mhz works, but e.g.
 // PLL at 13.mhz does not - might also be a bug
 // in PLL code ofc
-const MIN_USBFS_FREQ: Megahertz = Megahertz(24);
-const MIN_USBHS_FREQ: Megahertz = Megahertz(96);
-const DEFAULT_FREQ: Megahertz = Megahertz(12);
+const MIN_USBFS_FREQ: Megahertz = Megahertz::from_raw(24);
+const MIN_USBHS_FREQ: Megahertz = Megahertz::from_raw(96);
+const MIN_TOUCH_FREQ: Megahertz = Megahertz::from_raw(96);
+const DEFAULT_FREQ: Megahertz = Megahertz::from_raw(12);
 
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ClockRequirements {
     pub system_frequency: Option<Megahertz>,
     pub custom_pll: Option<Pll>,
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Clocks {
     pub(crate) main_clock: MainClock,
     pub(crate) system_frequency: Hertz,
@@ -58,7 +60,7 @@ impl Clocks {
     }
 
     pub fn support_usbfs_token(&self) -> Option<ClocksSupportUsbfsToken> {
-        let fast_enough = self.system_frequency >= Hertz::from(MIN_USBFS_FREQ);
+        let fast_enough = self.system_frequency >= MIN_USBFS_FREQ;
         let can_latch_sof = self.main_clock == MainClock::Fro96Mhz;
 
         if fast_enough && can_latch_sof {
@@ -69,7 +71,7 @@ impl Clocks {
     }
 
     pub fn support_usbhs_token(&self) -> Option<ClocksSupportUsbhsToken> {
-        let fast_enough = self.system_frequency >= Hertz::from(MIN_USBHS_FREQ);
+        let fast_enough = self.system_frequency >= MIN_USBHS_FREQ;
         if fast_enough {
             Some(ClocksSupportUsbhsToken{__: ()})
         } else {
@@ -86,7 +88,7 @@ impl Clocks {
     }
 
     pub fn support_touch_token(&self) -> Option<ClocksSupportTouchToken> {
-        if self.system_frequency.0 >= 96 {
+        if self.system_frequency >= MIN_TOUCH_FREQ {
             Some(ClocksSupportTouchToken{__: ()})
         } else {
             None
@@ -108,7 +110,8 @@ impl Clocks {
 /// Bypassing the input and/or output divider saves power."
 
 // #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Pll {
     n: u8,
     m: u16,
@@ -136,12 +139,28 @@ impl Pll {
 static mut CONFIGURED: bool = false;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum ClocksError {
     // TODO: Add "cause"
     AlreadyConfigured,
     NotFeasible,
+    /// The `ClockConfig` was not applied by `pre_init`
+    NotApplied,
+}
+
+impl core::fmt::Display for ClocksError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ClocksError::AlreadyConfigured => "clocks already configured",
+            ClocksError::NotFeasible => "clock requirements not feasible",
+            ClocksError::NotApplied => "clock configuration not applied",
+        })
+    }
 }
 
+impl core::error::Error for ClocksError {}
+
 pub type Result<T> = core::result::Result<T, ClocksError>;
 
 // TODO:
@@ -150,43 +169,11 @@ pub type Result<T> = core::result::Result<T, ClocksError>;
 
 impl ClockRequirements {
 
-    pub fn system_frequency<Freq>(mut self, freq: Freq) -> Self where Freq: Into<Megahertz> {
-        self.system_frequency = Some(freq.into());
+    pub fn system_frequency(mut self, freq: Megahertz) -> Self {
+        self.system_frequency = Some(freq);
         self
     }
 
-    // generated via `scripts/generate-pll-settings.py`,
-    // massaged a bit by hand
-    fn get_pll(freq: u32) -> Pll {
-        debug_assert!(freq >= 5);
-        debug_assert!(freq <= 150);
-        // let ns: [u32; 9] = [1, 2, 3, 4, 6, 8, 12, 16, 24];
-        // let ns: [u32; 2] = [1, 2];
-        // let ps: [u32; 11] = [2, 3, 4, 6, 8, 9, 12, 16, 18, 24, 30];
-        // let ps: [u32; 5] = [6, 8, 12, 16, 24];
-
-        // for n in ns.iter() { for p in ps.iter() { for m in 3..=97 {
-        for n in 1..=6 { for p in 1..=30 { for m in 1..=255 {
-            // if 2 * freq * (*n) * (*p) == 12 * m {
-            if 2 * freq * n * p == 12 * m {
-                // UM 4.6.6.3.2
-                let selp = (m >> 2) + 1; // <= 31
-                let seli = 2 * (m >> 2) + 3; // <= 63
-                return Pll {
-                    // n: *n as u8,
-                    n: n as u8,
-                    m: m as u16,
-                    // p: *p as u8,
-                    p: p as u8,
-                    selp: selp as u8,
-                    seli: seli as u8,
-                }
-            }
-        }}}
-
-        unreachable!();
-    }
-
     fn configure_pll0(pll: Pll, pmc: &mut Pmc, syscon: &mut Syscon) {
 
         pmc.raw.pdruncfg0.modify(|_, w| w
@@ -241,62 +228,31 @@ impl ClockRequirements {
     }
 
     fn get_clock_source_and_div_for_freq(freq: Megahertz, pmc: &mut Pmc, syscon: &mut Syscon) -> (MainClock, u8) {
-        let (main_clock, sys_divider) = match freq {
-            freq if freq <= 12_u32.MHz() && 12 % freq.0 == 0 => {
-                (MainClock::Fro12Mhz, 12 / freq.0)
-            },
-            freq if freq <= 96_u32.MHz() && 96 % freq.0 == 0 => {
-                (MainClock::Fro96Mhz, 96 / freq.0)
-            },
-            // For reference: how to get 150 MHz using 16Mhz external crystal
-            // freq if freq == 150.mhz() && 150 % freq.0 == 0 => {
-            //     // Use crystal as input to PLL0
-            //     // Power on 32M crystal for stable pll operation
-            //     pmc.raw.pdruncfg0.modify(|_,w| w.pden_xtal32m().poweredon());
-            //     pmc.raw.pdruncfg0.modify(|_,w| w.pden_ldoxo32m().poweredon());
-
-            //     // Connect external 32M as clk input
-            //     syscon.raw.clock_ctrl.modify(|_,w| w.clkin_ena().set_bit());
-            //     anactrl.raw.xo32m_ctrl.modify(|_,w| w.enable_system_clk_out().set_bit());
-
-            //     // select clkin for pll0
-            //     syscon.raw.pll0clksel.write(|w| unsafe{ w.bits(1) });
-
-            //     // 150 MHz settings
-            //     let pll = Pll {
-            //         n: 8,
-            //         m: 150,
-            //         p: 1,
-            //         selp: 31,
-            //         seli: 53,
-            //     };
-
-            //     Self::configure_pll0(pll, pmc, syscon);
-
-            //     (MainClock::Pll0, 1)
-            // }
-            // Get 150 MHz using internal FRO12
-            freq if freq == 150_u32.MHz() => {
-                syscon.raw.pll0clksel.write(|w| { w.sel().enum_0x0() /* FRO 12 MHz input */ });
-                Self::configure_pll0(Pll {
-                    n: 8,
-                    m: 200,
-                    p: 1,
-                    selp: 31,
-                    seli: 53,
-                }, pmc, syscon);
-                (MainClock::Pll0, 1)
-            }
+        let plan = Plan::for_frequency(freq.to_MHz());
+        Self::apply_plan(&plan, pmc, syscon);
+        (plan.main_clock, plan.sys_divider)
+    }
 
-            _ => {
-                let pll = Self::get_pll(freq.0);
-                syscon.raw.pll0clksel.write(|w| { w.sel().enum_0x0() /* FRO 12 MHz input */ });
-                Self::configure_pll0(pll, pmc, syscon);
-                (MainClock::Pll0, 1)
-            }
-        };
-        debug_assert!(sys_divider < 256);
-        (main_clock, sys_divider as u8)
+    fn apply_plan(plan: &Plan, pmc: &mut Pmc, syscon: &mut Syscon) {
+        if let Some(pll) = plan.pll {
+            syscon.raw.pll0clksel.write(|w| { w.sel().enum_0x0() /* FRO 12 MHz input */ });
+            Self::configure_pll0(pll, pmc, syscon);
+        }
+    }
+
+    fn enable_fros(anactrl: &mut Anactrl, syscon: &mut Syscon) {
+        // turn on FRO192M: clear bit 5, according to `fsl_power.h` from the SDK
+        // unsafe { pmc.raw.pdruncfgclr0.write(|w| w.bits(1u32 << 5)) };
+        // but it's hidden in UM, so let's assume this is always cleared
+
+        // turn on 1mhz, 12mhz and 96mhz clocks
+        anactrl.raw.fro192m_ctrl.modify(|_, w| w.ena_96mhzclk().enable());
+        anactrl.raw.fro192m_ctrl.modify(|_, w| w.ena_12mhzclk().enable());
+
+        syscon.raw.clock_ctrl.modify(|_, w| w
+            .fro1mhz_clk_ena().enable()
+            .fro1mhz_utick_ena().enable()
+        );
     }
 
     fn set_new_clock_source(freq: Megahertz, main_clock: MainClock, sys_divider: u8, syscon: &mut Syscon) {
@@ -328,21 +284,22 @@ impl ClockRequirements {
             }
         }
 
-        // fix wait cycles
-        match freq.0 {
-            0 ..= 99 => {
-                unsafe { syscon.raw.fmccr.modify(|_, w| w.flashtim().bits((freq.0/11) as u8 - 1)) };
-            }
-            100 ..= 115 => {
-                unsafe { syscon.raw.fmccr.modify(|_, w| w.flashtim().bits( 9 )) };
-            }
-            116 ..= 130=> {
-                unsafe { syscon.raw.fmccr.modify(|_, w| w.flashtim().bits( 10 )) };
-            }
-            _ => {
-                unsafe { syscon.raw.fmccr.modify(|_, w| w.flashtim().bits( 11 )) };
-            }
-        }
+        // fix wait cycles, as the SDK's FLASHTIM table
+        let flashtim = match freq.to_MHz() {
+            0 ..= 11 => 0,
+            12 ..= 22 => 1,
+            23 ..= 33 => 2,
+            34 ..= 44 => 3,
+            45 ..= 55 => 4,
+            56 ..= 66 => 5,
+            67 ..= 77 => 6,
+            78 ..= 88 => 7,
+            89 ..= 100 => 8,
+            101 ..= 115 => 9,
+            116 ..= 130 => 10,
+            _ => 11,
+        };
+        unsafe { syscon.raw.fmccr.modify(|_, w| w.flashtim().bits(flashtim)) };
     }
 
     /// Requirements solver - tries to generate and configure a clock configuration
@@ -356,18 +313,7 @@ impl ClockRequirements {
 
         let freq: Megahertz = self.system_frequency.unwrap_or(DEFAULT_FREQ);
 
-        // turn on FRO192M: clear bit 5, according to `fsl_power.h` from the SDK
-        // unsafe { pmc.raw.pdruncfgclr0.write(|w| w.bits(1u32 << 5)) };
-        // but it's hidden in UM, so let's assume this is always cleared
-
-        // turn on 1mhz, 12mhz and 96mhz clocks
-        anactrl.raw.fro192m_ctrl.modify(|_, w| w.ena_96mhzclk().enable());
-        anactrl.raw.fro192m_ctrl.modify(|_, w| w.ena_12mhzclk().enable());
-
-        syscon.raw.clock_ctrl.modify(|_, w| w
-            .fro1mhz_clk_ena().enable()
-            .fro1mhz_utick_ena().enable()
-        );
+        Self::enable_fros(anactrl, syscon);
 
         let (main_clock, sys_divider) = Self::get_clock_source_and_div_for_freq(freq, pmc, syscon);
         Self::set_new_clock_source(freq, main_clock, sys_divider, syscon);
@@ -376,7 +322,7 @@ impl ClockRequirements {
 
         Ok(Clocks {
             main_clock,
-            system_frequency: Hertz::try_from(freq).unwrap(),
+            system_frequency: freq.convert(),
         })
     }
 
@@ -392,7 +338,199 @@ impl ClockRequirements {
 
         Clocks {
             main_clock,
-            system_frequency: Hertz::try_from(freq).unwrap(),
+            system_frequency: freq.convert(),
+        }
+    }
+}
+
+/// How a system frequency is generated
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Plan {
+    main_clock: MainClock,
+    sys_divider: u8,
+    /// PLL0 settings, fed by FRO 12 MHz
+    pll: Option<Pll>,
+}
+
+impl Plan {
+    const fn for_frequency(freq: u32) -> Plan {
+        assert!(freq >= 1 && freq <= 150, "system frequency must be 1-150 MHz");
+        if freq <= 12 && 12 % freq == 0 {
+            return Plan { main_clock: MainClock::Fro12Mhz, sys_divider: (12 / freq) as u8, pll: None };
+        }
+        if freq <= 96 && 96 % freq == 0 {
+            return Plan { main_clock: MainClock::Fro96Mhz, sys_divider: (96 / freq) as u8, pll: None };
+        }
+        // For reference: how to get 150 MHz using 16Mhz external crystal
+        // freq if freq == 150.mhz() && 150 % freq.0 == 0 => {
+        //     // Use crystal as input to PLL0
+        //     // Power on 32M crystal for stable pll operation
+        //     pmc.raw.pdruncfg0.modify(|_,w| w.pden_xtal32m().poweredon());
+        //     pmc.raw.pdruncfg0.modify(|_,w| w.pden_ldoxo32m().poweredon());
+
+        //     // Connect external 32M as clk input
+        //     syscon.raw.clock_ctrl.modify(|_,w| w.clkin_ena().set_bit());
+        //     anactrl.raw.xo32m_ctrl.modify(|_,w| w.enable_system_clk_out().set_bit());
+
+        //     // select clkin for pll0
+        //     syscon.raw.pll0clksel.write(|w| unsafe{ w.bits(1) });
+
+        //     // 150 MHz settings
+        //     let pll = Pll {
+        //         n: 8,
+        //         m: 150,
+        //         p: 1,
+        //         selp: 31,
+        //         seli: 53,
+        //     };
+
+        //     Self::configure_pll0(pll, pmc, syscon);
+
+        //     (MainClock::Pll0, 1)
+        // }
+        // Get 150 MHz using internal FRO12
+        let pll = if freq == 150 {
+            Pll {
+                n: 8,
+                m: 200,
+                p: 1,
+                selp: 31,
+                seli: 53,
+            }
+        } else {
+            Self::get_pll(freq)
+        };
+        Plan { main_clock: MainClock::Pll0, sys_divider: 1, pll: Some(pll) }
+    }
+
+    // generated via `scripts/generate-pll-settings.py`,
+    // massaged a bit by hand
+    const fn get_pll(freq: u32) -> Pll {
+        debug_assert!(freq >= 5);
+        debug_assert!(freq <= 150);
+        // let ns: [u32; 9] = [1, 2, 3, 4, 6, 8, 12, 16, 24];
+        // let ns: [u32; 2] = [1, 2];
+        // let ps: [u32; 11] = [2, 3, 4, 6, 8, 9, 12, 16, 18, 24, 30];
+        // let ps: [u32; 5] = [6, 8, 12, 16, 24];
+
+        // for n in 1..=6 { for p in 1..=30 { for m in 1..=255 {
+        // (as `while` loops, to be usable in const contexts)
+        let mut n = 1;
+        while n <= 6 {
+            let mut p = 1;
+            while p <= 30 {
+                let mut m = 1;
+                while m <= 255 {
+                    if 2 * freq * n * p == 12 * m {
+                        // UM 4.6.6.3.2
+                        let selp = (m >> 2) + 1; // <= 31
+                        let seli = 2 * (m >> 2) + 3; // <= 63
+                        return Pll {
+                            n: n as u8,
+                            m: m as u16,
+                            p: p as u8,
+                            selp: selp as u8,
+                            seli: seli as u8,
+                        }
+                    }
+                    m += 1;
+                }
+                p += 1;
+            }
+            n += 1;
+        }
+
+        unreachable!();
+    }
+}
+
+/// Clock configuration computed at compile time, to be applied by `pre_init`.
+///
+/// This gets the core to its final speed before RAM is initialized and
+/// before `main` (or RTIC's `init`), so startup takes a known, short time:
+///
+/// ```ignore
+/// const CLOCKS: ClockConfig = ClockConfig::new(Megahertz::from_raw(96));
+///
+/// #[pre_init]
+/// unsafe fn before_main() {
+///     hal::drivers::clocks::pre_init(&CLOCKS);
+/// }
+///
+/// #[entry]
+/// fn main() -> ! {
+///     // instead of `ClockRequirements::configure`
+///     let clocks = CLOCKS.clocks().unwrap();
+///     ...
+/// }
+/// ```
+///
+/// Used in a `const`, an unsupported frequency is a compile-time error.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockConfig {
+    system_frequency: Megahertz,
+    plan: Plan,
+}
+
+impl ClockConfig {
+    /// Panics unless `system_frequency` is within 1-150 MHz.
+    pub const fn new(system_frequency: Megahertz) -> Self {
+        ClockConfig {
+            system_frequency,
+            plan: Plan::for_frequency(system_frequency.to_MHz()),
         }
     }
+
+    pub const fn system_frequency(&self) -> Megahertz {
+        self.system_frequency
+    }
+
+    /// The `Clocks` resulting from `pre_init`, after checking it was called.
+    ///
+    /// Like `ClockRequirements::configure`, this can be called only once.
+    pub fn clocks(&self) -> Result<Clocks> {
+        if unsafe { CONFIGURED } {
+            return Err(ClocksError::AlreadyConfigured);
+        }
+
+        let syscon = unsafe { &*crate::raw::SYSCON::ptr() };
+        let (sela, selb) = match self.plan.main_clock {
+            MainClock::Fro12Mhz => (0, 0),
+            MainClock::Fro96Mhz => (3, 0),
+            MainClock::Pll0 => (0, 1),
+        };
+        if syscon.mainclksela.read().sel().bits() != sela
+            || syscon.mainclkselb.read().sel().bits() != selb
+            || syscon.ahbclkdiv.read().div().bits() != self.plan.sys_divider - 1
+        {
+            return Err(ClocksError::NotApplied);
+        }
+
+        unsafe { CONFIGURED = true };
+
+        Ok(Clocks {
+            main_clock: self.plan.main_clock,
+            system_frequency: self.system_frequency.convert(),
+        })
+    }
+}
+
+/// Apply `config`, meant to be called from a `#[pre_init]` function.
+///
+/// # Safety
+///
+/// Takes over ANACTRL, PMC and SYSCON without owning them, so must run before
+/// anything else uses them. As statics are not yet initialized in `#[pre_init]`,
+/// call `ClockConfig::clocks` (not `ClockRequirements::configure`) afterwards.
+pub unsafe fn pre_init(config: &ClockConfig) {
+    let mut anactrl = Anactrl::steal();
+    let mut pmc = Pmc::steal();
+    let mut syscon = Syscon::steal();
+
+    ClockRequirements::enable_fros(&mut anactrl, &mut syscon);
+    ClockRequirements::apply_plan(&config.plan, &mut pmc, &mut syscon);
+    ClockRequirements::set_new_clock_source(
+        config.system_frequency, config.plan.main_clock, config.plan.sys_divider, &mut syscon);
 }
@@ -0,0 +1,109 @@
+//! Counting external pulses with a CTIMER (counter mode).
+//!
+//! The timer counter is clocked by edges on one of the timer's capture inputs,
+//! instead of the 1 MHz clock. The input is routed from a pin (IOCON function
+//! CT_INPn) through the INPUTMUX:
+//!
+//! ```ignore
+//! inputmux.select_ctimer_capture(1, 0, 7); // CTIMER1 CAP0 <- CT_INP7
+//! let mut counter = Counter::new(ctimer1, 0, CountEdge::Rising);
+//!
+//! // every second
+//! let pulses_per_second = counter.read_and_clear();
+//! ```
+
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::{
+    peripherals::{ctimer::Ctimer, syscon},
+    typestates::init_state,
+};
+
+/// Edges of the capture input which increment the counter (CTCR.CTMODE)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CountEdge {
+    Rising = 1,
+    Falling = 2,
+    Both = 3,
+}
+
+pub struct Counter<TIMER>
+where
+    TIMER: Ctimer<init_state::Enabled>,
+{
+    timer: TIMER,
+    last: u32,
+}
+
+impl<TIMER> Counter<TIMER>
+where TIMER: Ctimer<init_state::Enabled> {
+
+    /// Count `edge`s of capture input `capture` (0..=3), starting at zero.
+    ///
+    /// The edges are sampled with the timer's clock, so pulses need to be
+    /// longer than a period of it (1 µs).
+    pub fn new(timer: TIMER, capture: u8, edge: CountEdge) -> Self {
+        assert!(capture < 4);
+        timer.tcr.write(|w| w.crst().set_bit().cen().clear_bit());
+        timer.mcr.write(|w| unsafe { w.bits(0) });
+        // counter mode requires no capture on the counting input
+        timer.ccr.modify(|r, w| unsafe { w.bits(r.bits() & !(0x7 << (3 * capture))) });
+        timer.ctcr.write(|w| {
+            w.ctmode().bits(edge as u8)
+            .cinsel().bits(capture)
+        });
+        timer.pr.write(|w| unsafe { w.bits(0) });
+        timer.tcr.write(|w| w.crst().clear_bit().cen().set_bit());
+
+        Self { timer, last: 0 }
+    }
+
+    /// Total count since `new` or `reset`, wrapping at 2^32
+    pub fn count(&self) -> u32 {
+        self.timer.tc.read().bits()
+    }
+
+    /// The number of edges since the previous call (or `new`).
+    ///
+    /// The counter keeps running, so no edges are lost between calls, which
+    /// makes this suitable for calculating rates from periodic calls.
+    pub fn read_and_clear(&mut self) -> u32 {
+        let count = self.count();
+        let delta = count.wrapping_sub(self.last);
+        self.last = count;
+        delta
+    }
+
+    /// Restart counting at zero.
+    pub fn reset(&mut self) {
+        self.timer.tcr.write(|w| w.crst().set_bit().cen().set_bit());
+        self.timer.tcr.write(|w| w.crst().clear_bit().cen().set_bit());
+        self.last = 0;
+    }
+
+    /// Stop counting and return the timer to timer mode.
+    pub fn release(self) -> TIMER {
+        let this = ManuallyDrop::new(self);
+        this.timer.tcr.write(|w| w.crst().set_bit().cen().clear_bit());
+        this.timer.ctcr.write(|w| unsafe { w.bits(0) });
+        unsafe { ptr::read(&this.timer) }
+    }
+
+    /// The timer owned by the driver, which derefs to the raw registers.
+    pub fn raw(&self) -> &TIMER {
+        &self.timer
+    }
+}
+
+/// Stops the counter and disables the timer's clock.
+impl<TIMER> Drop for Counter<TIMER>
+where TIMER: Ctimer<init_state::Enabled>
+{
+    fn drop(&mut self) {
+        self.timer.tcr.write(|w| w.crst().set_bit().cen().clear_bit());
+        self.timer.ctcr.write(|w| unsafe { w.bits(0) });
+        syscon::disable_clock_on_drop(&self.timer);
+    }
+}
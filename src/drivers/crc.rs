@@ -0,0 +1,188 @@
+//! Hardware CRC computation.
+//!
+//! The CRC engine implements three fixed polynomials (CRC-CCITT, CRC-16 and CRC-32),
+//! with configurable seed, bit reversal and 1's complement of both input and output.
+//! This covers the common presets, which are available as constants on `Config`.
+//!
+//! ```ignore
+//! let mut crc = hal.crc.enabled(&mut syscon);
+//! let mut crc32 = crc.crc32();
+//! crc32.update(b"1234");
+//! crc32.update(b"56789");
+//! assert_eq!(crc32.sum(), 0xCBF4_3926);
+//! ```
+
+use crate::{
+    raw,
+    peripherals::crc::Crc as CrcPeripheral,
+    typestates::init_state::Enabled,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Polynomial {
+    /// x^16 + x^12 + x^5 + 1 (0x1021)
+    Ccitt,
+    /// x^16 + x^15 + x^2 + 1 (0x8005)
+    Crc16,
+    /// x^32 + x^26 + x^23 + ... + x^2 + x + 1 (0x04C1_1DB7)
+    Crc32,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    pub polynomial: Polynomial,
+    pub seed: u32,
+    /// reverse the bit order of each input byte
+    pub reflect_input: bool,
+    /// 1's complement the input data
+    pub complement_input: bool,
+    /// reverse the bit order of the checksum
+    pub reflect_output: bool,
+    /// 1's complement the checksum
+    pub complement_output: bool,
+}
+
+impl Config {
+    pub const CRC32: Config = Config {
+        polynomial: Polynomial::Crc32,
+        seed: 0xFFFF_FFFF,
+        reflect_input: true,
+        complement_input: false,
+        reflect_output: true,
+        complement_output: true,
+    };
+
+    pub const CRC16_CCITT: Config = Config {
+        polynomial: Polynomial::Ccitt,
+        seed: 0xFFFF,
+        reflect_input: false,
+        complement_input: false,
+        reflect_output: false,
+        complement_output: false,
+    };
+
+    pub const CRC16_IBM: Config = Config {
+        polynomial: Polynomial::Crc16,
+        seed: 0,
+        reflect_input: true,
+        complement_input: false,
+        reflect_output: true,
+        complement_output: false,
+    };
+
+    pub fn polynomial(mut self, polynomial: Polynomial) -> Self {
+        self.polynomial = polynomial;
+        self
+    }
+
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn reflect_input(mut self, reflect: bool) -> Self {
+        self.reflect_input = reflect;
+        self
+    }
+
+    pub fn complement_input(mut self, complement: bool) -> Self {
+        self.complement_input = complement;
+        self
+    }
+
+    pub fn reflect_output(mut self, reflect: bool) -> Self {
+        self.reflect_output = reflect;
+        self
+    }
+
+    pub fn complement_output(mut self, complement: bool) -> Self {
+        self.complement_output = complement;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::CRC32
+    }
+}
+
+/// A running CRC computation, borrowing the CRC engine.
+pub struct Checksum<'a> {
+    inner: &'a mut CrcPeripheral<Enabled>,
+    config: Config,
+}
+
+impl<'a> Checksum<'a> {
+    pub fn new(crc: &'a mut CrcPeripheral<Enabled>, config: Config) -> Self {
+        let mut crc = Self { inner: crc, config };
+        crc.reset();
+        crc
+    }
+
+    pub fn into_inner(self) -> &'a mut CrcPeripheral<Enabled> {
+        self.inner
+    }
+
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Start a new computation, with the same configuration.
+    pub fn reset(&mut self) {
+        let config = self.config;
+        self.inner.mode.write(|w| unsafe {
+            w
+            .crc_poly().bits(match config.polynomial {
+                Polynomial::Ccitt => 0b00,
+                Polynomial::Crc16 => 0b01,
+                Polynomial::Crc32 => 0b10,
+            })
+            .bit_rvs_wr().bit(config.reflect_input)
+            .cmpl_wr().bit(config.complement_input)
+            .bit_rvs_sum().bit(config.reflect_output)
+            .cmpl_sum().bit(config.complement_output)
+        });
+        // writing the seed also resets the engine
+        self.inner.seed.write(|w| unsafe { w.crc_seed().bits(config.seed) });
+    }
+
+    /// Feed more data into the computation.
+    pub fn update(&mut self, data: &[u8]) {
+        // WR_DATA, at offset 0x08
+        let wr_data = (raw::CRC_ENGINE::ptr() as usize + 0x08) as *mut u32;
+
+        // The engine takes 8, 16 and 32-bit writes; a 32-bit write
+        // is processed as its four bytes, least significant first.
+        let (head, words, tail) = unsafe { data.align_to::<u32>() };
+        for byte in head {
+            unsafe { core::ptr::write_volatile(wr_data as *mut u8, *byte) };
+        }
+        for word in words {
+            unsafe { core::ptr::write_volatile(wr_data, u32::from_le(*word)) };
+        }
+        for byte in tail {
+            unsafe { core::ptr::write_volatile(wr_data as *mut u8, *byte) };
+        }
+    }
+
+    /// The checksum of all data fed in since the last reset.
+    ///
+    /// For the 16-bit polynomials, only the lower 16 bits are significant.
+    pub fn sum(&self) -> u32 {
+        let sum = self.inner.sum().read().crc_sum().bits();
+        match self.config.polynomial {
+            Polynomial::Crc32 => sum,
+            _ => sum & 0xFFFF,
+        }
+    }
+
+    /// Compute the checksum of `data` in one go.
+    pub fn checksum(&mut self, data: &[u8]) -> u32 {
+        self.reset();
+        self.update(data);
+        self.sum()
+    }
+}
@@ -0,0 +1,194 @@
+//! Type-erased driver facades, for code size.
+//!
+//! The drivers are generic over their peripheral and pins, so code using them
+//! is instantiated once per driver type. Code written against these facades is
+//! instantiated once, dispatching to the driver through a trait object:
+//!
+//! ```ignore
+//! fn log(serial: &mut ErasedSerial<'_>, message: &str) {
+//!     serial.write_str(message).ok();
+//! }
+//!
+//! log(&mut ErasedSerial::new(&mut serial0), "hello");
+//! log(&mut ErasedSerial::new(&mut serial2), "world");
+//! ```
+
+use core::fmt;
+
+use crate::traits::wg::{
+    blocking,
+    serial as wg_serial,
+};
+use super::{i2c, serial, spi};
+
+/// Object-safe byte-wise serial interface, implemented for all `Serial`s
+pub trait SerialPort {
+    fn read(&mut self) -> nb::Result<u8, serial::Error>;
+    fn write(&mut self, byte: u8) -> nb::Result<(), serial::Error>;
+    fn flush(&mut self) -> nb::Result<(), serial::Error>;
+}
+
+impl<T> SerialPort for T
+where
+    T: wg_serial::Read<u8, Error = serial::Error> + wg_serial::Write<u8, Error = serial::Error>,
+{
+    fn read(&mut self) -> nb::Result<u8, serial::Error> {
+        wg_serial::Read::read(self)
+    }
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), serial::Error> {
+        wg_serial::Write::write(self, byte)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), serial::Error> {
+        wg_serial::Write::flush(self)
+    }
+}
+
+/// Any `Serial`, behind a trait object
+pub struct ErasedSerial<'a> {
+    inner: &'a mut dyn SerialPort,
+}
+
+impl<'a> ErasedSerial<'a> {
+    pub fn new(serial: &'a mut dyn SerialPort) -> Self {
+        Self { inner: serial }
+    }
+}
+
+impl wg_serial::Read<u8> for ErasedSerial<'_> {
+    type Error = serial::Error;
+
+    fn read(&mut self) -> nb::Result<u8, serial::Error> {
+        self.inner.read()
+    }
+}
+
+impl wg_serial::Write<u8> for ErasedSerial<'_> {
+    type Error = serial::Error;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), serial::Error> {
+        self.inner.write(byte)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), serial::Error> {
+        self.inner.flush()
+    }
+}
+
+impl blocking::serial::write::Default<u8> for ErasedSerial<'_> {}
+
+impl fmt::Write for ErasedSerial<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.bytes()
+            .try_for_each(|byte| nb::block!(self.inner.write(byte)))
+            .map_err(|_| fmt::Error)
+    }
+}
+
+/// Object-safe blocking SPI interface, implemented for all `SpiMaster`s
+pub trait SpiPort {
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], spi::Error>;
+    fn write(&mut self, words: &[u8]) -> Result<(), spi::Error>;
+}
+
+impl<T> SpiPort for T
+where
+    T: blocking::spi::Transfer<u8, Error = spi::Error> + blocking::spi::Write<u8, Error = spi::Error>,
+{
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], spi::Error> {
+        blocking::spi::Transfer::transfer(self, words)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), spi::Error> {
+        blocking::spi::Write::write(self, words)
+    }
+}
+
+/// Any `SpiMaster`, behind a trait object
+pub struct ErasedSpi<'a> {
+    inner: &'a mut dyn SpiPort,
+}
+
+impl<'a> ErasedSpi<'a> {
+    pub fn new(spi: &'a mut dyn SpiPort) -> Self {
+        Self { inner: spi }
+    }
+}
+
+impl blocking::spi::Transfer<u8> for ErasedSpi<'_> {
+    type Error = spi::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], spi::Error> {
+        self.inner.transfer(words)
+    }
+}
+
+impl blocking::spi::Write<u8> for ErasedSpi<'_> {
+    type Error = spi::Error;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), spi::Error> {
+        self.inner.write(words)
+    }
+}
+
+/// Object-safe blocking I2C interface, implemented for all `I2cMaster`s
+pub trait I2cPort {
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), i2c::Error>;
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), i2c::Error>;
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), i2c::Error>;
+}
+
+impl<T> I2cPort for T
+where
+    T: blocking::i2c::Write<Error = i2c::Error>
+        + blocking::i2c::Read<Error = i2c::Error>
+        + blocking::i2c::WriteRead<Error = i2c::Error>,
+{
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), i2c::Error> {
+        blocking::i2c::Write::write(self, address, bytes)
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), i2c::Error> {
+        blocking::i2c::Read::read(self, address, buffer)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), i2c::Error> {
+        blocking::i2c::WriteRead::write_read(self, address, bytes, buffer)
+    }
+}
+
+/// Any `I2cMaster`, behind a trait object
+pub struct ErasedI2c<'a> {
+    inner: &'a mut dyn I2cPort,
+}
+
+impl<'a> ErasedI2c<'a> {
+    pub fn new(i2c: &'a mut dyn I2cPort) -> Self {
+        Self { inner: i2c }
+    }
+}
+
+impl blocking::i2c::Write for ErasedI2c<'_> {
+    type Error = i2c::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), i2c::Error> {
+        self.inner.write(address, bytes)
+    }
+}
+
+impl blocking::i2c::Read for ErasedI2c<'_> {
+    type Error = i2c::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), i2c::Error> {
+        self.inner.read(address, buffer)
+    }
+}
+
+impl blocking::i2c::WriteRead for ErasedI2c<'_> {
+    type Error = i2c::Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), i2c::Error> {
+        self.inner.write_read(address, bytes, buffer)
+    }
+}
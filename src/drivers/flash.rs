@@ -3,12 +3,15 @@ use core::convert::TryInto;
 
 use crate::{
     peripherals::flash::Flash,
+    time::{Megahertz, Microseconds},
     typestates::init_state::Enabled,
-    traits::flash::{
-        Error,
-        Result,
-        Read,
-        WriteErase,
+    traits::{
+        flash::{
+            Error,
+            Result,
+            Read,
+            WriteErase,
+        },
     },
 };
 
@@ -29,6 +32,8 @@ pub const PAGE_SIZE: usize = 512;
 
 pub struct FlashGordon {
     flash: Flash<Enabled>,
+    // hook and its interval in cycles
+    feed_hook: Option<(fn(), u32)>,
 }
 
 impl FlashGordon {
@@ -45,6 +50,96 @@ impl FlashGordon {
 
         FlashGordon {
             flash,
+            feed_hook: None,
+        }
+    }
+
+    /// Wrap `flash`, without resetting the flash controller.
+    ///
+    /// # Safety
+    ///
+    /// The driver owning the peripheral must not be used concurrently.
+    pub unsafe fn steal(flash: Flash<Enabled>) -> Self {
+        FlashGordon {
+            flash,
+            feed_hook: None,
+        }
+    }
+
+    /// Returns the flash peripheral, e.g. to hand it to a different driver.
+    pub fn release(self) -> Flash<Enabled> {
+        self.flash
+    }
+
+    /// The raw PAC peripheral.
+    pub fn raw(&self) -> &raw::FLASH {
+        self.flash.raw()
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut raw::FLASH {
+        self.flash.raw_mut()
+    }
+
+    /// Call `hook` at least every `interval` while the flash is erased or
+    /// programmed, with the core running at `system_frequency`.
+    ///
+    /// Erasing or programming a page can take longer than a tight watchdog window,
+    /// the hook can feed the watchdog meanwhile:
+    ///
+    /// ```ignore
+    /// hal::ramfunc! {
+    ///     fn feed() { /* reload the WWDT */ }
+    /// }
+    ///
+    /// unsafe { flash.set_feed_hook(feed, Microseconds::millis(1), 96.MHz()) };
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// If the code executes from the internal flash, the flash can't be read
+    /// while waiting, so `hook` must be placed in SRAM with `ramfunc!`, and
+    /// must not call code in flash.
+    pub unsafe fn set_feed_hook(&mut self, hook: fn(), interval: Microseconds, system_frequency: Megahertz) {
+        let cycles = interval.to_micros().saturating_mul(system_frequency.to_MHz());
+        self.feed_hook = Some((hook, cycles));
+    }
+
+    pub fn clear_feed_hook(&mut self) {
+        self.feed_hook = None;
+    }
+
+    /// Start `command`, and wait until it is done.
+    ///
+    /// If the code executes from the internal flash, this happens in SRAM, as
+    /// the flash can't be read meanwhile.
+    fn execute(&self, command: FlashCommands) {
+        if executing_from_flash() {
+            if self.feed_hook.is_some() {
+                crate::enable_cycle_counter();
+            }
+            unsafe { execute_in_ram(&*self.flash.raw as *const _ as usize, command as u32, self.feed_hook) };
+        } else {
+            self.flash.raw.cmd.write(|w| unsafe { w.bits(command as u32) });
+            self.wait_done();
+        }
+    }
+
+    fn wait_done(&self) {
+        let flash = &self.flash.raw;
+        match self.feed_hook {
+            None => while flash.int_status.read().done().bit_is_clear() {},
+            Some((hook, interval)) => {
+                crate::enable_cycle_counter();
+                let mut last = crate::get_cycle_count();
+                while flash.int_status.read().done().bit_is_clear() {
+                    let now = crate::get_cycle_count();
+                    if now.wrapping_sub(last) >= interval {
+                        hook();
+                        last = now;
+                    }
+                }
+            }
         }
     }
 
@@ -87,13 +182,12 @@ impl FlashGordon {
 
         flash.event.write(|w| w.rst().set_bit());
         // seems immediate
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.wait_done();
         self.status()?;
         self.clear_status();
 
         flash.starta.write(|w| unsafe { w.starta().bits((address >> 4) as u32) } );
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Program as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.execute(FlashCommands::Program);
         debug_assert!(flash.int_status.read().err().bit_is_clear());
         debug_assert!(flash.int_status.read().fail().bit_is_clear());
         self.status()?;
@@ -112,9 +206,7 @@ impl FlashGordon {
                 flash.dataw[j].write(|w| unsafe { w.bits(0x0) });
             }
             flash.starta.write(|w| unsafe { w.starta().bits(i as u32) } );
-            flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Write as u32) });
-
-            while flash.int_status.read().done().bit_is_clear() {}
+            self.execute(FlashCommands::Write);
             debug_assert!(flash.int_status.read().err().bit_is_clear());
             debug_assert!(flash.int_status.read().fail().bit_is_clear());
             assert!(self.status().is_ok());
@@ -135,15 +227,13 @@ impl FlashGordon {
         flash.dataw[(address >> 2) % 4].write(|w| unsafe { w.bits(u32::from_ne_bytes(word)) });
         flash.starta.write(|w| unsafe { w.starta().bits(page_register_column as u32) } );
         self.clear_status();
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Write as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.execute(FlashCommands::Write);
         self.status()?;
 
         self.clear_status();
         // self.just_program_at(address & !(512 - 1));
         flash.starta.write(|w| unsafe { w.starta().bits((address >> 4) as u32) } );
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Program as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.execute(FlashCommands::Program);
         self.status()?;
         Ok(())
     }
@@ -161,15 +251,13 @@ impl FlashGordon {
         flash.dataw[(address >> 2) % 4].write(|w| unsafe { w.bits(word) });
         flash.starta.write(|w| unsafe { w.starta().bits(page_register_column as u32) } );
         self.clear_status();
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Write as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.execute(FlashCommands::Write);
         self.status()?;
 
         self.clear_status();
         // self.just_program_at(address & !(512 - 1));
         flash.starta.write(|w| unsafe { w.starta().bits((address >> 4) as u32) } );
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Program as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.execute(FlashCommands::Program);
         self.status()?;
 
         Ok(())
@@ -187,15 +275,13 @@ impl FlashGordon {
         }
         flash.starta.write(|w| unsafe { w.starta().bits((address >> 4) as u32) } );
         self.clear_status();
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Write as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.execute(FlashCommands::Write);
         self.status()?;
 
         self.clear_status();
         // self.just_program_at(address & !(512 - 1));
         flash.starta.write(|w| unsafe { w.starta().bits((address >> 4) as u32) } );
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Program as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+        self.execute(FlashCommands::Program);
         self.status()?;
 
         Ok(())
@@ -230,8 +316,7 @@ impl Read<U16> for FlashGordon {
         flash.starta.write(|w| unsafe { w.starta().bits(addr >> 4) } );
         // want to have normal reads
         flash.dataw[0].write(|w| unsafe { w.bits(0) } );
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::ReadSingleWord as u32) });
-        while flash.int_status.read().done().bit_is_clear() { continue; }
+        self.execute(FlashCommands::ReadSingleWord);
 
         assert!(flash.int_status.read().err().bit_is_clear());
         debug_assert!(flash.int_status.read().fail().bit_is_clear());
@@ -251,26 +336,27 @@ impl WriteErase<U512, U512> for FlashGordon {
 
     // TODO: use critical section?
     fn erase_page(&mut self, page: usize) -> Result {
-        // starta is still in flash words, of which a page has 32
-        let starta = page * 32;
-        // hprintln!("native erase page {}", page).ok();
+        crate::instrumented!(FLASH_ERASE, {
+            // starta is still in flash words, of which a page has 32
+            let starta = page * 32;
+            // hprintln!("native erase page {}", page).ok();
 
-        let flash = &self.flash.raw;
-        assert!(flash.int_status.read().done().bit_is_set());
-        self.clear_status();
-        assert!(flash.int_status.read().done().bit_is_clear());
+            let flash = &self.flash.raw;
+            assert!(flash.int_status.read().done().bit_is_set());
+            self.clear_status();
+            assert!(flash.int_status.read().done().bit_is_clear());
 
-        flash.starta.write(|w| unsafe { w.starta().bits(starta as u32) } );
-        flash.stopa.write(|w| unsafe { w.stopa().bits(starta as u32) } );
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::EraseRange as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
+            flash.starta.write(|w| unsafe { w.starta().bits(starta as u32) } );
+            flash.stopa.write(|w| unsafe { w.stopa().bits(starta as u32) } );
+            self.execute(FlashCommands::EraseRange);
 
-        debug_assert!(flash.int_status.read().err().bit_is_clear());
-        debug_assert!(flash.int_status.read().fail().bit_is_clear());
-        // cortex_m_semihosting::dbg!(self.status());
-        self.status()?;
+            debug_assert!(flash.int_status.read().err().bit_is_clear());
+            debug_assert!(flash.int_status.read().fail().bit_is_clear());
+            // cortex_m_semihosting::dbg!(self.status());
+            self.status()?;
 
-        Ok(())
+            Ok(())
+        })
     }
 
     fn write_native(
@@ -279,44 +365,85 @@ impl WriteErase<U512, U512> for FlashGordon {
         array: &GenericArray<u8, U512>,
         // cs: &CriticalSection,
     ) -> Result {
+        crate::instrumented!(FLASH_PROGRAM, {
 
-        // hprintln!("native write to {} of {:?} (first 16)", address, &array[..16]).ok();
-        let flash = &self.flash.raw;
-        assert!(flash.int_status.read().done().bit_is_set());
-        self.clear_status();
+            // hprintln!("native write to {} of {:?} (first 16)", address, &array[..16]).ok();
+            let flash = &self.flash.raw;
+            assert!(flash.int_status.read().done().bit_is_set());
+            self.clear_status();
 
-        // maybe check the page is erased?
+            // maybe check the page is erased?
 
-        // write one physical word (16 bytes) at a time
-        for (i, chunk) in array.chunks(16).enumerate() {
-            let starta = (address >> 4) + i;
-            flash.starta.write(|w| unsafe { w.starta().bits(starta as u32) } );
+            // write one physical word (16 bytes) at a time
+            for (i, chunk) in array.chunks(16).enumerate() {
+                let starta = (address >> 4) + i;
+                flash.starta.write(|w| unsafe { w.starta().bits(starta as u32) } );
+
+                for (j, word) in chunk.chunks(4).enumerate() {
+                    flash.dataw[j].write(|w| unsafe { w.bits(
+                        u32::from_ne_bytes(word.try_into().unwrap())
+                    ) } );
+                }
 
-            for (j, word) in chunk.chunks(4).enumerate() {
-                flash.dataw[j].write(|w| unsafe { w.bits(
-                    u32::from_ne_bytes(word.try_into().unwrap())
-                ) } );
+                self.execute(FlashCommands::Write);
+                // self.execute(FlashCommands::WriteProgram);
+                debug_assert!(flash.int_status.read().err().bit_is_clear());
+                debug_assert!(flash.int_status.read().fail().bit_is_clear());
+                self.status()?;
             }
+            self.clear_status();
 
-            flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Write as u32) });
-            // flash.cmd.write(|w| unsafe { w.bits(FlashCommands::WriteProgram as u32) });
-            while flash.int_status.read().done().bit_is_clear() {}
+            let starta = address >> 4;
+            flash.starta.write(|w| unsafe { w.starta().bits(starta as u32) } );
+            self.execute(FlashCommands::Program);
             debug_assert!(flash.int_status.read().err().bit_is_clear());
             debug_assert!(flash.int_status.read().fail().bit_is_clear());
             self.status()?;
-        }
-        self.clear_status();
 
-        let starta = address >> 4;
-        flash.starta.write(|w| unsafe { w.starta().bits(starta as u32) } );
-        flash.cmd.write(|w| unsafe { w.bits(FlashCommands::Program as u32) });
-        while flash.int_status.read().done().bit_is_clear() {}
-        debug_assert!(flash.int_status.read().err().bit_is_clear());
-        debug_assert!(flash.int_status.read().fail().bit_is_clear());
-        self.status()?;
+            Ok(())
 
-        Ok(())
+        })
+    }
+}
+
+/// Whether the code executes from the internal flash (or its secure alias)
+fn executing_from_flash() -> bool {
+    let pc = executing_from_flash as fn() -> bool as usize;
+    pc & !0x1000_0000 < crate::chip::FLASH_SIZE
+}
 
+/// `int_status` offset from the flash controller registers
+const INT_STATUS: usize = 0xfe0;
+/// `int_status.done`
+const DONE: u32 = 1 << 2;
+/// DWT cycle counter
+const CYCCNT: usize = 0xE000_1004;
+
+/// A load which is never a call (into flash)
+#[inline(always)]
+unsafe fn load(address: usize) -> u32 {
+    let value;
+    core::arch::asm!("ldr {}, [{}]", out(reg) value, in(reg) address, options(nostack, readonly));
+    value
+}
+
+crate::ramfunc! {
+    /// Start `command` and wait until it is done, without fetching from flash.
+    unsafe fn execute_in_ram(flash: usize, command: u32, feed_hook: Option<(fn(), u32)>) {
+        core::arch::asm!("str {}, [{}]", in(reg) command, in(reg) flash, options(nostack));
+        match feed_hook {
+            None => while load(flash + INT_STATUS) & DONE == 0 {},
+            Some((hook, interval)) => {
+                let mut last = load(CYCCNT);
+                while load(flash + INT_STATUS) & DONE == 0 {
+                    let now = load(CYCCNT);
+                    if now.wrapping_sub(last) >= interval {
+                        hook();
+                        last = now;
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -362,8 +489,7 @@ macro_rules! littlefs2_filesystem {
         littlefs2_filesystem!(
             $Name: (
                 $BASE_OFFSET,
-                //     631.5KB
-                ((631 * 1024 + 512) - $BASE_OFFSET) / 512
+                ($crate::chip::USER_FLASH_END - $BASE_OFFSET) / 512
             )
         );
     };
@@ -374,9 +500,9 @@ macro_rules! littlefs2_filesystem {
         //
         // Compile time assertion that $BASE_OFFSET is 512 byte aligned.
         const _ZERO_SIZED_CHECK: usize = ((core::mem::size_of::<[u8; ($BASE_OFFSET % 512)]>() == 0) as usize) - 1;
-        // Compile time assertion that flash region does NOT spill over the 631.5KB boundary.
+        // Compile time assertion that flash region does NOT spill over the end of user flash.
         const _OVERFLOW_SIZE_CHECK: usize = ((
-            core::mem::size_of::<[u8; (($BASE_OFFSET + $BLOCK_COUNT * 512) <= (631 * 1024 + 512)) as usize]>() == 1) as usize) - 1;
+            core::mem::size_of::<[u8; (($BASE_OFFSET + $BLOCK_COUNT * 512) <= $crate::chip::USER_FLASH_END) as usize]>() == 1) as usize) - 1;
 
         pub struct $Name {
             flash_gordon: $crate::drivers::flash::FlashGordon
@@ -432,7 +558,7 @@ macro_rules! littlefs2_filesystem {
     }
 }
 
-#[cfg(feature = "littlefs")]
+#[cfg(all(feature = "littlefs", feature = "_prince"))]
 #[macro_export]
 macro_rules! littlefs2_prince_filesystem {
     ($Name:ident: (
@@ -441,8 +567,7 @@ macro_rules! littlefs2_prince_filesystem {
         littlefs2_prince_filesystem!(
             $Name: (
                 $BASE_OFFSET,
-                //     631.5KB
-                ((631 * 1024 + 512) - $BASE_OFFSET) / 512
+                ($crate::chip::USER_FLASH_END - $BASE_OFFSET) / 512
             )
         );
     };
@@ -453,9 +578,9 @@ macro_rules! littlefs2_prince_filesystem {
         //
         // Compile time assertion that $BASE_OFFSET is 512 byte aligned.
         const _ZERO_SIZED_CHECK_0: usize = ((core::mem::size_of::<[u8; ($BASE_OFFSET % 512)]>() == 0) as usize) - 1;
-        // Compile time assertion that flash region does NOT spill over the 631.5KB boundary.
+        // Compile time assertion that flash region does NOT spill over the end of user flash.
         const _OVERFLOW_SIZE_CHECK_0: usize = ((
-            core::mem::size_of::<[u8; (($BASE_OFFSET + $BLOCK_COUNT * 512) <= (631 * 1024 + 512)) as usize]>() == 1) as usize) - 1;
+            core::mem::size_of::<[u8; (($BASE_OFFSET + $BLOCK_COUNT * 512) <= $crate::chip::USER_FLASH_END) as usize]>() == 1) as usize) - 1;
 
 
         pub struct $Name {
@@ -1,3 +1,20 @@
+//! Group interrupts (GINT0/GINT1): one interrupt for a set of pins.
+//!
+//! Each pin of the group has a polarity; the group is active if any (`or`)
+//! or all (`and`) of its pins are at their active level. Unlike PINT, this
+//! doesn't use up a slot per pin, which suits waking up on any key of a keypad:
+//!
+//! ```ignore
+//! let keys = GroupInterrupt::new_edge_triggered(gint0)
+//!     .or_on_low(&row0)
+//!     .on_low(&row1)
+//!     .on_low(&row2);
+//! keys.enable_wakeup();
+//! unsafe { NVIC::unmask(Interrupt::GINT0) };
+//! hal::power::deep_sleep(&mut scb, DebugPolicy::Ignore);
+//! keys.clear_interrupt();
+//! ```
+
 use crate::{
     drivers::pins::Pin,
     traits::Gint,
@@ -83,6 +100,27 @@ where
     }
 }
 
+impl<GINT, MODE> GroupInterrupt<GINT, MODE>
+where
+    GINT: Gint,
+    MODE: Mode,
+{
+    /// Returns the GINT peripheral. The configured pins remain in the group.
+    pub fn release(self) -> GINT {
+        self.gint
+    }
+
+    /// The peripheral owned by the driver, which derefs to the raw registers.
+    pub fn raw(&self) -> &GINT {
+        &self.gint
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut GINT {
+        &mut self.gint
+    }
+}
+
 
 impl<GINT, MODE> GroupInterrupt<GINT, MODE>
 where
@@ -179,6 +217,30 @@ where
         self.gint.ctrl.modify(|_, w| w.int().set_bit());
     }
 
+    /// Is the group interrupt pending?
+    pub fn is_pending(&self) -> bool {
+        self.gint.ctrl.read().int().bit_is_set()
+    }
+
+    /// Removes `pin` from the group.
+    pub fn remove<PIO: PinId>(&self, _pin: &Pin<PIO, pin::state::Gpio<pin::gpio::direction::Input>>) {
+        self.gint.port_ena[PIO::PORT].modify(|r, w| unsafe {
+            w.ena().bits(r.ena().bits() & !PIO::MASK)
+        });
+    }
+
+    /// Makes the group interrupt a wake-up source from deep-sleep.
+    ///
+    /// Level triggering keeps the interrupt pending (and the chip awake)
+    /// as long as the group is active.
+    pub fn enable_wakeup(&self) {
+        crate::power::enable_wakeup(GINT::INTERRUPT);
+    }
+
+    pub fn disable_wakeup(&self) {
+        crate::power::disable_wakeup(GINT::INTERRUPT);
+    }
+
 }
 
 impl<GINT, MODE> GroupInterrupt<GINT, MODE>
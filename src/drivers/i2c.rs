@@ -1,5 +1,8 @@
 use core::marker::PhantomData;
 use core::cmp::min;
+use core::mem::ManuallyDrop;
+use core::ptr;
+use crate::peripherals::{flexcomm::Repurpose, syscon};
 use crate::time::Hertz;
 use crate::traits::wg::blocking::i2c::{
     Read,
@@ -15,6 +18,11 @@ use crate::typestates::pin::{
     PinId,
 };
 
+pub mod monitor;
+pub use monitor::I2cMonitor;
+pub mod slave;
+pub use slave::I2cSlave;
+
 pub mod prelude {
     pub use super::I2cMaster;
     pub use super::Error as I2cError;
@@ -23,6 +31,8 @@ pub mod prelude {
 
 /// I2C error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
     /// Bus error (catch-all)
     Bus,
@@ -34,13 +44,48 @@ pub enum Error {
     NackData,
     /// Start/Stop error
     StartStop,
+}
 
-    #[doc(hidden)]
-    _Extensible,
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Error::Bus => "bus error",
+            Error::ArbitrationLoss => "arbitration lost",
+            Error::NackAddress => "address not acknowledged",
+            Error::NackData => "data not acknowledged",
+            Error::StartStop => "start or stop condition in an illegal position",
+        })
+    }
 }
 
+impl core::error::Error for Error {}
+
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// Bus speeds of the I2C specification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mode {
+    /// 100 kbit/s
+    Standard,
+    /// 400 kbit/s
+    Fast,
+    /// 1 Mbit/s
+    FastPlus,
+    /// 3.4 Mbit/s, supported by the slave function only (CFG.HSCAPABLE)
+    HighSpeed,
+}
+
+impl Mode {
+    pub fn frequency(self) -> Hertz {
+        Hertz::from_raw(match self {
+            Mode::Standard => 100_000,
+            Mode::Fast => 400_000,
+            Mode::FastPlus => 1_000_000,
+            Mode::HighSpeed => 3_400_000,
+        })
+    }
+}
 
 // TODO: Parametrize with Master/Slave MODE
 /// I2C peripheral operating in master mode
@@ -66,10 +111,9 @@ where
 {
     /// Weird crashes happen when running system at 150Mhz PLL.
     /// Suggested use: 100khz or 400khz
-    pub fn new<Speed: Into<Hertz>>(i2c: I2C, pins: PINS, speed: Speed) -> Self {
-        // Simplified setup: We always use 12MHz clock, and only support 100kHz
-        let speed: Hertz = speed.into();
-        let speed: u32 = speed.0;
+    pub fn new(i2c: I2C, pins: PINS, speed: Hertz) -> Self {
+        let speed: u32 = speed.to_Hz();
+        let clock: u32 = i2c.clock_frequency().expect("unknown function clock").to_Hz();
         assert!(speed <= 1_000_000);
         i2c.cfg.modify(|_, w| w
             .msten().enabled()
@@ -86,8 +130,8 @@ where
         let mut best_err: u32 = 0;
         for scl in (2..=9).rev() {
             let denominator = 2 * scl * speed;
-            let div = min(10_000, 12_000_000 / denominator);
-            let err = 12_000_000 - div * denominator;
+            let div = min(10_000, clock / denominator);
+            let err = clock - div * denominator;
             if err < best_err || best_err == 0 {
                 // first time, or smaller error
                 best_div = div as u16; // limited by 10_000
@@ -132,8 +176,53 @@ where
         }
     }
 
+    /// Master at the bus speed of `mode`, with SCL timing derived from the
+    /// Flexcomm's function clock.
+    ///
+    /// The master can't do `Mode::HighSpeed`.
+    pub fn with_mode(i2c: I2C, pins: PINS, mode: Mode) -> Self {
+        assert!(mode != Mode::HighSpeed, "the I2C master does not support high-speed mode");
+        Self::new(i2c, pins, mode.frequency())
+    }
+
+    /// Wrap `i2c` as configured by `new` before, without touching the hardware.
+    ///
+    /// # Safety
+    ///
+    /// The driver owning the peripheral must not be used concurrently.
+    pub unsafe fn steal(i2c: I2C, pins: PINS) -> Self {
+        Self {
+            i2c,
+            pins,
+            _pin1: PhantomData,
+            _pin2: PhantomData,
+        }
+    }
+
     pub fn release(self) -> (I2C, PINS) {
-        (self.i2c, self.pins)
+        let this = ManuallyDrop::new(self);
+        unsafe { (ptr::read(&this.i2c), ptr::read(&this.pins)) }
+    }
+
+    /// Masks the interrupts and tears down the I2C personality, returning
+    /// the disabled Flexcomm (to enable it as another) and the pins.
+    pub fn teardown(self, syscon: &mut syscon::Syscon) -> (I2C::Flexcomm, PINS)
+    where
+        I2C: Repurpose,
+    {
+        self.i2c.intenclr.write(|w| unsafe { w.bits(!0) });
+        let (i2c, pins) = self.release();
+        (i2c.teardown(syscon), pins)
+    }
+
+    /// The peripheral owned by the driver, which derefs to the raw registers.
+    pub fn raw(&self) -> &I2C {
+        &self.i2c
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut I2C {
+        &mut self.i2c
     }
 
     #[inline(always)]
@@ -158,13 +247,13 @@ where
         Ok(())
     }
 
-    fn write_without_stop(&mut self, addr: u8, bytes: &[u8]) -> Result<()> {
+    fn start(&mut self, addr: u8, read: bool) -> Result<()> {
         self.return_on_error()?;
 
-        // Write the slave address with the RW bit set to 0 to the master data register MSTDAT.
+        // Write the slave address with the RW bit to the master data register MSTDAT.
         self.i2c
             .mstdat
-            .modify(|_, w| unsafe { w.data().bits(addr << 1) });
+            .modify(|_, w| unsafe { w.data().bits((addr << 1) | read as u8) });
         // Start the transmission by setting the MSTSTART bit to 1 in the master control register.
         self.i2c.mstctl.write(|w| w.mststart().start());
         // Wait for the pending status to be set (MSTPENDING = 1) by polling the STAT register
@@ -174,11 +263,20 @@ where
         }
 
         self.return_on_error()?;
-        if !self.i2c.stat.read().mststate().is_transmit_ready() {
+        let ready = if read {
+            self.i2c.stat.read().mststate().is_receive_ready()
+        } else {
+            self.i2c.stat.read().mststate().is_transmit_ready()
+        };
+        if !ready {
             // dbg!(Error::Bus);
             return Err(Error::Bus);
         }
 
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
         // Send bytes
         for byte in bytes {
             // write a byte
@@ -200,6 +298,41 @@ where
         Ok(())
     }
 
+    /// Reads into `buffer`, after a read `start`.
+    ///
+    /// The last byte is only acknowledged if `more` bytes are to be read afterwards,
+    /// otherwise the next start or stop NACKs it.
+    fn read_bytes(&mut self, buffer: &mut [u8], more: bool) -> Result<()> {
+        let len = buffer.len();
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            // Read a byte
+            *byte = self.i2c.mstdat.read().data().bits();
+
+            if i + 1 == len && !more {
+                break;
+            }
+
+            // Instruct master to continue
+            self.i2c.mstctl.write(|w| w.mstcontinue().continue_());
+
+            // Wait for next byte
+            while self.i2c.stat.read().mstpending().is_in_progress() {}
+
+            self.return_on_error()?;
+            if !self.i2c.stat.read().mststate().is_receive_ready() {
+                return Err(Error::Bus);
+            }
+        }
+
+        // Fallthrough is success
+        Ok(())
+    }
+
+    fn write_without_stop(&mut self, addr: u8, bytes: &[u8]) -> Result<()> {
+        self.start(addr, false)?;
+        self.write_bytes(bytes)
+    }
+
     fn stop(&mut self) -> Result<()> {
         // Stop the transmission by setting the MSTSTOP bit to 1 in the master control register.
         self.i2c.mstctl.write(|w| w.mststop().stop());
@@ -217,6 +350,20 @@ where
     }
 }
 
+/// Masks the interrupts and gates the clock.
+impl<PIO1, PIO2, I2C, PINS> Drop for I2cMaster<PIO1, PIO2, I2C, PINS>
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    I2C: I2c,
+    PINS: I2cPins<PIO1, PIO2, I2C>,
+{
+    fn drop(&mut self) {
+        self.i2c.intenclr.write(|w| unsafe { w.bits(!0) });
+        syscon::disable_clock_on_drop(&self.i2c);
+    }
+}
+
 impl<PIO1, PIO2, I2C, PINS> Write for I2cMaster<PIO1, PIO2, I2C, PINS>
 where
     PIO1: PinId,
@@ -242,40 +389,9 @@ where
     type Error = Error;
 
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<()> {
-        if let Some((last, buffer)) = buffer.split_last_mut() {
-
-            // Write the slave address with the RW bit set to 1 to the master data register MSTDAT.
-            self.i2c.mstdat.modify(|_, w| unsafe { w.data().bits((addr << 1) | 1) } );
-            // Start the transmission by setting the MSTSTART bit to 1 in the master control register.
-            self.i2c.mstctl.write(|w| w.mststart().start());
-
-            // Wait for the pending status to be set (MSTPENDING = 1) by polling the STAT register
-            while self.i2c.stat.read().mstpending().is_in_progress() {}
-
-            self.return_on_error()?;
-            if !self.i2c.stat.read().mststate().is_receive_ready() {
-                return Err(Error::Bus);
-            }
-
-
-            for byte in buffer {
-                // Read a byte
-                *byte = self.i2c.mstdat.read().data().bits();
-                // Instruct master to continue
-                self.i2c.mstctl.write(|w| w.mstcontinue().continue_());
-
-                // Wait for next byte
-                while self.i2c.stat.read().mstpending().is_in_progress() {}
-
-                self.return_on_error()?;
-                if !self.i2c.stat.read().mststate().is_receive_ready() {
-                    return Err(Error::Bus);
-                }
-            }
-
-            // Read last byte
-            *last = self.i2c.mstdat.read().data().bits();
-
+        if !buffer.is_empty() {
+            self.start(addr, true)?;
+            self.read_bytes(buffer, false)?;
             self.stop()?;
         }
 
@@ -301,6 +417,249 @@ where
     }
 }
 
+#[cfg(feature = "eh1")]
+mod eh1_impls {
+    use crate::traits::eh1::i2c::{
+        self,
+        ErrorKind,
+        ErrorType,
+        NoAcknowledgeSource,
+        Operation,
+        SevenBitAddress,
+    };
+    use crate::typestates::pin::{
+        flexcomm::{
+            I2c,
+            I2cPins,
+        },
+        PinId,
+    };
+    use super::{Error, I2cMaster, Result};
+
+    impl i2c::Error for Error {
+        fn kind(&self) -> ErrorKind {
+            match self {
+                Error::Bus => ErrorKind::Bus,
+                Error::ArbitrationLoss => ErrorKind::ArbitrationLoss,
+                Error::NackAddress => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+                Error::NackData => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data),
+                _ => ErrorKind::Other,
+            }
+        }
+    }
+
+    impl<PIO1, PIO2, I2C, PINS> ErrorType for I2cMaster<PIO1, PIO2, I2C, PINS>
+    where
+        PIO1: PinId,
+        PIO2: PinId,
+        I2C: I2c,
+        PINS: I2cPins<PIO1, PIO2, I2C>,
+    {
+        type Error = Error;
+    }
+
+    impl<PIO1, PIO2, I2C, PINS> i2c::I2c<SevenBitAddress> for I2cMaster<PIO1, PIO2, I2C, PINS>
+    where
+        PIO1: PinId,
+        PIO2: PinId,
+        I2C: I2c,
+        PINS: I2cPins<PIO1, PIO2, I2C>,
+    {
+        fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<()> {
+            // Adjacent operations of the same kind are not separated by a (repeated) start.
+            // Empty reads are skipped, as the hardware reads a byte on each read start.
+            let mut previous: Option<bool> = None;
+            let count = operations.len();
+            for i in 0..count {
+                let more_reads = operations[i + 1..].iter()
+                    .find(|op| !matches!(op, Operation::Read(buffer) if buffer.is_empty()))
+                    .map(|op| matches!(op, Operation::Read(_)))
+                    .unwrap_or(false);
+                match &mut operations[i] {
+                    Operation::Write(bytes) => {
+                        if previous != Some(false) {
+                            self.start(address, false)?;
+                        }
+                        self.write_bytes(bytes)?;
+                        previous = Some(false);
+                    }
+                    Operation::Read(buffer) => {
+                        if buffer.is_empty() {
+                            continue;
+                        }
+                        if previous != Some(true) {
+                            self.start(address, true)?;
+                        }
+                        self.read_bytes(buffer, more_reads)?;
+                        previous = Some(true);
+                    }
+                }
+            }
+
+            if previous.is_some() {
+                self.stop()?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+mod async_impls {
+    use embedded_hal_async::i2c::{self, Operation, SevenBitAddress};
+    use crate::drivers::asynch;
+    use crate::typestates::pin::{
+        flexcomm::{
+            I2c,
+            I2cPins,
+        },
+        PinId,
+    };
+    use super::{Error, I2cMaster, Result};
+
+    /// Needs `asynch::FlexcommInterruptHandler` bound to the Flexcomm's interrupt.
+    impl<PIO1, PIO2, I2C, PINS> I2cMaster<PIO1, PIO2, I2C, PINS>
+    where
+        PIO1: PinId,
+        PIO2: PinId,
+        I2C: I2c,
+        PINS: I2cPins<PIO1, PIO2, I2C>,
+    {
+        async fn pending(&mut self) {
+            let i2c = &self.i2c;
+            let index = asynch::flexcomm_index(&**i2c as *const _);
+            asynch::wait_for(
+                &asynch::FLEXCOMM_WAKERS[index],
+                || {
+                    let stat = i2c.stat.read();
+                    stat.mstpending().is_pending()
+                        || stat.mstarbloss().is_arbitration_loss()
+                        || stat.mstststperr().is_error()
+                },
+                || i2c.intenset.write(|w| w
+                    .mstpendingen().enabled()
+                    .mstarblossen().enabled()
+                    .mstststperren().enabled()
+                ),
+            ).await
+        }
+
+        async fn start_async(&mut self, addr: u8, read: bool) -> Result<()> {
+            self.return_on_error()?;
+
+            self.i2c
+                .mstdat
+                .modify(|_, w| unsafe { w.data().bits((addr << 1) | read as u8) });
+            self.i2c.mstctl.write(|w| w.mststart().start());
+            self.pending().await;
+
+            self.return_on_error()?;
+            let ready = if read {
+                self.i2c.stat.read().mststate().is_receive_ready()
+            } else {
+                self.i2c.stat.read().mststate().is_transmit_ready()
+            };
+            if !ready {
+                return Err(Error::Bus);
+            }
+
+            Ok(())
+        }
+
+        async fn write_bytes_async(&mut self, bytes: &[u8]) -> Result<()> {
+            for byte in bytes {
+                self.i2c.mstdat.modify(|_, w| unsafe { w.data().bits(*byte) } );
+                self.i2c.mstctl.write(|w| w.mstcontinue().continue_());
+                self.pending().await;
+
+                self.return_on_error()?;
+                if !self.i2c.stat.read().mststate().is_transmit_ready() {
+                    return Err(Error::Bus);
+                }
+            }
+
+            Ok(())
+        }
+
+        async fn read_bytes_async(&mut self, buffer: &mut [u8], more: bool) -> Result<()> {
+            let len = buffer.len();
+            for (i, byte) in buffer.iter_mut().enumerate() {
+                *byte = self.i2c.mstdat.read().data().bits();
+
+                if i + 1 == len && !more {
+                    break;
+                }
+
+                self.i2c.mstctl.write(|w| w.mstcontinue().continue_());
+                self.pending().await;
+
+                self.return_on_error()?;
+                if !self.i2c.stat.read().mststate().is_receive_ready() {
+                    return Err(Error::Bus);
+                }
+            }
+
+            Ok(())
+        }
+
+        async fn stop_async(&mut self) -> Result<()> {
+            self.i2c.mstctl.write(|w| w.mststop().stop());
+            self.pending().await;
+
+            self.return_on_error()?;
+            if !self.i2c.stat.read().mststate().is_idle() {
+                return Err(Error::Bus);
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<PIO1, PIO2, I2C, PINS> i2c::I2c<SevenBitAddress> for I2cMaster<PIO1, PIO2, I2C, PINS>
+    where
+        PIO1: PinId,
+        PIO2: PinId,
+        I2C: I2c,
+        PINS: I2cPins<PIO1, PIO2, I2C>,
+    {
+        async fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<()> {
+            // Same framing as the blocking `transaction`.
+            let mut previous: Option<bool> = None;
+            let count = operations.len();
+            for i in 0..count {
+                let more_reads = operations[i + 1..].iter()
+                    .find(|op| !matches!(op, Operation::Read(buffer) if buffer.is_empty()))
+                    .map(|op| matches!(op, Operation::Read(_)))
+                    .unwrap_or(false);
+                match &mut operations[i] {
+                    Operation::Write(bytes) => {
+                        if previous != Some(false) {
+                            self.start_async(address, false).await?;
+                        }
+                        self.write_bytes_async(bytes).await?;
+                        previous = Some(false);
+                    }
+                    Operation::Read(buffer) => {
+                        if buffer.is_empty() {
+                            continue;
+                        }
+                        if previous != Some(true) {
+                            self.start_async(address, true).await?;
+                        }
+                        self.read_bytes_async(buffer, more_reads).await?;
+                        previous = Some(true);
+                    }
+                }
+            }
+
+            if previous.is_some() {
+                self.stop_async().await?;
+            }
+            Ok(())
+        }
+    }
+}
+
         // impl<PINS> I2cCommon for I2cMaster<I2C, PINS>
         // where
         //     PINS: I2cPins<I2C>,
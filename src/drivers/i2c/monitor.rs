@@ -0,0 +1,193 @@
+//! The I2C monitor function, passively capturing the bus traffic.
+//!
+//! The monitor sees every byte on the bus, with the start conditions before it
+//! and whether it was acknowledged, so it makes an in-system sniffer:
+//!
+//! ```ignore
+//! let mut monitor = I2cMonitor::new(i2c, (scl, sda), true);
+//! let mut transaction = [Capture::default(); 64];
+//! loop {
+//!     let len = monitor.capture(&mut transaction).unwrap();
+//!     for byte in &transaction[..len] {
+//!         defmt::info!("{}", byte);
+//!     }
+//! }
+//! ```
+//!
+//! Bytes not read before the next one is complete are lost, unless the monitor
+//! stretches the clock, which slows down the bus to the pace of software.
+
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::peripherals::{flexcomm::Repurpose, syscon};
+use crate::typestates::pin::{
+    flexcomm::{
+        I2c,
+        I2cPins,
+    },
+    PinId,
+};
+
+/// A byte seen on the bus
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Capture {
+    pub data: u8,
+    /// Follows a start, so it's an address (with the RW bit)
+    pub start: bool,
+    /// Follows a repeated start, so it's an address (with the RW bit)
+    pub restart: bool,
+    /// Was not acknowledged
+    pub nack: bool,
+}
+
+/// Bytes were lost, as they were not read in time (STAT.MONOV)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Overrun;
+
+impl core::fmt::Display for Overrun {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("I2C monitor overrun")
+    }
+}
+
+impl core::error::Error for Overrun {}
+
+/// I2C peripheral operating as bus monitor, see the module documentation
+pub struct I2cMonitor<PIO1, PIO2, I2C, PINS>
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    I2C: I2c,
+    PINS: I2cPins<PIO1, PIO2, I2C>,
+{
+    i2c: I2C,
+    pins: PINS,
+    _pin1: PhantomData<PIO1>,
+    _pin2: PhantomData<PIO2>,
+}
+
+impl<PIO1, PIO2, I2C, PINS> I2cMonitor<PIO1, PIO2, I2C, PINS>
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    I2C: I2c,
+    PINS: I2cPins<PIO1, PIO2, I2C>,
+{
+    /// Monitor the bus, stretching the clock until each byte is read if `clock_stretching`.
+    pub fn new(i2c: I2C, pins: PINS, clock_stretching: bool) -> Self {
+        i2c.cfg.modify(|_, w| w
+            .monen().enabled()
+            .monclkstr().bit(clock_stretching)
+        );
+        // clear by writing 1
+        i2c.stat.write(|w| w.monov().set_bit().monidle().set_bit());
+
+        Self {
+            i2c,
+            pins,
+            _pin1: PhantomData,
+            _pin2: PhantomData,
+        }
+    }
+
+    pub fn release(self) -> (I2C, PINS) {
+        let this = ManuallyDrop::new(self);
+        unsafe { (ptr::read(&this.i2c), ptr::read(&this.pins)) }
+    }
+
+    /// Masks the interrupts and tears down the I2C personality, returning
+    /// the disabled Flexcomm (to enable it as another) and the pins.
+    pub fn teardown(self, syscon: &mut syscon::Syscon) -> (I2C::Flexcomm, PINS)
+    where
+        I2C: Repurpose,
+    {
+        self.i2c.intenclr.write(|w| unsafe { w.bits(!0) });
+        let (i2c, pins) = self.release();
+        (i2c.teardown(syscon), pins)
+    }
+
+    /// Interrupt when a byte was captured, bytes were lost, or the bus became idle.
+    pub fn listen(&mut self) {
+        self.i2c.intenset.write(|w| w
+            .monrdyen().enabled()
+            .monoven().enabled()
+            .monidleen().enabled()
+        );
+    }
+
+    pub fn unlisten(&mut self) {
+        self.i2c.intenclr.write(|w| w
+            .monrdyclr().set_bit()
+            .monovclr().set_bit()
+            .monidleclr().set_bit()
+        );
+    }
+
+    /// A master is on the bus: there was a start more recently than a stop.
+    pub fn is_active(&self) -> bool {
+        self.i2c.stat.read().monactive().bit_is_set()
+    }
+
+    /// The next byte captured.
+    ///
+    /// After an `Overrun`, the following bytes are captured again.
+    pub fn read(&mut self) -> nb::Result<Capture, Overrun> {
+        let stat = self.i2c.stat.read();
+        if stat.monov().bit_is_set() {
+            self.i2c.stat.write(|w| w.monov().set_bit());
+            return Err(nb::Error::Other(Overrun));
+        }
+        if stat.monrdy().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+        // clears MONRDY, ending the clock stretching
+        let monrxdat = self.i2c.monrxdat.read();
+        Ok(Capture {
+            data: monrxdat.monrxdat().bits(),
+            start: monrxdat.monstart().is_start_detected(),
+            restart: monrxdat.monrestart().is_detected(),
+            nack: monrxdat.monnack().is_not_acknowledged(),
+        })
+    }
+
+    /// Wait for traffic, and capture it into `buffer` until the bus becomes
+    /// idle, or the buffer is full. Returns the number of bytes captured.
+    pub fn capture(&mut self, buffer: &mut [Capture]) -> Result<usize, Overrun> {
+        // idle since before doesn't count
+        self.i2c.stat.write(|w| w.monidle().set_bit());
+        let mut len = 0;
+        while len < buffer.len() {
+            match self.read() {
+                Ok(capture) => {
+                    buffer[len] = capture;
+                    len += 1;
+                }
+                Err(nb::Error::Other(overrun)) => return Err(overrun),
+                Err(nb::Error::WouldBlock) => {
+                    if len > 0 && self.i2c.stat.read().monidle().bit_is_set() {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(len)
+    }
+}
+
+/// Masks the interrupts and gates the clock.
+impl<PIO1, PIO2, I2C, PINS> Drop for I2cMonitor<PIO1, PIO2, I2C, PINS>
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    I2C: I2c,
+    PINS: I2cPins<PIO1, PIO2, I2C>,
+{
+    fn drop(&mut self) {
+        self.i2c.intenclr.write(|w| unsafe { w.bits(!0) });
+        syscon::disable_clock_on_drop(&self.i2c);
+    }
+}
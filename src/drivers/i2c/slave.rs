@@ -0,0 +1,230 @@
+//! The I2C slave function, to emulate a device such as a battery gauge.
+//!
+//! The slave answers to up to four addresses, and stretches the clock until
+//! software handles each address, received byte or byte to transmit. `service`
+//! does so by calling the event handlers of a `Device`:
+//!
+//! ```ignore
+//! struct Gauge { register: u8 }
+//!
+//! impl Device for Gauge {
+//!     fn on_write(&mut self, _address: u8, byte: u8) -> bool {
+//!         self.register = byte;
+//!         true
+//!     }
+//!
+//!     fn on_read(&mut self, _address: u8) -> u8 {
+//!         let value = read_register(self.register);
+//!         self.register = self.register.wrapping_add(1);
+//!         value
+//!     }
+//!
+//!     fn on_stop(&mut self, _address: u8) {}
+//! }
+//!
+//! let mut slave = I2cSlave::new(i2c, (scl, sda), &[0x55], Mode::Fast);
+//! loop {
+//!     slave.service(&mut gauge);
+//! }
+//! ```
+//!
+//! To service the slave from the Flexcomm interrupt instead, `listen`, and call
+//! `service` from the handler registered with `flexcomm::dispatch`.
+
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::peripherals::{flexcomm::Repurpose, syscon};
+use crate::typestates::pin::{
+    flexcomm::{
+        I2c,
+        I2cPins,
+    },
+    PinId,
+};
+use super::Mode;
+
+/// The device emulated by an `I2cSlave`, see the module documentation
+pub trait Device {
+    /// A master addressed `address` (one of the slave's), to read from it if `read`.
+    ///
+    /// Returning false NACKs the address. Repeated starts are reported as well.
+    fn on_start(&mut self, address: u8, read: bool) -> bool {
+        let _ = (address, read);
+        true
+    }
+
+    /// The master wrote `byte` to `address`; returning false NACKs it.
+    fn on_write(&mut self, address: u8, byte: u8) -> bool;
+
+    /// The master reads a byte from `address`.
+    fn on_read(&mut self, address: u8) -> u8;
+
+    /// The transaction with `address` ended, by a stop or the master addressing
+    /// another device.
+    fn on_stop(&mut self, address: u8);
+}
+
+/// I2C peripheral operating in slave mode
+pub struct I2cSlave<PIO1, PIO2, I2C, PINS>
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    I2C: I2c,
+    PINS: I2cPins<PIO1, PIO2, I2C>,
+{
+    i2c: I2C,
+    pins: PINS,
+    _pin1: PhantomData<PIO1>,
+    _pin2: PhantomData<PIO2>,
+    // the address of the ongoing transaction
+    selected: Option<u8>,
+}
+
+impl<PIO1, PIO2, I2C, PINS> I2cSlave<PIO1, PIO2, I2C, PINS>
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    I2C: I2c,
+    PINS: I2cPins<PIO1, PIO2, I2C>,
+{
+    /// Slave answering to the 7-bit `addresses` (one to four) on a bus of `mode`.
+    ///
+    /// The data setup time of `mode` is derived from the Flexcomm's function clock.
+    pub fn new(i2c: I2C, pins: PINS, addresses: &[u8], mode: Mode) -> Self {
+        assert!(!addresses.is_empty() && addresses.len() <= 4);
+        assert!(addresses.iter().all(|&address| address < 0x80));
+
+        // tSU;DAT, as in `fsl_i2c.c` of the SDK
+        let setup_ns: u32 = match mode {
+            Mode::Standard => 250,
+            Mode::Fast => 100,
+            Mode::FastPlus => 50,
+            Mode::HighSpeed => 10,
+        };
+        let clock_khz = i2c.clock_frequency().expect("unknown function clock").to_Hz() / 1_000;
+        let div = (clock_khz * setup_ns / 1_000_000).min(0xFFFF);
+        i2c.clkdiv.write(|w| unsafe { w.divval().bits(div as u16) });
+
+        for index in 0..4 {
+            // SADISABLE unless used
+            let bits = match addresses.get(index) {
+                Some(&address) => (address as u32) << 1,
+                None => 1,
+            };
+            match index {
+                0 => i2c.slvadr0.write(|w| unsafe { w.bits(bits) }),
+                1 => i2c.slvadr1.write(|w| unsafe { w.bits(bits) }),
+                2 => i2c.slvadr2.write(|w| unsafe { w.bits(bits) }),
+                _ => i2c.slvadr3.write(|w| unsafe { w.bits(bits) }),
+            }
+        }
+
+        i2c.cfg.modify(|_, w| w
+            .slven().enabled()
+            .hscapable().bit(mode == Mode::HighSpeed)
+        );
+
+        Self {
+            i2c,
+            pins,
+            _pin1: PhantomData,
+            _pin2: PhantomData,
+            selected: None,
+        }
+    }
+
+    pub fn release(self) -> (I2C, PINS) {
+        let this = ManuallyDrop::new(self);
+        unsafe { (ptr::read(&this.i2c), ptr::read(&this.pins)) }
+    }
+
+    /// Masks the interrupts and tears down the I2C personality, returning
+    /// the disabled Flexcomm (to enable it as another) and the pins.
+    pub fn teardown(self, syscon: &mut syscon::Syscon) -> (I2C::Flexcomm, PINS)
+    where
+        I2C: Repurpose,
+    {
+        self.i2c.intenclr.write(|w| unsafe { w.bits(!0) });
+        let (i2c, pins) = self.release();
+        (i2c.teardown(syscon), pins)
+    }
+
+    /// Interrupt when the slave needs servicing.
+    pub fn listen(&mut self) {
+        self.i2c.intenset.write(|w| w.slvpendingen().enabled().slvdeselen().enabled());
+    }
+
+    pub fn unlisten(&mut self) {
+        self.i2c.intenclr.write(|w| w.slvpendingclr().set_bit().slvdeselclr().set_bit());
+    }
+
+    /// The address of the ongoing transaction, if any
+    pub fn selected(&self) -> Option<u8> {
+        self.selected
+    }
+
+    /// Handle the pending events by calling `device`, returning whether there were any.
+    ///
+    /// The clock is stretched until an event is handled, so the master waits
+    /// for the slave to be serviced.
+    pub fn service<D: Device>(&mut self, device: &mut D) -> bool {
+        let mut serviced = false;
+        loop {
+            let stat = self.i2c.stat.read();
+            if stat.slvdesel().bit_is_set() {
+                // clear by writing 1
+                self.i2c.stat.write(|w| w.slvdesel().set_bit());
+                if let Some(address) = self.selected.take() {
+                    device.on_stop(address);
+                }
+            } else if stat.slvpending().bit_is_set() {
+                let state = stat.slvstate();
+                let ack = if state.is_slave_address() {
+                    let byte = self.i2c.slvdat.read().data().bits();
+                    let (address, read) = (byte >> 1, byte & 1 != 0);
+                    if let Some(previous) = self.selected.take() {
+                        if previous != address {
+                            device.on_stop(previous);
+                        }
+                    }
+                    let ack = device.on_start(address, read);
+                    if ack {
+                        self.selected = Some(address);
+                    }
+                    ack
+                } else if state.is_slave_receive() {
+                    let byte = self.i2c.slvdat.read().data().bits();
+                    self.selected.is_some_and(|address| device.on_write(address, byte))
+                } else {
+                    let byte = self.selected.map_or(0xFF, |address| device.on_read(address));
+                    self.i2c.slvdat.write(|w| unsafe { w.data().bits(byte) });
+                    true
+                };
+                if ack {
+                    self.i2c.slvctl.write(|w| w.slvcontinue().continue_());
+                } else {
+                    self.i2c.slvctl.write(|w| w.slvnack().nack());
+                }
+            } else {
+                return serviced;
+            }
+            serviced = true;
+        }
+    }
+}
+
+/// Masks the interrupts and gates the clock.
+impl<PIO1, PIO2, I2C, PINS> Drop for I2cSlave<PIO1, PIO2, I2C, PINS>
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    I2C: I2c,
+    PINS: I2cPins<PIO1, PIO2, I2C>,
+{
+    fn drop(&mut self) {
+        self.i2c.intenclr.write(|w| unsafe { w.bits(!0) });
+        syscon::disable_clock_on_drop(&self.i2c);
+    }
+}
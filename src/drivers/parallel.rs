@@ -0,0 +1,157 @@
+//! Bit-banged parallel bus, for memory-bus style peripherals (e.g. Intel 8080
+//! mode displays such as the ILI9341), which the LPC55 has no controller for.
+//!
+//! The data pins form a `PinGroup`, so they change together; the strobes are
+//! any output pins. Delays are busy-waits on the DWT cycle counter:
+//!
+//! ```ignore
+//! let data = PinGroup::new([d0, d1, d2, d3, d4, d5, d6, d7]).ok().unwrap();
+//! let timing = Timing::from_nanos(15, 30, 10, clocks.system_frequency.convert());
+//! let mut bus = ParallelBus::new(data, wr, rd, cs, timing);
+//!
+//! dc.set_low().ok();
+//! bus.write(0x2c);
+//! dc.set_high().ok();
+//! bus.write_all(&pixels);
+//! ```
+
+use core::convert::Infallible;
+
+use crate::{
+    drivers::pins::PinGroup,
+    time::Megahertz,
+    traits::wg::digital::v2::OutputPin,
+    typestates::pin::gpio::direction,
+};
+
+/// Bus timing, in CPU cycles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timing {
+    /// From data valid (or chip select) to the strobe
+    pub setup: u32,
+    /// Width of the strobe, also the read access time
+    pub strobe: u32,
+    /// From the end of the strobe until the data changes
+    pub hold: u32,
+}
+
+impl Timing {
+    /// The timing for the given durations in nanoseconds, rounded up to cycles
+    /// of the `system_frequency`.
+    pub fn from_nanos(setup: u32, strobe: u32, hold: u32, system_frequency: Megahertz) -> Self {
+        let cycles = |nanos: u32| (nanos * system_frequency.to_MHz()).div_ceil(1_000);
+        Self {
+            setup: cycles(setup),
+            strobe: cycles(strobe),
+            hold: cycles(hold),
+        }
+    }
+}
+
+impl Default for Timing {
+    /// No delays, for slow system clocks
+    fn default() -> Self {
+        Self { setup: 0, strobe: 0, hold: 0 }
+    }
+}
+
+fn delay_cycles(cycles: u32) {
+    if cycles == 0 {
+        return;
+    }
+    let start = crate::get_cycle_count();
+    while crate::get_cycle_count().wrapping_sub(start) < cycles {}
+}
+
+/// Parallel bus of `N` data pins with active-low write, read and chip select strobes
+pub struct ParallelBus<WR, RD, CS, const N: usize>
+where
+    WR: OutputPin<Error = Infallible>,
+    RD: OutputPin<Error = Infallible>,
+    CS: OutputPin<Error = Infallible>,
+{
+    data: PinGroup<direction::Output, N>,
+    wr: WR,
+    rd: RD,
+    cs: CS,
+    timing: Timing,
+}
+
+impl<WR, RD, CS, const N: usize> ParallelBus<WR, RD, CS, N>
+where
+    WR: OutputPin<Error = Infallible>,
+    RD: OutputPin<Error = Infallible>,
+    CS: OutputPin<Error = Infallible>,
+{
+    /// Bit `i` of the words is `data[i]`. Enables the cycle counter, and
+    /// deasserts the strobes.
+    pub fn new(data: PinGroup<direction::Output, N>, mut wr: WR, mut rd: RD, mut cs: CS, timing: Timing) -> Self {
+        crate::enable_cycle_counter();
+        wr.set_high().ok();
+        rd.set_high().ok();
+        cs.set_high().ok();
+        Self { data, wr, rd, cs, timing }
+    }
+
+    pub fn release(self) -> (PinGroup<direction::Output, N>, WR, RD, CS) {
+        (self.data, self.wr, self.rd, self.cs)
+    }
+
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing;
+    }
+
+    fn write_word(&mut self, word: u16) {
+        self.data.write_bus(word as u32);
+        delay_cycles(self.timing.setup);
+        self.wr.set_low().ok();
+        delay_cycles(self.timing.strobe);
+        self.wr.set_high().ok();
+        delay_cycles(self.timing.hold);
+    }
+
+    /// Write one word, in its own chip select cycle.
+    pub fn write(&mut self, word: u16) {
+        self.write_all(&[word]);
+    }
+
+    /// Write `words`, all in one chip select cycle.
+    pub fn write_all(&mut self, words: &[u16]) {
+        self.cs.set_low().ok();
+        for word in words {
+            self.write_word(*word);
+        }
+        self.cs.set_high().ok();
+    }
+
+    fn read_word(&mut self) -> u16 {
+        self.rd.set_low().ok();
+        delay_cycles(self.timing.strobe);
+        let word = self.data.read_bus() as u16;
+        self.rd.set_high().ok();
+        delay_cycles(self.timing.hold);
+        word
+    }
+
+    /// Read one word, in its own chip select cycle.
+    pub fn read(&mut self) -> u16 {
+        let mut word = [0];
+        self.read_all(&mut word);
+        word[0]
+    }
+
+    /// Read into `words`, all in one chip select cycle.
+    ///
+    /// The data pins are inputs for the duration.
+    pub fn read_all(&mut self, words: &mut [u16]) {
+        self.data.release_bus();
+        self.cs.set_low().ok();
+        delay_cycles(self.timing.setup);
+        for word in words.iter_mut() {
+            *word = self.read_word();
+        }
+        self.cs.set_high().ok();
+        self.data.drive_bus();
+    }
+}
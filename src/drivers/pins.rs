@@ -1,7 +1,8 @@
 use crate::{
     peripherals::{
+        adc,
         gpio::Gpio,
-        iocon::Iocon,
+        iocon::{Analog, Digital, Iocon, PinConfig},
         flexcomm,
         ctimer,
     },
@@ -16,6 +17,7 @@ use crate::{
             // All the I2cSclPin etc. are here
             flexcomm as fc,
             flexcomm::ChipSelect,
+            signal::{self, Route},
         },
     },
 };
@@ -27,6 +29,22 @@ pub use crate::typestates::pin::gpio::{
 
 // Implements GPIO pins
 pub mod gpio;
+pub use gpio::ErasedPin;
+
+// Implements single-instruction access to GPIO pins
+mod fast;
+pub use fast::FastPin;
+
+// Implements GPIO pins of one port changing together
+mod group;
+pub use group::PinGroup;
+
+// Implements pins controlled by the secure GPIO
+mod secure;
+pub use secure::SecurePin;
+
+mod inspect;
+pub use inspect::{inspect, PinDirection, PinReport, Pull};
 
 pub use crate::typestates::pin::{
     PinId,
@@ -49,15 +67,7 @@ impl Pin<Pio0_22, state::Unused> {
         self,
         iocon: &mut Iocon<init_state::Enabled>,
     ) -> Pin<Pio0_22, state::Special<function::USB0_VBUS>> {
-        iocon.raw.pio0_22.modify(|_, w|
-            w
-            .func().alt7() // FUNC7, pin configured as USB0_VBUS
-            .mode().inactive() // MODE_INACT, no additional pin function
-            .slew().standard() // SLEW_STANDARD, standard mode, slew rate control is enabled
-            .invert().disabled() // INV_DI, input function is not inverted
-            .digimode().digital() // DIGITAL_EN, enable digital fucntion
-            .od().normal() // OPENDRAIN_DI, open drain is disabled
-        );
+        iocon.configure_pin(0, 22, 7); // FUNC7, pin configured as USB0_VBUS
 
         Pin {
             id: self.id,
@@ -68,6 +78,239 @@ impl Pin<Pio0_22, state::Unused> {
     }
 }
 
+/// Identifies pin `PIOPORT_NUMBER`
+///
+/// The pin types such as `Pio0_5` are aliases of this, so methods common to
+/// all pins are implemented once, and the IOCON configuration they share is
+/// not repeated for every pin.
+pub struct Pio<const PORT: usize, const NUMBER: u8>(());
+
+impl<const PORT: usize, const NUMBER: u8> PinId for Pio<PORT, NUMBER> {
+    const PORT: usize = PORT;
+    const NUMBER: u8 = NUMBER;
+    const MASK: u32 = 0x1 << NUMBER;
+    const OFFSET: usize = (0x20 << PORT) + (0x1 << NUMBER);
+    const TYPE: PinType = PIN_TYPES[PORT][NUMBER as usize];
+}
+
+impl<const PORT: usize, const NUMBER: u8> Pio<PORT, NUMBER> {
+    pub fn release(self) {
+        unsafe { PIN_TAKEN[PORT][NUMBER as usize] = false; }
+    }
+}
+
+impl<const PORT: usize, const NUMBER: u8> Pin<Pio<PORT, NUMBER>, state::Unused> {
+    /// Transition pin to GPIO state
+    pub fn into_gpio_pin(
+        self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        _: &mut Gpio<init_state::Enabled>,
+    ) -> Pin<Pio<PORT, NUMBER>, state::Gpio<direction::Unknown>> {
+        iocon.configure_pin(PORT, NUMBER, 0);
+        Pin {
+            id: self.id,
+            state: state::Gpio {
+                // b: RegClusterProxy::new(),
+                // w: RegClusterProxy::new(),
+                dirset: RegClusterProxy::new(),
+                dirclr: RegClusterProxy::new(),
+                pin: RegClusterProxy::new(),
+                set: RegClusterProxy::new(),
+                clr: RegClusterProxy::new(),
+                not: RegClusterProxy::new(),
+
+                _direction: direction::Unknown,
+            },
+        }
+    }
+
+    /// Transition pin to GPIO input
+    pub fn into_gpio_input(
+        self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        gpio: &mut Gpio<init_state::Enabled>,
+    ) -> Pin<Pio<PORT, NUMBER>, state::Gpio<direction::Input>> {
+        self.into_gpio_pin(iocon, gpio).into_input()
+    }
+
+    /// Transition pin to GPIO output, at level `initial`
+    pub fn into_gpio_output(
+        self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        gpio: &mut Gpio<init_state::Enabled>,
+        initial: Level,
+    ) -> Pin<Pio<PORT, NUMBER>, state::Gpio<direction::Output>> {
+        self.into_gpio_pin(iocon, gpio).into_output(initial)
+    }
+}
+
+/// Generates the generic routing to each Flexcomm signal, see `signal::Route`
+macro_rules! route_methods {
+    ($($method:ident: $Peripheral:ident, $Marker:ident;)*) => {
+        impl<T: PinId> Pin<T, state::Unused> {
+            $(
+                pub fn $method<P: fc::$Peripheral>(
+                    self,
+                    iocon: &mut Iocon<init_state::Enabled>,
+                ) -> Pin<T, Special<<T as Route<P, signal::$Marker>>::Function>>
+                where
+                    T: Route<P, signal::$Marker>,
+                {
+                    self.into_signal::<P, signal::$Marker>(iocon)
+                }
+            )*
+        }
+    }
+}
+
+impl<T: PinId> Pin<T, state::Unused> {
+    /// Route the pin to signal `S` of peripheral `P`, if it can carry it.
+    pub fn into_signal<P, S>(
+        self,
+        iocon: &mut Iocon<init_state::Enabled>,
+    ) -> Pin<T, Special<<T as Route<P, S>>::Function>>
+    where
+        T: Route<P, S>,
+    {
+        iocon.configure_pin(T::PORT, T::NUMBER, <T as Route<P, S>>::FUNC);
+        Pin {
+            id: self.id,
+            state: Special {
+                _function: <T as Route<P, S>>::function(),
+            },
+        }
+    }
+}
+
+route_methods! {
+    into_usart_tx: Usart, UsartTxPin;
+    into_usart_rx: Usart, UsartRxPin;
+    into_usart_rts: Usart, UsartRtsPin;
+    into_usart_cts: Usart, UsartCtsPin;
+    into_usart_sclk: Usart, UsartSclkPin;
+    into_spi_sck: Spi, SpiSckPin;
+    into_spi_mosi: Spi, SpiMosiPin;
+    into_spi_miso: Spi, SpiMisoPin;
+    into_spi_cs: Spi, SpiCsPin;
+    into_i2c_scl: I2c, I2cSclPin;
+    into_i2c_sda: I2c, I2cSdaPin;
+    into_i2s_sck: I2s, I2sSckPin;
+    into_i2s_sda: I2s, I2sSdaPin;
+    into_i2s_ws: I2s, I2sWsPin;
+}
+
+impl<T: PinId, F: function::Function> Pin<T, state::Special<F>> {
+    /// Return the pin from its special function (e.g. after tearing down the
+    /// driver using it), routing it back to GPIO.
+    pub fn into_unused_pin(self, iocon: &mut Iocon<init_state::Enabled>) -> Pin<T, state::Unused> {
+        iocon.configure_pin(T::PORT, T::NUMBER, 0);
+        Pin {
+            id: self.id,
+            state: state::Unused,
+        }
+    }
+}
+
+impl<T: PinId, D: direction::Direction> Pin<T, state::Gpio<D>> {
+    /// Apply the electrical configuration `config`, see `PinConfig`.
+    ///
+    /// Open drain pins stay open drain.
+    pub fn configure(&mut self, iocon: &mut Iocon<init_state::Enabled>, config: PinConfig<Digital>) {
+        let config = if D::OPEN_DRAIN { config.open_drain(true) } else { config };
+        iocon.apply_config(T::PORT, T::NUMBER, T::TYPE, config);
+    }
+
+    /// Change part of the electrical configuration, keeping the rest:
+    ///
+    /// ```ignore
+    /// pin.modify_config(&mut iocon, |config| config.slew(Slew::Fast));
+    /// ```
+    pub fn modify_config(
+        &mut self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        f: impl FnOnce(PinConfig<Digital>) -> PinConfig<Digital>,
+    ) {
+        let config = f(iocon.read_config(T::PORT, T::NUMBER, T::TYPE, Digital));
+        self.configure(iocon, config);
+    }
+
+    /// Route the pin to signal `S` of peripheral `P`, like `into_signal`, but
+    /// only changing its function: pull, slew rate etc. are kept.
+    pub fn reconfigure<P, S>(
+        self,
+        iocon: &mut Iocon<init_state::Enabled>,
+    ) -> Pin<T, Special<<T as Route<P, S>>::Function>>
+    where
+        T: Route<P, S>,
+    {
+        iocon.set_function(T::PORT, T::NUMBER, <T as Route<P, S>>::FUNC);
+        Pin {
+            id: self.id,
+            state: Special {
+                _function: <T as Route<P, S>>::function(),
+            },
+        }
+    }
+}
+
+impl<T: PinId, F: function::Function> Pin<T, state::Special<F>> {
+    /// Apply the electrical configuration `config`, see `PinConfig`.
+    pub fn configure(&mut self, iocon: &mut Iocon<init_state::Enabled>, config: PinConfig<Digital>) {
+        iocon.apply_config(T::PORT, T::NUMBER, T::TYPE, config);
+    }
+
+    /// Change part of the electrical configuration, keeping the rest.
+    pub fn modify_config(
+        &mut self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        f: impl FnOnce(PinConfig<Digital>) -> PinConfig<Digital>,
+    ) {
+        let config = f(iocon.read_config(T::PORT, T::NUMBER, T::TYPE, Digital));
+        self.configure(iocon, config);
+    }
+
+    /// Route the pin back to GPIO, like `into_unused_pin` and `into_gpio_pin`,
+    /// but only changing its function: pull, slew rate etc. are kept.
+    ///
+    /// The GPIO direction is left as it was, hence `Unknown`.
+    pub fn reconfigure_gpio(
+        self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        _: &mut Gpio<init_state::Enabled>,
+    ) -> Pin<T, state::Gpio<direction::Unknown>> {
+        iocon.set_function(T::PORT, T::NUMBER, 0);
+        Pin {
+            id: self.id,
+            state: state::Gpio {
+                dirset: RegClusterProxy::new(),
+                dirclr: RegClusterProxy::new(),
+                pin: RegClusterProxy::new(),
+                set: RegClusterProxy::new(),
+                clr: RegClusterProxy::new(),
+                not: RegClusterProxy::new(),
+
+                _direction: direction::Unknown,
+            },
+        }
+    }
+}
+
+impl<T: PinId, D: direction::Direction> Pin<T, state::Analog<D>> {
+    /// Apply the electrical configuration `config`, see `PinConfig`.
+    pub fn configure(&mut self, iocon: &mut Iocon<init_state::Enabled>, config: PinConfig<Analog>) {
+        iocon.apply_config(T::PORT, T::NUMBER, T::TYPE, config);
+    }
+
+    /// Change part of the electrical configuration, keeping the rest.
+    pub fn modify_config(
+        &mut self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        f: impl FnOnce(PinConfig<Analog>) -> PinConfig<Analog>,
+    ) {
+        let config = f(iocon.read_config(T::PORT, T::NUMBER, T::TYPE, Analog));
+        self.configure(iocon, config);
+    }
+}
 
 // seems a bit inefficient, but want to be able to safely
 // take individual pins instead of the whole bunch
@@ -128,6 +371,11 @@ macro_rules! pins {
                 Self::set_all_released();
             }
 
+            /// Take all pins, even if they are owned elsewhere.
+            ///
+            /// # Safety
+            ///
+            /// The caller must ensure the other instances are not used concurrently.
             pub unsafe fn steal() -> Self {
                 Self {
                     $(
@@ -138,47 +386,17 @@ macro_rules! pins {
         }
 
 
+        const PIN_TYPES: [[PinType; 32]; 2] = {
+            let mut types = [[PinType::D; 32]; 2];
+            $(types[$port][$number] = $type;)*
+            types
+        };
+
         $(
             /// Identifies a specific pin
             ///
             /// Pins can be `take`n individually, or en bloc via `Pins`.
-            #[allow(non_camel_case_types)]
-            pub struct $pin(());
-
-            impl Pin<$pin, state::Unused>  {
-                /// Transition pin to GPIO state
-                pub fn into_gpio_pin(
-                    self,
-                    iocon: &mut Iocon<init_state::Enabled>,
-                    _: &mut Gpio<init_state::Enabled>,
-                ) -> Pin<$pin, state::Gpio<direction::Unknown>> {
-                    // TODO: need to set FUNC to 0 at minimum
-                    iocon.raw.$field.modify(|_, w| w
-                        .func().alt0() // FUNC $i, pin configured as $FUNCTION
-                        .mode().inactive() // MODE_INACT, no additional pin function
-                        .slew().standard() // SLEW_STANDARD, standard mode, slew rate control is enabled
-                        .invert().disabled() // INV_DI, input function is not inverted
-                        .digimode().digital() // DIGITAL_EN, enable digital fucntion
-                        .od().normal() // OPENDRAIN_DI, open drain is disabled
-                    );
-                    Pin {
-                        id: self.id,
-                        state: state::Gpio {
-                            // b: RegClusterProxy::new(),
-                            // w: RegClusterProxy::new(),
-                            dirset: RegClusterProxy::new(),
-                            dirclr: RegClusterProxy::new(),
-                            pin: RegClusterProxy::new(),
-                            set: RegClusterProxy::new(),
-                            clr: RegClusterProxy::new(),
-
-                            _direction: direction::Unknown,
-                        },
-                    }
-                }
-
-
-            }
+            pub type $pin = Pio<$port, $number>;
 
             impl $pin {
                 pub fn take() -> Option<Pin<Self, $default_state_ty>> {
@@ -191,10 +409,11 @@ macro_rules! pins {
                     }
                 }
 
-                pub fn release(self) {
-                    unsafe { PIN_TAKEN[$port][$number] = false; }
-                }
-
+                /// Take the pin in its default state, even if it is owned elsewhere.
+                ///
+                /// # Safety
+                ///
+                /// The caller must ensure the other instance is not used concurrently.
                 pub unsafe fn steal() -> Pin<Self, $default_state_ty> {
                     PIN_TAKEN[$port][$number] = true;
                     Pin {
@@ -203,18 +422,13 @@ macro_rules! pins {
                     }
                 }
             }
-
-            impl PinId for $pin {
-                const PORT: usize = $port;
-                const NUMBER: u8 = $number;
-                const MASK: u32 = 0x1 << $number;
-                const OFFSET: usize = (0x20 << $port) + (0x1 << $number);
-                const TYPE: PinType = $type;
-            }
         )*
     }
 }
 
+/// An analog input pin, see `Pin::into_analog`
+pub type AnalogPin<T> = Pin<T, state::Analog<direction::Input>>;
+
 macro_rules! analog_pins {
     ($(
         $field:ident,
@@ -224,27 +438,18 @@ macro_rules! analog_pins {
         $type:expr,
         $default_state_ty:ty,
         $default_state_val:expr,
-        $channel:expr;
+        $channel:tt;
     )*) => {
-        /// Transition pin to Analog input
         $(
             impl Pin<$pin, state::Unused>  {
-                pub fn into_analog_input(
+                /// Transition pin to analog input, disabling its digital input buffer
+                pub fn into_analog(
                     self,
                     iocon: &mut Iocon<init_state::Enabled>,
                     _: &mut Gpio<init_state::Enabled>,
-                ) -> Pin<$pin, state::Analog<direction::Input>> {
-
-                    // TODO: need to set FUNC to 0 at minimum
-                    iocon.raw.$field.modify(|_, w| w
-                        .func().alt0() // FUNC $i, pin configured as $FUNCTION
-                        .mode().inactive() // MODE_INACT, no additional pin function
-                        .slew().standard() // SLEW_STANDARD, standard mode, slew rate control is enabled
-                        .invert().disabled() // INV_DI, input function is not inverted
-                        .digimode().analog() // DIGITAL_EN, enable digital fucntion
-                        .od().normal() // OPENDRAIN_DI, open drain is disabled
-                        .asw().set_bit() // ASW, analog input enabled
-                    );
+                ) -> AnalogPin<$pin> {
+
+                    iocon.configure_analog_pin($port, $number);
 
                     // self.state.dirclr[T::PORT].write(|w| unsafe { w.dirclrp().bits(T::MASK) });
                     let pin = Pin {
@@ -257,9 +462,29 @@ macro_rules! analog_pins {
                     };
                     return pin.into_input();
                 }
+
+                /// Transition pin to Analog input, same as `into_analog`
+                pub fn into_analog_input(
+                    self,
+                    iocon: &mut Iocon<init_state::Enabled>,
+                    gpio: &mut Gpio<init_state::Enabled>,
+                ) -> AnalogPin<$pin> {
+                    self.into_analog(iocon, gpio)
+                }
             }
+
+            analog_pins!(@channel $pin, $channel);
         )*
-    }
+    };
+    // not an ADC input
+    (@channel $pin:ident, 0xffu8) => {};
+    // ADC0_8 to ADC0_12 are the B sides of channels 0 to 4
+    (@channel $pin:ident, $channel:expr) => {
+        impl adc::Channel for AnalogPin<$pin> {
+            const CHANNEL: u8 = $channel % 8;
+            const SIDE: adc::Side = if $channel < 8 { adc::Side::A } else { adc::Side::B };
+        }
+    };
 }
 
 macro_rules! ctimer_match_output_pins {
@@ -280,15 +505,7 @@ macro_rules! ctimer_match_output_pins {
                     iocon: &mut Iocon<init_state::Enabled>,
                 ) -> Pin<$pin, state::Special<function::$channel_type<$ctimer>>> {
 
-                    // TODO: need to set FUNC to 0 at minimum
-                    iocon.raw.$field.modify(|_, w| unsafe { w
-                        .func().bits($func) // CMAT function
-                        .mode().inactive() // MODE_INACT, no additional pin function
-                        .slew().standard() // SLEW_STANDARD, standard mode, slew rate control is enabled
-                        .invert().disabled() // INV_DI, input function is not inverted
-                        .digimode().digital() // DIGITAL_EN, enable digital function
-                        .od().normal() // OPENDRAIN_DI, open drain is disabled
-                    });
+                    iocon.configure_pin(<$pin as PinId>::PORT, <$pin as PinId>::NUMBER, $func); // CMAT function
 
                     Pin {
                         id: self.id,
@@ -399,7 +616,7 @@ analog_pins!(
     pio1_8 , Pio1_8 , 1,  8, PinType::A, state::Unused, state::Unused, 4u8;
     pio1_9 , Pio1_9 , 1,  9, PinType::A, state::Unused, state::Unused, 12u8;
     pio1_14, Pio1_14, 1, 14, PinType::A, state::Unused, state::Unused, 3u8;
-    pio1_19, Pio1_19, 1, 19, PinType::A, state::Unused, state::Unused, 0xffu8;   // ACMP_ref
+    pio1_19, Pio1_19, 1, 19, PinType::A, state::Unused, state::Unused, 0xffu8;   // ACMP_ref, no ADC channel
 );
 
 ctimer_match_output_pins!(
@@ -419,7 +636,7 @@ macro_rules! special_pins {
             $(
                 ($alt_func:expr, $SPECIAL_FUNCTION:ident): [
                     $(
-                        ($method:ident,$Peripheral:ty,$Marker:ident),
+                        ($method:ident,$Peripheral:ident,$Marker:ident),
                     )*
                 ]
             )+
@@ -431,17 +648,7 @@ macro_rules! special_pins {
                 self,
                 iocon: &mut Iocon<init_state::Enabled>,
             ) ->Pin<$Pin, state::Special<function::$SPECIAL_FUNCTION>> {
-                // unfortunately, data sheet has more FUNCs than SVD has alts
-                // otherwise, it would be safe
-                iocon.raw.$pin.modify(|_, w| unsafe {
-                    w
-                    .func().bits($alt_func) // FUNC $i, pin configured as $FUNCTION
-                    .mode().inactive() // MODE_INACT, no additional pin function
-                    .slew().standard() // SLEW_STANDARD, standard mode, slew rate control is enabled
-                    .invert().disabled() // INV_DI, input function is not inverted
-                    .digimode().digital() // DIGITAL_EN, enable digital function
-                    .od().normal() // OPENDRAIN_DI, open drain is disabled
-                });
+                iocon.configure_pin(<$Pin as PinId>::PORT, <$Pin as PinId>::NUMBER, $alt_func);
 
                 Pin {
                     id: self.id,
@@ -452,6 +659,14 @@ macro_rules! special_pins {
                 }
             }
         }
+
+        impl Route<flexcomm::$Peripheral, signal::$Marker> for $Pin {
+            const FUNC: u8 = $alt_func;
+            type Function = function::$SPECIAL_FUNCTION;
+            fn function() -> Self::Function {
+                function::$SPECIAL_FUNCTION {}
+            }
+        }
     )*)+)*
     }
 }
@@ -462,12 +677,12 @@ macro_rules! special_pins {
 // Note also that these two are precisely the specialized I2C pins.
 ///////////////////////////////////////////////////////////////////////////////
 
-// TODO: remove the $Marker argument
 special_pins!{
     (Pio0_0, pio0_0): {
         (2, FC3_SCK): [
             (into_usart3_sclk_pin, Usart3, UsartSclkPin),
             (into_spi3_sck_pin, Spi3, SpiSckPin),
+            (into_i2s3_sck_pin, I2s3, I2sSckPin),
         ]
     }
     (Pio0_1, pio0_1): {
@@ -497,6 +712,7 @@ special_pins!{
         (2, FC4_SCK): [
             (into_usart4_sclk_pin, Usart4, UsartSclkPin),
             (into_spi4_sck_pin, Spi4, SpiSckPin),
+            (into_i2s4_sck_pin, I2s4, I2sSckPin),
         ]
     }
     (Pio0_5, pio0_5): {
@@ -518,6 +734,7 @@ special_pins!{
         (1, FC3_SCK): [
             (into_usart3_sclk_pin, Usart3, UsartSclkPin),
             (into_spi3_sck_pin, Spi3, SpiSckPin),
+            (into_i2s3_sck_pin, I2s3, I2sSckPin),
         ]
     }
     (Pio0_7, pio0_7): {
@@ -531,12 +748,14 @@ special_pins!{
         (3, FC5_SCK): [
             (into_usart5_sclk_pin, Usart5, UsartSclkPin),
             (into_spi5_sck_pin, Spi5, SpiSckPin),
+            (into_i2s5_sck_pin, I2s5, I2sSckPin),
         ]
     }
     (Pio0_7, pio0_7): {
         (4, FC1_SCK): [
             (into_usart1_sclk_pin, Usart1, UsartSclkPin),
             (into_spi1_sck_pin, Spi1, SpiSckPin),
+            (into_i2s1_sck_pin, I2s1, I2sSckPin),
         ]
     }
     (Pio0_8, pio0_8): {
@@ -569,6 +788,7 @@ special_pins!{
         (1, FC6_SCK): [
             (into_usart6_sclk_pin, Usart6, UsartSclkPin),
             (into_spi6_sck_pin, Spi6, SpiSckPin),
+            (into_i2s6_sck_pin, I2s6, I2sSckPin),
         ]
     }
     (Pio0_10, pio0_10): {
@@ -706,6 +926,7 @@ special_pins!{
         (7, FC7_SCK): [
             (into_usart7_sclk_pin, Usart7, UsartSclkPin),
             (into_spi7_sck_pin, Spi7, SpiSckPin),
+            (into_i2s7_sck_pin, I2s7, I2sSckPin),
         ]
     }
     (Pio0_22, pio0_22): {
@@ -751,6 +972,7 @@ special_pins!{
         (8, FC0_SCK): [
             (into_usart0_sclk_pin, Usart0, UsartSclkPin),
             (into_spi0_sck_pin, Spi0, SpiSckPin),
+            (into_i2s0_sck_pin, I2s0, I2sSckPin),
         ]
     }
     (Pio0_26, pio0_26): {
@@ -778,6 +1000,7 @@ special_pins!{
         (1, FC0_SCK): [
             (into_usart0_sclk_pin, Usart0, UsartSclkPin),
             (into_spi0_sck_pin, Spi0, SpiSckPin),
+            (into_i2s0_sck_pin, I2s0, I2sSckPin),
         ]
     }
     (Pio0_29, pio0_29): {
@@ -837,6 +1060,7 @@ special_pins!{
         (1, FC0_SCK): [
             (into_usart0_sclk_pin, Usart0, UsartSclkPin),
             (into_spi0_sck_pin, Spi0, SpiSckPin),
+            (into_i2s0_sck_pin, I2s0, I2sSckPin),
         ]
     }
     (Pio1_5, pio1_5): {
@@ -878,6 +1102,7 @@ special_pins!{
         (2, FC1_SCK): [
             (into_usart1_sclk_pin, Usart1, UsartSclkPin),
             (into_spi1_sck_pin, Spi1, SpiSckPin),
+            (into_i2s1_sck_pin, I2s1, I2sSckPin),
         ]
     }
     (Pio1_9, pio1_9): {
@@ -907,6 +1132,7 @@ special_pins!{
         (2, FC6_SCK): [
             (into_usart6_sclk_pin, Usart6, UsartSclkPin),
             (into_spi6_sck_pin, Spi6, SpiSckPin),
+            (into_i2s6_sck_pin, I2s6, I2sSckPin),
         ]
     }
     (Pio1_12, pio1_12): {
@@ -962,6 +1188,7 @@ special_pins!{
         (5, FC4_SCK): [
             (into_usart4_sclk_pin, Usart4, UsartSclkPin),
             (into_spi4_sck_pin, Spi4, SpiSckPin),
+            (into_i2s4_sck_pin, I2s4, I2sSckPin),
         ]
     }
     (Pio1_20, pio1_20): {
@@ -1003,6 +1230,7 @@ special_pins!{
         (1, FC2_SCK): [
             (into_usart2_sclk_pin, Usart2, UsartSclkPin),
             (into_spi2_sck_pin, Spi2, SpiSckPin),
+            (into_i2s2_sck_pin, I2s2, I2sSckPin),
         ]
     }
     (Pio1_23, pio1_23): {
@@ -1054,6 +1282,7 @@ special_pins!{
         (1, FC7_SCK): [
             (into_usart7_sclk_pin, Usart7, UsartSclkPin),
             (into_spi7_sck_pin, Spi7, SpiSckPin),
+            (into_i2s7_sck_pin, I2s7, I2sSckPin),
         ]
     }
     (Pio1_29, pio1_29): {
@@ -1106,6 +1335,14 @@ impl<PIO: PinId> fc::I2cSdaPin<PIO, flexcomm::I2c6> for Pin<PIO, Special<functio
 impl<PIO: PinId> fc::I2cSdaPin<PIO, flexcomm::I2c6> for Pin<PIO, Special<function::FC6_RXD_SDA_MOSI_DATA>> {}
 impl<PIO: PinId> fc::I2cSdaPin<PIO, flexcomm::I2c7> for Pin<PIO, Special<function::FC7_CTS_SDA_SSEL0>> {}
 impl<PIO: PinId> fc::I2cSdaPin<PIO, flexcomm::I2c7> for Pin<PIO, Special<function::FC7_RXD_SDA_MOSI_DATA>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s0> for Pin<PIO, Special<function::FC0_SCK>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s1> for Pin<PIO, Special<function::FC1_SCK>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s2> for Pin<PIO, Special<function::FC2_SCK>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s3> for Pin<PIO, Special<function::FC3_SCK>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s4> for Pin<PIO, Special<function::FC4_SCK>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s5> for Pin<PIO, Special<function::FC5_SCK>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s6> for Pin<PIO, Special<function::FC6_SCK>> {}
+impl<PIO: PinId> fc::I2sSckPin<PIO, flexcomm::I2s7> for Pin<PIO, Special<function::FC7_SCK>> {}
 impl<PIO: PinId> fc::I2sSdaPin<PIO, flexcomm::I2s0> for Pin<PIO, Special<function::FC0_RXD_SDA_MOSI_DATA>> {}
 impl<PIO: PinId> fc::I2sSdaPin<PIO, flexcomm::I2s1> for Pin<PIO, Special<function::FC1_RXD_SDA_MOSI_DATA>> {}
 impl<PIO: PinId> fc::I2sSdaPin<PIO, flexcomm::I2s2> for Pin<PIO, Special<function::FC2_RXD_SDA_MOSI_DATA>> {}
@@ -0,0 +1,252 @@
+//! Single-instruction access to individual GPIO pins.
+//!
+//! Besides the port-wide registers, the GPIO has a byte (B) and a word (W)
+//! register per pin. Reading them gives the level of just that pin, and writing
+//! them sets its output, so no masking and no choice between SET and CLR is
+//! needed: a read or a write is a single load or store.
+
+use crate::{
+    raw,
+    traits::wg::digital::v2::{InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin},
+    typestates::pin::{
+        state,
+        gpio::direction,
+        PinId,
+    },
+};
+use super::Pin;
+
+/// Offset of the byte pin registers in the GPIO block
+const B_OFFSET: usize = 0x0000;
+/// Offset of the word pin registers in the GPIO block
+const W_OFFSET: usize = 0x1000;
+/// Offset of the port output registers (SET) in the GPIO block
+const SET_OFFSET: usize = 0x2200;
+/// Offset of the port toggle registers (NOT) in the GPIO block
+const NOT_OFFSET: usize = 0x2300;
+
+/// A GPIO pin accessed through its byte and word pin registers, see `Pin::into_fast_pin`
+pub struct FastPin<T: PinId, D: direction::Direction> {
+    pin: Pin<T, state::Gpio<D>>,
+}
+
+impl<T: PinId, D: direction::Direction> Pin<T, state::Gpio<D>> {
+    pub fn into_fast_pin(self) -> FastPin<T, D> {
+        FastPin { pin: self }
+    }
+}
+
+impl<T: PinId, D: direction::Direction> FastPin<T, D> {
+    const BYTE: *mut u8 = (raw::GPIO::PTR as *mut u8)
+        .wrapping_add(B_OFFSET + 32 * T::PORT + T::NUMBER as usize);
+    const WORD: *mut u32 = (raw::GPIO::PTR as *mut u8)
+        .wrapping_add(W_OFFSET + 4 * (32 * T::PORT + T::NUMBER as usize)) as *mut u32;
+
+    const SET: *mut u32 = (raw::GPIO::PTR as *mut u8)
+        .wrapping_add(SET_OFFSET + 4 * T::PORT) as *mut u32;
+    const NOT: *mut u32 = (raw::GPIO::PTR as *mut u8)
+        .wrapping_add(NOT_OFFSET + 4 * T::PORT) as *mut u32;
+
+    pub fn into_pin(self) -> Pin<T, state::Gpio<D>> {
+        self.pin
+    }
+
+    /// The level of the pin, as 0 or 1 (the byte pin register)
+    #[inline(always)]
+    pub fn read_byte(&self) -> u8 {
+        unsafe { Self::BYTE.read_volatile() }
+    }
+
+    /// The level of the pin, as 0 or 0xFFFF_FFFF (the word pin register),
+    /// for use as a mask
+    #[inline(always)]
+    pub fn read_word(&self) -> u32 {
+        unsafe { Self::WORD.read_volatile() }
+    }
+
+    #[inline(always)]
+    pub fn is_high(&self) -> bool {
+        self.read_byte() != 0
+    }
+
+    #[inline(always)]
+    pub fn is_low(&self) -> bool {
+        self.read_byte() == 0
+    }
+}
+
+impl<T: PinId> FastPin<T, direction::Output> {
+    /// Drive the pin high if `high`, low otherwise.
+    #[inline(always)]
+    pub fn set(&mut self, high: bool) {
+        unsafe { Self::BYTE.write_volatile(high as u8) };
+    }
+
+    #[inline(always)]
+    pub fn set_high(&mut self) {
+        self.set(true);
+    }
+
+    #[inline(always)]
+    pub fn set_low(&mut self) {
+        self.set(false);
+    }
+
+    /// Is the output driven high (the output latch, not the pin)?
+    #[inline(always)]
+    pub fn is_set_high(&self) -> bool {
+        let set = unsafe { Self::SET.read_volatile() };
+        set & T::MASK != 0
+    }
+
+    /// Toggle the output, in a single write
+    #[inline(always)]
+    pub fn toggle(&mut self) {
+        unsafe { Self::NOT.write_volatile(T::MASK) };
+    }
+}
+
+impl<T: PinId> OutputPin for FastPin<T, direction::Output> {
+    type Error = core::convert::Infallible;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set(true);
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set(false);
+        Ok(())
+    }
+}
+
+impl<T: PinId> StatefulOutputPin for FastPin<T, direction::Output> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(FastPin::is_set_high(self))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!FastPin::is_set_high(self))
+    }
+}
+
+impl<T: PinId> ToggleableOutputPin for FastPin<T, direction::Output> {
+    type Error = core::convert::Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        FastPin::toggle(self);
+        Ok(())
+    }
+}
+
+impl<T: PinId> InputPin for FastPin<T, direction::Input> {
+    type Error = core::convert::Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(FastPin::is_high(self))
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(FastPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "eh1")]
+mod eh1_impls {
+    use crate::traits::eh1::digital::{
+        ErrorType,
+        InputPin,
+        OutputPin,
+        StatefulOutputPin,
+    };
+    use crate::typestates::pin::{
+        gpio::direction,
+        PinId,
+    };
+    use super::FastPin;
+
+    impl<T: PinId, D: direction::Direction> ErrorType for FastPin<T, D> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl<T: PinId> OutputPin for FastPin<T, direction::Output> {
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.set(true);
+            Ok(())
+        }
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.set(false);
+            Ok(())
+        }
+    }
+
+    impl<T: PinId> StatefulOutputPin for FastPin<T, direction::Output> {
+        fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(FastPin::is_set_high(self))
+        }
+
+        fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!FastPin::is_set_high(self))
+        }
+
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            FastPin::toggle(self);
+            Ok(())
+        }
+    }
+
+    impl<T: PinId> InputPin for FastPin<T, direction::Input> {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(FastPin::is_high(self))
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(FastPin::is_low(self))
+        }
+    }
+}
+
+/// Each wait takes a free pin interrupt slot for its duration, as for `Pin`s
+#[cfg(feature = "async")]
+mod async_impls {
+    use embedded_hal_async::digital::Wait;
+
+    use crate::peripherals::pint::{wait_for_pin, Mode};
+    use crate::typestates::pin::{
+        gpio::direction,
+        PinId,
+    };
+    use super::FastPin;
+
+    impl<T: PinId> Wait for FastPin<T, direction::Input> {
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            if self.is_low() {
+                wait_for_pin(T::PORT, T::NUMBER, &[Mode::ActiveHigh]).await;
+            }
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            if self.is_high() {
+                wait_for_pin(T::PORT, T::NUMBER, &[Mode::ActiveLow]).await;
+            }
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(T::PORT, T::NUMBER, &[Mode::RisingEdge]).await;
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(T::PORT, T::NUMBER, &[Mode::FallingEdge]).await;
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(T::PORT, T::NUMBER, &[Mode::BothEdges]).await;
+            Ok(())
+        }
+    }
+}
@@ -4,7 +4,7 @@ use crate::traits::{
             InputPin,
             OutputPin,
             StatefulOutputPin,
-            toggleable,
+            ToggleableOutputPin,
         },
     }
 };
@@ -21,13 +21,20 @@ use crate::typestates::{
     reg_proxy::RegClusterProxy,
 };
 
-use super::Pin;
+use super::{Pin, Pio};
+
+// Implements debouncing of inputs
+mod debounce;
+pub use debounce::{Debounced, Event, Sampler};
 
 use crate::{
+    peripherals::iocon::Iocon,
+    typestates::init_state,
     raw::gpio::{
         // B,
         // W,
         CLR,
+        NOT,
         DIRSET,
         DIRCLR,
         PIN,
@@ -43,56 +50,254 @@ reg_cluster!(DIRCLR, DIRCLR, raw::GPIO, dirclr);
 reg_cluster!(PIN, PIN, raw::GPIO, pin);
 reg_cluster!(SET, SET, raw::GPIO, set);
 reg_cluster!(CLR, CLR, raw::GPIO, clr);
+reg_cluster!(NOT, NOT, raw::GPIO, not);
 
 
-impl<T> OutputPin for Pin<T, state::Gpio<direction::Output>>
+impl<T, D> OutputPin for Pin<T, state::Gpio<D>>
 where
     T: PinId,
+    D: direction::Drive,
 {
     type Error = core::convert::Infallible;
 
     /// Set the pin output to HIGH
     fn set_high(&mut self) -> Result<(), Self::Error> {
-        self.state.set[T::PORT].write(|w| unsafe { w.setp().bits(T::MASK) });
+        self.state.set.write_bits(T::PORT, T::MASK);
         Ok(())
     }
 
     /// Set the pin output to LOW
     fn set_low(&mut self) -> Result<(), Self::Error> {
-        self.state.clr[T::PORT].write(|w| unsafe { w.clrp().bits(T::MASK) });
+        self.state.clr.write_bits(T::PORT, T::MASK);
         Ok(())
     }
 }
 
-impl<T> StatefulOutputPin for Pin<T, state::Gpio<direction::Output>>
+impl<T, D> StatefulOutputPin for Pin<T, state::Gpio<D>>
 where
     T: PinId,
+    D: direction::Drive,
 {
+    /// Reads the output latch (SET), rather than the pin
     fn is_set_high(&self) -> Result<bool, Self::Error> {
-        Ok(self.state.pin[T::PORT].read().port().bits() & T::MASK == T::MASK)
+        Ok(self.state.set.read_bits(T::PORT) & T::MASK == T::MASK)
     }
 
     fn is_set_low(&self) -> Result<bool, Self::Error> {
-        Ok(!self.state.pin[T::PORT].read().port().bits() & T::MASK == T::MASK)
+        Ok(!self.state.set.read_bits(T::PORT) & T::MASK == T::MASK)
     }
 }
 
-impl<T: PinId> toggleable::Default for Pin<T, state::Gpio<direction::Output>> {}
+impl<T, D> ToggleableOutputPin for Pin<T, state::Gpio<D>>
+where
+    T: PinId,
+    D: direction::Drive,
+{
+    type Error = core::convert::Infallible;
+
+    /// Toggles the output in a single write (NOT), without reading it first
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.state.not.write_bits(T::PORT, T::MASK);
+        Ok(())
+    }
+}
 
-impl<T> InputPin for Pin<T, state::Gpio<direction::Input>>
+impl<T, D> InputPin for Pin<T, state::Gpio<D>>
 where
     T: PinId,
+    D: direction::Sense,
 {
     type Error = core::convert::Infallible;
 
     fn is_high(&self) -> Result<bool, Self::Error> {
         // Ok(self.state.b[T::OFFSET].b_.read().pbyte())
-        Ok(self.state.pin[T::PORT].read().port().bits() & T::MASK == T::MASK)
+        Ok(self.state.pin.read_bits(T::PORT) & T::MASK == T::MASK)
     }
 
     fn is_low(&self) -> Result<bool, Self::Error> {
         // Ok(!self.state.b.b_[T::OFFSET].read().pbyte())
-        Ok(!self.state.pin[T::PORT].read().port().bits() & T::MASK == T::MASK)
+        Ok(!self.state.pin.read_bits(T::PORT) & T::MASK == T::MASK)
+    }
+}
+
+#[cfg(feature = "eh1")]
+mod eh1_impls {
+    use crate::traits::eh1::digital::{
+        ErrorType,
+        InputPin,
+        OutputPin,
+        StatefulOutputPin,
+    };
+    use crate::typestates::pin::{
+        state,
+        gpio::direction,
+        PinId,
+    };
+    use super::{ErasedPin, Pin};
+
+    impl<T: PinId, D: direction::Direction> ErrorType for Pin<T, state::Gpio<D>> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl<T: PinId, D: direction::Drive> OutputPin for Pin<T, state::Gpio<D>> {
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.state.set.write_bits(T::PORT, T::MASK);
+            Ok(())
+        }
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.state.clr.write_bits(T::PORT, T::MASK);
+            Ok(())
+        }
+    }
+
+    impl<T: PinId, D: direction::Drive> StatefulOutputPin for Pin<T, state::Gpio<D>> {
+        fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.state.set.read_bits(T::PORT) & T::MASK == T::MASK)
+        }
+
+        fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.state.set.read_bits(T::PORT) & T::MASK == T::MASK)
+        }
+
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            self.state.not.write_bits(T::PORT, T::MASK);
+            Ok(())
+        }
+    }
+
+    impl<T: PinId, D: direction::Sense> InputPin for Pin<T, state::Gpio<D>> {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.state.pin.read_bits(T::PORT) & T::MASK == T::MASK)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.state.pin.read_bits(T::PORT) & T::MASK == T::MASK)
+        }
+    }
+
+    impl<D: direction::Direction> ErrorType for ErasedPin<D> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl<D: direction::Drive> OutputPin for ErasedPin<D> {
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.state.set.write_bits(self.port, self.mask());
+            Ok(())
+        }
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.state.clr.write_bits(self.port, self.mask());
+            Ok(())
+        }
+    }
+
+    impl<D: direction::Drive> StatefulOutputPin for ErasedPin<D> {
+        fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.is_set_in_latch())
+        }
+
+        fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.is_set_in_latch())
+        }
+
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            self.state.not.write_bits(self.port, self.mask());
+            Ok(())
+        }
+    }
+
+    impl<D: direction::Sense> InputPin for ErasedPin<D> {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.is_set())
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.is_set())
+        }
+    }
+}
+
+/// Each wait takes a free pin interrupt slot (from 7 downwards) for its
+/// duration, see `peripherals::pint`.
+#[cfg(feature = "async")]
+mod async_impls {
+    use embedded_hal_async::digital::Wait;
+
+    use crate::peripherals::pint::{wait_for_pin, Mode};
+    use crate::typestates::pin::{
+        state,
+        gpio::direction,
+        PinId,
+    };
+    use super::{ErasedPin, Pin};
+
+    impl<T: PinId, D: direction::Sense> Pin<T, state::Gpio<D>> {
+        fn is_set(&self) -> bool {
+            self.state.pin.read_bits(T::PORT) & T::MASK == T::MASK
+        }
+    }
+
+    impl<T: PinId, D: direction::Sense> Wait for Pin<T, state::Gpio<D>> {
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            if !self.is_set() {
+                wait_for_pin(T::PORT, T::NUMBER, &[Mode::ActiveHigh]).await;
+            }
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            if self.is_set() {
+                wait_for_pin(T::PORT, T::NUMBER, &[Mode::ActiveLow]).await;
+            }
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(T::PORT, T::NUMBER, &[Mode::RisingEdge]).await;
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(T::PORT, T::NUMBER, &[Mode::FallingEdge]).await;
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(T::PORT, T::NUMBER, &[Mode::BothEdges]).await;
+            Ok(())
+        }
+    }
+
+    impl<D: direction::Sense> Wait for ErasedPin<D> {
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            if !self.is_set() {
+                wait_for_pin(self.port, self.number, &[Mode::ActiveHigh]).await;
+            }
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            if self.is_set() {
+                wait_for_pin(self.port, self.number, &[Mode::ActiveLow]).await;
+            }
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(self.port, self.number, &[Mode::RisingEdge]).await;
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(self.port, self.number, &[Mode::FallingEdge]).await;
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            wait_for_pin(self.port, self.number, &[Mode::BothEdges]).await;
+            Ok(())
+        }
     }
 }
 
@@ -109,11 +314,11 @@ where
     }
     pub fn into_output(self, initial: Level) -> Pin<T, state::Gpio<direction::Output>> {
         match initial {
-            Level::High => self.state.set[T::PORT].write(|w| unsafe { w.setp().bits(T::MASK) }),
-            Level::Low => self.state.clr[T::PORT].write(|w| unsafe { w.clrp().bits(T::MASK) }),
+            Level::High => self.state.set.write_bits(T::PORT, T::MASK),
+            Level::Low => self.state.clr.write_bits(T::PORT, T::MASK),
         }
 
-        self.state.dirset[T::PORT].write(|w| unsafe { w.dirsetp().bits(T::MASK) });
+        self.state.dirset.write_bits(T::PORT, T::MASK);
 
         Pin {
             id: self.id,
@@ -126,6 +331,7 @@ where
                 pin: RegClusterProxy::new(),
                 set: RegClusterProxy::new(),
                 clr: RegClusterProxy::new(),
+                not: RegClusterProxy::new(),
 
                 _direction: direction::Output,
             },
@@ -143,7 +349,7 @@ where
         // currently, `into_gpio_pin()` sets `.digimode().digital()` in IOCON,
         // meaning input is enabled for all pins
 
-        self.state.dirclr[T::PORT].write(|w| unsafe { w.dirclrp().bits(T::MASK) });
+        self.state.dirclr.write_bits(T::PORT, T::MASK);
 
         Pin {
             id: self.id,
@@ -156,6 +362,7 @@ where
                 pin: RegClusterProxy::new(),
                 set: RegClusterProxy::new(),
                 clr: RegClusterProxy::new(),
+                not: RegClusterProxy::new(),
 
                 _direction: direction::Input,
             },
@@ -163,6 +370,47 @@ where
     }
 }
 
+impl<T, D> Pin<T, state::Gpio<D>>
+where
+    T: PinId,
+    D: direction::Direction,
+{
+    /// Transition to open drain output, releasing the line if `initial` is high.
+    ///
+    /// The pin only ever pulls the line low, and its level reads back the line.
+    /// With `pull_up`, the internal pull-up holds the line high when no one pulls
+    /// it low; I2C pins (`PinType::I`) have no internal pull-up.
+    pub fn into_open_drain(
+        self,
+        iocon: &mut Iocon<init_state::Enabled>,
+        initial: Level,
+        pull_up: bool,
+    ) -> Pin<T, state::Gpio<direction::OpenDrain>> {
+        iocon.configure_open_drain(T::PORT, T::NUMBER, pull_up);
+        match initial {
+            Level::High => self.state.set.write_bits(T::PORT, T::MASK),
+            Level::Low => self.state.clr.write_bits(T::PORT, T::MASK),
+        }
+
+        self.state.dirset.write_bits(T::PORT, T::MASK);
+
+        Pin {
+            id: self.id,
+
+            state: state::Gpio {
+                dirset: RegClusterProxy::new(),
+                dirclr: RegClusterProxy::new(),
+                pin: RegClusterProxy::new(),
+                set: RegClusterProxy::new(),
+                clr: RegClusterProxy::new(),
+                not: RegClusterProxy::new(),
+
+                _direction: direction::OpenDrain,
+            },
+        }
+    }
+}
+
 
 impl<T, D> Pin<T, state::Analog<D>>
 where
@@ -174,7 +422,7 @@ where
         // currently, `into_gpio_pin()` sets `.digimode().digital()` in IOCON,
         // meaning input is enabled for all pins
 
-        self.state.dirclr[T::PORT].write(|w| unsafe { w.dirclrp().bits(T::MASK) });
+        self.state.dirclr.write_bits(T::PORT, T::MASK);
 
         Pin {
             id: self.id,
@@ -188,8 +436,121 @@ where
     }
 }
 
+/// A GPIO pin whose identity is only known at runtime, see `Pin::downgrade`
+///
+/// Unlike `Pin`s, these all have the same type for a given direction, so they
+/// can be stored in arrays and iterated over:
+///
+/// ```ignore
+/// let mut leds = [red.downgrade(), green.downgrade(), blue.downgrade()];
+/// for led in leds.iter_mut() {
+///     led.set_low().ok();
+/// }
+/// ```
+pub struct ErasedPin<D: direction::Direction> {
+    port: usize,
+    number: u8,
+    state: state::Gpio<D>,
+}
+
+impl<T, D> Pin<T, state::Gpio<D>>
+where
+    T: PinId,
+    D: direction::Direction,
+{
+    /// Erase the pin's identity from its type
+    pub fn downgrade(self) -> ErasedPin<D> {
+        ErasedPin {
+            port: T::PORT,
+            number: T::NUMBER,
+            state: self.state,
+        }
+    }
+}
+
+impl<D: direction::Direction> ErasedPin<D> {
+    /// This is `X` for `PIOX_Y`.
+    pub fn port(&self) -> usize {
+        self.port
+    }
 
+    /// This is `Y` for `PIOX_Y`.
+    pub fn number(&self) -> u8 {
+        self.number
+    }
 
+    fn mask(&self) -> u32 {
+        1 << self.number
+    }
 
+    fn is_set(&self) -> bool {
+        self.state.pin.read_bits(self.port) & self.mask() != 0
+    }
 
+    fn is_set_in_latch(&self) -> bool {
+        self.state.set.read_bits(self.port) & self.mask() != 0
+    }
 
+    /// Recover the typed pin, or get the erased pin back if it is not `PIOPORT_NUMBER`
+    pub fn upgrade<const PORT: usize, const NUMBER: u8>(self) -> Result<Pin<Pio<PORT, NUMBER>, state::Gpio<D>>, Self> {
+        if self.port == PORT && self.number == NUMBER {
+            Ok(Pin {
+                id: Pio(()),
+                state: self.state,
+            })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<D: direction::Direction> core::fmt::Debug for ErasedPin<D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PIO{}_{}", self.port, self.number)
+    }
+}
+
+impl<D: direction::Drive> OutputPin for ErasedPin<D> {
+    type Error = core::convert::Infallible;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.state.set.write_bits(self.port, self.mask());
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.state.clr.write_bits(self.port, self.mask());
+        Ok(())
+    }
+}
+
+impl<D: direction::Drive> StatefulOutputPin for ErasedPin<D> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set_in_latch())
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set_in_latch())
+    }
+}
+
+impl<D: direction::Drive> ToggleableOutputPin for ErasedPin<D> {
+    type Error = core::convert::Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.state.not.write_bits(self.port, self.mask());
+        Ok(())
+    }
+}
+
+impl<D: direction::Sense> InputPin for ErasedPin<D> {
+    type Error = core::convert::Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set())
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set())
+    }
+}
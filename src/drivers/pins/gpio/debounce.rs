@@ -0,0 +1,127 @@
+//! Debouncing of inputs such as buttons.
+//!
+//! A `Sampler` runs the micro-tick timer at the sampling period; each time it
+//! ticks, the `Debounced` inputs take a sample. An input changes state once
+//! `samples` consecutive samples (more precisely, an integrator over them)
+//! agree:
+//!
+//! ```ignore
+//! let mut sampler = Sampler::new(utick, Microseconds::millis(1));
+//! let mut button = Debounced::new(user_button, Level::Low, 20);
+//!
+//! loop {
+//!     if sampler.tick() {
+//!         if let Some(Event::Pressed) = button.sample() {
+//!             led.toggle().ok();
+//!         }
+//!     }
+//! }
+//! ```
+
+use core::convert::Infallible;
+
+use crate::{
+    peripherals::utick::EnabledUtick,
+    time::Microseconds,
+    traits::wg::{digital::v2::InputPin, timer::CountDown},
+    typestates::pin::gpio::Level,
+};
+
+/// A change of the debounced state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    Pressed,
+    Released,
+}
+
+/// Ticks once per sampling period, from the micro-tick timer
+pub struct Sampler {
+    utick: EnabledUtick,
+    period: u32,
+}
+
+impl Sampler {
+    /// Start ticking every `period` (at least 2 us).
+    pub fn new(mut utick: EnabledUtick, period: Microseconds) -> Self {
+        let period = period.to_micros();
+        utick.start(period);
+        Self { utick, period }
+    }
+
+    pub fn release(self) -> EnabledUtick {
+        self.utick
+    }
+
+    /// Has a period passed since the last tick? Restarts the timer if so.
+    ///
+    /// Periods missed by not calling this in time are skipped.
+    pub fn tick(&mut self) -> bool {
+        match self.utick.wait() {
+            Ok(()) => {
+                self.utick.start(self.period);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// An input with its debounced state, see the module documentation
+pub struct Debounced<P: InputPin<Error = Infallible>> {
+    pin: P,
+    pressed_level: Level,
+    samples: u8,
+    integrator: u8,
+    pressed: bool,
+}
+
+impl<P: InputPin<Error = Infallible>> Debounced<P> {
+    /// The input reads `pressed_level` while pressed, and changes state after
+    /// `samples` (at least 1) agreeing samples.
+    ///
+    /// Starts released.
+    pub fn new(pin: P, pressed_level: Level, samples: u8) -> Self {
+        Self {
+            pin,
+            pressed_level,
+            samples: samples.max(1),
+            integrator: 0,
+            pressed: false,
+        }
+    }
+
+    pub fn release(self) -> P {
+        self.pin
+    }
+
+    /// The debounced state
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// The raw level reads pressed (bouncing or not)
+    pub fn is_pressed_now(&self) -> bool {
+        let high = self.pin.is_high().unwrap();
+        high == (self.pressed_level == Level::High)
+    }
+
+    /// Take a sample, returning the change of state it completes, if any.
+    pub fn sample(&mut self) -> Option<Event> {
+        if self.is_pressed_now() {
+            self.integrator = self.integrator.saturating_add(1).min(self.samples);
+        } else {
+            self.integrator = self.integrator.saturating_sub(1);
+        }
+
+        if !self.pressed && self.integrator == self.samples {
+            self.pressed = true;
+            Some(Event::Pressed)
+        } else if self.pressed && self.integrator == 0 {
+            self.pressed = false;
+            Some(Event::Released)
+        } else {
+            None
+        }
+    }
+}
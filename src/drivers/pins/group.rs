@@ -0,0 +1,115 @@
+//! Several GPIO pins of one port, changing together.
+//!
+//! Writes go through the masked port register (MPIN), which only affects the
+//! pins not masked in the port's MASK register, so all pins of the group change
+//! in the same bus access. This is what parallel buses need:
+//!
+//! ```ignore
+//! let mut bus = PinGroup::new([d0.downgrade(), d1.downgrade(), d2.downgrade(), d3.downgrade()])
+//!     .ok().unwrap();
+//! bus.write_bus(0b1010);
+//! ```
+
+use crate::{
+    raw,
+    typestates::pin::gpio::direction,
+};
+use super::ErasedPin;
+
+/// `N` GPIO pins of the same port, see the module documentation
+pub struct PinGroup<D: direction::Direction, const N: usize> {
+    port: usize,
+    mask: u32,
+    pins: [ErasedPin<D>; N],
+}
+
+impl<D: direction::Direction, const N: usize> PinGroup<D, N> {
+    /// Group `pins`, or give them back if they are not all of the same port.
+    ///
+    /// Bit `i` of the bus values (`write_bus`, `read_bus`) is `pins[i]`.
+    pub fn new(pins: [ErasedPin<D>; N]) -> Result<Self, [ErasedPin<D>; N]> {
+        let port = match pins.first() {
+            Some(pin) => pin.port(),
+            None => 0,
+        };
+        if pins.iter().any(|pin| pin.port() != port) {
+            return Err(pins);
+        }
+        let mask = pins.iter().fold(0, |mask, pin| mask | (1 << pin.number()));
+        Ok(Self { port, mask, pins })
+    }
+
+    pub fn release(self) -> [ErasedPin<D>; N] {
+        self.pins
+    }
+
+    pub fn port(&self) -> usize {
+        self.port
+    }
+
+    /// The pins of the group, as bits of the port
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    /// The levels of the group's pins, as bits of the port (the others read as zero)
+    pub fn read(&self) -> u32 {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        gpio.pin[self.port].read().bits() & self.mask
+    }
+
+    /// The levels of the group's pins, bit `i` being `pins[i]`
+    pub fn read_bus(&self) -> u32 {
+        let port = self.read();
+        self.pins.iter().enumerate().fold(0, |bus, (i, pin)| {
+            bus | (((port >> pin.number()) & 1) << i)
+        })
+    }
+}
+
+impl<D: direction::Drive, const N: usize> PinGroup<D, N> {
+    /// Set the outputs of the group's pins to `value`, as bits of the port,
+    /// all at once. Bits of other pins are ignored.
+    pub fn write(&mut self, value: u32) {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        // MASK is shared by the port, restore it for its other users
+        cortex_m::interrupt::free(|_| {
+            let mask = gpio.mask[self.port].read().bits();
+            gpio.mask[self.port].write(|w| unsafe { w.bits(!self.mask) });
+            gpio.mpin[self.port].write(|w| unsafe { w.bits(value) });
+            gpio.mask[self.port].write(|w| unsafe { w.bits(mask) });
+        });
+    }
+
+    /// Set the outputs of the group's pins, bit `i` of `bus` for `pins[i]`, all at once.
+    pub fn write_bus(&mut self, bus: u32) {
+        let value = self.pins.iter().enumerate().fold(0, |value, (i, pin)| {
+            value | (((bus >> i) & 1) << pin.number())
+        });
+        self.write(value);
+    }
+
+    /// Drive the pins in `mask` (bits of the port) high, in one write (SET)
+    pub fn set_high(&mut self, mask: u32) {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        gpio.set[self.port].write(|w| unsafe { w.bits(mask & self.mask) });
+    }
+
+    /// Drive the pins in `mask` (bits of the port) low, in one write (CLR)
+    pub fn set_low(&mut self, mask: u32) {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        gpio.clr[self.port].write(|w| unsafe { w.bits(mask & self.mask) });
+    }
+
+    /// Make the group's pins inputs, for a bus turnaround
+    pub(crate) fn release_bus(&mut self) {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        gpio.dirclr[self.port].write(|w| unsafe { w.bits(self.mask) });
+    }
+
+    /// Make the group's pins outputs again, after `release_bus`
+    pub(crate) fn drive_bus(&mut self) {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        gpio.dirset[self.port].write(|w| unsafe { w.bits(self.mask) });
+    }
+}
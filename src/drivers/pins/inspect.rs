@@ -0,0 +1,99 @@
+//! Reading back the configuration of any pin, for diagnostics.
+
+use crate::raw;
+use super::Level;
+
+/// Pull resistor of a pin (IOCON MODE)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Pull {
+    None,
+    Down,
+    Up,
+    Repeater,
+}
+
+/// GPIO direction of a pin, which only matters if it is in GPIO function (0)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PinDirection {
+    Input,
+    Output,
+}
+
+/// The current state of a pin, as returned by `inspect`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PinReport {
+    pub port: usize,
+    pub number: u8,
+    /// IOCON function, 0 being GPIO
+    pub function: u8,
+    pub direction: PinDirection,
+    pub pull: Pull,
+    /// Digital input buffer enabled (DIGIMODE)
+    pub digital: bool,
+    /// Analog switch closed (ASW), for pins with analog functions
+    pub analog_switch: bool,
+    pub open_drain: bool,
+    pub inverted: bool,
+    pub fast_slew: bool,
+    /// The level read by GPIO, even if the pin is not in GPIO function
+    pub level: Level,
+}
+
+/// The current configuration and level of pin `PIOport_number`.
+///
+/// This reads the registers behind the back of the pin's owner, and does not
+/// change anything. Returns `None` if the pin does not exist, or the IOCON
+/// or the GPIO port are not enabled (their registers can't be read then).
+pub fn inspect(port: usize, number: u8) -> Option<PinReport> {
+    if port >= 2 || number >= 32 {
+        return None;
+    }
+    let clocks = unsafe { &*raw::SYSCON::ptr() }.ahbclkctrl0.read();
+    let gpio_enabled = match port {
+        0 => clocks.gpio0().is_enable(),
+        _ => clocks.gpio1().is_enable(),
+    };
+    if clocks.iocon().is_disable() || !gpio_enabled {
+        return None;
+    }
+
+    // the PIOx_y registers are contiguous, PIO1_0 following PIO0_31
+    let iocon = unsafe { &*raw::IOCON::ptr() };
+    let config = unsafe {
+        (&iocon.pio0_0 as *const _ as *const u32)
+            .add(32 * port + number as usize)
+            .read_volatile()
+    };
+    let gpio = unsafe { &*raw::GPIO::ptr() };
+    let mask = 1 << number;
+
+    Some(PinReport {
+        port,
+        number,
+        function: (config & 0xf) as u8,
+        direction: if gpio.dir[port].read().bits() & mask != 0 {
+            PinDirection::Output
+        } else {
+            PinDirection::Input
+        },
+        pull: match (config >> 4) & 0x3 {
+            0 => Pull::None,
+            1 => Pull::Down,
+            2 => Pull::Up,
+            _ => Pull::Repeater,
+        },
+        fast_slew: config & (1 << 6) != 0,
+        inverted: config & (1 << 7) != 0,
+        digital: config & (1 << 8) != 0,
+        open_drain: config & (1 << 9) != 0,
+        analog_switch: config & (1 << 10) != 0,
+        level: if gpio.pin[port].read().bits() & mask != 0 {
+            Level::High
+        } else {
+            Level::Low
+        },
+    })
+}
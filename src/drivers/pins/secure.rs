@@ -0,0 +1,239 @@
+//! Pins of port 0 controlled by the secure GPIO, see `peripherals::secgpio`.
+//!
+//! ```ignore
+//! let secgpio = hal.secgpio.enabled(&mut syscon);
+//! let mut tamper_led = pins.pio0_1
+//!     .into_gpio_output(&mut iocon, &mut gpio, Level::Low)
+//!     .into_secure(&mut secgpio);
+//! tamper_led.set_high().ok();
+//! ```
+
+use crate::{
+    raw,
+    peripherals::secgpio::{self, SecGpio},
+    traits::wg::digital::v2::{
+        InputPin,
+        OutputPin,
+        StatefulOutputPin,
+        ToggleableOutputPin,
+    },
+    typestates::{
+        init_state,
+        pin::{
+            state,
+            gpio::{direction, Level},
+            PinId,
+        },
+    },
+};
+use super::{Pin, Pio};
+
+/// A GPIO pin of port 0, controlled by the secure GPIO, see `Pin::into_secure`
+pub struct SecurePin<T: PinId, D: direction::Direction> {
+    pin: Pin<T, state::Gpio<D>>,
+}
+
+fn secgpio() -> &'static raw::secgpio::RegisterBlock {
+    unsafe { &*raw::SECGPIO::ptr() }
+}
+
+impl<const NUMBER: u8, D: direction::Direction> Pin<Pio<0, NUMBER>, state::Gpio<D>> {
+    /// Move the pin to the secure GPIO, keeping its direction and output level,
+    /// and block the non-secure GPIO from reading it.
+    pub fn into_secure(self, _secgpio: &mut SecGpio<init_state::Enabled>) -> SecurePin<Pio<0, NUMBER>, D> {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        let secgpio = secgpio();
+        let mask = 1 << NUMBER;
+
+        if gpio.set[0].read().bits() & mask != 0 {
+            secgpio.set0.write(|w| unsafe { w.bits(mask) });
+        } else {
+            secgpio.clr0.write(|w| unsafe { w.bits(mask) });
+        }
+        if gpio.dir[0].read().bits() & mask != 0 {
+            secgpio.dirset0.write(|w| unsafe { w.bits(mask) });
+        } else {
+            secgpio.dirclr0.write(|w| unsafe { w.bits(mask) });
+        }
+        // the non-secure GPIO stops driving the pin
+        gpio.dirclr[0].write(|w| unsafe { w.bits(mask) });
+        secgpio::set_blocked(NUMBER, true);
+
+        SecurePin { pin: self }
+    }
+}
+
+impl<T: PinId, D: direction::Direction> SecurePin<T, D> {
+    /// Return the pin to the non-secure GPIO, with its direction and output level.
+    pub fn into_nonsecure(self) -> Pin<T, state::Gpio<D>> {
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        let secgpio = secgpio();
+
+        if secgpio.set0.read().bits() & T::MASK != 0 {
+            gpio.set[0].write(|w| unsafe { w.bits(T::MASK) });
+        } else {
+            gpio.clr[0].write(|w| unsafe { w.bits(T::MASK) });
+        }
+        if secgpio.dir0.read().bits() & T::MASK != 0 {
+            gpio.dirset[0].write(|w| unsafe { w.bits(T::MASK) });
+        }
+        secgpio.dirclr0.write(|w| unsafe { w.bits(T::MASK) });
+        secgpio::set_blocked(T::NUMBER, false);
+
+        self.pin
+    }
+
+    fn is_set(&self) -> bool {
+        secgpio().pin0.read().bits() & T::MASK != 0
+    }
+
+    fn is_set_in_latch(&self) -> bool {
+        secgpio().set0.read().bits() & T::MASK != 0
+    }
+
+    fn write(&self, level: Level) {
+        match level {
+            Level::High => secgpio().set0.write(|w| unsafe { w.bits(T::MASK) }),
+            Level::Low => secgpio().clr0.write(|w| unsafe { w.bits(T::MASK) }),
+        }
+    }
+
+    fn toggle_latch(&self) {
+        secgpio().not0.write(|w| unsafe { w.bits(T::MASK) });
+    }
+
+    fn with_direction<E: direction::Direction>(self, direction: E) -> SecurePin<T, E> {
+        let Pin { id, state } = self.pin;
+        SecurePin {
+            pin: Pin {
+                id,
+                state: state::Gpio {
+                    dirset: state.dirset,
+                    dirclr: state.dirclr,
+                    pin: state.pin,
+                    set: state.set,
+                    clr: state.clr,
+                    not: state.not,
+
+                    _direction: direction,
+                },
+            },
+        }
+    }
+}
+
+impl<T: PinId, D: direction::NotOutput> SecurePin<T, D> {
+    pub fn into_output(self, initial: Level) -> SecurePin<T, direction::Output> {
+        self.write(initial);
+        secgpio().dirset0.write(|w| unsafe { w.bits(T::MASK) });
+        self.with_direction(direction::Output)
+    }
+}
+
+impl<T: PinId, D: direction::NotInput> SecurePin<T, D> {
+    pub fn into_input(self) -> SecurePin<T, direction::Input> {
+        secgpio().dirclr0.write(|w| unsafe { w.bits(T::MASK) });
+        self.with_direction(direction::Input)
+    }
+}
+
+impl<T: PinId, D: direction::Drive> OutputPin for SecurePin<T, D> {
+    type Error = core::convert::Infallible;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.write(Level::High);
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.write(Level::Low);
+        Ok(())
+    }
+}
+
+impl<T: PinId, D: direction::Drive> StatefulOutputPin for SecurePin<T, D> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set_in_latch())
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set_in_latch())
+    }
+}
+
+impl<T: PinId, D: direction::Drive> ToggleableOutputPin for SecurePin<T, D> {
+    type Error = core::convert::Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.toggle_latch();
+        Ok(())
+    }
+}
+
+impl<T: PinId, D: direction::Sense> InputPin for SecurePin<T, D> {
+    type Error = core::convert::Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.is_set())
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set())
+    }
+}
+
+#[cfg(feature = "eh1")]
+mod eh1_impls {
+    use crate::traits::eh1::digital::{
+        ErrorType,
+        InputPin,
+        OutputPin,
+        StatefulOutputPin,
+    };
+    use crate::typestates::pin::{
+        gpio::{direction, Level},
+        PinId,
+    };
+    use super::SecurePin;
+
+    impl<T: PinId, D: direction::Direction> ErrorType for SecurePin<T, D> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl<T: PinId, D: direction::Drive> OutputPin for SecurePin<T, D> {
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.write(Level::High);
+            Ok(())
+        }
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.write(Level::Low);
+            Ok(())
+        }
+    }
+
+    impl<T: PinId, D: direction::Drive> StatefulOutputPin for SecurePin<T, D> {
+        fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.is_set_in_latch())
+        }
+
+        fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.is_set_in_latch())
+        }
+
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            self.toggle_latch();
+            Ok(())
+        }
+    }
+
+    impl<T: PinId, D: direction::Sense> InputPin for SecurePin<T, D> {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.is_set())
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.is_set())
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use core::mem::ManuallyDrop;
+use core::ptr;
+
 use crate::{
-    peripherals::ctimer::Ctimer,
+    peripherals::{ctimer::Ctimer, syscon},
     time::Microseconds,
     traits::wg,
     typestates::init_state,
@@ -73,8 +76,30 @@ where TIMER: Ctimer<init_state::Enabled> {
         }
     }
 
+    /// Wrap `timer` as configured by `new` before, without touching the hardware.
+    ///
+    /// # Safety
+    ///
+    /// The driver owning the peripheral must not be used concurrently.
+    pub unsafe fn steal(timer: TIMER) -> Self {
+        Self {
+            timer,
+        }
+    }
+
     pub fn release(self) -> TIMER {
-        self.timer
+        let this = ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.timer) }
+    }
+
+    /// The timer owned by the driver, which derefs to the raw registers.
+    pub fn raw(&self) -> &TIMER {
+        &self.timer
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut TIMER {
+        &mut self.timer
     }
 
     /// Increase maximum value for the duty cycle.
@@ -82,9 +107,29 @@ where TIMER: Ctimer<init_state::Enabled> {
         self.timer.mr[3].write(|w| unsafe { w.bits(0xff * duty) });
     }
 
+    /// Single channel (0-2), implementing `SetDutyCycle` of embedded-hal 1.0
+    #[cfg(feature = "eh1")]
+    pub fn channel(&mut self, channel: u8) -> PwmChannel<'_, TIMER> {
+        assert!(channel < 3, "Cannot use channel outside 0-2 for PWM.");
+        PwmChannel { pwm: self, channel }
+    }
+
 }
 //pin: & Pin<impl PinId, state::Analog<direction::Input>>
 
+/// Stops the timer, masks its interrupts and disables its clock.
+///
+/// To keep the PWM outputs running, `release` the driver, or `core::mem::forget` it.
+impl<TIMER> Drop for Pwm<TIMER>
+where TIMER: Ctimer<init_state::Enabled>
+{
+    fn drop(&mut self) {
+        self.timer.tcr.write(|w| w.crst().set_bit().cen().clear_bit());
+        self.timer.mcr.write(|w| unsafe { w.bits(0) });
+        syscon::disable_clock_on_drop(&self.timer);
+    }
+}
+
 impl<TIMER> wg::Pwm for Pwm<TIMER>
 where TIMER: Ctimer<init_state::Enabled>
 {
@@ -148,7 +193,7 @@ where TIMER: Ctimer<init_state::Enabled>
     }
 
     fn get_period(&self) -> Self::Time {
-        Microseconds(1_000_000 / self.get_max_duty() as u32)
+        Microseconds::from_ticks(1_000_000 / self.get_max_duty() as u32)
     }
 
     fn set_period<P>(&mut self, _period: P)
@@ -172,3 +217,33 @@ where TIMER: Ctimer<init_state::Enabled>
 
 }
 
+
+#[cfg(feature = "eh1")]
+pub struct PwmChannel<'a, TIMER>
+where
+    TIMER: Ctimer<init_state::Enabled>,
+{
+    pwm: &'a mut Pwm<TIMER>,
+    channel: u8,
+}
+
+#[cfg(feature = "eh1")]
+impl<TIMER> crate::traits::eh1::pwm::ErrorType for PwmChannel<'_, TIMER>
+where TIMER: Ctimer<init_state::Enabled>
+{
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "eh1")]
+impl<TIMER> crate::traits::eh1::pwm::SetDutyCycle for PwmChannel<'_, TIMER>
+where TIMER: Ctimer<init_state::Enabled>
+{
+    fn max_duty_cycle(&self) -> u16 {
+        wg::Pwm::get_max_duty(self.pwm)
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        wg::Pwm::set_duty(self.pwm, self.channel, duty);
+        Ok(())
+    }
+}
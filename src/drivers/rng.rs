@@ -1,14 +1,50 @@
-use crate::traits::{
-    rand_core,
-    wg::blocking::rng,
-};
+use crate::traits::wg::blocking::rng;
 
 use crate::typestates::init_state;
 
 use crate::Rng;
 
-#[derive(Debug)]
-pub enum Error {}
+#[cfg(feature = "csprng")]
+mod csprng;
+#[cfg(feature = "csprng")]
+pub use csprng::Csprng;
+
+#[cfg(feature = "getrandom")]
+pub mod global;
+
+mod health;
+pub use health::{HealthMonitor, APT_WINDOW};
+
+mod pool;
+pub use pool::EntropyPool;
+
+/// RNG error
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Error {
+    /// The entropy counters did not refresh in time
+    Timeout,
+    /// The online chi-squared test of the entropy source failed
+    HealthTest,
+    /// A word repeated too often in a row (SP 800-90B repetition count test)
+    RepetitionCount,
+    /// A word occurred too often in a window (SP 800-90B adaptive proportion test)
+    AdaptiveProportion,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Error::Timeout => "entropy source timed out",
+            Error::HealthTest => "entropy source failed its health test",
+            Error::RepetitionCount => "entropy source failed the repetition count test",
+            Error::AdaptiveProportion => "entropy source failed the adaptive proportion test",
+        })
+    }
+}
+
+impl core::error::Error for Error {}
 
 impl rng::Read for Rng<init_state::Enabled> {
     type Error = Error;
@@ -30,22 +66,31 @@ impl rng::Read for Rng<init_state::Enabled> {
     }
 }
 
-impl rand_core::RngCore for Rng<init_state::Enabled> {
-    fn next_u32(&mut self) -> u32 {
-        self.get_random_u32()
-    }
+#[cfg(feature = "rand_core")]
+mod rand_core_impls {
+    use crate::traits::rand_core::{self, CryptoRng, RngCore};
+    use crate::typestates::init_state;
+    use crate::Rng;
 
-    fn next_u64(&mut self) -> u64 {
-        rand_core::impls::next_u64_via_u32(self)
-    }
+    impl RngCore for Rng<init_state::Enabled> {
+        fn next_u32(&mut self) -> u32 {
+            self.get_random_u32()
+        }
 
-    fn fill_bytes(&mut self, dest: &mut [u8]) {
-        rand_core::impls::fill_bytes_via_next(self, dest)
-    }
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_u32(self)
+        }
 
-    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
-        Ok(self.fill_bytes(dest))
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
     }
-}
 
-impl rand_core::CryptoRng for Rng<init_state::Enabled> {}
+    // the words are taken from the hardware entropy source
+    impl CryptoRng for Rng<init_state::Enabled> {}
+}
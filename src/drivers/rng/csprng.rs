@@ -0,0 +1,111 @@
+//! A ChaCha20 DRBG seeded from the RNG (feature `csprng`).
+//!
+//! The RNG waits for its entropy counters for every word, the DRBG produces
+//! output as fast as ChaCha20 runs. It is reseeded from the RNG after a budget
+//! of output bytes, and rekeys itself from its own output after every request,
+//! so a later compromise of its state does not reveal earlier output.
+//!
+//! ```ignore
+//! let mut csprng = Csprng::new(hal.rng.enabled(&mut syscon), Csprng::DEFAULT_RESEED_INTERVAL);
+//! let mut nonce = [0u8; 12];
+//! csprng.fill_bytes(&mut nonce);
+//! ```
+
+use rand_chacha::ChaCha20Rng;
+
+use crate::traits::rand_core::{self, CryptoRng, RngCore, SeedableRng};
+use crate::typestates::init_state;
+use crate::Rng;
+
+type Seed = <ChaCha20Rng as SeedableRng>::Seed;
+
+/// ChaCha20 DRBG in front of the RNG
+pub struct Csprng {
+    rng: Rng<init_state::Enabled>,
+    drbg: ChaCha20Rng,
+    reseed_interval: usize,
+    since_reseed: usize,
+}
+
+impl Csprng {
+    /// Reseed interval of 1 MiB of output
+    pub const DEFAULT_RESEED_INTERVAL: usize = 1 << 20;
+
+    /// Seed the DRBG from `rng`, reseeding after `reseed_interval` bytes of output.
+    pub fn new(rng: Rng<init_state::Enabled>, reseed_interval: usize) -> Self {
+        let drbg = ChaCha20Rng::from_seed(Self::seed_from(&rng));
+        Self {
+            rng,
+            drbg,
+            reseed_interval,
+            since_reseed: 0,
+        }
+    }
+
+    fn seed_from(rng: &Rng<init_state::Enabled>) -> Seed {
+        let mut seed = Seed::default();
+        for chunk in seed.chunks_mut(4) {
+            chunk.copy_from_slice(&rng.get_random_u32().to_ne_bytes());
+        }
+        seed
+    }
+
+    /// Reseed the DRBG from the RNG now.
+    pub fn reseed(&mut self) {
+        // mixes in the current state, so a weak reseed does not weaken the DRBG
+        let mut seed = Self::seed_from(&self.rng);
+        let mut current = Seed::default();
+        self.drbg.fill_bytes(&mut current);
+        for (byte, current) in seed.iter_mut().zip(current.iter()) {
+            *byte ^= current;
+        }
+        self.drbg = ChaCha20Rng::from_seed(seed);
+        self.since_reseed = 0;
+    }
+
+    /// Bytes of output since the last reseed
+    pub fn since_reseed(&self) -> usize {
+        self.since_reseed
+    }
+
+    /// Release the RNG.
+    pub fn release(self) -> Rng<init_state::Enabled> {
+        self.rng
+    }
+
+    fn rekey(&mut self) {
+        let mut seed = Seed::default();
+        self.drbg.fill_bytes(&mut seed);
+        self.drbg = ChaCha20Rng::from_seed(seed);
+    }
+}
+
+impl RngCore for Csprng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_ne_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_ne_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if self.since_reseed.saturating_add(dest.len()) > self.reseed_interval {
+            self.reseed();
+        }
+        self.drbg.fill_bytes(dest);
+        self.since_reseed = self.since_reseed.saturating_add(dest.len());
+        self.rekey();
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for Csprng {}
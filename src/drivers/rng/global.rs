@@ -0,0 +1,52 @@
+//! The RNG as `getrandom` backend (feature `getrandom`).
+//!
+//! Crates using `getrandom`, possibly transitively, get their random bytes
+//! from the RNG once it is registered:
+//!
+//! ```ignore
+//! hal::drivers::rng::global::register(hal.rng.enabled(&mut syscon));
+//! ```
+//!
+//! Before that, `getrandom` fails. All the bytes of one call are generated in
+//! a single critical section, each word waiting for the entropy counters: for
+//! one refresh on revision A1, and for all 31 on A0, where the online test is
+//! not used. Interrupts are delayed accordingly, so request few bytes at a time.
+
+use core::cell::RefCell;
+use core::num::NonZeroU32;
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::typestates::init_state;
+use crate::Rng;
+
+static RNG: Mutex<RefCell<Option<Rng<init_state::Enabled>>>> = Mutex::new(RefCell::new(None));
+
+/// `getrandom` error code if no RNG is registered
+pub const NOT_REGISTERED: u32 = getrandom::Error::CUSTOM_START;
+
+/// Make `rng` the source of `getrandom`, returning the previously registered one.
+pub fn register(rng: Rng<init_state::Enabled>) -> Option<Rng<init_state::Enabled>> {
+    interrupt::free(|cs| RNG.borrow(cs).replace(Some(rng)))
+}
+
+/// Take back the registered RNG; `getrandom` fails afterwards.
+pub fn unregister() -> Option<Rng<init_state::Enabled>> {
+    interrupt::free(|cs| RNG.borrow(cs).take())
+}
+
+/// Run `f` with the registered RNG, if any.
+pub fn with<R>(f: impl FnOnce(&mut Rng<init_state::Enabled>) -> R) -> Option<R> {
+    interrupt::free(|cs| RNG.borrow(cs).borrow_mut().as_mut().map(f))
+}
+
+fn fill(dest: &mut [u8]) -> Result<(), getrandom::Error> {
+    with(|rng| {
+        for chunk in dest.chunks_mut(4) {
+            chunk.copy_from_slice(&rng.get_random_u32().to_ne_bytes()[..chunk.len()]);
+        }
+    })
+    .ok_or_else(|| NonZeroU32::new(NOT_REGISTERED).unwrap().into())
+}
+
+getrandom::register_custom_getrandom!(fill);
@@ -0,0 +1,148 @@
+//! Continuous health tests of the RNG output (NIST SP 800-90B, section 4.4).
+//!
+//! The repetition count test catches a source stuck at one value, the adaptive
+//! proportion test one which produces a value far too often. Both run over the
+//! 32-bit words of the RNG. Once a test fails, the monitor keeps failing until
+//! it is `reset`:
+//!
+//! ```ignore
+//! let mut monitor = HealthMonitor::new();
+//! let word = monitor.get_random_u32(&rng)?;
+//!
+//! // or stop the RNG altogether on failure
+//! let (word, rng) = match monitor.get_random_u32_or_disable(rng, &mut syscon) {
+//!     Ok(output) => output,
+//!     Err((error, disabled_rng)) => return report(error),
+//! };
+//! ```
+
+use crate::peripherals::syscon::Syscon;
+use crate::typestates::init_state;
+use crate::Rng;
+
+use super::Error;
+
+/// Window of the adaptive proportion test, for non-binary samples
+pub const APT_WINDOW: u32 = 512;
+
+/// Continuous health tests over the words of the RNG
+pub struct HealthMonitor {
+    rct_cutoff: u32,
+    apt_cutoff: u32,
+    last: Option<u32>,
+    repetitions: u32,
+    apt_reference: u32,
+    apt_matches: u32,
+    apt_samples: u32,
+    failure: Option<Error>,
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthMonitor {
+    /// Cutoffs for a false positive probability of 2^-20, assuming (very
+    /// conservatively) one bit of min-entropy per word.
+    ///
+    /// With these, only a severely degraded source fails.
+    pub const fn new() -> Self {
+        // RCT: 1 + ceil(20 / H); APT: SP 800-90B table 2, W = 512, H = 1
+        Self::with_cutoffs(21, 410)
+    }
+
+    /// Cutoffs computed for the application's entropy estimate, see SP 800-90B 4.4.
+    ///
+    /// The repetition count test fails once a word repeats `rct_cutoff` times
+    /// in a row, the adaptive proportion test once the first word of a window
+    /// of `APT_WINDOW` words occurs `apt_cutoff` times in it.
+    pub const fn with_cutoffs(rct_cutoff: u32, apt_cutoff: u32) -> Self {
+        Self {
+            rct_cutoff,
+            apt_cutoff,
+            last: None,
+            repetitions: 0,
+            apt_reference: 0,
+            apt_matches: 0,
+            apt_samples: 0,
+            failure: None,
+        }
+    }
+
+    /// The failure of a test, if any, since the last `reset`
+    pub fn failure(&self) -> Option<Error> {
+        self.failure
+    }
+
+    /// Restart both tests, clearing a failure.
+    pub fn reset(&mut self) {
+        *self = Self::with_cutoffs(self.rct_cutoff, self.apt_cutoff);
+    }
+
+    /// Run the tests on a word of the RNG, returning it if they pass.
+    pub fn check(&mut self, word: u32) -> Result<u32, Error> {
+        if let Some(failure) = self.failure {
+            return Err(failure);
+        }
+
+        // repetition count test
+        if self.last == Some(word) {
+            self.repetitions += 1;
+            if self.repetitions >= self.rct_cutoff {
+                self.failure = Some(Error::RepetitionCount);
+            }
+        } else {
+            self.last = Some(word);
+            self.repetitions = 1;
+        }
+
+        // adaptive proportion test
+        if self.apt_samples == 0 {
+            self.apt_reference = word;
+            self.apt_matches = 1;
+        } else if word == self.apt_reference {
+            self.apt_matches += 1;
+            if self.apt_matches >= self.apt_cutoff {
+                self.failure = Some(Error::AdaptiveProportion);
+            }
+        }
+        self.apt_samples = (self.apt_samples + 1) % APT_WINDOW;
+
+        match self.failure {
+            Some(failure) => Err(failure),
+            None => Ok(word),
+        }
+    }
+
+    /// A word of the RNG which passed the tests.
+    pub fn get_random_u32(&mut self, rng: &Rng<init_state::Enabled>) -> Result<u32, Error> {
+        if let Some(failure) = self.failure {
+            return Err(failure);
+        }
+        self.check(rng.get_random_u32())
+    }
+
+    /// Like `get_random_u32`, disabling `rng` on failure, so that no further
+    /// output is produced until the application enables it again.
+    pub fn get_random_u32_or_disable(
+        &mut self,
+        rng: Rng<init_state::Enabled>,
+        syscon: &mut Syscon,
+    ) -> Result<(u32, Rng<init_state::Enabled>), (Error, Rng<init_state::Disabled>)> {
+        match self.get_random_u32(&rng) {
+            Ok(word) => Ok((word, rng)),
+            Err(error) => Err((error, rng.disabled(syscon))),
+        }
+    }
+
+    /// Fill `buffer` with words of the RNG which passed the tests.
+    pub fn read(&mut self, rng: &Rng<init_state::Enabled>, buffer: &mut [u8]) -> Result<(), Error> {
+        for chunk in buffer.chunks_mut(4) {
+            let word = self.get_random_u32(rng)?;
+            chunk.copy_from_slice(&word.to_ne_bytes()[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! A pool of random words, filled in the background.
+//!
+//! Each word from the RNG waits for the entropy counters to refresh. The pool
+//! is topped up without blocking, from a periodic timer interrupt or an idle
+//! loop, so reads return immediately while it has words left:
+//!
+//! ```ignore
+//! static POOL: Mutex<RefCell<EntropyPool<64>>> = Mutex::new(RefCell::new(EntropyPool::new()));
+//!
+//! #[interrupt]
+//! fn CTIMER1() {
+//!     interrupt::free(|cs| POOL.borrow(cs).borrow_mut().refill(rng).ok());
+//! }
+//!
+//! // e.g. while answering a USB request
+//! let read = interrupt::free(|cs| POOL.borrow(cs).borrow_mut().read(&mut challenge));
+//! ```
+
+use crate::typestates::init_state;
+use crate::Rng;
+
+use super::Error;
+
+/// Ring buffer of up to `N` random words.
+///
+/// Each word is handed out once.
+pub struct EntropyPool<const N: usize> {
+    words: [u32; N],
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Default for EntropyPool<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> EntropyPool<N> {
+    pub const fn new() -> Self {
+        Self {
+            words: [0; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Number of words in the pool
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Add the words the RNG has ready, without waiting for more.
+    ///
+    /// Returns the number of words added. Fails if the RNG's online test
+    /// rejects the entropy source, keeping the words added before.
+    pub fn refill(&mut self, rng: &Rng<init_state::Enabled>) -> Result<usize, Error> {
+        let mut added = 0;
+        while !self.is_full() {
+            match rng.try_get_random_u32() {
+                Ok(word) => {
+                    self.words[(self.start + self.len) % N] = word;
+                    self.len += 1;
+                    added += 1;
+                }
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(error)) => return Err(error),
+            }
+        }
+        Ok(added)
+    }
+
+    /// Take a word from the pool, if any.
+    pub fn get_u32(&mut self) -> Option<u32> {
+        if self.is_empty() {
+            return None;
+        }
+        let word = self.words[self.start];
+        // not kept around once handed out
+        self.words[self.start] = 0;
+        self.start = (self.start + 1) % N;
+        self.len -= 1;
+        Some(word)
+    }
+
+    /// Fill `buffer` from the pool, as far as it has words.
+    ///
+    /// Returns the number of bytes filled; the unused bytes of a partially
+    /// used word are discarded.
+    pub fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let mut filled = 0;
+        for chunk in buffer.chunks_mut(4) {
+            match self.get_u32() {
+                Some(word) => {
+                    chunk.copy_from_slice(&word.to_ne_bytes()[..chunk.len()]);
+                    filled += chunk.len();
+                }
+                None => break,
+            }
+        }
+        filled
+    }
+
+    /// Fill `buffer` from the pool, then from the RNG (blocking) once the pool is empty.
+    pub fn read_or_generate(&mut self, rng: &Rng<init_state::Enabled>, buffer: &mut [u8]) {
+        let filled = self.read(buffer);
+        for chunk in buffer[filled..].chunks_mut(4) {
+            chunk.copy_from_slice(&rng.get_random_u32().to_ne_bytes()[..chunk.len()]);
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use core::fmt;
 use core::ops::Deref;
 use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
 
 use crate::{
     typestates::{
+        init_state,
         pin::{
             flexcomm::{
                 // Trait marking USART peripherals and pins
@@ -13,14 +16,26 @@ use crate::{
             PinId,
         },
     },
-    traits::wg::serial,
-    time::Hertz,
+    time::{Hertz, Megahertz},
+    traits::wg::{blocking, serial},
+    peripherals::{
+        dma,
+        flexcomm::{dispatch, ClockSource, Fifo, FifoConfig, Repurpose},
+        syscon,
+    },
 };
 
+pub mod buffered;
+pub use buffered::{BufferedRx, BufferedTx, RingBuffer};
 pub mod config;
+pub mod idle;
+pub use idle::IdleFrames;
+pub mod logger;
 
 /// Serial error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
     /// Framing error
     Framing,
@@ -30,10 +45,24 @@ pub enum Error {
     Overrun,
     /// Parity check error
     Parity,
-    #[doc(hidden)]
-    _Extensible,
+    /// The autobaud start bit was too long to be measured
+    Autobaud,
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::Framing => "framing error",
+            Error::Noise => "noise detected",
+            Error::Overrun => "RX FIFO overrun",
+            Error::Parity => "parity error",
+            Error::Autobaud => "autobaud time-out",
+        })
+    }
+}
+
+impl core::error::Error for Error {}
+
 // /// Interrupt event
 // pub enum Event {
 //     /// New data has been received
@@ -91,6 +120,78 @@ impl<USART: Usart> Deref for Rx<USART> {
     }
 }
 
+// so a split-off receiver can be streamed into a `DmaRingBuffer`
+impl<USART: Usart + dma::RxRequest> dma::RxRequest for Rx<USART> {
+    const RX_CHANNEL: u8 = USART::RX_CHANNEL;
+
+    fn rx_data_address(&self) -> u32 {
+        &self.fiford as *const _ as u32
+    }
+
+    fn enable_rx_request(&mut self) {
+        self.fifocfg.modify(|_, w| w.dmarx().enabled());
+    }
+
+    fn disable_rx_request(&mut self) {
+        self.fifocfg.modify(|_, w| w.dmarx().disabled());
+    }
+}
+
+// so a split-off transmitter can be fed by the DMA
+impl<USART: Usart + dma::TxRequest> dma::TxRequest for Tx<USART> {
+    const TX_CHANNEL: u8 = USART::TX_CHANNEL;
+
+    fn tx_data_address(&self) -> u32 {
+        &self.fifowr as *const _ as u32
+    }
+
+    fn enable_tx_request(&mut self) {
+        self.fifocfg.modify(|_, w| w.dmatx().enabled());
+    }
+
+    fn disable_tx_request(&mut self) {
+        self.fifocfg.modify(|_, w| w.dmatx().disabled());
+    }
+}
+
+impl<USART: Usart + dma::TxRequest> Tx<USART> {
+    /// Transmit `bytes` by DMA, blocking until the DMA is done, without a
+    /// per-byte interrupt or poll. The last bytes may still be in the TX FIFO,
+    /// see `flush`.
+    ///
+    /// For transfers in the background, see `dma::Dma::transmit`.
+    pub fn write_all_dma(&mut self, dma: &mut dma::Dma<init_state::Enabled>, bytes: &[u8]) {
+        dma.write_all(self, bytes);
+    }
+}
+
+impl<USART: Usart + dma::RxRequest> Rx<USART> {
+    /// Fill `buffer` by DMA, blocking until the DMA is done.
+    ///
+    /// Errors are reported for the buffer as a whole, as the DMA does not stop on them.
+    /// For transfers in the background, see `dma::Dma::receive`.
+    pub fn read_exact_dma(&mut self, dma: &mut dma::Dma<init_state::Enabled>, buffer: &mut [u8]) -> Result<(), Error> {
+        // clear the sticky flags of earlier errors
+        self.stat.write(|w| w.framerrint().set_bit().parityerrint().set_bit().rxnoiseint().set_bit());
+        dma.read_exact(self, buffer);
+
+        // STAT.FRAMERRINT, PARITYERRINT and RXNOISEINT are write-only in the PAC
+        let stat = self.stat.read().bits();
+        if self.fifostat.read().rxerr().bit_is_set() {
+            self.fifostat.write(|w| w.rxerr().set_bit());
+            Err(Error::Overrun)
+        } else if stat & (1 << 13) != 0 {
+            Err(Error::Framing)
+        } else if stat & (1 << 14) != 0 {
+            Err(Error::Parity)
+        } else if stat & (1 << 15) != 0 {
+            Err(Error::Noise)
+        } else {
+            Ok(())
+        }
+    }
+}
+
 impl<TX, RX, USART, PINS> Serial<TX, RX, USART, PINS>
 where
     TX: PinId,
@@ -98,25 +199,28 @@ where
     USART: Usart,
     PINS: UsartPins<TX, RX, USART>,
 {
-    const CLOCK_SPEED: u32 = 12_000_000;
-
+    /// Configure the USART for `pins`, `(tx, rx)`, `(tx, rx, rts, cts)` or `(tx, rx, rts)`.
+    ///
+    /// With RTS and CTS, hardware flow control is enabled: the transmitter only
+    /// sends while CTS is asserted, and RTS is deasserted while the RX FIFO is full.
+    ///
+    /// With RTS only, it enables the driver of an RS-485 transceiver while
+    /// transmitting, see `config::Rs485`.
     pub fn new(usart: USART, pins: PINS, config: config::Config) -> Self {
         use self::config::*;
 
-        let speed: Hertz = config.speed.into();
-        let speed: u32 = speed.0;
+        let speed: u32 = config.speed.to_Hz();
+        let clock: u32 = usart.clock_frequency().expect("unknown function clock").to_Hz();
+        let mode32k = usart.clock_source() == Some(ClockSource::Osc32k);
 
-        usart.fifocfg.modify(|_, w| w
-            .enabletx().enabled()
-            .enablerx().enabled()
-        );
+        usart.configure_fifo(config.fifo);
 
-        usart.fifotrig.modify(|_, w| unsafe { w
-            .txlvl().bits(0)
-            .txlvlena().enabled()
-            .rxlvl().bits(1)
-            .rxlvlena().enabled()
-        });
+        let rs485 = config.rs485;
+        if let Some(address) = config.address {
+            usart.addr.write(|w| unsafe { w.address().bits(address) });
+        }
+        // the receiver discards everything before the address
+        usart.ctl.modify(|_, w| w.addrdet().bit(config.address.is_some()));
 
         usart.cfg.write(|w| unsafe { w
             .paritysel().bits(match config.parity {
@@ -129,11 +233,23 @@ where
                 StopBits::STOP2 => true,
             })
             .datalen().bits(match config.wordlength {
+                _ if config.address.is_some() => 2,
                 WordLength::DataBits7 => 0,
                 WordLength::DataBits8 => 1,
                 WordLength::DataBits9 => 2,
             })
 
+            // with flow control, the transmitter waits for CTS and the receiver
+            // drives RTS; for RS-485, RTS is the output enable instead
+            .ctsen().bit(PINS::FLOW_CONTROL)
+            .oesel().bit(PINS::DRIVER_ENABLE)
+            .oepol().bit(rs485.active_high)
+            .oeta().bit(rs485.turnaround)
+            .autoaddr().bit(config.address.is_some())
+            .txpol().bit(config.invert_tx)
+            .rxpol().bit(config.invert_rx)
+            .mode32k().bit(mode32k)
+
             // these are just some defaults (of zero)
 
             // loopback mode
@@ -147,35 +263,57 @@ where
             .enable().enabled()
         });
 
-        // baudrate logic from `fsl_usart.c` in SDK
-        let mut best_diff = !0;
-        let mut best_osr = 15;
-        let mut best_brg = !0;
-
-        // SDK says: "Smaller values of OSR can make the sampling position within a data bit less
-        // accurate and may potentially cause more noise errors or incorrect data."
-        for osr in (9..=16).rev() {
-            let brg = Self::CLOCK_SPEED / (osr * speed);
-            if brg >= 0xffff {
-                continue;
+        if mode32k {
+            // the 32 kHz mode clocks bits at 9600 baud, divided by BRG
+            if speed == 0 || speed > 9600 || 9600 % speed != 0 {
+                panic!("baudrate not supported");
             }
-            let realized_speed = Self::CLOCK_SPEED / (osr * brg);
-            let diff = if speed > realized_speed { speed - realized_speed} else { realized_speed - speed };
-            if diff < best_diff {
-                best_diff = diff;
-                best_osr = osr;
-                best_brg = brg;
+            usart.brg.write(|w| unsafe { w.brgval().bits((9600 / speed - 1) as u16) });
+        } else {
+            // baudrate logic from `fsl_usart.c` in SDK
+            let mut best_diff = !0;
+            let mut best_osr = 15;
+            let mut best_brg = !0;
+
+            // SDK says: "Smaller values of OSR can make the sampling position within a data bit less
+            // accurate and may potentially cause more noise errors or incorrect data."
+            for osr in (9..=16).rev() {
+                let brg = clock / (osr * speed);
+                if brg == 0 || brg >= 0xffff {
+                    continue;
+                }
+                let realized_speed = clock / (osr * brg);
+                let diff = if speed > realized_speed { speed - realized_speed} else { realized_speed - speed };
+                if diff < best_diff {
+                    best_diff = diff;
+                    best_osr = osr;
+                    best_brg = brg;
+                }
+            }
+
+            // TODO: return Result instead of panicking
+            if best_brg >= 0xffff {
+                panic!("baudrate not supported");
             }
-        }
 
-        // TODO: return Result instead of panicking
-        if best_brg >= 0xffff {
-            panic!("baudrate not supported");
+            usart.brg.write(|w| unsafe { w.brgval().bits(best_brg as u16 - 1) });
+            usart.osr.write(|w| unsafe { w.osrval().bits(best_osr as u8 - 1) });
         }
 
-        usart.brg.write(|w| unsafe { w.brgval().bits(best_brg as u16 - 1) });
-        usart.osr.write(|w| unsafe { w.osrval().bits(best_osr as u8 - 1) });
+        Self {
+            usart,
+            pins,
+            _tx: PhantomData,
+            _rx: PhantomData,
+        }
+    }
 
+    /// Wrap `usart` as configured by `new` before, without touching the hardware.
+    ///
+    /// # Safety
+    ///
+    /// The driver owning the peripheral must not be used concurrently.
+    pub unsafe fn steal(usart: USART, pins: PINS) -> Self {
         Self {
             usart,
             pins,
@@ -188,6 +326,17 @@ where
         &(*self.usart) as *const _ as usize
     }
 
+    /// Transmitter and receiver, borrowing the peripheral from `self`
+    fn halves(&self) -> (Tx<USART>, Rx<USART>) {
+        (
+            Tx { addr: self.addr(), _usart: PhantomData },
+            Rx { addr: self.addr(), _usart: PhantomData },
+        )
+    }
+
+    /// Split into transmitter and receiver, which own the peripheral from now on.
+    ///
+    /// Neither of them disables the peripheral when dropped.
     pub fn split(self) -> (Tx<USART>, Rx<USART>) {
         // so umm... Tx/Rx "promise" to not step on each others' toes
         //
@@ -199,20 +348,236 @@ where
         // - reads fifostat and fiford
         // - modifies fifocfg + fifostat on buffer overflow
 
+        let this = ManuallyDrop::new(self);
         (
             Tx {
-                addr: self.addr(),
+                addr: this.addr(),
                 _usart: PhantomData,
             },
             Rx {
-                addr: self.addr(),
+                addr: this.addr(),
                 _usart: PhantomData,
             },
         )
     }
 
+    /// Split into halves buffered in `tx_buffer` and `rx_buffer`, see `buffered`.
+    pub fn split_buffered<const T: usize, const R: usize>(
+        self,
+        tx_buffer: &'static RingBuffer<T>,
+        rx_buffer: &'static RingBuffer<R>,
+    ) -> (BufferedTx<USART>, BufferedRx<USART>) {
+        let (tx, rx) = self.split();
+        (tx.into_buffered(tx_buffer), rx.into_buffered(rx_buffer))
+    }
+
+    /// Ignore the received frames until one starts with `config::Config::address`.
+    ///
+    /// This is the state after `new` with an address. Once the address has been
+    /// read, `accept_frame` receives the rest of the frame.
+    pub fn wait_for_address(&mut self) {
+        self.usart.ctl.modify(|_, w| w.addrdet().enabled());
+    }
+
+    /// Receive all words, after the matching address was read.
+    pub fn accept_frame(&mut self) {
+        self.usart.ctl.modify(|_, w| w.addrdet().disabled());
+    }
+
+    /// The bit rate, as configured from the function clock (zero if that is unknown)
+    pub fn baud_rate(&self) -> Hertz {
+        if self.usart.cfg.read().mode32k().bit_is_set() {
+            return Hertz::from_raw(9600 / (self.usart.brg.read().brgval().bits() as u32 + 1));
+        }
+        let divider = (self.usart.osr.read().osrval().bits() as u32 + 1)
+            * (self.usart.brg.read().brgval().bits() as u32 + 1);
+        Hertz::from_raw(self.usart.clock_frequency().map_or(0, |clock| clock.to_Hz()) / divider)
+    }
+
+    /// Measure the bit rate of the next received character, and adopt it.
+    ///
+    /// The hardware times the start bit, so the character must have its least
+    /// significant bit set (e.g. `U` or `\r`). The receiver must be idle.
+    /// See `autobaud_result` for the outcome.
+    pub fn start_autobaud(&mut self) {
+        // clear an earlier error (STAT.ABERR is write-only in the PAC)
+        self.usart.stat.write(|w| w.aberr().set_bit());
+        self.usart.ctl.modify(|_, w| w.autobaud().enabled());
+    }
+
+    /// The bit rate measured since `start_autobaud`, now in use.
+    pub fn autobaud_result(&mut self) -> nb::Result<Hertz, Error> {
+        if self.usart.stat.read().bits() & (1 << 16) != 0 {
+            self.usart.stat.write(|w| w.aberr().set_bit());
+            self.usart.ctl.modify(|_, w| w.autobaud().disabled());
+            return Err(nb::Error::Other(Error::Autobaud));
+        }
+        if self.usart.ctl.read().autobaud().is_enabled() {
+            return Err(nb::Error::WouldBlock);
+        }
+        Ok(self.baud_rate())
+    }
+
+    /// Wait for a character to measure the bit rate from, see `start_autobaud`.
+    pub fn autobaud(&mut self) -> Result<Hertz, Error> {
+        self.start_autobaud();
+        nb::block!(self.autobaud_result())
+    }
+
+    /// Let a received character wake the chip from deep-sleep, by interrupting
+    /// on its start bit (INTENSET.STARTEN) and enabling the Flexcomm interrupt
+    /// as a wake-up source.
+    ///
+    /// In deep-sleep, only the 32 kHz oscillator keeps running, so the USART
+    /// needs to be clocked from `ClockSource::Osc32k` (9600 baud or less), and
+    /// the rest of the wake-up character is still received. The interrupt
+    /// needs to be unmasked, and is acknowledged with `start_detected`.
+    pub fn enable_wakeup(&mut self) {
+        self.usart.intenset.write(|w| w.starten().set_bit());
+        crate::power::enable_wakeup(dispatch::interrupt(dispatch::instance(&self.usart)));
+    }
+
+    pub fn disable_wakeup(&mut self) {
+        self.usart.intenclr.write(|w| w.startclr().set_bit());
+        crate::power::disable_wakeup(dispatch::interrupt(dispatch::instance(&self.usart)));
+    }
+
+    /// Was a start bit received since the last call? (STAT.START)
+    pub fn start_detected(&mut self) -> bool {
+        // START is write-only in the PAC
+        let detected = self.usart.stat.read().bits() & (1 << 12) != 0;
+        if detected {
+            self.usart.stat.write(|w| w.start().set_bit());
+        }
+        detected
+    }
+
+    /// Send a break of `bits` bit times (e.g. 23 at 250 kBd for the 92 µs of DMX512),
+    /// once everything written has been sent.
+    ///
+    /// The break is timed with the DWT cycle counter, at the `system_frequency`,
+    /// so it lasts at least `bits` bit times even if interrupted. Enables the
+    /// cycle counter, which then also runs without a debugger attached.
+    pub fn send_break(&mut self, bits: u32, system_frequency: Megahertz) {
+        let cycles = bits as u64 * system_frequency.to_Hz() as u64 / self.baud_rate().to_Hz().max(1) as u64;
+        crate::enable_cycle_counter();
+        let (mut tx, _) = self.halves();
+        tx.start_break();
+        let start = crate::get_cycle_count();
+        while (crate::get_cycle_count().wrapping_sub(start) as u64) < cycles {}
+        tx.end_break();
+    }
+
+    /// Is a break being received? See `Rx::is_break`.
+    pub fn is_break(&self) -> bool {
+        self.usart.stat.read().rxbrk().bit_is_set()
+    }
+
+    /// Has a break started or ended since the last call? See `Rx::break_changed`.
+    pub fn break_changed(&mut self) -> bool {
+        let mut rx: Rx<USART> = Rx {
+            addr: self.addr(),
+            _usart: PhantomData,
+        };
+        rx.break_changed()
+    }
+
+    /// Read a word of up to 9 bits, see `Rx::read_word`.
+    pub fn read_word(&mut self) -> nb::Result<u16, Error> {
+        let mut rx: Rx<USART> = Rx {
+            addr: self.addr(),
+            _usart: PhantomData,
+        };
+        rx.read_word()
+    }
+
+    /// Write a word of up to 9 bits, see `Tx::write_word`.
+    pub fn write_word(&mut self, word: u16) -> nb::Result<(), Error> {
+        let mut tx: Tx<USART> = Tx {
+            addr: self.addr(),
+            _usart: PhantomData,
+        };
+        tx.write_word(word)
+    }
+
+    /// Address a multidrop node, see `Tx::write_address`.
+    pub fn write_address(&mut self, address: u8) -> nb::Result<(), Error> {
+        let mut tx: Tx<USART> = Tx {
+            addr: self.addr(),
+            _usart: PhantomData,
+        };
+        tx.write_address(address)
+    }
+
+    /// Transmit `bytes` by DMA, see `Tx::write_all_dma`.
+    pub fn write_all_dma(&mut self, dma: &mut dma::Dma<init_state::Enabled>, bytes: &[u8])
+    where
+        USART: dma::TxRequest,
+    {
+        let mut tx: Tx<USART> = Tx {
+            addr: self.addr(),
+            _usart: PhantomData,
+        };
+        tx.write_all_dma(dma, bytes)
+    }
+
+    /// Fill `buffer` by DMA, see `Rx::read_exact_dma`.
+    pub fn read_exact_dma(&mut self, dma: &mut dma::Dma<init_state::Enabled>, buffer: &mut [u8]) -> Result<(), Error>
+    where
+        USART: dma::RxRequest,
+    {
+        let mut rx: Rx<USART> = Rx {
+            addr: self.addr(),
+            _usart: PhantomData,
+        };
+        rx.read_exact_dma(dma, buffer)
+    }
+
+    /// Change the FIFO configuration, see `config::Config::fifo`.
+    pub fn configure_fifo(&mut self, config: FifoConfig) {
+        self.usart.configure_fifo(config);
+    }
+
     pub fn release(self) -> (USART, PINS) {
-        (self.usart, self.pins)
+        let this = ManuallyDrop::new(self);
+        unsafe { (ptr::read(&this.usart), ptr::read(&this.pins)) }
+    }
+
+    /// Masks the interrupts and tears down the USART personality, returning
+    /// the disabled Flexcomm (to enable it as another) and the pins.
+    pub fn teardown(self, syscon: &mut syscon::Syscon) -> (USART::Flexcomm, PINS)
+    where
+        USART: Repurpose,
+    {
+        self.usart.fifointenclr.write(|w| unsafe { w.bits(!0) });
+        self.usart.intenclr.write(|w| unsafe { w.bits(!0) });
+        let (usart, pins) = self.release();
+        (usart.teardown(syscon), pins)
+    }
+
+    /// The peripheral owned by the driver, which derefs to the raw registers.
+    pub fn raw(&self) -> &USART {
+        &self.usart
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut USART {
+        &mut self.usart
+    }
+}
+
+/// Masks the interrupts and gates the clock.
+impl<TX, RX, USART, PINS> Drop for Serial<TX, RX, USART, PINS>
+where
+    TX: PinId,
+    RX: PinId,
+    USART: Usart,
+    PINS: UsartPins<TX, RX, USART>,
+{
+    fn drop(&mut self) {
+        self.usart.fifointenclr.write(|w| unsafe { w.bits(!0) });
+        self.usart.intenclr.write(|w| unsafe { w.bits(!0) });
+        syscon::disable_clock_on_drop(&self.usart);
     }
 }
 
@@ -234,10 +599,9 @@ where
     }
 }
 
-impl<USART: Usart> serial::Read<u8> for Rx<USART> {
-    type Error = Error;
-
-    fn read(&mut self) -> nb::Result<u8, Error> {
+impl<USART: Usart> Rx<USART> {
+    /// Read a word of up to 9 bits, e.g. a multidrop address (9th bit set).
+    pub fn read_word(&mut self) -> nb::Result<u16, Error> {
         let fifostat = self.fifostat.read();
 
         if fifostat.rxnotempty().bit() {
@@ -265,7 +629,7 @@ impl<USART: Usart> serial::Read<u8> for Rx<USART> {
                 return Err(nb::Error::Other(Error::Overrun));
             }
 
-            Ok(fiford.rxdata().bits() as u8)
+            Ok(fiford.rxdata().bits())
 
         } else {
             // cortex_m_semihosting::hprintln!("not rxnotempty").ok();
@@ -274,6 +638,14 @@ impl<USART: Usart> serial::Read<u8> for Rx<USART> {
     }
 }
 
+impl<USART: Usart> serial::Read<u8> for Rx<USART> {
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        self.read_word().map(|word| word as u8)
+    }
+}
+
 
 impl<TX, RX, USART, PINS> serial::Write<u8> for Serial<TX, RX, USART, PINS>
 where
@@ -313,18 +685,84 @@ impl<USART: Usart> serial::Write<u8> for Tx<USART> {
     }
 
     fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        self.write_word(byte as u16)
+    }
+}
+
+impl<USART: Usart> Tx<USART> {
+    /// Write a word of up to 9 bits (with 9-bit words configured).
+    pub fn write_word(&mut self, word: u16) -> nb::Result<(), Error> {
         if self.fifostat.read().txnotfull().bit() {
             // TODO: figure out if we need to perform an 8-bit write
             // This would not be possible via svd2rust API, and need some acrobatics
-            self.fifowr.write(|w| unsafe { w.bits(byte as u32) } );
+            self.fifowr.write(|w| unsafe { w.bits((word & 0x1ff) as u32) } );
 
             Ok(())
         } else {
             Err(nb::Error::WouldBlock)
         }
     }
+
+    /// Write the address of a multidrop node, as a 9-bit word with the 9th bit set.
+    ///
+    /// The following bytes go to the addressed node.
+    pub fn write_address(&mut self, address: u8) -> nb::Result<(), Error> {
+        self.write_word(0x100 | address as u16)
+    }
+
+    /// Hold the line low (a break) until `end_break`, once everything written
+    /// has been sent.
+    pub fn start_break(&mut self) {
+        while self.fifostat.read().txempty().bit_is_clear() {}
+        // TXBRKEN must only change while the transmitter is disabled
+        self.ctl.modify(|_, w| w.txdis().disabled());
+        while self.stat.read().txdisstat().bit_is_clear() {}
+        self.ctl.modify(|_, w| w.txbrken().continous());
+    }
+
+    pub fn end_break(&mut self) {
+        self.ctl.modify(|_, w| w.txbrken().normal());
+        self.ctl.modify(|_, w| w.txdis().enabled());
+    }
 }
 
+impl<USART: Usart> Rx<USART> {
+    /// The line is held low, for 16 bit times or more (STAT.RXBRK)
+    pub fn is_break(&self) -> bool {
+        self.stat.read().rxbrk().bit_is_set()
+    }
+
+    /// Has a break started or ended since the last call? (STAT.DELTARXBRK)
+    pub fn break_changed(&mut self) -> bool {
+        // DELTARXBRK is write-only in the PAC
+        let changed = self.stat.read().bits() & (1 << 11) != 0;
+        if changed {
+            self.stat.write(|w| w.deltarxbrk().set_bit());
+        }
+        changed
+    }
+
+    /// Interrupt when a break starts or ends.
+    pub fn listen_break(&mut self) {
+        self.intenset.write(|w| w.deltarxbrken().set_bit());
+    }
+
+    pub fn unlisten_break(&mut self) {
+        self.intenclr.write(|w| w.deltarxbrkclr().set_bit());
+    }
+}
+
+// `blocking::serial::Write`, by blocking on `serial::Write`
+impl<TX, RX, USART, PINS> blocking::serial::write::Default<u8> for Serial<TX, RX, USART, PINS>
+where
+    TX: PinId,
+    RX: PinId,
+    USART: Usart,
+    PINS: UsartPins<TX, RX, USART>,
+{}
+
+impl<USART: Usart> blocking::serial::write::Default<u8> for Tx<USART> {}
+
 impl<USART: Usart> fmt::Write for Tx<USART>
 where
     Tx<USART>: serial::Write<u8>,
@@ -339,3 +777,372 @@ where
         Ok(())
     }
 }
+
+impl<TX, RX, USART, PINS> fmt::Write for Serial<TX, RX, USART, PINS>
+where
+    TX: PinId,
+    RX: PinId,
+    USART: Usart,
+    PINS: UsartPins<TX, RX, USART>,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut tx: Tx<USART> = Tx {
+            addr: self.addr(),
+            _usart: PhantomData,
+        };
+        tx.write_str(s)
+    }
+}
+
+#[cfg(feature = "io")]
+mod io_impls {
+    use crate::traits::io::{self, ErrorKind, ErrorType, ReadReady, WriteReady};
+    use crate::traits::wg::serial::{Read, Write};
+    use crate::typestates::pin::{flexcomm::{Usart, UsartPins}, PinId};
+    use super::{BufferedRx, BufferedTx, Error, Rx, Serial, Tx};
+
+    impl io::Error for Error {
+        fn kind(&self) -> ErrorKind {
+            match self {
+                Error::Framing | Error::Noise | Error::Parity => ErrorKind::InvalidData,
+                Error::Overrun | Error::Autobaud => ErrorKind::Other,
+            }
+        }
+    }
+
+    impl<USART: Usart> ErrorType for Tx<USART> {
+        type Error = Error;
+    }
+
+    impl<USART: Usart> ErrorType for Rx<USART> {
+        type Error = Error;
+    }
+
+    impl<TX, RX, USART, PINS> ErrorType for Serial<TX, RX, USART, PINS>
+    where
+        TX: PinId,
+        RX: PinId,
+        USART: Usart,
+        PINS: UsartPins<TX, RX, USART>,
+    {
+        type Error = Error;
+    }
+
+    impl<USART: Usart> ErrorType for BufferedTx<USART> {
+        type Error = Error;
+    }
+
+    impl<USART: Usart> ErrorType for BufferedRx<USART> {
+        type Error = Error;
+    }
+
+    /// Blocks until the first byte fits into the TX FIFO, then writes as many as fit.
+    impl<USART: Usart> io::Write for Tx<USART> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            let Some((first, rest)) = buf.split_first() else {
+                return Ok(0);
+            };
+            nb::block!(Write::write(self, *first))?;
+            Ok(1 + rest.iter().take_while(|&&byte| Write::write(self, byte).is_ok()).count())
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            nb::block!(Write::flush(self))
+        }
+    }
+
+    impl<USART: Usart> WriteReady for Tx<USART> {
+        fn write_ready(&mut self) -> Result<bool, Error> {
+            Ok(self.fifostat.read().txnotfull().bit_is_set())
+        }
+    }
+
+    /// Blocks until the first byte is received, then reads the bytes in the RX FIFO.
+    impl<USART: Usart> io::Read for Rx<USART> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            let Some((first, rest)) = buf.split_first_mut() else {
+                return Ok(0);
+            };
+            *first = nb::block!(Read::read(self))?;
+            let mut n = 1;
+            for byte in rest {
+                match Read::read(self) {
+                    Ok(received) => *byte = received,
+                    Err(nb::Error::WouldBlock) => break,
+                    Err(nb::Error::Other(error)) => return Err(error),
+                }
+                n += 1;
+            }
+            Ok(n)
+        }
+    }
+
+    impl<USART: Usart> ReadReady for Rx<USART> {
+        fn read_ready(&mut self) -> Result<bool, Error> {
+            Ok(self.fifostat.read().rxnotempty().bit_is_set())
+        }
+    }
+
+    impl<TX, RX, USART, PINS> io::Write for Serial<TX, RX, USART, PINS>
+    where
+        TX: PinId,
+        RX: PinId,
+        USART: Usart,
+        PINS: UsartPins<TX, RX, USART>,
+    {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            io::Write::write(&mut self.halves().0, buf)
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            io::Write::flush(&mut self.halves().0)
+        }
+    }
+
+    impl<TX, RX, USART, PINS> io::Read for Serial<TX, RX, USART, PINS>
+    where
+        TX: PinId,
+        RX: PinId,
+        USART: Usart,
+        PINS: UsartPins<TX, RX, USART>,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            io::Read::read(&mut self.halves().1, buf)
+        }
+    }
+
+    /// Blocks until the first byte fits into the buffer, then buffers as many as fit.
+    impl<USART: Usart> io::Write for BufferedTx<USART> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            let Some(first) = buf.first() else {
+                return Ok(0);
+            };
+            nb::block!(Write::write(self, *first))?;
+            Ok(1 + BufferedTx::write(self, &buf[1..]))
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            nb::block!(Write::flush(self))
+        }
+    }
+
+    /// Blocks until the first byte is received, then reads the buffered bytes.
+    impl<USART: Usart> io::Read for BufferedRx<USART> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            while self.is_empty() {}
+            Ok(BufferedRx::read(self, buf))
+        }
+    }
+
+    impl<USART: Usart> io::BufRead for BufferedRx<USART> {
+        fn fill_buf(&mut self) -> Result<&[u8], Error> {
+            while self.is_empty() {}
+            Ok(self.buffered())
+        }
+
+        fn consume(&mut self, amt: usize) {
+            BufferedRx::consume(self, amt)
+        }
+    }
+
+    impl<USART: Usart> ReadReady for BufferedRx<USART> {
+        fn read_ready(&mut self) -> Result<bool, Error> {
+            Ok(!self.is_empty())
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+mod async_impls {
+    use crate::drivers::asynch;
+    use crate::raw;
+    use crate::peripherals::flexcomm::fifo::FIFO_DEPTH;
+    use crate::traits::wg::serial::{Read, Write};
+    use crate::typestates::pin::{flexcomm::{Usart, UsartPins}, PinId};
+    use super::{Error, Rx, Serial, Tx};
+
+    /// Needs `asynch::FlexcommInterruptHandler` bound to the Flexcomm's interrupt.
+    impl<USART: Usart> Tx<USART> {
+        async fn writable(&self) {
+            let index = asynch::flexcomm_index(self.addr as *const u8);
+            // `Serial::new` sets the TX trigger level to an empty FIFO
+            self.fifotrig.modify(|_, w| w.txlvlena().enabled());
+            asynch::wait_for(
+                &asynch::FLEXCOMM_WAKERS[index],
+                || self.fifostat.read().txnotfull().bit_is_set(),
+                || self.fifointenset.write(|w| w.txlvl().set_bit()),
+            ).await
+        }
+
+        /// Write all of `bytes`, refilling the TX FIFO each time it drains to the
+        /// TX trigger level.
+        pub async fn write_all_async(&mut self, mut bytes: &[u8]) -> Result<(), Error> {
+            while !bytes.is_empty() {
+                self.writable().await;
+                let n = bytes.iter().take_while(|&&byte| self.write(byte).is_ok()).count();
+                bytes = &bytes[n..];
+            }
+            Ok(())
+        }
+
+        /// Wait until all bytes have been sent.
+        pub async fn flush_async(&mut self) -> Result<(), Error> {
+            let index = asynch::flexcomm_index(self.addr as *const u8);
+            asynch::wait_for(
+                &asynch::FLEXCOMM_WAKERS[index],
+                || self.fifostat.read().txempty().bit_is_set(),
+                || self.fifointenset.write(|w| w.txlvl().set_bit()),
+            ).await;
+            // last stop bit
+            nb::block!(self.flush())
+        }
+    }
+
+    /// The RX trigger fields of FIFOTRIG, restored when dropped (also if the
+    /// future waiting is), leaving the TX trigger to `Tx::writable`
+    struct RxTrigger<'a> {
+        fifotrig: &'a raw::usart0::FIFOTRIG,
+        level: u8,
+        enabled: bool,
+    }
+
+    impl<'a> RxTrigger<'a> {
+        fn save(fifotrig: &'a raw::usart0::FIFOTRIG) -> Self {
+            let r = fifotrig.read();
+            Self { fifotrig, level: r.rxlvl().bits(), enabled: r.rxlvlena().bit_is_set() }
+        }
+    }
+
+    impl Drop for RxTrigger<'_> {
+        fn drop(&mut self) {
+            self.fifotrig.modify(|_, w| unsafe { w.rxlvl().bits(self.level).rxlvlena().bit(self.enabled) });
+        }
+    }
+
+    /// Needs `asynch::FlexcommInterruptHandler` bound to the Flexcomm's interrupt.
+    impl<USART: Usart> Rx<USART> {
+        /// Wait for `level` (1 to half the `FIFO_DEPTH`) bytes in the RX FIFO, or an overrun.
+        ///
+        /// Capped at half the FIFO, so bytes arriving while the task is woken
+        /// up don't overrun it. The RX trigger of `Config::fifo` is restored after.
+        async fn readable(&self, level: usize) {
+            let index = asynch::flexcomm_index(self.addr as *const u8);
+            let level = level.clamp(1, FIFO_DEPTH as usize / 2);
+            let _restore = RxTrigger::save(&self.fifotrig);
+            // trigger once the FIFO holds `level` bytes
+            self.fifotrig.modify(|_, w| unsafe { w.rxlvl().bits(level as u8 - 1).rxlvlena().enabled() });
+            asynch::wait_for(
+                &asynch::FLEXCOMM_RX_WAKERS[index],
+                || {
+                    let fifostat = self.fifostat.read();
+                    fifostat.rxlvl().bits() as usize >= level || fifostat.rxerr().bit_is_set()
+                },
+                || self.fifointenset.write(|w| w.rxlvl().set_bit()),
+            ).await;
+        }
+
+        /// Fill `buffer`, waking up once per half RX FIFO of bytes rather than per byte.
+        pub async fn read_exact_async(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+            for chunk in buffer.chunks_mut(FIFO_DEPTH as usize / 2) {
+                self.readable(chunk.len()).await;
+                for byte in chunk.iter_mut() {
+                    *byte = nb::block!(self.read())?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Waits for room for the first byte, then writes as many as fit into the TX FIFO.
+    #[cfg(feature = "io")]
+    impl<USART: Usart> crate::traits::io_async::Write for Tx<USART> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            self.writable().await;
+            Ok(buf.iter().take_while(|&&byte| Write::write(self, byte).is_ok()).count())
+        }
+
+        async fn flush(&mut self) -> Result<(), Error> {
+            self.flush_async().await
+        }
+    }
+
+    /// Waits for the first byte, then reads the bytes in the RX FIFO.
+    #[cfg(feature = "io")]
+    impl<USART: Usart> crate::traits::io_async::Read for Rx<USART> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            self.readable(1).await;
+            let mut n = 0;
+            for byte in buf.iter_mut() {
+                match Read::read(self) {
+                    Ok(received) => *byte = received,
+                    Err(nb::Error::WouldBlock) => break,
+                    Err(nb::Error::Other(error)) => return Err(error),
+                }
+                n += 1;
+            }
+            Ok(n)
+        }
+    }
+
+    /// Needs `asynch::FlexcommInterruptHandler` bound to the Flexcomm's interrupt.
+    impl<TX, RX, USART, PINS> Serial<TX, RX, USART, PINS>
+    where
+        TX: PinId,
+        RX: PinId,
+        USART: Usart,
+        PINS: UsartPins<TX, RX, USART>,
+    {
+        /// See `Tx::write_all_async`.
+        pub async fn write_all_async(&mut self, bytes: &[u8]) -> Result<(), Error> {
+            self.halves().0.write_all_async(bytes).await
+        }
+
+        pub async fn flush_async(&mut self) -> Result<(), Error> {
+            self.halves().0.flush_async().await
+        }
+
+        /// See `Rx::read_exact_async`.
+        pub async fn read_exact_async(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+            self.halves().1.read_exact_async(buffer).await
+        }
+    }
+
+    #[cfg(feature = "io")]
+    impl<TX, RX, USART, PINS> crate::traits::io_async::Write for Serial<TX, RX, USART, PINS>
+    where
+        TX: PinId,
+        RX: PinId,
+        USART: Usart,
+        PINS: UsartPins<TX, RX, USART>,
+    {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            crate::traits::io_async::Write::write(&mut self.halves().0, buf).await
+        }
+
+        async fn flush(&mut self) -> Result<(), Error> {
+            self.flush_async().await
+        }
+    }
+
+    #[cfg(feature = "io")]
+    impl<TX, RX, USART, PINS> crate::traits::io_async::Read for Serial<TX, RX, USART, PINS>
+    where
+        TX: PinId,
+        RX: PinId,
+        USART: Usart,
+        PINS: UsartPins<TX, RX, USART>,
+    {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            crate::traits::io_async::Read::read(&mut self.halves().1, buf).await
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Interrupt-driven serial halves, buffered in ring buffers.
+//!
+//! The Flexcomm interrupt moves the bytes between the FIFOs and the buffers, so
+//! writing only blocks once the TX buffer is full, and bytes are received while
+//! the application is busy (until the RX buffer is full). The handler is
+//! registered with `flexcomm::dispatch`, whose `DispatchHandler` must be bound:
+//!
+//! ```ignore
+//! hal::bind_interrupts!(struct Irqs {
+//!     FLEXCOMM0 => hal::peripherals::flexcomm::DispatchHandler<0>;
+//! });
+//!
+//! static TX_BUFFER: RingBuffer<256> = RingBuffer::new();
+//! static RX_BUFFER: RingBuffer<64> = RingBuffer::new();
+//!
+//! let (mut tx, mut rx) = serial.split_buffered(&TX_BUFFER, &RX_BUFFER);
+//! hal::interrupt::unmask::<DispatchHandler<0>>(Irqs);
+//!
+//! writeln!(tx, "booted").ok();
+//! let mut line = [0u8; 64];
+//! let received = rx.read(&mut line);
+//! ```
+
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::{
+    peripherals::flexcomm::dispatch,
+    raw,
+    traits::wg::{blocking, serial},
+    typestates::pin::flexcomm::Usart,
+};
+use super::{Error, Rx, Tx};
+
+/// Byte buffer between a serial half and the interrupt, `N` must be a power of two.
+///
+/// Meant to be a `static`; a buffer can only be used by one half at a time.
+pub struct RingBuffer<const N: usize> {
+    bytes: UnsafeCell<[u8; N]>,
+    // number of bytes ever pushed and popped, wrapping
+    pushed: AtomicUsize,
+    popped: AtomicUsize,
+    dropped: AtomicU32,
+    in_use: AtomicBool,
+}
+
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
+impl<const N: usize> RingBuffer<N> {
+    pub const fn new() -> Self {
+        const { assert!(N.is_power_of_two(), "the capacity must be a power of two") };
+        RingBuffer {
+            bytes: UnsafeCell::new([0; N]),
+            pushed: AtomicUsize::new(0),
+            popped: AtomicUsize::new(0),
+            dropped: AtomicU32::new(0),
+            in_use: AtomicBool::new(false),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Number of bytes buffered
+    pub fn len(&self) -> usize {
+        self.pushed.load(Ordering::Acquire).wrapping_sub(self.popped.load(Ordering::Acquire))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of received bytes dropped because the buffer (or the RX FIFO) was full
+    pub fn dropped(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Type-erased `RingBuffer`, used by one producer and one consumer
+trait ByteRing: Sync {
+    fn push(&self, byte: u8) -> bool;
+    fn pop(&self) -> Option<u8>;
+    fn is_empty(&self) -> bool;
+    /// The oldest buffered bytes, as far as they are contiguous
+    fn peek(&self) -> &[u8];
+    fn consume(&self, n: usize);
+    fn drop_byte(&self);
+    fn claim(&self);
+    fn unclaim(&self);
+}
+
+impl<const N: usize> ByteRing for RingBuffer<N> {
+    fn push(&self, byte: u8) -> bool {
+        let pushed = self.pushed.load(Ordering::Relaxed);
+        if pushed.wrapping_sub(self.popped.load(Ordering::Acquire)) == N {
+            return false;
+        }
+        // per byte, as the consumer may hold a `peek` slice of others
+        unsafe { self.bytes.get().cast::<u8>().add(pushed % N).write(byte) };
+        self.pushed.store(pushed.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    fn pop(&self) -> Option<u8> {
+        let popped = self.popped.load(Ordering::Relaxed);
+        if self.pushed.load(Ordering::Acquire) == popped {
+            return None;
+        }
+        let byte = unsafe { self.bytes.get().cast::<u8>().add(popped % N).read() };
+        self.popped.store(popped.wrapping_add(1), Ordering::Release);
+        Some(byte)
+    }
+
+    fn is_empty(&self) -> bool {
+        RingBuffer::is_empty(self)
+    }
+
+    fn peek(&self) -> &[u8] {
+        let popped = self.popped.load(Ordering::Relaxed);
+        let len = self.pushed.load(Ordering::Acquire).wrapping_sub(popped);
+        let start = popped % N;
+        // only over the readable bytes, the producer writing to the others
+        unsafe {
+            core::slice::from_raw_parts(self.bytes.get().cast::<u8>().add(start), len.min(N - start))
+        }
+    }
+
+    fn consume(&self, n: usize) {
+        let popped = self.popped.load(Ordering::Relaxed);
+        let n = n.min(self.pushed.load(Ordering::Acquire).wrapping_sub(popped));
+        self.popped.store(popped.wrapping_add(n), Ordering::Release);
+    }
+
+    fn drop_byte(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn claim(&self) {
+        assert!(!self.in_use.swap(true, Ordering::AcqRel), "ring buffer already in use");
+        // the other side of a previous use may have left bytes
+        self.popped.store(self.pushed.load(Ordering::Acquire), Ordering::Release);
+    }
+
+    fn unclaim(&self) {
+        self.in_use.store(false, Ordering::Release);
+    }
+}
+
+type Slot = Mutex<Cell<Option<&'static dyn ByteRing>>>;
+
+static TX_BUFFERS: [Slot; dispatch::INSTANCES] = [const { Mutex::new(Cell::new(None)) }; dispatch::INSTANCES];
+static RX_BUFFERS: [Slot; dispatch::INSTANCES] = [const { Mutex::new(Cell::new(None)) }; dispatch::INSTANCES];
+
+/// The interrupt logic of the buffered halves of the USART on Flexcomm `index`,
+/// registered with `flexcomm::dispatch` by `into_buffered`
+pub fn on_interrupt(index: usize) {
+    let usart = unsafe { &*(dispatch::base(index) as *const raw::usart0::RegisterBlock) };
+    let (tx, rx) = interrupt::free(|cs| (
+        TX_BUFFERS[index].borrow(cs).get(),
+        RX_BUFFERS[index].borrow(cs).get(),
+    ));
+
+    if let Some(rx) = rx {
+        while usart.fifostat.read().rxnotempty().bit_is_set() {
+            let byte = usart.fiford.read().rxdata().bits() as u8;
+            if !rx.push(byte) {
+                rx.drop_byte();
+            }
+        }
+        if usart.fifostat.read().rxerr().bit_is_set() {
+            // the RX FIFO overflowed, an unknown number of bytes is lost
+            usart.fifostat.write(|w| w.rxerr().set_bit());
+            rx.drop_byte();
+        }
+    }
+
+    if let Some(tx) = tx {
+        while usart.fifostat.read().txnotfull().bit_is_set() {
+            match tx.pop() {
+                Some(byte) => usart.fifowr.write(|w| unsafe { w.bits(byte as u32) }),
+                None => {
+                    usart.fifointenclr.write(|w| w.txlvl().set_bit());
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// one of the halves of Flexcomm `index` is buffered, so `on_interrupt` is registered
+fn attached(index: usize, cs: &interrupt::CriticalSection) -> bool {
+    TX_BUFFERS[index].borrow(cs).get().is_some() || RX_BUFFERS[index].borrow(cs).get().is_some()
+}
+
+fn attach(slots: &'static [Slot; dispatch::INSTANCES], index: usize, buffer: &'static dyn ByteRing) {
+    buffer.claim();
+    interrupt::free(|cs| {
+        if !attached(index, cs) {
+            dispatch::try_register(index, on_interrupt)
+                .expect("the Flexcomm interrupt is handled by another driver");
+        }
+        slots[index].borrow(cs).set(Some(buffer));
+    });
+}
+
+fn detach(slots: &'static [Slot; dispatch::INSTANCES], index: usize, buffer: &'static dyn ByteRing) {
+    interrupt::free(|cs| {
+        slots[index].borrow(cs).set(None);
+        if !attached(index, cs) {
+            dispatch::unregister(index);
+        }
+    });
+    buffer.unclaim();
+}
+
+/// Serial transmitter writing through a `RingBuffer`
+pub struct BufferedTx<USART: Usart> {
+    tx: Tx<USART>,
+    buffer: &'static dyn ByteRing,
+    index: usize,
+}
+
+/// Serial receiver reading from a `RingBuffer`
+pub struct BufferedRx<USART: Usart> {
+    rx: Rx<USART>,
+    buffer: &'static dyn ByteRing,
+    index: usize,
+}
+
+impl<USART: Usart> Tx<USART> {
+    /// Transmit through `buffer`, from the Flexcomm interrupt.
+    ///
+    /// Panics if `buffer` is in use by another half, or another driver handles
+    /// the Flexcomm interrupt.
+    pub fn into_buffered<const N: usize>(self, buffer: &'static RingBuffer<N>) -> BufferedTx<USART> {
+        let index = dispatch::instance(&self);
+        // the TX FIFO level interrupt is enabled while there is something to send
+        self.fifotrig.modify(|_, w| w.txlvlena().enabled());
+        attach(&TX_BUFFERS, index, buffer);
+        BufferedTx { tx: self, buffer, index }
+    }
+}
+
+impl<USART: Usart> Rx<USART> {
+    /// Receive into `buffer`, from the Flexcomm interrupt.
+    ///
+    /// Panics if `buffer` is in use by another half, or another driver handles
+    /// the Flexcomm interrupt.
+    pub fn into_buffered<const N: usize>(self, buffer: &'static RingBuffer<N>) -> BufferedRx<USART> {
+        let index = dispatch::instance(&self);
+        attach(&RX_BUFFERS, index, buffer);
+        // interrupt on every received byte, and on overflows of the RX FIFO
+        self.fifotrig.modify(|_, w| unsafe { w.rxlvl().bits(0).rxlvlena().enabled() });
+        self.fifointenset.write(|w| w.rxlvl().set_bit().rxerr().set_bit());
+        BufferedRx { rx: self, buffer, index }
+    }
+}
+
+impl<USART: Usart> BufferedTx<USART> {
+    fn kick(&self) {
+        self.tx.fifointenset.write(|w| w.txlvl().set_bit());
+    }
+
+    /// Buffer as many of `bytes` as fit, returning their number.
+    pub fn write(&mut self, bytes: &[u8]) -> usize {
+        let written = bytes.iter().take_while(|&&byte| self.buffer.push(byte)).count();
+        if written > 0 {
+            self.kick();
+        }
+        written
+    }
+
+    /// Buffer all of `bytes`, waiting for room in the buffer as needed.
+    pub fn write_all(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            while !self.buffer.push(byte) {
+                self.kick();
+            }
+        }
+        self.kick();
+    }
+
+    /// Stop buffering, discarding the bytes not yet sent (`flush` first to send them).
+    pub fn release(self) -> Tx<USART> {
+        self.tx.fifointenclr.write(|w| w.txlvl().set_bit());
+        detach(&TX_BUFFERS, self.index, self.buffer);
+        self.tx
+    }
+}
+
+impl<USART: Usart> BufferedRx<USART> {
+    /// Move the received bytes into `buffer`, returning their number.
+    pub fn read(&mut self, buffer: &mut [u8]) -> usize {
+        buffer.iter_mut()
+            .map_while(|slot| self.buffer.pop().map(|byte| *slot = byte))
+            .count()
+    }
+
+    /// The oldest received bytes (as far as they are contiguous in the buffer),
+    /// without removing them, see `consume`
+    pub fn buffered(&self) -> &[u8] {
+        self.buffer.peek()
+    }
+
+    /// Remove the first `n` of the received bytes.
+    pub fn consume(&mut self, n: usize) {
+        self.buffer.consume(n);
+    }
+
+    /// Are there received bytes to read?
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Stop buffering, discarding the bytes not yet read.
+    pub fn release(self) -> Rx<USART> {
+        self.rx.fifointenclr.write(|w| w.rxlvl().set_bit().rxerr().set_bit());
+        detach(&RX_BUFFERS, self.index, self.buffer);
+        self.rx
+    }
+}
+
+impl<USART: Usart> serial::Write<u8> for BufferedTx<USART> {
+    type Error = Error;
+
+    /// Waits for the buffer to drain, and the last byte to be sent.
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if self.buffer.is_empty() {
+            serial::Write::flush(&mut self.tx)
+        } else {
+            self.kick();
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        let pushed = self.buffer.push(byte);
+        self.kick();
+        if pushed {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl<USART: Usart> blocking::serial::write::Default<u8> for BufferedTx<USART> {}
+
+impl<USART: Usart> fmt::Write for BufferedTx<USART> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_all(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl<USART: Usart> serial::Read<u8> for BufferedRx<USART> {
+    type Error = Error;
+
+    /// Bytes with framing or parity errors are not told apart, see `RingBuffer::dropped`
+    /// for overruns.
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.buffer.pop().ok_or(nb::Error::WouldBlock)
+    }
+}
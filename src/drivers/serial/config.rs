@@ -1,6 +1,10 @@
-use crate::time::Hertz;
+use crate::{
+    peripherals::flexcomm::FifoConfig,
+    time::Hertz,
+};
 
 #[derive(Clone,Copy,Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum WordLength {
     DataBits7,
     DataBits8,
@@ -8,13 +12,18 @@ pub enum WordLength {
 }
 
 #[derive(Clone,Copy,Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Parity {
     ParityNone,
     ParityEven,
     ParityOdd,
 }
 
+/// The Flexcomm USART only has 1 or 2 stop bits. It has no smartcard (ISO 7816)
+/// mode either: no NACK on parity errors, no guard time, and no clock output
+/// in asynchronous mode.
 #[derive(Clone,Copy,Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum StopBits {
     #[doc = "1 stop bit"]
     STOP1,
@@ -26,17 +35,54 @@ pub enum StopBits {
     // STOP1P5,
 }
 
+/// RS-485 half-duplex operation, with the RTS pin enabling the transceiver's
+/// driver (pins `(tx, rx, rts)`, see `UsartPins`)
 #[derive(Clone,Copy,Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Rs485 {
+    /// The driver enable is active high (as for most transceivers), or low
+    pub active_high: bool,
+    /// Keep the driver enabled for one more character time after the last stop
+    /// bit, the only turnaround delay the hardware offers
+    pub turnaround: bool,
+}
+
+impl Default for Rs485 {
+    fn default() -> Self {
+        Rs485 {
+            active_high: true,
+            turnaround: false,
+        }
+    }
+}
+
+#[derive(Clone,Copy,Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Config {
     pub speed: Hertz,
     pub wordlength: WordLength,
     pub parity: Parity,
     pub stopbits: StopBits,
+    pub fifo: FifoConfig,
+    pub rs485: Rs485,
+    /// Multidrop: only receive frames starting with this address (a 9-bit word
+    /// with the 9th bit set), matched by the hardware, see `Serial::wait_for_address`.
+    ///
+    /// Implies 9-bit words.
+    pub address: Option<u8>,
+    /// Invert the TX and RX lines (idle low), e.g. for IR transceivers with
+    /// active-high LEDs.
+    ///
+    /// The Flexcomm USART has no IrDA encoder, so the pulses span whole bits
+    /// rather than 3/16 of them; transceivers needing SIR pulses require
+    /// external shaping.
+    pub invert_tx: bool,
+    pub invert_rx: bool,
 }
 
 impl Config {
-    pub fn speed<Speed: Into<Hertz>>(mut self, speed: Speed) -> Self {
-        self.speed = speed.into();
+    pub fn speed(mut self, speed: Hertz) -> Self {
+        self.speed = speed;
         self
     }
 
@@ -55,6 +101,11 @@ impl Config {
         self
     }
 
+    pub fn wordlength_7(mut self) -> Self {
+        self.wordlength = WordLength::DataBits7;
+        self
+    }
+
     pub fn wordlength_8(mut self) -> Self {
         self.wordlength = WordLength::DataBits8;
         self
@@ -69,20 +120,60 @@ impl Config {
         self.stopbits = stopbits;
         self
     }
+
+    /// FIFO configuration, by default with level triggers for an empty TX FIFO
+    /// and two entries in the RX FIFO
+    pub fn fifo(mut self, fifo: FifoConfig) -> Self {
+        self.fifo = fifo;
+        self
+    }
+
+    /// RS-485 settings, used with `(tx, rx, rts)` pins
+    pub fn rs485(mut self, rs485: Rs485) -> Self {
+        self.rs485 = rs485;
+        self
+    }
+
+    /// Invert the TX and RX lines, see `Config::invert_tx`
+    pub fn inverted(mut self, tx: bool, rx: bool) -> Self {
+        self.invert_tx = tx;
+        self.invert_rx = rx;
+        self
+    }
+
+    /// Address of this node on a multidrop bus, see `Config::address`
+    pub fn address(mut self, address: Option<u8>) -> Self {
+        self.address = address;
+        self
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct InvalidConfig;
 
+impl core::fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("invalid serial configuration")
+    }
+}
+
+impl core::error::Error for InvalidConfig {}
+
 impl Default for Config {
     /// The default ist 9600(8N1)
     fn default() -> Config {
         Config {
-            // speed: Hertz(19_200),
-            speed: Hertz(9_600),
+            // speed: Hertz::from_raw(19_200),
+            speed: Hertz::from_raw(9_600),
             wordlength: WordLength::DataBits8,
             parity: Parity::ParityNone,
             stopbits: StopBits::STOP1,
+            fifo: FifoConfig::new().tx_level(Some(0)).rx_level(Some(1)),
+            rs485: Rs485::default(),
+            address: None,
+            invert_tx: false,
+            invert_rx: false,
         }
     }
 }
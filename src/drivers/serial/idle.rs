@@ -0,0 +1,110 @@
+//! Reception of frames of unknown length, delimited by an idle line (e.g. Modbus RTU
+//! or NMEA sentences).
+//!
+//! The DMA streams the received bytes into a `DmaRingBuffer`; a frame has ended
+//! once the line has been idle for a gap of some bit times. The Flexcomm USART
+//! has no receive time-out interrupt, so `poll` measures the gap with the DWT
+//! cycle counter, and needs to be called more often than the gap lasts. Frames
+//! only separated by a gap that `poll` missed merge into one, as do frames
+//! received back-to-back before the previous one was `read`. Each frame is also
+//! signalled by an `events::Event::RxIdle` from `poll`.
+//!
+//! ```ignore
+//! static mut BUFFER: [u8; 256] = [0; 256];
+//!
+//! let baud_rate = serial.baud_rate();
+//! let (tx, rx) = serial.split();
+//! let ring = dma.ring_buffer(rx, unsafe { &mut *addr_of_mut!(BUFFER) });
+//! // Modbus RTU: 3.5 characters of 11 bits
+//! let mut frames = IdleFrames::new(ring, baud_rate, 39, clocks.system_frequency.convert());
+//!
+//! loop {
+//!     if let Some(len) = frames.poll()? {
+//!         let n = frames.read(&mut frame[..len])?;
+//!         handle(&frame[..n]);
+//!     }
+//! }
+//! ```
+
+use crate::{
+    events::{self, Event},
+    peripherals::{
+        dma::{DmaRingBuffer, Overrun, RxRequest},
+        flexcomm::dispatch,
+    },
+    time::{Hertz, Megahertz},
+    typestates::pin::flexcomm::Usart,
+};
+use super::Rx;
+
+/// Frames received by DMA, delimited by an idle line, see the module documentation
+pub struct IdleFrames<USART: Usart + RxRequest> {
+    ring: DmaRingBuffer<u8, Rx<USART>>,
+    gap: u32,
+    available: usize,
+    changed: u32,
+    // `Event::RxIdle` was pushed for the current frame
+    signalled: bool,
+}
+
+impl<USART: Usart + RxRequest> IdleFrames<USART> {
+    /// Frames end after `gap_bits` bit times of silence, at `baud_rate`. The
+    /// gap is timed at the `system_frequency`.
+    ///
+    /// Enables the cycle counter.
+    pub fn new(
+        ring: DmaRingBuffer<u8, Rx<USART>>,
+        baud_rate: Hertz,
+        gap_bits: u32,
+        system_frequency: Megahertz,
+    ) -> Self {
+        crate::enable_cycle_counter();
+        let gap = gap_bits as u64 * system_frequency.to_Hz() as u64 / baud_rate.to_Hz().max(1) as u64;
+        Self {
+            ring,
+            gap: gap.min(u32::MAX as u64) as u32,
+            available: 0,
+            changed: crate::get_cycle_count(),
+            signalled: false,
+        }
+    }
+
+    pub fn release(self) -> DmaRingBuffer<u8, Rx<USART>> {
+        self.ring
+    }
+
+    /// The length of the received frame, once the line has been idle for the gap.
+    ///
+    /// Keeps returning the length until the frame is `read`. Bytes received after
+    /// the gap start the next frame, so reading exactly the length is important.
+    ///
+    /// Pushes an `Event::RxIdle` once per frame.
+    pub fn poll(&mut self) -> Result<Option<usize>, Overrun> {
+        let available = self.ring.available()?;
+        let now = crate::get_cycle_count();
+        if available != self.available {
+            self.available = available;
+            self.changed = now;
+            self.signalled = false;
+            return Ok(None);
+        }
+        let idle = self.ring.source().stat.read().rxidle().bit_is_set();
+        if available > 0 && idle && now.wrapping_sub(self.changed) >= self.gap {
+            if !self.signalled {
+                self.signalled = true;
+                let flexcomm = dispatch::instance(self.ring.source()) as u8;
+                events::push(Event::RxIdle { flexcomm });
+            }
+            Ok(Some(available))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Move received bytes into `buffer`, returning how many.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Overrun> {
+        let n = self.ring.read(buffer)?;
+        self.available -= n.min(self.available);
+        Ok(n)
+    }
+}
@@ -0,0 +1,103 @@
+//! Global logger over a serial transmitter.
+//!
+//! After handing a transmitter to `init`, `Logger` can be written to from
+//! anywhere, including interrupt handlers:
+//!
+//! ```ignore
+//! let (tx, rx) = serial.split();
+//! hal::drivers::serial::logger::init(tx);
+//!
+//! writeln!(Logger, "booted at {} MHz", clocks.system_frequency.to_MHz()).ok();
+//! ```
+//!
+//! With feature `log`, `init_log` also installs it as the `log` backend.
+//!
+//! Each write happens in a critical section, so messages from different contexts
+//! don't interleave; at low baud rates this delays interrupts noticeably.
+
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::{
+    peripherals::flexcomm::Usart0,
+    typestates::pin::flexcomm::Usart,
+};
+use super::Tx;
+
+// address of the transmitter's USART, which is all a `Tx` consists of
+static TX: Mutex<Cell<Option<usize>>> = Mutex::new(Cell::new(None));
+
+/// Make `tx` the transmitter of the `Logger`, for good.
+pub fn init<USART: Usart>(tx: Tx<USART>) {
+    interrupt::free(|cs| TX.borrow(cs).set(Some(tx.addr)));
+}
+
+/// Writes to the transmitter passed to `init`, discarding output before that.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Logger;
+
+impl Logger {
+    fn with<R>(f: impl FnOnce(Option<&mut Tx<Usart0>>) -> R) -> R {
+        interrupt::free(|cs| {
+            // the USART type of a `Tx` is only a marker
+            let mut tx = TX.borrow(cs).get().map(|addr| Tx::<Usart0> { addr, _usart: PhantomData });
+            f(tx.as_mut())
+        })
+    }
+}
+
+impl fmt::Write for Logger {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Self::with(|tx| match tx {
+            Some(tx) => tx.write_str(s),
+            None => Ok(()),
+        })
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        // the whole message in one critical section
+        Self::with(|tx| match tx {
+            Some(tx) => tx.write_fmt(args),
+            None => Ok(()),
+        })
+    }
+}
+
+#[cfg(feature = "log")]
+mod log_impls {
+    use core::fmt::Write as _;
+
+    use super::Logger;
+
+    static LOGGER: Logger = Logger;
+
+    impl log::Log for Logger {
+        fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+            metadata.level() <= log::max_level()
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            if self.enabled(record.metadata()) {
+                writeln!(Logger, "[{} {}] {}\r", record.level(), record.target(), record.args()).ok();
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// `init`, and install the `Logger` as `log` backend with maximum level `level`.
+    pub fn init_log<USART: crate::typestates::pin::flexcomm::Usart>(
+        tx: super::Tx<USART>,
+        level: log::LevelFilter,
+    ) -> Result<(), log::SetLoggerError> {
+        super::init(tx);
+        log::set_logger(&LOGGER)?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+#[cfg(feature = "log")]
+pub use log_impls::init_log;
@@ -113,16 +113,18 @@ impl<Size: OutputSize> Sha<'_, Size> {
         peripheral: &mut Hashcrypt<Enabled>,
         input: &GenericArray<u8, BlockSize>,
     ) {
-        // input must be word-aligned
-        let input: Aligned<A4, GenericArray<u8, BlockSize>> = Aligned(input.clone());
-        let addr: u32 = &input[0] as *const _ as _;
-        assert_eq!(addr & 0x3, 0);
-        while peripheral.raw.status.read().waiting().is_not_waiting() {
-            continue;
-        }
-        peripheral.raw.memaddr.write(|w| unsafe { w.bits(addr) } );
-        peripheral.raw.memctrl.write(|w| unsafe {
-            w.master().enabled().count().bits(1) });
+        crate::instrumented!(HASH_BLOCK, {
+            // input must be word-aligned
+            let input: Aligned<A4, GenericArray<u8, BlockSize>> = Aligned(input.clone());
+            let addr: u32 = &input[0] as *const _ as _;
+            assert_eq!(addr & 0x3, 0);
+            while peripheral.raw.status.read().waiting().is_not_waiting() {
+                continue;
+            }
+            peripheral.raw.memaddr.write(|w| unsafe { w.bits(addr) } );
+            peripheral.raw.memctrl.write(|w| unsafe {
+                w.master().enabled().count().bits(1) });
+        })
     }
 
     fn finish(&mut self) {
@@ -7,6 +7,8 @@
 ///
 ///
 use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
 
 pub use crate::traits::wg::spi::{
     FullDuplex,
@@ -18,6 +20,7 @@ use crate::typestates::pin::{
     flexcomm::{
         // Trait marking I2C peripherals and pins
         Spi,
+        SpiCsPin,
         SpiPins,
         ChipSelect,
     },
@@ -26,6 +29,15 @@ use crate::typestates::pin::{
 use crate::time::{
     Hertz,
 };
+use crate::peripherals::{
+    dma,
+    flexcomm::{ClockSource, Fifo, FifoConfig, Flexcomm8, Repurpose, Spi8},
+    syscon,
+};
+use crate::typestates::{init_state, ClocksSupportFlexcommToken};
+
+pub mod background;
+pub use background::BackgroundTransfer;
 
 pub mod prelude {
     pub use super::SpiMaster;
@@ -34,21 +46,65 @@ pub mod prelude {
 }
 
 /// SPI error
-/// TODO: Use the actual ones from the chip
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
-    /// Overrun occurred
+    /// RX FIFO overflowed, data was lost
     Overrun,
-    /// Mode fault occurred
-    ModeFault,
-    /// CRC error
-    Crc,
-    #[doc(hidden)]
-    _Extensible,
+    /// TX FIFO ran empty while data was needed (slave mode)
+    Underrun,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Error::Overrun => "RX FIFO overrun",
+            Error::Underrun => "TX FIFO underrun",
+        })
+    }
 }
 
+impl core::error::Error for Error {}
+
 pub type Result<T> = nb::Result<T, Error>;
 
+/// Delays inserted by the master, in SCK periods (at most 15 each)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Delays {
+    /// After asserting the chip select, before the first clock edge
+    pub pre: u8,
+    /// After the last clock edge, before deasserting the chip select
+    pub post: u8,
+    /// Between frames marked as ends of frames, see `Framing::frame_delay`
+    pub frame: u8,
+    /// Between transfers, the chip select staying deasserted
+    pub transfer: u8,
+}
+
+/// How the master frames the data it sends
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Framing {
+    /// Deassert the chip select after every frame (FIFOWR.EOT), for devices
+    /// latching each word on the deasserting edge
+    pub deassert_between_frames: bool,
+    /// Deassert the chip select once a transfer is done (STAT.ENDTRANSFER),
+    /// instead of keeping it asserted until the next one
+    pub deassert_after_transfer: bool,
+    /// Insert `Delays::frame` after every frame (FIFOWR.EOF)
+    pub frame_delay: bool,
+}
+
+/// DIV for the fastest SCK from `clock` not above `speed`
+fn divider(clock: u32, speed: u32) -> u16 {
+    assert!(speed > 0);
+    let div = clock.div_ceil(speed).max(1) - 1;
+    assert!(div <= 0xFFFF, "SPI speed too low for the function clock");
+    div as u16
+}
+
 /// SPI peripheral operating in master mode
 pub struct SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
 where
@@ -66,6 +122,8 @@ where
     _miso: PhantomData<MISO>,
     _cs: PhantomData<CS>,
     cs: ChipSelect,
+    frame_bits: u8,
+    framing: Framing,
 }
 
 impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
@@ -78,16 +136,14 @@ where
     PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
     // CSPIN: SpiSselPin<CS, SPI>,
 {
-    pub fn new<Speed: Into<Hertz>>(spi: SPI, pins: PINS, speed: Speed, mode: Mode) -> Self {
-        let speed: Hertz = speed.into();
-        let speed: u32 = speed.0;
+    /// Configure the SPI as master in `mode`, clocking SCK at `speed` or the next
+    /// frequency below it that the function clock divides down to (see `speed`).
+    pub fn new(spi: SPI, pins: PINS, speed: Hertz, mode: Mode) -> Self {
+        let speed: u32 = speed.to_Hz();
 
         while spi.stat.read().mstidle().bit_is_clear() { continue; }
 
-        spi.fifocfg.modify(|_, w| w
-            .enabletx().disabled()
-            .enablerx().disabled()
-        );
+        spi.configure_fifo(FifoConfig::new().tx(false).rx(false));
         spi.cfg.modify(|_, w| w
             .enable().disabled()
             .master().master_mode()
@@ -97,9 +153,8 @@ where
             .loop_().disabled()
         );
 
-        let div: u32 = 12_000_000 / speed - 1;
-        debug_assert!(div <= 0xFFFF);
-        spi.div.modify(|_, w| unsafe { w.divval().bits(div as u16) } );
+        let div = divider(spi.clock_frequency().expect("unknown function clock").to_Hz(), speed);
+        spi.div.modify(|_, w| unsafe { w.divval().bits(div) } );
 
         // spi.raw.fifowr.write(|w| w
         //     .rxignore().ignore() // otherwise transmit halts if FIFORD buffer is full
@@ -108,10 +163,7 @@ where
         //     .enabletx().enabled()
         //     .enablerx().enabled()
         // );
-        spi.fifocfg.modify(|_, w| w
-            .enabletx().enabled()
-            .enablerx().enabled()
-        );
+        spi.configure_fifo(FifoConfig::new());
         spi.cfg.modify(|_, w| w
             .enable().enabled()
         );
@@ -129,20 +181,364 @@ where
             _cs: PhantomData,
             // _cs_pin: PhantomData,
             cs: PINS::CS,
+            frame_bits: 8,
+            framing: Framing::default(),
         }
     }
 
+    /// Wrap `spi` as configured by `new` before, without touching the hardware.
+    ///
+    /// # Safety
+    ///
+    /// The driver owning the peripheral must not be used concurrently.
+    pub unsafe fn steal(spi: SPI, pins: PINS) -> Self {
+        Self {
+            spi,
+            pins,
+            _sck: PhantomData,
+            _mosi: PhantomData,
+            _miso: PhantomData,
+            _cs: PhantomData,
+            cs: PINS::CS,
+            frame_bits: 8,
+            framing: Framing::default(),
+        }
+    }
+
+    /// The SCK frequency, the fastest the function clock divides down to without
+    /// exceeding the requested one (zero if the function clock is unknown).
+    pub fn speed(&self) -> Hertz {
+        let div = self.spi.div.read().divval().bits() as u32 + 1;
+        Hertz::from_raw(self.spi.clock_frequency().map_or(0, |clock| clock.to_Hz()) / div)
+    }
+
+    /// Change the SCK frequency, between transfers.
+    pub fn set_speed(&mut self, speed: Hertz) {
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+        let div = divider(self.spi.clock_frequency().expect("unknown function clock").to_Hz(), speed.to_Hz());
+        self.spi.div.write(|w| unsafe { w.divval().bits(div) });
+    }
+
+    /// Change the clock polarity and phase, between transfers (e.g. for another device on the bus).
+    pub fn set_mode(&mut self, mode: Mode) {
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+        self.spi.cfg.modify(|_, w| w.enable().disabled());
+        self.spi.cfg.modify(|_, w| w
+            .cpha().bit(mode.phase == Phase::CaptureOnSecondTransition)
+            .cpol().bit(mode.polarity == Polarity::IdleHigh)
+        );
+        self.spi.cfg.modify(|_, w| w.enable().enabled());
+    }
+
+    /// Assert the hardware chip select `cs` during the following transfers, instead
+    /// of the one of the pins passed to `new`, to talk to another device on the bus.
+    ///
+    /// The pin stays with the caller, it only needs to be configured as an SSEL of this SPI.
+    pub fn select<PIO: PinId, P: SpiCsPin<PIO, SPI>>(&mut self, _cs: &P) {
+        self.cs = P::CS;
+    }
+
+    /// Go back to the chip select of the pins passed to `new`.
+    pub fn select_default(&mut self) {
+        self.cs = PINS::CS;
+    }
+
+    /// The chip select asserted during transfers
+    pub fn chip_select(&self) -> ChipSelect {
+        self.cs
+    }
+
+    /// Make chip select `cs` active high, instead of active low (CFG.SPOLn).
+    pub fn set_chip_select_active_high(&mut self, cs: ChipSelect, active_high: bool) {
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+        self.spi.cfg.modify(|_, w| match cs {
+            ChipSelect::Chip0 => w.spol0().bit(active_high),
+            ChipSelect::Chip1 => w.spol1().bit(active_high),
+            ChipSelect::Chip2 => w.spol2().bit(active_high),
+            ChipSelect::Chip3 => w.spol3().bit(active_high),
+            ChipSelect::NoChips => w,
+        });
+    }
+
+    /// Set the delays around and between frames (DLY).
+    pub fn set_delays(&mut self, delays: Delays) {
+        assert!(delays.pre < 16 && delays.post < 16 && delays.frame < 16 && delays.transfer < 16);
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+        self.spi.dly.modify(|_, w| unsafe { w
+            .pre_delay().bits(delays.pre)
+            .post_delay().bits(delays.post)
+            .frame_delay().bits(delays.frame)
+            .transfer_delay().bits(delays.transfer)
+        });
+    }
+
+    /// Frame the following transfers according to `framing`.
+    pub fn set_framing(&mut self, framing: Framing) {
+        self.framing = framing;
+    }
+
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Deassert the chip select once the frames already sent are done, inserting
+    /// the frame and transfer delays.
+    pub fn end_transfer(&mut self) {
+        // the other bits are read-only or cleared by writing 1
+        self.spi.stat.write(|w| w.endtransfer().set_bit());
+    }
+
+    /// Apply `Framing::deassert_after_transfer`.
+    fn finish_transfer(&mut self) {
+        if self.framing.deassert_after_transfer {
+            self.end_transfer();
+        }
+    }
+
+    /// Change the FIFO configuration, e.g. for level interrupts or DMA.
+    ///
+    /// Both FIFOs need to stay enabled.
+    pub fn configure_fifo(&mut self, config: FifoConfig) {
+        self.spi.configure_fifo(config);
+    }
+
     pub fn release(self) -> (SPI, PINS) {
-        (self.spi, self.pins)
+        let this = ManuallyDrop::new(self);
+        unsafe { (ptr::read(&this.spi), ptr::read(&this.pins)) }
+    }
+
+    /// Masks the interrupts and tears down the SPI personality, returning
+    /// the disabled Flexcomm (to enable it as another) and the pins.
+    pub fn teardown(self, syscon: &mut syscon::Syscon) -> (SPI::Flexcomm, PINS)
+    where
+        SPI: Repurpose,
+    {
+        self.spi.fifointenclr.write(|w| unsafe { w.bits(!0) });
+        self.spi.intenclr.write(|w| unsafe { w.bits(!0) });
+        let (spi, pins) = self.release();
+        (spi.teardown(syscon), pins)
+    }
+
+    /// The peripheral owned by the driver, which derefs to the raw registers.
+    pub fn raw(&self) -> &SPI {
+        &self.spi
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut SPI {
+        &mut self.spi
+    }
+
+    /// Frames of `bits` (4 to 16) bits from now on.
+    ///
+    /// The `u8` transfers need frames of at most 8 bits; the `*_words` transfers
+    /// (and the embedded-hal 1.0 `SpiBus<u16>`) take any size.
+    pub fn set_frame_size(&mut self, bits: u8) {
+        assert!((4..=16).contains(&bits));
+        self.frame_bits = bits;
+    }
+
+    pub fn frame_size(&self) -> u8 {
+        self.frame_bits
+    }
+
+    /// Exchange `words` of the frame size with the device.
+    pub fn transfer_words(&mut self, words: &mut [u16]) -> core::result::Result<(), Error> {
+        for word in words.iter_mut() {
+            nb::block!(self.send_frame(*word))?;
+            *word = nb::block!(self.read_frame())?;
+        }
+        self.finish_transfer();
+        Ok(())
+    }
+
+    /// Write `words` of the frame size to the device, and wait until they are sent.
+    ///
+    /// What the device sends back is not even received (FIFOWR.RXIGNORE).
+    pub fn write_words(&mut self, words: &[u16]) -> core::result::Result<(), Error> {
+        let control = self.control(true);
+        for word in words {
+            nb::block!(self.push_frame(control, *word))?;
+        }
+        while self.spi.fifostat.read().txempty().bit_is_clear() {}
+        self.finish_transfer();
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+        match self.return_on_error() {
+            Err(nb::Error::Other(error)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// The upper half of FIFOWR for the following data: the frame size, the chip
+    /// select asserted, the framing.
+    fn control(&self, ignore_rx: bool) -> u16 {
+        let ssel_n: u16 = match self.cs {
+            ChipSelect::Chip0 => 0b1110,
+            ChipSelect::Chip1 => 0b1101,
+            ChipSelect::Chip2 => 0b1011,
+            ChipSelect::Chip3 => 0b0111,
+            ChipSelect::NoChips => 0b1111,
+        };
+        ssel_n
+            | (self.framing.deassert_between_frames as u16) << 4
+            | (self.framing.frame_delay as u16) << 5
+            | (ignore_rx as u16) << 6
+            | (self.frame_bits as u16 - 1) << 8
+    }
+
+    fn send_frame(&mut self, frame: u16) -> Result<()> {
+        let control = self.control(false);
+        self.push_frame(control, frame)
+    }
+
+    fn push_frame(&mut self, control: u16, frame: u16) -> Result<()> {
+        // NB: UM says "Do not read-modify-write the register."
+        self.return_on_error()?;
+        if self.spi.fifostat.read().txnotfull().bit_is_set() {
+            self.spi.fifowr.write(|w| unsafe { w.bits((control as u32) << 16 | frame as u32) });
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn read_frame(&mut self) -> Result<u16> {
+        if self.spi.fifostat.read().rxnotempty().bit_is_set() {
+            Ok(self.spi.fiford.read().rxdata().bits())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
     }
 
     fn return_on_error(&self) -> Result<()> {
-        // TODO: error readout
+        let fifostat = self.spi.fifostat.read();
+        if fifostat.rxerr().bit_is_set() {
+            // clear by writing 1
+            self.spi.fifocfg.modify(|_, w| w.emptyrx().set_bit());
+            self.spi.fifostat.write(|w| w.rxerr().set_bit());
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+        if fifostat.txerr().bit_is_set() {
+            self.spi.fifostat.write(|w| w.txerr().set_bit());
+            return Err(nb::Error::Other(Error::Underrun));
+        }
         Ok(())
     }
 
 }
 
+impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+where
+    SCK: PinId,
+    MOSI: PinId,
+    MISO: PinId,
+    CS: PinId,
+    SPI: Spi + dma::TxRequest + dma::RxRequest,
+    PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+{
+    /// Set the control bits of the following data written to the TX FIFO, by a
+    /// halfword write to the upper half of FIFOWR.
+    fn write_control(&mut self, ignore_rx: bool) {
+        // the DMA moves bytes
+        assert!(self.frame_bits <= 8);
+        let control = self.control(ignore_rx);
+        let upper = (&self.spi.fifowr as *const _ as *mut u16).wrapping_add(1);
+        while self.spi.fifostat.read().txnotfull().bit_is_clear() {}
+        unsafe { ptr::write_volatile(upper, control) };
+    }
+
+    /// Exchange `words` with the device by DMA, like `Transfer::transfer`, without
+    /// the CPU handling each byte.
+    pub fn transfer_dma(&mut self, dma: &mut dma::Dma<init_state::Enabled>, words: &mut [u8]) -> core::result::Result<(), Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+        // stale received data would shift the reply
+        self.spi.empty_rx_fifo();
+        self.write_control(false);
+        dma.transfer_in_place(&mut self.spi, words);
+        self.finish_transfer();
+        match self.return_on_error() {
+            Err(nb::Error::Other(error)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write `words` to the device by DMA, ignoring what it sends back, and wait
+    /// until they are sent.
+    pub fn write_dma(&mut self, dma: &mut dma::Dma<init_state::Enabled>, words: &[u8]) {
+        if words.is_empty() {
+            return;
+        }
+        self.write_control(true);
+        dma.write_all(&mut self.spi, words);
+        while self.spi.fifostat.read().txempty().bit_is_clear() {}
+        self.finish_transfer();
+        while self.spi.stat.read().mstidle().bit_is_clear() {}
+    }
+}
+
+/// The high-speed SPI (Flexcomm 8) as master
+pub type HsSpi<SCK, MOSI, MISO, CS, PINS> = SpiMaster<SCK, MOSI, MISO, CS, Spi8, PINS>;
+
+/// The high-speed SPI clocks SCK at up to this frequency as master
+pub const HS_SPI_MAX_SPEED: Hertz = Hertz::from_raw(50_000_000);
+
+impl<SCK, MOSI, MISO, CS, PINS> SpiMaster<SCK, MOSI, MISO, CS, Spi8, PINS>
+where
+    SCK: PinId,
+    MOSI: PinId,
+    MISO: PinId,
+    CS: PinId,
+    PINS: SpiPins<SCK, MOSI, MISO, CS, Spi8>,
+{
+    /// Enable Flexcomm 8 as high-speed SPI master, clocking SCK at `speed` (at most
+    /// `HS_SPI_MAX_SPEED`) or the next frequency below it that a function clock
+    /// divides down to.
+    ///
+    /// The function clock is the 12 MHz FRO if that gets as close, the 96 MHz FRO
+    /// otherwise (e.g. 48 MHz for 50 MHz), which sets FROHFDIV to divide by one,
+    /// so panics if another Flexcomm uses `ClockSource::Fro32Mhz`.
+    pub fn new_high_speed<State>(
+        flexcomm: Flexcomm8<State>,
+        syscon: &mut syscon::Syscon,
+        clocks_token: &ClocksSupportFlexcommToken,
+        pins: PINS,
+        speed: Hertz,
+        mode: Mode,
+    ) -> Self {
+        assert!(speed <= HS_SPI_MAX_SPEED);
+        let realized = |source: ClockSource| {
+            let clock = source.frequency().to_Hz();
+            clock / (divider(clock, speed.to_Hz()) as u32 + 1)
+        };
+        let source = if realized(ClockSource::Fro12Mhz) >= realized(ClockSource::Fro96Mhz) {
+            ClockSource::Fro12Mhz
+        } else {
+            ClockSource::Fro96Mhz
+        };
+        let spi = flexcomm.enabled_as_spi_clocked(syscon, clocks_token, source);
+        Self::new(spi, pins, speed, mode)
+    }
+}
+
+/// Masks the interrupts and gates the clock.
+impl<SCK, MOSI, MISO, CS, SPI, PINS> Drop for SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+where
+    SCK: PinId,
+    MOSI: PinId,
+    MISO: PinId,
+    CS: PinId,
+    SPI: Spi,
+    PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+{
+    fn drop(&mut self) {
+        self.spi.fifointenclr.write(|w| unsafe { w.bits(!0) });
+        self.spi.intenclr.write(|w| unsafe { w.bits(!0) });
+        syscon::disable_clock_on_drop(&self.spi);
+    }
+}
+
 impl<SCK, MOSI, MISO, CS, SPI, PINS> FullDuplex<u8> for SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
 where
     SCK: PinId,
@@ -151,86 +547,21 @@ where
     CS: PinId,
     SPI: Spi,
     PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
-    // CSPIN: SpiSselPin<CS, SPI>,
 {
     type Error = Error;
 
     fn read(&mut self) -> Result<u8> {
-        // self.return_on_error()?;
-        if self.spi.fifostat.read().rxnotempty().bit_is_set() {
-            // TODO: not sure how to turn this from u32 (or u16??) into u8
-            // Or whatever...
-            let byte = self.spi.fiford.read().rxdata().bits();
-            Ok(byte as u8)
-        } else {
-            Err(nb::Error::WouldBlock)
-        }
+        self.read_frame().map(|frame| frame as u8)
     }
 
+    /// Needs frames of at most 8 bits, see `set_frame_size`.
     fn send(&mut self, byte: u8) -> Result<()> {
-
-        // NB: UM says "Do not read-modify-write the register."
-        // - writing 0 to upper-half word means: keep previous control settings
-
-        self.return_on_error()?;
-        if self.spi.fifostat.read().txnotfull().bit_is_set() {
-            // NB: we set 8 bits in constructor
-            // We could probably repeat this here
-            use ChipSelect::*;
-            match self.cs {
-                Chip0 =>  {
-                    self.spi.fifowr.write(|w| unsafe { w
-                        // control
-                        .len().bits(7) // 8 bits
-                        .txssel0_n().asserted()
-                        // data
-                        .txdata().bits(byte as u16)
-                    });
-                },
-                Chip1 =>  {
-                    self.spi.fifowr.write(|w| unsafe { w
-                        // control
-                        .len().bits(7) // 8 bits
-                        .txssel1_n().asserted()
-                        // data
-                        .txdata().bits(byte as u16)
-                    });
-                },
-                Chip2 =>  {
-                    self.spi.fifowr.write(|w| unsafe { w
-                        // control
-                        .len().bits(7) // 8 bits
-                        .txssel2_n().asserted()
-                        // data
-                        .txdata().bits(byte as u16)
-                    });
-                },
-                Chip3 =>  {
-                    self.spi.fifowr.write(|w| unsafe { w
-                        // control
-                        .len().bits(7) // 8 bits
-                        .txssel3_n().asserted()
-                        // data
-                        .txdata().bits(byte as u16)
-                    });
-                },
-                NoChips =>  {
-                    self.spi.fifowr.write(|w| unsafe { w
-                        // control
-                        .len().bits(7) // 8 bits
-                        // data
-                        .txdata().bits(byte as u16)
-                    });
-                },
-            }
-            Ok(())
-        } else {
-            Err(nb::Error::WouldBlock)
-        }
+        debug_assert!(self.frame_bits <= 8);
+        self.send_frame(byte as u16)
     }
 }
 
-impl<SCK, MOSI, MISO, CS, SPI, PINS> crate::traits::wg::blocking::spi::transfer::Default<u8>
+impl<SCK, MOSI, MISO, CS, SPI, PINS> crate::traits::wg::blocking::spi::Transfer<u8>
 for
     SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
 where
@@ -240,9 +571,23 @@ where
     CS: PinId,
     SPI: Spi,
     PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
-{}
+{
+    type Error = Error;
+
+    // same as `transfer::Default`, instrumented
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> core::result::Result<&'w [u8], Error> {
+        crate::instrumented!(SPI_TRANSFER, {
+            for word in words.iter_mut() {
+                nb::block!(self.send(*word))?;
+                *word = nb::block!(self.read())?;
+            }
+            self.finish_transfer();
+            Ok(&*words)
+        })
+    }
+}
 
-impl<SCK, MOSI, MISO, CS, SPI, PINS> crate::traits::wg::blocking::spi::write::Default<u8>
+impl<SCK, MOSI, MISO, CS, SPI, PINS> crate::traits::wg::blocking::spi::Write<u8>
 for
     SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
 where
@@ -252,7 +597,396 @@ where
     CS: PinId,
     SPI: Spi,
     PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
-{}
+{
+    type Error = Error;
+
+    // same as `write::Default`, instrumented
+    fn write(&mut self, words: &[u8]) -> core::result::Result<(), Error> {
+        crate::instrumented!(SPI_TRANSFER, {
+            for word in words {
+                nb::block!(self.send(*word))?;
+                nb::block!(self.read())?;
+            }
+            self.finish_transfer();
+            Ok(())
+        })
+    }
+}
+
+/// An SPI bus owned by the driver of a single device, asserting `cs` (active low)
+/// during its transactions.
+///
+/// Implements the embedded-hal 1.0 `SpiDevice`, like `ExclusiveDevice` of
+/// `embedded-hal-bus`; delays within a transaction are busy waits. For a bus
+/// shared by several devices, see `shared::SpiBus`.
+///
+/// ```ignore
+/// let spi = SpiMaster::new(spi, (sck, mosi, miso, NoCs), 8.MHz(), MODE_0);
+/// let flash = ExclusiveDevice::new(spi, flash_cs);
+/// // any driver taking an `embedded_hal::spi::SpiDevice`
+/// let mut flash = FlashDriver::new(flash);
+/// ```
+#[cfg(feature = "eh1")]
+pub struct ExclusiveDevice<SPI, CS> {
+    bus: SPI,
+    cs: CS,
+}
+
+#[cfg(feature = "eh1")]
+impl<SPI, CS: crate::traits::eh1::digital::OutputPin> ExclusiveDevice<SPI, CS> {
+    /// Deasserts `cs`.
+    pub fn new(bus: SPI, mut cs: CS) -> Self {
+        cs.set_high().ok();
+        Self { bus, cs }
+    }
+
+    pub fn bus(&self) -> &SPI {
+        &self.bus
+    }
+
+    /// The bus, e.g. to change its speed or mode between transactions
+    pub fn bus_mut(&mut self) -> &mut SPI {
+        &mut self.bus
+    }
+
+    pub fn release(self) -> (SPI, CS) {
+        (self.bus, self.cs)
+    }
+}
+
+#[cfg(feature = "eh1")]
+pub(crate) use eh1_impls::transaction;
+
+#[cfg(feature = "eh1")]
+mod eh1_impls {
+    use core::convert::Infallible;
+
+    use crate::traits::eh1::digital::OutputPin;
+    use crate::traits::eh1::spi::{
+        self,
+        ErrorKind,
+        ErrorType,
+        Operation,
+        SpiBus,
+        SpiDevice,
+    };
+    use crate::typestates::pin::{
+        flexcomm::{
+            Spi,
+            SpiPins,
+        },
+        PinId,
+    };
+    use super::{Error, ExclusiveDevice, FullDuplex, SpiMaster};
+
+    /// Run `operations` on `bus` with `cs` asserted, flushing the bus before
+    /// deasserting it again.
+    pub(crate) fn transaction<SPI, CS>(
+        bus: &mut SPI,
+        cs: &mut CS,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), SPI::Error>
+    where
+        SPI: SpiBus<u8>,
+        CS: OutputPin<Error = Infallible>,
+    {
+        cs.set_low().ok();
+        let result = operations.iter_mut().try_for_each(|operation| match operation {
+            Operation::Read(words) => bus.read(words),
+            Operation::Write(words) => bus.write(words),
+            Operation::Transfer(read, write) => bus.transfer(read, write),
+            Operation::TransferInPlace(words) => bus.transfer_in_place(words),
+            Operation::DelayNs(ns) => {
+                bus.flush()?;
+                crate::wait_at_least(ns.div_ceil(1_000));
+                Ok(())
+            }
+        });
+        let flushed = bus.flush();
+        cs.set_high().ok();
+        result.and(flushed)
+    }
+
+    impl<SPI: ErrorType, CS> ErrorType for ExclusiveDevice<SPI, CS> {
+        type Error = SPI::Error;
+    }
+
+    impl<SPI, CS> SpiDevice<u8> for ExclusiveDevice<SPI, CS>
+    where
+        SPI: SpiBus<u8>,
+        CS: OutputPin<Error = Infallible>,
+    {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+            transaction(&mut self.bus, &mut self.cs, operations)
+        }
+    }
+
+    impl spi::Error for Error {
+        fn kind(&self) -> ErrorKind {
+            match self {
+                Error::Overrun => ErrorKind::Overrun,
+                Error::Underrun => ErrorKind::Other,
+            }
+        }
+    }
+
+    impl<SCK, MOSI, MISO, CS, SPI, PINS> ErrorType for SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+    where
+        SCK: PinId,
+        MOSI: PinId,
+        MISO: PinId,
+        CS: PinId,
+        SPI: Spi,
+        PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+    {
+        type Error = Error;
+    }
+
+    impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiBus<u8> for SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+    where
+        SCK: PinId,
+        MOSI: PinId,
+        MISO: PinId,
+        CS: PinId,
+        SPI: Spi,
+        PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+    {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
+            crate::instrumented!(SPI_TRANSFER, {
+                for word in words.iter_mut() {
+                    nb::block!(self.send(0))?;
+                    *word = nb::block!(FullDuplex::read(self))?;
+                }
+                Ok(())
+            })
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Error> {
+            crate::instrumented!(SPI_TRANSFER, {
+                for word in words {
+                    nb::block!(self.send(*word))?;
+                    nb::block!(FullDuplex::read(self))?;
+                }
+                Ok(())
+            })
+        }
+
+        fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
+            crate::instrumented!(SPI_TRANSFER, {
+                let len = core::cmp::max(read.len(), write.len());
+                for i in 0..len {
+                    nb::block!(self.send(write.get(i).copied().unwrap_or(0)))?;
+                    let word = nb::block!(FullDuplex::read(self))?;
+                    if let Some(r) = read.get_mut(i) {
+                        *r = word;
+                    }
+                }
+                Ok(())
+            })
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Error> {
+            crate::instrumented!(SPI_TRANSFER, {
+                for word in words.iter_mut() {
+                    nb::block!(self.send(*word))?;
+                    *word = nb::block!(FullDuplex::read(self))?;
+                }
+                Ok(())
+            })
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            while self.spi.fifostat.read().txempty().bit_is_clear() {}
+            self.finish_transfer();
+            while self.spi.stat.read().mstidle().bit_is_clear() {}
+            Ok(())
+        }
+    }
+
+    /// Frames of the frame size, see `SpiMaster::set_frame_size`.
+    impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiBus<u16> for SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+    where
+        SCK: PinId,
+        MOSI: PinId,
+        MISO: PinId,
+        CS: PinId,
+        SPI: Spi,
+        PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+    {
+        fn read(&mut self, words: &mut [u16]) -> Result<(), Error> {
+            words.fill(0);
+            self.transfer_words(words)
+        }
+
+        fn write(&mut self, words: &[u16]) -> Result<(), Error> {
+            self.write_words(words)
+        }
+
+        fn transfer(&mut self, read: &mut [u16], write: &[u16]) -> Result<(), Error> {
+            let len = core::cmp::max(read.len(), write.len());
+            for i in 0..len {
+                nb::block!(self.send_frame(write.get(i).copied().unwrap_or(0)))?;
+                let word = nb::block!(self.read_frame())?;
+                if let Some(r) = read.get_mut(i) {
+                    *r = word;
+                }
+            }
+            Ok(())
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u16]) -> Result<(), Error> {
+            self.transfer_words(words)
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            SpiBus::<u8>::flush(self)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+mod async_impls {
+    use embedded_hal_async::spi::SpiBus;
+    use crate::drivers::asynch;
+    use crate::typestates::pin::{
+        flexcomm::{
+            Spi,
+            SpiPins,
+        },
+        PinId,
+    };
+    use super::{Error, SpiMaster};
+
+    /// Frames exchanged per wake-up, well within the FIFOs
+    const CHUNK: usize = 8;
+
+    /// Needs `asynch::FlexcommInterruptHandler` bound to the Flexcomm's interrupt.
+    impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+    where
+        SCK: PinId,
+        MOSI: PinId,
+        MISO: PinId,
+        CS: PinId,
+        SPI: Spi,
+        PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+    {
+        /// Exchange `len` frames (`next` to send, `store` received), in chunks that
+        /// fill the TX FIFO at once, waking up when a chunk was received.
+        async fn exchange(
+            &mut self,
+            len: usize,
+            mut next: impl FnMut(usize) -> u16,
+            mut store: impl FnMut(usize, u16),
+        ) -> Result<(), Error> {
+            let index = asynch::flexcomm_index(&*self.spi as *const _);
+            let mut done = 0;
+            while done < len {
+                let chunk = (len - done).min(CHUNK);
+                let spi = &self.spi;
+                // trigger once the whole chunk was received
+                spi.fifotrig.modify(|_, w| unsafe { w.rxlvl().bits(chunk as u8 - 1).rxlvlena().enabled() });
+                for i in done..done + chunk {
+                    nb::block!(self.send_frame(next(i)))?;
+                }
+
+                let spi = &self.spi;
+                asynch::wait_for(
+                    &asynch::FLEXCOMM_WAKERS[index],
+                    || {
+                        let fifostat = spi.fifostat.read();
+                        fifostat.rxlvl().bits() as usize >= chunk || fifostat.rxerr().bit_is_set()
+                    },
+                    || spi.fifointenset.write(|w| w.rxlvl().set_bit()),
+                ).await;
+                for i in done..done + chunk {
+                    store(i, nb::block!(self.read_frame())?);
+                }
+                done += chunk;
+            }
+            Ok(())
+        }
+    }
+
+    impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiBus<u8> for SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+    where
+        SCK: PinId,
+        MOSI: PinId,
+        MISO: PinId,
+        CS: PinId,
+        SPI: Spi,
+        PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+    {
+        async fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
+            self.exchange(words.len(), |_| 0, |i, word| words[i] = word as u8).await
+        }
+
+        async fn write(&mut self, words: &[u8]) -> Result<(), Error> {
+            self.exchange(words.len(), |i| words[i] as u16, |_, _| {}).await
+        }
+
+        async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
+            let len = core::cmp::max(read.len(), write.len());
+            self.exchange(
+                len,
+                |i| write.get(i).copied().unwrap_or(0) as u16,
+                |i, word| if let Some(r) = read.get_mut(i) {
+                    *r = word as u8;
+                },
+            ).await
+        }
+
+        async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Error> {
+            let words = core::cell::Cell::from_mut(words).as_slice_of_cells();
+            self.exchange(words.len(), |i| words[i].get() as u16, |i, word| words[i].set(word as u8)).await
+        }
+
+        async fn flush(&mut self) -> Result<(), Error> {
+            // every word is read back, so the bus is idle within a bit time
+            self.finish_transfer();
+            while self.spi.stat.read().mstidle().bit_is_clear() {}
+            Ok(())
+        }
+    }
+
+    /// Frames of the frame size, see `SpiMaster::set_frame_size`.
+    impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiBus<u16> for SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+    where
+        SCK: PinId,
+        MOSI: PinId,
+        MISO: PinId,
+        CS: PinId,
+        SPI: Spi,
+        PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+    {
+        async fn read(&mut self, words: &mut [u16]) -> Result<(), Error> {
+            self.exchange(words.len(), |_| 0, |i, word| words[i] = word).await
+        }
+
+        async fn write(&mut self, words: &[u16]) -> Result<(), Error> {
+            self.exchange(words.len(), |i| words[i], |_, _| {}).await
+        }
+
+        async fn transfer(&mut self, read: &mut [u16], write: &[u16]) -> Result<(), Error> {
+            let len = core::cmp::max(read.len(), write.len());
+            self.exchange(
+                len,
+                |i| write.get(i).copied().unwrap_or(0),
+                |i, word| if let Some(r) = read.get_mut(i) {
+                    *r = word;
+                },
+            ).await
+        }
+
+        async fn transfer_in_place(&mut self, words: &mut [u16]) -> Result<(), Error> {
+            let words = core::cell::Cell::from_mut(words).as_slice_of_cells();
+            self.exchange(words.len(), |i| words[i].get(), |i, word| words[i].set(word)).await
+        }
+
+        async fn flush(&mut self) -> Result<(), Error> {
+            SpiBus::<u8>::flush(self).await
+        }
+    }
+}
 
 // impl<SPI, PINS> crate::traits::wg::blocking::spi::transfer::Default<u8> for SpiMaster<SPI, PINS>
 // where
@@ -0,0 +1,180 @@
+//! Interrupt-driven SPI transfers, running in the background.
+//!
+//! The Flexcomm interrupt keeps the FIFOs serviced, so a transfer proceeds while
+//! the application does something else, without using DMA channels. The FIFO
+//! level triggers act as watermarks: the interrupt refills the TX FIFO once it
+//! drains to a few entries, and empties the RX FIFO once a few entries arrived.
+//! The handler is registered with `flexcomm::dispatch`, whose `DispatchHandler`
+//! must be bound:
+//!
+//! ```ignore
+//! hal::bind_interrupts!(struct Irqs {
+//!     FLEXCOMM3 => hal::peripherals::flexcomm::DispatchHandler<3>;
+//! });
+//! hal::interrupt::unmask::<DispatchHandler<3>>(Irqs);
+//!
+//! static mut BUFFER: [u8; 512] = [0; 512];
+//!
+//! let transfer = spi.transfer_in_background(unsafe { &mut *addr_of_mut!(BUFFER) });
+//! do_something_else();
+//! let (buffer, spi) = transfer.wait();
+//! ```
+
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::{
+    peripherals::flexcomm::{dispatch, Fifo},
+    raw,
+    typestates::pin::{
+        flexcomm::{Spi, SpiPins},
+        PinId,
+    },
+};
+use super::SpiMaster;
+
+/// Frames sent but not yet received, so the RX FIFO can't overflow
+const IN_FLIGHT: usize = 8;
+/// The TX FIFO is refilled once it holds at most this many frames
+const TX_WATERMARK: u8 = 3;
+/// The RX FIFO is emptied once it holds this many frames
+const RX_WATERMARK: usize = 4;
+
+#[derive(Clone, Copy)]
+struct State {
+    // the buffer, as address for `Send`
+    address: usize,
+    len: usize,
+    sent: usize,
+    received: usize,
+    control: u16,
+    // `Framing::deassert_after_transfer`
+    end_transfer: bool,
+}
+
+type Slot = Mutex<Cell<Option<State>>>;
+
+static STATES: [Slot; dispatch::INSTANCES] =
+    [const { Mutex::new(Cell::new(None)) }; dispatch::INSTANCES];
+
+/// Services the FIFOs of the background transfer of Flexcomm `index`, if any.
+///
+/// Registered with `flexcomm::dispatch` by `transfer_in_background`, until `wait`.
+pub fn on_interrupt(index: usize) {
+    let spi = unsafe { &*(dispatch::base(index) as *const raw::spi0::RegisterBlock) };
+    interrupt::free(|cs| {
+        let slot = STATES[index].borrow(cs);
+        let Some(mut state) = slot.get() else {
+            return;
+        };
+        let buffer = state.address as *mut u8;
+
+        while state.received < state.len && spi.fifostat.read().rxnotempty().bit_is_set() {
+            let frame = spi.fiford.read().rxdata().bits() as u8;
+            unsafe { buffer.add(state.received).write_volatile(frame) };
+            state.received += 1;
+        }
+
+        while state.sent < state.len
+            && state.sent - state.received < IN_FLIGHT
+            && spi.fifostat.read().txnotfull().bit_is_set()
+        {
+            let frame = unsafe { buffer.add(state.sent).read_volatile() };
+            let mut control = state.control;
+            if state.sent + 1 == state.len && state.end_transfer {
+                // EOT, deasserting the chip select once the last frame was sent
+                control |= 1 << 4;
+            }
+            spi.fifowr.write(|w| unsafe { w.bits((control as u32) << 16 | frame as u32) });
+            state.sent += 1;
+        }
+
+        if state.sent < state.len && state.sent - state.received < IN_FLIGHT {
+            spi.fifointenset.write(|w| w.txlvl().set_bit());
+        } else {
+            // done sending, or resumed once frames are received
+            spi.fifointenclr.write(|w| w.txlvl().set_bit());
+        }
+        let remaining = state.len - state.received;
+        if remaining == 0 {
+            spi.fifointenclr.write(|w| w.rxlvl().set_bit());
+        } else {
+            // the last frames trigger on their own
+            let level = remaining.min(RX_WATERMARK) as u8 - 1;
+            spi.fifotrig.modify(|_, w| unsafe { w.rxlvl().bits(level) });
+        }
+        slot.set(Some(state));
+    });
+}
+
+/// An SPI transfer in progress on the `SpiMaster` `SPIM`, see the module documentation
+pub struct BackgroundTransfer<SPIM> {
+    spi: SPIM,
+    buffer: &'static mut [u8],
+    index: usize,
+}
+
+impl<SCK, MOSI, MISO, CS, SPI, PINS> SpiMaster<SCK, MOSI, MISO, CS, SPI, PINS>
+where
+    SCK: PinId,
+    MOSI: PinId,
+    MISO: PinId,
+    CS: PinId,
+    SPI: Spi,
+    PINS: SpiPins<SCK, MOSI, MISO, CS, SPI>,
+{
+    /// Start exchanging `buffer` with the device, from the Flexcomm interrupt.
+    ///
+    /// The received bytes replace the sent ones. Needs frames of at most 8 bits,
+    /// and panics if another driver handles the Flexcomm interrupt.
+    pub fn transfer_in_background(self, buffer: &'static mut [u8]) -> BackgroundTransfer<Self> {
+        assert!(self.frame_bits <= 8);
+        let index = dispatch::instance(&self.spi);
+        // stale received data would shift the reply
+        self.spi.empty_rx_fifo();
+
+        let state = State {
+            address: buffer.as_mut_ptr() as usize,
+            len: buffer.len(),
+            sent: 0,
+            received: 0,
+            control: self.control(false),
+            end_transfer: self.framing.deassert_after_transfer,
+        };
+        dispatch::try_register(index, on_interrupt)
+            .expect("the Flexcomm interrupt is handled by another driver");
+        interrupt::free(|cs| STATES[index].borrow(cs).set(Some(state)));
+
+        if !buffer.is_empty() {
+            let level = buffer.len().min(RX_WATERMARK) as u8 - 1;
+            self.spi.fifotrig.modify(|_, w| unsafe { w
+                .txlvl().bits(TX_WATERMARK).txlvlena().enabled()
+                .rxlvl().bits(level).rxlvlena().enabled()
+            });
+            // the empty TX FIFO triggers right away
+            self.spi.fifointenset.write(|w| w.txlvl().set_bit().rxlvl().set_bit());
+        }
+
+        BackgroundTransfer { spi: self, buffer, index }
+    }
+}
+
+impl<SPIM> BackgroundTransfer<SPIM> {
+    /// Number of bytes exchanged so far
+    pub fn progress(&self) -> usize {
+        interrupt::free(|cs| STATES[self.index].borrow(cs).get().map_or(0, |state| state.received))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.progress() == self.buffer.len()
+    }
+
+    /// Wait for the transfer to complete, returning the buffer with the received bytes.
+    pub fn wait(self) -> (&'static mut [u8], SPIM) {
+        while !self.is_done() {}
+        interrupt::free(|cs| STATES[self.index].borrow(cs).set(None));
+        dispatch::unregister(self.index);
+        (self.buffer, self.spi)
+    }
+}
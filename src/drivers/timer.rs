@@ -1,11 +1,13 @@
 use core::convert::Infallible;
+use core::mem::ManuallyDrop;
+use core::ptr;
 
 use nb;
 use void::Void;
 
 use crate::{
     traits::wg::timer,
-    peripherals::ctimer::Ctimer,
+    peripherals::{ctimer::Ctimer, syscon},
     time::Microseconds,
     typestates::init_state,
 };
@@ -33,9 +35,33 @@ where TIMER: Ctimer<init_state::Enabled> {
     }
 
     pub fn release(self) -> TIMER {
-        self.timer
+        let this = ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.timer) }
     }
 
+    /// The timer owned by the driver, which derefs to the raw registers.
+    pub fn raw(&self) -> &TIMER {
+        &self.timer
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut TIMER {
+        &mut self.timer
+    }
+
+}
+
+/// Stops the timer, masks its interrupts and disables its clock.
+///
+/// To keep the timer running, `release` the driver, or `core::mem::forget` it.
+impl<TIMER> Drop for Timer<TIMER>
+where TIMER: Ctimer<init_state::Enabled>
+{
+    fn drop(&mut self) {
+        self.timer.tcr.write(|w| w.crst().set_bit().cen().clear_bit());
+        self.timer.mcr.write(|w| unsafe { w.bits(0) });
+        syscon::disable_clock_on_drop(&self.timer);
+    }
 }
 
 type TimeUnits = Microseconds;
@@ -43,7 +69,7 @@ type TimeUnits = Microseconds;
 impl <TIMER> Elapsed for Timer<TIMER>
 where TIMER: Ctimer<init_state::Enabled> {
     fn elapsed(&self) -> Microseconds {
-        Microseconds(self.timer.tc.read().bits())
+        Microseconds::from_ticks(self.timer.tc.read().bits())
     }
 }
 
@@ -64,7 +90,7 @@ where TIMER: Ctimer<init_state::Enabled>
         } );
 
         // Set match to target time.  Ctimer fixed input 1MHz.
-        self.timer.mr[0].write(|w| unsafe { w.bits(count.into().0) });
+        self.timer.mr[0].write(|w| unsafe { w.bits(count.into().ticks()) });
 
         // No divsion necessary.
         self.timer.pr.write(|w| unsafe {w.bits(0)});
@@ -105,3 +131,63 @@ where TIMER: Ctimer<init_state::Enabled>
         Ok(())
     }
 }
+
+#[cfg(feature = "eh1")]
+impl<TIMER> crate::traits::eh1::delay::DelayNs for Timer<TIMER>
+where TIMER: Ctimer<init_state::Enabled>
+{
+    /// Resolution is 1us, the timer runs at 1MHz.
+    fn delay_ns(&mut self, ns: u32) {
+        if ns == 0 {
+            return;
+        }
+        timer::CountDown::start(self, Microseconds::from_ticks(ns.div_ceil(1_000)));
+        nb::block!(timer::CountDown::wait(self)).ok();
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        if us == 0 {
+            return;
+        }
+        timer::CountDown::start(self, Microseconds::from_ticks(us));
+        nb::block!(timer::CountDown::wait(self)).ok();
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TIMER> embedded_hal_async::delay::DelayNs for Timer<TIMER>
+where TIMER: Ctimer<init_state::Enabled>
+{
+    /// Resolution is 1us, the timer runs at 1MHz.
+    ///
+    /// Needs `asynch::CtimerInterruptHandler` bound to the timer's interrupt.
+    async fn delay_ns(&mut self, ns: u32) {
+        if ns == 0 {
+            return;
+        }
+        self.delay_us(ns.div_ceil(1_000)).await
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        use core::sync::atomic::Ordering;
+        use crate::drivers::asynch;
+
+        if us == 0 {
+            return;
+        }
+        let index = asynch::ctimer_index(&*self.timer as *const _);
+        let matched = &asynch::CTIMER_MATCHED[index];
+        matched.store(false, Ordering::Relaxed);
+        // `start` also enables the match interrupt
+        timer::CountDown::start(self, Microseconds::from_ticks(us));
+        let timer = &self.timer;
+        // the interrupt handler clears IR, recording the match
+        asynch::wait_for(
+            &asynch::CTIMER_WAKERS[index],
+            || timer.ir.read().mr0int().bit_is_set() || matched.load(Ordering::Acquire),
+            || timer.mcr.modify(|_, w| w.mr0i().set_bit()),
+        ).await;
+        timer::Cancel::cancel(self).ok();
+        matched.store(false, Ordering::Relaxed);
+    }
+}
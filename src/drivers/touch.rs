@@ -1,5 +1,5 @@
 use core::ops::{Deref, DerefMut};
-use embedded_time::duration::Extensions;
+use crate::time::DurationExtensions;
 
 use crate::traits::wg::timer::CountDown;
 use crate::{
@@ -40,6 +40,7 @@ pub struct ButtonPins<P1 : PinId, P2 :  PinId, P3 : PinId>(
 );
 
 type Adc = crate::peripherals::adc::Adc<init_state::Enabled>;
+type ChargePin = Pin<pins::Pio1_16, state::Special<function::MATCH_OUTPUT3<ctimer::Ctimer1<init_state::Enabled>>>>;
 
 
 pub struct TouchSensor<P1 : PinId, P2 :  PinId, P3 : PinId,
@@ -51,7 +52,8 @@ pub struct TouchSensor<P1 : PinId, P2 :  PinId, P3 : PinId,
     adc: Adc,
     adc_timer: ctimer::Ctimer1<init_state::Enabled>,
     sample_timer: ctimer::Ctimer2<init_state::Enabled>,
-    _buttons: ButtonPins<P1,P2,P3>,
+    charge_pin: ChargePin,
+    buttons: ButtonPins<P1,P2,P3>,
     // pub _state: State,
 }
 
@@ -95,7 +97,7 @@ where P1: PinId, P2: PinId, P3: PinId
         adc: Adc,
         adc_timer: ctimer::Ctimer1<init_state::Enabled>,
         sample_timer: ctimer::Ctimer2<init_state::Enabled>,
-        _charge_pin: Pin<pins::Pio1_16, state::Special<function::MATCH_OUTPUT3<ctimer::Ctimer1<init_state::Enabled>>>>,
+        charge_pin: ChargePin,
         buttons: ButtonPins<P1,P2,P3>,
     ) -> Self {
 
@@ -183,7 +185,8 @@ where P1: PinId, P2: PinId, P3: PinId
             adc: adc,
             adc_timer: adc_timer,
             sample_timer: sample_timer,
-            _buttons: buttons,
+            charge_pin,
+            buttons,
             threshold: threshold,
             confidence: confidence,
             // _state: init_state::Unknown,
@@ -220,6 +223,19 @@ where P1: PinId, P2: PinId, P3: PinId,
 
         self
     }
+
+    /// Stops the timers and DMA channels, returning all consumed resources.
+    #[allow(clippy::type_complexity)]
+    pub fn release(
+        mut self,
+        dma: &mut Dma<init_state::Enabled>,
+    ) -> (Adc, ctimer::Ctimer1<init_state::Enabled>, ctimer::Ctimer2<init_state::Enabled>, ChargePin, ButtonPins<P1,P2,P3>) {
+        self.adc_timer.tcr.write(|w| w.crst().set_bit().cen().clear_bit());
+        self.sample_timer.tcr.write(|w| w.crst().set_bit().cen().clear_bit());
+        dma.release_adc(&mut self.adc);
+
+        (self.adc, self.adc_timer, self.sample_timer, self.charge_pin, self.buttons)
+    }
 }
 
 
@@ -229,12 +245,14 @@ pub struct TouchResult {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Edge {
     Rising,
     Falling
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Compare {
     AboveThreshold,
     BelowThreshold
@@ -416,17 +434,17 @@ pub fn profile_touch_sensing(touch_sensor: &mut TouchSensor<impl PinId, impl Pin
                             times: &mut [u32],
                             ){
 
-    let start = delay_timer.elapsed().0;
+    let start = delay_timer.elapsed().ticks();
     let results = touch_sensor.get_results();
 
-    delay_timer.start(300_000.microseconds());
+    delay_timer.start(300_000.micros());
 
     loop {
         let mut has_zero = false;
         for i in 0 .. 125 {
             if results[i] != 0 {
                 if times[i] == 0 {
-                    times[i] = delay_timer.elapsed().0 - start;
+                    times[i] = delay_timer.elapsed().ticks() - start;
                     copy[i] = results[i];
                 }
             }
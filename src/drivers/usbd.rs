@@ -302,19 +302,24 @@ where
                 return PollResult::Reset
             }
 
-            // if devcmdstat.read().dsus_c().bit_is_set() {
-            //     cortex_m_semihosting::hprintln!("suspend bit set!").ok();
-            // }
-
-            // if devcmdstat.read().dsus_c().bit_is_set() {
-            //     cortex_m_semihosting::hprintln!("device suspended!").ok();
-            // }
-
-            // if devcmdstat.read().dcon_c().bit_is_set() {
-            //     cortex_m_semihosting::hprintln!("connect bit set!").ok();
-            // }
-
-            // TODO: Resume, Suspend handling
+            // Suspend or resume (by the host or a bus reset)?
+            let status = devcmdstat.read();
+            if status.dsus_c().bit_is_set() {
+                // clear by writing 1, without clearing the other change flags or SETUP
+                devcmdstat.modify(|_, w| w
+                    .dsus_c().set_bit()
+                    .dcon_c().clear_bit()
+                    .dres_c().clear_bit()
+                    .setup().clear_bit()
+                );
+                return if status.dsus().bit_is_set() {
+                    crate::events::push(crate::events::Event::UsbSuspend);
+                    PollResult::Suspend
+                } else {
+                    crate::events::push(crate::events::Event::UsbResume);
+                    PollResult::Resume
+                };
+            }
 
             let mut ep_out = 0;
             let mut ep_in_complete = 0;
@@ -0,0 +1,110 @@
+//! Crate-wide error type.
+//!
+//! Each driver has its own error type, all of them convert into `hal::Error`,
+//! so applications can propagate any of them with `?`:
+//!
+//! ```ignore
+//! fn run(i2c: &mut impl Write<Error = I2cError>, adc: &mut Adc<Enabled>, ...) -> Result<(), hal::Error> {
+//!     i2c.write(0x3c, &[0xaf])?;
+//!     let sample = adc.read(&pin)?;
+//!     ...
+//! }
+//! ```
+
+use core::fmt;
+
+use crate::{
+    drivers::{
+        clocks::ClocksError,
+        i2c,
+        rng,
+        serial,
+        spi,
+    },
+    peripherals::{
+        adc,
+        dma,
+    },
+    traits::flash,
+};
+#[cfg(feature = "_puf")]
+use crate::peripherals::puf;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Error {
+    Adc(adc::Underflow),
+    Clocks(ClocksError),
+    Dma(dma::Overrun),
+    Flash(flash::Error),
+    I2c(i2c::Error),
+    #[cfg(feature = "_puf")]
+    Puf(puf::Error),
+    Rng(rng::Error),
+    Serial(serial::Error),
+    SerialConfig(serial::config::InvalidConfig),
+    Spi(spi::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Adc(e) => write!(f, "ADC: {}", e),
+            Error::Clocks(e) => write!(f, "clocks: {}", e),
+            Error::Dma(e) => write!(f, "DMA: {}", e),
+            Error::Flash(e) => write!(f, "flash: {}", e),
+            Error::I2c(e) => write!(f, "I2C: {}", e),
+            #[cfg(feature = "_puf")]
+            Error::Puf(e) => write!(f, "PUF: {}", e),
+            Error::Rng(e) => write!(f, "RNG: {}", e),
+            Error::Serial(e) => write!(f, "serial: {}", e),
+            Error::SerialConfig(e) => write!(f, "serial: {}", e),
+            Error::Spi(e) => write!(f, "SPI: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(match self {
+            Error::Adc(e) => e,
+            Error::Clocks(e) => e,
+            Error::Dma(e) => e,
+            Error::Flash(e) => e,
+            Error::I2c(e) => e,
+            #[cfg(feature = "_puf")]
+            Error::Puf(e) => e,
+            Error::Rng(e) => e,
+            Error::Serial(e) => e,
+            Error::SerialConfig(e) => e,
+            Error::Spi(e) => e,
+        })
+    }
+}
+
+macro_rules! from_error {
+    ($($(#[$meta:meta])* $error:ty => $variant:ident,)*) => {$(
+        $(#[$meta])*
+        impl From<$error> for Error {
+            fn from(error: $error) -> Self {
+                Error::$variant(error)
+            }
+        }
+    )*}
+}
+
+from_error! {
+    adc::Underflow => Adc,
+    ClocksError => Clocks,
+    dma::Overrun => Dma,
+    flash::Error => Flash,
+    i2c::Error => I2c,
+    #[cfg(feature = "_puf")]
+    puf::Error => Puf,
+    rng::Error => Rng,
+    serial::Error => Serial,
+    serial::config::InvalidConfig => SerialConfig,
+    spi::Error => Spi,
+}
+
@@ -0,0 +1,233 @@
+//! Queue of peripheral events, pushed from interrupt handlers and drained by the application.
+//!
+//! A middle ground between handling everything in interrupt handlers and an
+//! async runtime: the handlers of this module (or the application's own)
+//! acknowledge the interrupt and push a typed `Event`, the main loop handles
+//! the events in order.
+//!
+//! ```ignore
+//! hal::bind_interrupts!(struct Irqs {
+//!     PIN_INT0 => hal::events::PintEventHandler<0>;
+//!     DMA0 => hal::events::DmaEventHandler;
+//! });
+//!
+//! loop {
+//!     while let Some(event) = hal::events::pop() {
+//!         match event {
+//!             Event::PinEdge { slot: 0, edge: Edge::Falling } => button_pressed(),
+//!             Event::DmaComplete { channel } => ...,
+//!             _ => {}
+//!         }
+//!     }
+//!     cortex_m::asm::wfi();
+//! }
+//! ```
+//!
+//! The queue is lock-free, so it can be pushed to from interrupt handlers of any
+//! priority without delaying higher ones. Events pushed while it is full are
+//! dropped and counted.
+
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+};
+
+use crate::{
+    interrupt::Handler,
+    raw::{self, Interrupt},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    Rising,
+    Falling,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Event {
+    /// Edge on the pin assigned to a PINT slot, pushed by `PintEventHandler`
+    PinEdge { slot: u8, edge: Edge },
+    /// The receiver of the USART on Flexcomm interface `flexcomm` went idle, pushed by `IdleFrames::poll`
+    RxIdle { flexcomm: u8 },
+    /// A DMA channel completed a transfer with interrupt A set, pushed by `DmaEventHandler`
+    DmaComplete { channel: u8 },
+    /// The USB host suspended the bus, pushed by `UsbBus::poll`
+    UsbSuspend,
+    /// The USB host resumed the bus, pushed by `UsbBus::poll`
+    UsbResume,
+}
+
+struct Slot {
+    // position of the last push or pop, relative to the slot's index
+    sequence: AtomicUsize,
+    event: UnsafeCell<MaybeUninit<Event>>,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Slot {
+            sequence: AtomicUsize::new(0),
+            event: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+/// Bounded multi-producer queue of events (Vyukov's), `N` must be a power of two.
+pub struct EventQueue<const N: usize> {
+    slots: [Slot; N],
+    enqueue: AtomicUsize,
+    dequeue: AtomicUsize,
+    dropped: AtomicU32,
+}
+
+unsafe impl<const N: usize> Sync for EventQueue<N> {}
+
+impl<const N: usize> EventQueue<N> {
+    pub const fn new() -> Self {
+        const { assert!(N.is_power_of_two(), "the capacity must be a power of two") };
+        EventQueue {
+            slots: [const { Slot::new() }; N],
+            enqueue: AtomicUsize::new(0),
+            dequeue: AtomicUsize::new(0),
+            dropped: AtomicU32::new(0),
+        }
+    }
+
+    /// Append `event`, handing it back if the queue is full.
+    pub fn push(&self, event: Event) -> Result<(), Event> {
+        let mut position = self.enqueue.load(Ordering::Relaxed);
+        loop {
+            let index = position % N;
+            let slot = &self.slots[index];
+            let sequence = slot.sequence.load(Ordering::Acquire).wrapping_add(index);
+            let lag = sequence.wrapping_sub(position) as isize;
+            if lag == 0 {
+                match self.enqueue.compare_exchange_weak(
+                    position, position.wrapping_add(1), Ordering::Relaxed, Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.event.get()).write(event) };
+                        slot.sequence.store(position.wrapping_add(1).wrapping_sub(index), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => position = current,
+                }
+            } else if lag < 0 {
+                // not yet popped
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return Err(event);
+            } else {
+                // another push got this position
+                position = self.enqueue.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Remove the oldest event.
+    pub fn pop(&self) -> Option<Event> {
+        let mut position = self.dequeue.load(Ordering::Relaxed);
+        loop {
+            let index = position % N;
+            let slot = &self.slots[index];
+            let sequence = slot.sequence.load(Ordering::Acquire).wrapping_add(index);
+            let lag = sequence.wrapping_sub(position.wrapping_add(1)) as isize;
+            if lag == 0 {
+                match self.dequeue.compare_exchange_weak(
+                    position, position.wrapping_add(1), Ordering::Relaxed, Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let event = unsafe { (*slot.event.get()).assume_init_read() };
+                        slot.sequence.store(position.wrapping_add(N).wrapping_sub(index), Ordering::Release);
+                        return Some(event);
+                    }
+                    Err(current) => position = current,
+                }
+            } else if lag < 0 {
+                // empty, or the push is still in progress
+                return None;
+            } else {
+                position = self.dequeue.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Number of events dropped because the queue was full
+    pub fn dropped(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<const N: usize> Default for EventQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The queue the handlers of this module push to
+pub static EVENTS: EventQueue<32> = EventQueue::new();
+
+/// Push `event` to `EVENTS`, dropping it if the queue is full.
+pub fn push(event: Event) {
+    EVENTS.push(event).ok();
+}
+
+/// Pop the oldest event from `EVENTS`.
+pub fn pop() -> Option<Event> {
+    EVENTS.pop()
+}
+
+/// Interrupt handler for pin interrupt slot `N` (0-7), configured for edges
+pub struct PintEventHandler<const N: usize>;
+
+impl<const N: usize> Handler for PintEventHandler<N> {
+    const INTERRUPT: Interrupt = match N {
+        0 => Interrupt::PIN_INT0,
+        1 => Interrupt::PIN_INT1,
+        2 => Interrupt::PIN_INT2,
+        3 => Interrupt::PIN_INT3,
+        4 => Interrupt::PIN_INT4,
+        5 => Interrupt::PIN_INT5,
+        6 => Interrupt::PIN_INT6,
+        7 => Interrupt::PIN_INT7,
+        _ => panic!("there are only pin interrupts 0-7"),
+    };
+
+    unsafe fn on_interrupt() {
+        let pint = &*raw::PINT::ptr();
+        let bit = 1 << N;
+        let rising = pint.rise.read().rdet().bits() & bit != 0;
+        let falling = pint.fall.read().fdet().bits() & bit != 0;
+        pint.rise.write(|w| w.rdet().bits(bit));
+        pint.fall.write(|w| w.fdet().bits(bit));
+        pint.ist.write(|w| w.pstat().bits(bit));
+        if rising {
+            push(Event::PinEdge { slot: N as u8, edge: Edge::Rising });
+        }
+        if falling {
+            push(Event::PinEdge { slot: N as u8, edge: Edge::Falling });
+        }
+    }
+}
+
+/// Interrupt handler for the DMA, pushing completions of all channels
+pub struct DmaEventHandler;
+
+impl Handler for DmaEventHandler {
+    const INTERRUPT: Interrupt = Interrupt::DMA0;
+
+    unsafe fn on_interrupt() {
+        let dma = &*raw::DMA0::ptr();
+        // only channels with the interrupt enabled, `DmaRingBuffer`s poll their flags
+        let completed = dma.inta0.read().bits() & dma.intenset0.read().bits();
+        dma.inta0.write(|w| w.bits(completed));
+        for channel in 0..32 {
+            if completed & (1 << channel) != 0 {
+                push(Event::DmaComplete { channel });
+            }
+        }
+    }
+}
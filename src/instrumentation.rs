@@ -0,0 +1,120 @@
+//! Cycle counts of driver operations (feature `instrumentation`).
+//!
+//! Key blocking operations record their duration in DWT cycles into the
+//! counters of this module, which can be inspected at runtime to spot
+//! performance regressions or bus contention:
+//!
+//! ```ignore
+//! for counter in hal::instrumentation::COUNTERS {
+//!     let stats = counter.stats();
+//!     rprintln!("{}: {} x, mean {} cycles, max {}", counter.name(), stats.count, stats.mean(), stats.max);
+//! }
+//! ```
+//!
+//! The cycle counter is enabled on first use. Durations include time spent in
+//! interrupt handlers preempting the operation.
+
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::raw::DWT;
+
+/// Accumulated cycle counts of one kind of operation
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stats {
+    pub count: u32,
+    pub total: u64,
+    /// Meaningless if `count` is zero
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Stats {
+    const EMPTY: Self = Stats { count: 0, total: 0, min: u32::MAX, max: 0 };
+
+    pub fn mean(&self) -> u32 {
+        if self.count == 0 {
+            0
+        } else {
+            (self.total / self.count as u64) as u32
+        }
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+pub struct Counter {
+    name: &'static str,
+    stats: Mutex<Cell<Stats>>,
+}
+
+impl Counter {
+    const fn new(name: &'static str) -> Self {
+        Counter { name, stats: Mutex::new(Cell::new(Stats::EMPTY)) }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn stats(&self) -> Stats {
+        interrupt::free(|cs| self.stats.borrow(cs).get())
+    }
+
+    pub fn reset(&self) {
+        interrupt::free(|cs| self.stats.borrow(cs).set(Stats::EMPTY));
+    }
+
+    #[doc(hidden)]
+    pub fn record(&self, start: u32) {
+        let cycles = DWT::cycle_count().wrapping_sub(start);
+        interrupt::free(|cs| {
+            let cell = self.stats.borrow(cs);
+            let mut stats = cell.get();
+            stats.count = stats.count.wrapping_add(1);
+            stats.total += cycles as u64;
+            stats.min = stats.min.min(cycles);
+            stats.max = stats.max.max(cycles);
+            cell.set(stats);
+        });
+    }
+}
+
+/// Blocking SPI transfers, per call (not per byte)
+pub static SPI_TRANSFER: Counter = Counter::new("SPI transfer");
+/// Programming one flash page
+pub static FLASH_PROGRAM: Counter = Counter::new("flash program");
+/// Erasing one flash page
+pub static FLASH_ERASE: Counter = Counter::new("flash erase");
+/// Feeding one block to the SHA engine
+pub static HASH_BLOCK: Counter = Counter::new("hash block");
+/// Reading one word from the RNG
+pub static RNG_WORD: Counter = Counter::new("RNG word");
+
+pub static COUNTERS: [&Counter; 5] = [
+    &SPI_TRANSFER,
+    &FLASH_PROGRAM,
+    &FLASH_ERASE,
+    &HASH_BLOCK,
+    &RNG_WORD,
+];
+
+pub fn reset_all() {
+    for counter in COUNTERS.iter() {
+        counter.reset();
+    }
+}
+
+#[doc(hidden)]
+pub fn start() -> u32 {
+    if !DWT::cycle_counter_enabled() {
+        crate::enable_cycle_counter();
+    }
+    DWT::cycle_count()
+}
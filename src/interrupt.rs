@@ -0,0 +1,260 @@
+//! Binding of interrupt vectors to driver interrupt logic.
+//!
+//! Drivers which rely on interrupts provide a `Handler` per interrupt they use.
+//! The `bind_interrupts!` macro generates the vector functions which call these
+//! handlers, along with a type proving the binding exists:
+//!
+//! ```ignore
+//! hal::bind_interrupts!(struct Irqs {
+//!     FLEXCOMM2 => hal::drivers::asynch::FlexcommInterruptHandler<2>;
+//!     CTIMER0 => hal::drivers::asynch::CtimerInterruptHandler<0>;
+//! });
+//!
+//! // safe, as the vector is guaranteed to call into the driver
+//! hal::interrupt::unmask::<FlexcommInterruptHandler<2>>(Irqs);
+//! ```
+//!
+//! Binding a handler to the wrong vector is a compile-time error.
+//!
+//! Priorities are set with the typed `Priority`, which converts to and from
+//! RTIC's logical priorities. `with_ceiling` and `with_masked` provide the two
+//! kinds of critical sections RTIC builds its resource locks from:
+//!
+//! ```ignore
+//! unsafe {
+//!     hal::interrupt::set_priority(&mut nvic, Interrupt::FLEXCOMM2, Priority::P2);
+//!     hal::interrupt::set_priority(&mut nvic, Interrupt::CTIMER0, Priority::P4);
+//! }
+//!
+//! // CTIMER0 can't preempt, FLEXCOMM2 still can
+//! hal::interrupt::with_ceiling(Priority::P4, || shared.update());
+//! ```
+
+use crate::raw::Interrupt;
+
+/// Interrupt logic of a driver
+pub trait Handler {
+    /// The vector this handler must be bound to
+    const INTERRUPT: Interrupt;
+
+    /// Called from the interrupt vector.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called from the interrupt vector `INTERRUPT`.
+    unsafe fn on_interrupt();
+}
+
+/// Proof that the `Handler` is bound to its vector, generated by `bind_interrupts!`.
+///
+/// # Safety
+///
+/// Only implement this if the vector `H::INTERRUPT` calls `H::on_interrupt`.
+pub unsafe trait Binding<H: Handler>: Copy {}
+
+/// Unmask the interrupt of a bound handler in the NVIC.
+pub fn unmask<H: Handler>(_binding: impl Binding<H>) {
+    unsafe { cortex_m::peripheral::NVIC::unmask(H::INTERRUPT) };
+}
+
+/// Mask the interrupt of a handler in the NVIC.
+pub fn mask<H: Handler>() {
+    cortex_m::peripheral::NVIC::mask(H::INTERRUPT);
+}
+
+/// Priority levels implemented by the NVIC (`raw::NVIC_PRIO_BITS` bits)
+pub const PRIORITY_LEVELS: u8 = 1 << crate::raw::NVIC_PRIO_BITS;
+
+/// Hardware priority of an interrupt, `P0` being the most urgent.
+///
+/// RTIC counts the other way round, see `logical` and `from_logical`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Priority {
+    P0 = 0,
+    P1 = 1,
+    P2 = 2,
+    P3 = 3,
+    P4 = 4,
+    P5 = 5,
+    P6 = 6,
+    P7 = 7,
+}
+
+impl Priority {
+    const ALL: [Priority; PRIORITY_LEVELS as usize] = [
+        Priority::P0, Priority::P1, Priority::P2, Priority::P3,
+        Priority::P4, Priority::P5, Priority::P6, Priority::P7,
+    ];
+
+    const SHIFT: u8 = 8 - crate::raw::NVIC_PRIO_BITS;
+
+    /// The value of the NVIC priority registers (and of BASEPRI) for this priority
+    pub const fn to_bits(self) -> u8 {
+        (self as u8) << Self::SHIFT
+    }
+
+    /// The priority of an NVIC priority register value, ignoring the unimplemented bits
+    pub const fn from_bits(bits: u8) -> Self {
+        Self::ALL[(bits >> Self::SHIFT) as usize]
+    }
+
+    /// RTIC's logical priority, from 1 (least urgent) to `PRIORITY_LEVELS`
+    pub const fn logical(self) -> u8 {
+        PRIORITY_LEVELS - self as u8
+    }
+
+    /// The priority of an RTIC task or resource ceiling, if within 1..=`PRIORITY_LEVELS`
+    pub const fn from_logical(logical: u8) -> Option<Self> {
+        if logical == 0 || logical > PRIORITY_LEVELS {
+            None
+        } else {
+            Some(Self::ALL[(PRIORITY_LEVELS - logical) as usize])
+        }
+    }
+}
+
+/// Set the priority of an interrupt.
+///
+/// # Safety
+///
+/// Changing priorities can break priority-based critical sections, such as
+/// RTIC resource locks or `with_ceiling`, if done while they are in use.
+pub unsafe fn set_priority(nvic: &mut cortex_m::peripheral::NVIC, interrupt: Interrupt, priority: Priority) {
+    nvic.set_priority(interrupt, priority.to_bits());
+}
+
+/// The priority of an interrupt.
+pub fn priority(interrupt: Interrupt) -> Priority {
+    Priority::from_bits(cortex_m::peripheral::NVIC::get_priority(interrupt))
+}
+
+/// Set the priority of a handler's interrupt.
+///
+/// # Safety
+///
+/// See `set_priority`.
+pub unsafe fn set_handler_priority<H: Handler>(nvic: &mut cortex_m::peripheral::NVIC, priority: Priority) {
+    set_priority(nvic, H::INTERRUPT, priority);
+}
+
+/// Split of the priority bits into preemption priority and subpriority (AIRCR.PRIGROUP).
+///
+/// Only the preemption priority decides whether an interrupt preempts another,
+/// the subpriority orders pending interrupts. RTIC expects `Preempt3Sub0`,
+/// which is the reset value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PriorityGrouping {
+    /// All priority bits preempt
+    Preempt3Sub0 = 4,
+    Preempt2Sub1 = 5,
+    Preempt1Sub2 = 6,
+    /// No preemption among interrupts
+    Preempt0Sub3 = 7,
+}
+
+const AIRCR_VECTKEY: u32 = 0x05FA << 16;
+const AIRCR_PRIGROUP_SHIFT: u32 = 8;
+const AIRCR_PRIGROUP_MASK: u32 = 0x7 << AIRCR_PRIGROUP_SHIFT;
+/// SYSRESETREQS, BFHFNMINS and PRIS, which a write must preserve
+const AIRCR_PRESERVE: u32 = (1 << 3) | (1 << 13) | (1 << 14);
+
+pub fn set_priority_grouping(scb: &mut cortex_m::peripheral::SCB, grouping: PriorityGrouping) {
+    let aircr = scb.aircr.read();
+    unsafe {
+        scb.aircr.write(
+            AIRCR_VECTKEY
+                | (aircr & AIRCR_PRESERVE)
+                | ((grouping as u32) << AIRCR_PRIGROUP_SHIFT),
+        )
+    };
+}
+
+pub fn priority_grouping(scb: &cortex_m::peripheral::SCB) -> PriorityGrouping {
+    match (scb.aircr.read() & AIRCR_PRIGROUP_MASK) >> AIRCR_PRIGROUP_SHIFT {
+        7 => PriorityGrouping::Preempt0Sub3,
+        6 => PriorityGrouping::Preempt1Sub2,
+        5 => PriorityGrouping::Preempt2Sub1,
+        // 0 to 4 leave all three implemented bits for preemption
+        _ => PriorityGrouping::Preempt3Sub0,
+    }
+}
+
+/// Run `f` with interrupts of priority `ceiling` and below masked (via BASEPRI),
+/// like an RTIC lock on a resource with this ceiling.
+///
+/// Interrupts of higher priority keep preempting `f`. As BASEPRI can't mask
+/// priority `P0`, a `P0` ceiling masks all interrupts (via PRIMASK).
+pub fn with_ceiling<R>(ceiling: Priority, f: impl FnOnce() -> R) -> R {
+    if ceiling == Priority::P0 {
+        return cortex_m::interrupt::free(|_| f());
+    }
+    let previous = cortex_m::register::basepri::read();
+    // only ever raises the masking priority
+    cortex_m::register::basepri_max::write(ceiling.to_bits());
+    let result = f();
+    unsafe { cortex_m::register::basepri::write(previous) };
+    result
+}
+
+/// A set of interrupts, for masking them together.
+///
+/// This is how RTIC implements locks on cores without BASEPRI; here it serves to
+/// mask the interrupts sharing a resource, independently of their priorities.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Masks([u32; 2]);
+
+impl Masks {
+    pub const fn new() -> Self {
+        Masks([0; 2])
+    }
+
+    pub const fn with(self, interrupt: Interrupt) -> Self {
+        let number = interrupt as usize;
+        let mut masks = self.0;
+        masks[number / 32] |= 1 << (number % 32);
+        Masks(masks)
+    }
+
+    pub const fn from_interrupts(interrupts: &[Interrupt]) -> Self {
+        let mut masks = Self::new();
+        let mut i = 0;
+        while i < interrupts.len() {
+            masks = masks.with(interrupts[i]);
+            i += 1;
+        }
+        masks
+    }
+
+    pub const fn contains(&self, interrupt: Interrupt) -> bool {
+        let number = interrupt as usize;
+        self.0[number / 32] & (1 << (number % 32)) != 0
+    }
+
+    /// One bit per interrupt number, as in the NVIC ISER/ICER registers
+    pub const fn bits(&self) -> [u32; 2] {
+        self.0
+    }
+}
+
+/// Run `f` with the interrupts in `masks` masked in the NVIC.
+///
+/// Afterwards, only those interrupts which were unmasked before are unmasked again.
+pub fn with_masked<R>(masks: Masks, f: impl FnOnce() -> R) -> R {
+    let nvic = unsafe { &*cortex_m::peripheral::NVIC::PTR };
+    let mut enabled = [0u32; 2];
+    cortex_m::interrupt::free(|_| {
+        for (i, enabled) in enabled.iter_mut().enumerate() {
+            *enabled = nvic.iser[i].read() & masks.0[i];
+            unsafe { nvic.icer[i].write(*enabled) };
+        }
+    });
+    cortex_m::asm::dsb();
+    cortex_m::asm::isb();
+    let result = f();
+    for (i, enabled) in enabled.iter().enumerate() {
+        unsafe { nvic.iser[i].write(*enabled) };
+    }
+    result
+}
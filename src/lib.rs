@@ -39,6 +39,28 @@ pub extern crate lpc55_pac as raw;
 
 pub mod prelude;
 
+pub mod error;
+pub use error::Error;
+
+pub mod interrupt;
+
+pub mod events;
+
+pub mod shared;
+
+#[cfg(feature = "instrumentation")]
+pub mod instrumentation;
+
+#[cfg(feature = "panic-persist")]
+pub mod persist;
+
+pub mod chip;
+
+pub mod power;
+
+pub mod board;
+pub use board::init;
+
 // #[macro_use]
 pub mod macros;
 
@@ -54,28 +76,39 @@ pub mod peripherals;
 pub use peripherals::{
     adc::Adc,
     anactrl::Anactrl,
-    casper::Casper,
+    crc::Crc,
     ctimer::Ctimers,
     dma::Dma,
     flash::Flash,
     flexcomm::Flexcomm,
     gpio::Gpio,
     gint::Gint,
-    hashcrypt::Hashcrypt,
     inputmux::InputMux,
     iocon::Iocon,
-    pint::Pint,
-    pfr::Pfr,
+    ostimer::OsTimer,
+    pint::{Pint, SecPint},
+    plu::Plu,
     pmc::Pmc,
-    prince::Prince,
-    puf::Puf,
     rng::Rng,
     rtc::Rtc,
+    secgpio::SecGpio,
     syscon::Syscon,
-    usbfs::Usbfs,
-    usbhs::Usbhs,
     utick::Utick,
 };
+#[cfg(feature = "_casper")]
+pub use peripherals::casper::Casper;
+#[cfg(feature = "_hashcrypt")]
+pub use peripherals::hashcrypt::Hashcrypt;
+#[cfg(feature = "_pfr")]
+pub use peripherals::pfr::Pfr;
+#[cfg(feature = "_prince")]
+pub use peripherals::prince::Prince;
+#[cfg(feature = "_puf")]
+pub use peripherals::puf::Puf;
+#[cfg(feature = "_usb")]
+pub use peripherals::usbfs::Usbfs;
+#[cfg(feature = "_usb")]
+pub use peripherals::usbhs::Usbhs;
 
 pub mod drivers;
 pub use drivers::{
@@ -85,8 +118,9 @@ pub use drivers::{
     SpiMaster,
     Pin,
     Pins,
-    UsbBus,
 };
+#[cfg(all(feature = "usb", feature = "_usb"))]
+pub use drivers::UsbBus;
 
 
 pub fn new() -> Peripherals {
@@ -126,8 +160,12 @@ pub struct Peripherals {
     pub anactrl: Anactrl,
 
     /// Cryptographic Accelerator and Signal Processing Engine with RAM sharing
+    #[cfg(feature = "_casper")]
     pub casper: Casper,
 
+    /// CRC engine
+    pub crc: Crc,
+
     /// Standard counter/timer (CTIMER)
     pub ctimer: Ctimers,
 
@@ -147,6 +185,7 @@ pub struct Peripherals {
     pub gpio: Gpio,
 
     /// SHA and AES Engine
+    #[cfg(feature = "_hashcrypt")]
     pub hashcrypt: Hashcrypt,
 
     /// Input multiplexer
@@ -155,16 +194,24 @@ pub struct Peripherals {
     /// I/O configuration
     pub iocon: Iocon,
 
+    /// OS event timer
+    pub ostimer: OsTimer,
+
     /// Pin Interrupt and Pattern Match
     pub pint: Pint,
 
+    /// Programmable Logic Unit
+    pub plu: Plu,
+
     /// Protect flash region controller
+    #[cfg(feature = "_pfr")]
     pub pfr: Pfr,
 
     /// Power configuration
     pub pmc: Pmc,
 
     // PRINCE
+    #[cfg(feature = "_prince")]
     pub prince: Prince,
 
     /// Random number generator
@@ -173,22 +220,27 @@ pub struct Peripherals {
     /// Real time clock
     pub rtc: Rtc,
 
+    /// Secure GPIO, port 0 for the secure world
+    pub secgpio: SecGpio,
+
+    /// Secure pin interrupts
+    pub secpint: SecPint,
+
     /// System configuration
     pub syscon: Syscon,
 
     /// USB full-speed device or, not implemented, host
+    #[cfg(feature = "_usb")]
     pub usbfs: Usbfs,
 
     /// USB high-speed device or, not implemented, host
+    #[cfg(feature = "_usb")]
     pub usbhs: Usbhs,
 
     /// Micro-Tick Timer
     pub utick: Utick,
 
 
-    /// CRC engine - not HAL-ified.
-    pub CRC_ENGINE: raw::CRC_ENGINE,
-
     pub FLASH_CMPA: raw::FLASH_CMPA,
     pub FLASH_CFPA0: raw::FLASH_CFPA0,
 
@@ -234,7 +286,9 @@ impl From<(raw::Peripherals, rtic::Peripherals)> for Peripherals {
             // HAL peripherals
             adc: Adc::from(p.ADC0),
             anactrl: Anactrl::from(p.ANACTRL),
+            #[cfg(feature = "_casper")]
             casper: Casper::from(p.CASPER),
+            crc: Crc::from(p.CRC_ENGINE),
             ctimer: (
                 peripherals::ctimer::Ctimer0::from(p.CTIMER0),
                 peripherals::ctimer::Ctimer1::from(p.CTIMER1),
@@ -257,23 +311,31 @@ impl From<(raw::Peripherals, rtic::Peripherals)> for Peripherals {
             ),
             gint: Gint::from((p.GINT0, p.GINT1)),
             gpio: Gpio::from(p.GPIO),
+            #[cfg(feature = "_hashcrypt")]
             hashcrypt: Hashcrypt::from(p.HASHCRYPT),
             inputmux: InputMux::from(p.INPUTMUX),
             iocon: Iocon::from(p.IOCON),
+            ostimer: OsTimer::from(p.OSTIMER),
             pint: Pint::from(p.PINT),
+            plu: Plu::from(p.PLU),
+            #[cfg(feature = "_pfr")]
             pfr: Pfr::new(),
             pmc: Pmc::from(p.PMC),
+            #[cfg(feature = "_prince")]
             prince: Prince::from(p.PRINCE),
             rng: Rng::from(p.RNG),
             rtc: Rtc::from(p.RTC),
+            secgpio: SecGpio::from(p.SECGPIO),
+            secpint: SecPint::from(p.SECPINT),
             syscon: Syscon::from(p.SYSCON),
+            #[cfg(feature = "_usb")]
             usbfs: Usbfs::from((p.USB0, p.USBFSH)),
+            #[cfg(feature = "_usb")]
             usbhs: Usbhs::from((p.USBPHY, p.USB1, p.USBHSH)),
             utick: Utick::from(p.UTICK0),
 
             // Raw peripherals
             AHB_SECURE_CTRL: p.AHB_SECURE_CTRL,
-            CRC_ENGINE: p.CRC_ENGINE,
             FLASH_CMPA: p.FLASH_CMPA,
             FLASH_CFPA0: p.FLASH_CFPA0,
             SAU: p.SAU,
@@ -298,7 +360,9 @@ impl From<(raw::Peripherals, raw::CorePeripherals)> for Peripherals {
             // HAL peripherals
             adc: Adc::from(p.ADC0),
             anactrl: Anactrl::from(p.ANACTRL),
+            #[cfg(feature = "_casper")]
             casper: Casper::from(p.CASPER),
+            crc: Crc::from(p.CRC_ENGINE),
 
             ctimer: (
                 peripherals::ctimer::Ctimer0::from(p.CTIMER0),
@@ -322,23 +386,31 @@ impl From<(raw::Peripherals, raw::CorePeripherals)> for Peripherals {
             ),
             gint: Gint::from((p.GINT0, p.GINT1)),
             gpio: Gpio::from(p.GPIO),
+            #[cfg(feature = "_hashcrypt")]
             hashcrypt: Hashcrypt::from(p.HASHCRYPT),
             inputmux: InputMux::from(p.INPUTMUX),
             iocon: Iocon::from(p.IOCON),
+            ostimer: OsTimer::from(p.OSTIMER),
             pint: Pint::from(p.PINT),
+            plu: Plu::from(p.PLU),
+            #[cfg(feature = "_pfr")]
             pfr: Pfr::new(),
             pmc: Pmc::from(p.PMC),
+            #[cfg(feature = "_prince")]
             prince: Prince::from(p.PRINCE),
             rng: Rng::from(p.RNG),
             rtc: Rtc::from(p.RTC),
+            secgpio: SecGpio::from(p.SECGPIO),
+            secpint: SecPint::from(p.SECPINT),
             syscon: Syscon::from(p.SYSCON),
+            #[cfg(feature = "_usb")]
             usbfs: Usbfs::from((p.USB0, p.USBFSH)),
+            #[cfg(feature = "_usb")]
             usbhs: Usbhs::from((p.USBPHY, p.USB1, p.USBHSH)),
             utick: Utick::from(p.UTICK0),
 
             // Raw peripherals
             AHB_SECURE_CTRL: p.AHB_SECURE_CTRL,
-            CRC_ENGINE: p.CRC_ENGINE,
             FLASH_CMPA: p.FLASH_CMPA,
             FLASH_CFPA0: p.FLASH_CFPA0,
             SAU: p.SAU,
@@ -377,6 +449,11 @@ impl Peripherals {
     //     )))
     // }
 
+    /// Take all peripherals, even if they are owned elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the other instances are not used concurrently.
     #[cfg(not(feature = "rtic-peripherals"))]
     pub unsafe fn steal() -> Self {
         Self::from((raw::Peripherals::steal(), raw::CorePeripherals::steal()))
@@ -384,8 +461,11 @@ impl Peripherals {
 
 }
 
+/// Start the DWT cycle counter, also without a debugger attached (DEMCR.TRCENA).
 pub fn enable_cycle_counter() {
-    unsafe { &mut raw::CorePeripherals::steal().DWT }.enable_cycle_counter();
+    let mut core = unsafe { raw::CorePeripherals::steal() };
+    core.DCB.enable_trace();
+    core.DWT.enable_cycle_counter();
 }
 
 pub fn get_cycle_count() -> u32 {
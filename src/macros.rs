@@ -30,6 +30,19 @@ macro_rules! reg_cluster {
     };
 }
 
+/// Define a set of bits of a register, to be owned by a `RegFieldProxy`
+#[macro_export]
+macro_rules! reg_field {
+    ($ty:ident, $reg:ty, $mask:expr) => {
+        pub struct $ty;
+
+        unsafe impl $crate::traits::reg_proxy::RegField for $ty {
+            type Reg = $reg;
+            const MASK: u32 = $mask;
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! wrap_always_on_peripheral {
     ($hal_name:ident, $pac_name:ident) => {
@@ -51,6 +64,14 @@ macro_rules! wrap_always_on_peripheral {
                 $hal_name { raw }
             }
 
+            /// Take the peripheral, even if it is owned elsewhere.
+            ///
+            /// Meant for fault handlers and panic paths, where the instance owned
+            /// by the application is out of reach.
+            ///
+            /// # Safety
+            ///
+            /// The caller must ensure the other instance is not used concurrently.
             pub unsafe fn steal() -> Self {
                 // seems a little wastefule to steal the full peripherals but ok..
                 Self::new(raw::Peripherals::steal().$pac_name)
@@ -59,6 +80,16 @@ macro_rules! wrap_always_on_peripheral {
             pub fn release(self) -> raw::$pac_name {
                 self.raw
             }
+
+            /// The raw PAC peripheral.
+            pub fn raw(&self) -> &raw::$pac_name {
+                &self.raw
+            }
+
+            /// Like `raw`, mutably.
+            pub fn raw_mut(&mut self) -> &mut raw::$pac_name {
+                &mut self.raw
+            }
         }
     };
 }
@@ -85,6 +116,14 @@ macro_rules! wrap_stateful_peripheral {
                 }
             }
 
+            /// Take the peripheral, even if it is owned elsewhere.
+            ///
+            /// Meant for fault handlers and panic paths, where the instance owned
+            /// by the application is out of reach.
+            ///
+            /// # Safety
+            ///
+            /// The caller must ensure the other instance is not used concurrently.
             pub unsafe fn steal() -> Self {
                 // seems a little wastefule to steal the full peripherals but ok..
                 Self::new(raw::Peripherals::steal().$pac_name)
@@ -95,6 +134,16 @@ macro_rules! wrap_stateful_peripheral {
             pub fn release(self) -> raw::$pac_name {
                 self.raw
             }
+
+            /// The raw PAC peripheral.
+            pub fn raw(&self) -> &raw::$pac_name {
+                &self.raw
+            }
+
+            /// Like `raw`, mutably.
+            pub fn raw_mut(&mut self) -> &mut raw::$pac_name {
+                &mut self.raw
+            }
         }
     };
 }
@@ -126,6 +175,35 @@ macro_rules! stateful_peripheral_enable_disable {
     }
 }
 
+/// Place functions in SRAM, from where they execute while the flash is busy.
+///
+/// The functions are put in a subsection of `.data`, which the `cortex-m-rt`
+/// startup code copies from flash to SRAM, so no changes to the linker script
+/// are needed. Code they call (unless inlined) still executes from flash.
+///
+/// ```ignore
+/// hal::ramfunc! {
+///     fn feed_watchdog() {
+///         // ...
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! ramfunc {
+    ($(#[$attr:meta])* $vis:vis fn $($rest:tt)*) => {
+        $(#[$attr])*
+        #[link_section = ".data.lpc55-hal.ramfunc"]
+        #[inline(never)]
+        $vis fn $($rest)*
+    };
+    ($(#[$attr:meta])* $vis:vis unsafe fn $($rest:tt)*) => {
+        $(#[$attr])*
+        #[link_section = ".data.lpc55-hal.ramfunc"]
+        #[inline(never)]
+        $vis unsafe fn $($rest)*
+    };
+}
+
 // #[macro_export]
 // macro_rules! reg_write {
 //     ($peripheral:ident, $register:ident, $field:ident, $value:expr) => {
@@ -203,3 +281,50 @@ macro_rules! reg_read {
 //         dbg!(reg_read_bits!($peripheral, $register, $field));
 //     };
 // }
+
+/// Bind interrupt vectors to `interrupt::Handler`s.
+///
+/// Generates the vector functions, and a unit struct implementing
+/// `interrupt::Binding` for each handler, see the `interrupt` module.
+#[macro_export]
+macro_rules! bind_interrupts {
+    ($vis:vis struct $name:ident { $($irq:ident => $($handler:ty),+;)* }) => {
+        #[derive(Copy, Clone)]
+        $vis struct $name;
+
+        $(
+            #[allow(non_snake_case)]
+            #[no_mangle]
+            unsafe extern "C" fn $irq() {
+                $(
+                    <$handler as $crate::interrupt::Handler>::on_interrupt();
+                )+
+            }
+
+            $(
+                const _: () = assert!(
+                    <$handler as $crate::interrupt::Handler>::INTERRUPT as u16
+                        == $crate::raw::Interrupt::$irq as u16,
+                    concat!("handler bound to the wrong interrupt: ", stringify!($irq)),
+                );
+
+                unsafe impl $crate::interrupt::Binding<$handler> for $name {}
+            )+
+        )*
+    };
+}
+
+/// Evaluates `$body`, recording its duration in `instrumentation::$counter`
+/// if feature `instrumentation` is enabled.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! instrumented {
+    ($counter:ident, $body:expr) => {{
+        #[cfg(feature = "instrumentation")]
+        let start = $crate::instrumentation::start();
+        let result = $body;
+        #[cfg(feature = "instrumentation")]
+        $crate::instrumentation::$counter.record(start);
+        result
+    }};
+}
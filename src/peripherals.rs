@@ -1,7 +1,9 @@
 //! HAL wrappers around raw PAC peripherals.
 //!
 //! The APIs presented only implement basic functionality.
-//! For more complex things, consult `hal::drivers`.
+//! For more complex things, consult `hal::drivers`. The raw PAC peripheral is
+//! available via `raw` and `raw_mut`, for registers the HAL does not model, at
+//! the risk of invalidating the HAL's assumptions.
 //!
 //! In an RTIC setup, RTIC owns Peripherals and CorePeripherals,
 //! so here use
@@ -21,24 +23,35 @@
 
 pub mod adc;
 pub mod anactrl;
+#[cfg(feature = "_casper")]
 pub mod casper;
+pub mod crc;
 pub mod ctimer;
 pub mod dma;
 pub mod flash;
 pub mod flexcomm;
 pub mod gint;
 pub mod gpio;
+#[cfg(feature = "_hashcrypt")]
 pub mod hashcrypt;
 pub mod inputmux;
 pub mod iocon;
+pub mod ostimer;
+#[cfg(feature = "_pfr")]
 pub mod pfr;
 pub mod pint;
+pub mod plu;
 pub mod pmc;
+#[cfg(feature = "_puf")]
 pub mod puf;
+#[cfg(feature = "_prince")]
 pub mod prince;
 pub mod rng;
 pub mod rtc;
+pub mod secgpio;
 pub mod syscon;
+#[cfg(feature = "_usb")]
 pub mod usbfs;
+#[cfg(feature = "_usb")]
 pub mod usbhs;
 pub mod utick;
@@ -5,29 +5,114 @@ use crate::{
         syscon::Syscon,
         pmc::Pmc,
     },
-    drivers::{
-        pins::Pin,
-    },
     typestates::{
         init_state,
-        pin::{
-            state,
-            gpio::{
-                direction,
-            },
-            PinId,
-        },
     }
 };
 
+/// Implemented by analog pins which are inputs of the ADC
+pub trait Channel {
+    /// The ADC channel, side A and B of a channel having the same number
+    const CHANNEL: u8;
+    /// The side of the channel the pin is connected to
+    const SIDE: Side = Side::A;
+}
+
+/// Side of an ADC channel, converted single-ended (CMDL.CTYPE)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Side {
+    A,
+    B,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Config {
     pub conversion_delay: u16,
+    pub reference: Reference,
+    pub conversion: Conversion,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config{
             conversion_delay: 0,
+            reference: Reference::Vdda { millivolts: 3300 },
+            conversion: Default::default(),
+        }
+    }
+}
+
+/// Voltage reference of the ADC (CFG.REFSEL), which is full scale.
+///
+/// The voltage is not measured, it is what the conversions to millivolts assume.
+/// For absolute accuracy, use a precise reference on the VREFH pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Reference {
+    /// External reference on the VREFH pin
+    Vrefh { millivolts: u16 },
+    /// The analog supply VDDA
+    Vdda { millivolts: u16 },
+}
+
+impl Reference {
+    pub fn millivolts(&self) -> u16 {
+        match *self {
+            Reference::Vrefh { millivolts } | Reference::Vdda { millivolts } => millivolts,
+        }
+    }
+}
+
+/// Resolution of conversions (CMDL.MODE), which sets the scale of the samples
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Resolution {
+    /// 12 bit single-ended conversions
+    Standard,
+    /// 16 bit single-ended conversions
+    High,
+}
+
+impl Resolution {
+    /// Largest sample value, corresponding to the reference voltage
+    pub fn full_scale(&self) -> u16 {
+        match self {
+            Resolution::Standard => 0xfff,
+            Resolution::High => 0xffff,
+        }
+    }
+
+    /// Position of the sample in the 16 bit result
+    fn shift(&self) -> u32 {
+        match self {
+            Resolution::Standard => 3,
+            Resolution::High => 0,
+        }
+    }
+}
+
+/// Options of the conversion commands (CMDLx/CMDHx).
+///
+/// The LPADC has no analog gain stage; the digital gain is the calibration
+/// (`GCRx`), and resolution and averaging determine scale and noise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Conversion {
+    pub resolution: Resolution,
+    /// Average 2^`averaging` samples (CMDH.AVGS, 0..=7)
+    pub averaging: u8,
+    /// Lengthen the sample phase to 3 + 2^`sample_time` ADC clocks (CMDH.STS, 0..=7),
+    /// for sources of higher impedance
+    pub sample_time: u8,
+}
+
+impl Default for Conversion {
+    fn default() -> Self {
+        Conversion {
+            resolution: Resolution::Standard,
+            averaging: 7,
+            sample_time: 0,
         }
     }
 }
@@ -59,6 +144,11 @@ impl Adc {
         }
     }
 
+    /// Take the peripheral, even if it is owned elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the other instance is not used concurrently.
     pub unsafe fn steal() -> Self {
         // seems a little wastefule to steal the full peripherals but ok..
         Self::new(raw::Peripherals::steal().ADC0)
@@ -69,16 +159,45 @@ impl<State> Adc<State> {
     pub fn release(self) -> raw::ADC0 {
         self.raw
     }
+
+    /// The raw PAC peripheral.
+    pub fn raw(&self) -> &raw::ADC0 {
+        &self.raw
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut raw::ADC0 {
+        &mut self.raw
+    }
 }
 
-impl<State> Deref for Adc<State> {
+impl Deref for Adc<init_state::Enabled> {
     type Target = raw::adc0::RegisterBlock;
     fn deref(&self) -> &Self::Target {
         &self.raw
     }
 }
 
-impl<State> Adc<State> {
+// results of FIFO A, for streaming into a `DmaRingBuffer<u32, _>`
+impl crate::peripherals::dma::RxRequest for Adc<init_state::Enabled> {
+    const RX_CHANNEL: u8 = 21;
+
+    fn rx_data_address(&self) -> u32 {
+        &self.raw.resfifo[0] as *const _ as u32
+    }
+
+    fn enable_rx_request(&mut self) {
+        // request on every result
+        self.raw.fctrl[0].modify(|_, w| unsafe { w.fwmark().bits(0) });
+        self.raw.de.modify(|_, w| w.fwmde0().set_bit());
+    }
+
+    fn disable_rx_request(&mut self) {
+        self.raw.de.modify(|_, w| w.fwmde0().clear_bit());
+    }
+}
+
+impl Adc<init_state::Enabled> {
     #[allow(dead_code)]
     fn autocal (&mut self,) {
         // Calibration + offset trimming
@@ -110,24 +229,28 @@ impl<State> Adc<State> {
         }
     }
 
-    pub fn arm_normal_channel(&mut self, channel_id: u8) {
+    pub fn arm_normal_channel(&mut self, channel_id: u8, side: Side) {
+        let conversion = self.config.conversion;
         self.raw.cmdl2.write(|w| unsafe {  w.adch().bits(channel_id)
-                                    .ctype().ctype_0()
-                                    .mode().mode_0()
+                                    .ctype().bits(side as u8)
+                                    .mode().bit(conversion.resolution == Resolution::High)
                                     } );
-        self.raw.cmdh2.write(|w| unsafe { w.avgs().avgs_7()
+        self.raw.cmdh2.write(|w| unsafe { w.avgs().bits(conversion.averaging & 0x7)
+                                    .sts().bits(conversion.sample_time & 0x7)
                                     .cmpen().bits(0b00)        // no compare
                                     .loop_().bits(0)
                                     .next().bits(0)
                                 } );
     }
 
-    pub fn arm_comparator_channel(&mut self, channel_id: u8) {
+    pub fn arm_comparator_channel(&mut self, channel_id: u8, side: Side) {
+        let conversion = self.config.conversion;
         self.raw.cmdl1.write(|w| unsafe {  w.adch().bits(channel_id)
-                                    .ctype().ctype_0()
-                                    .mode().mode_0()
+                                    .ctype().bits(side as u8)
+                                    .mode().bit(conversion.resolution == Resolution::High)
                                     } );
-        self.raw.cmdh1.write(|w| unsafe { w.avgs().avgs_7()      // average 2^3 samples
+        self.raw.cmdh1.write(|w| unsafe { w.avgs().bits(conversion.averaging & 0x7)
+                                    .sts().bits(conversion.sample_time & 0x7)
                                     .cmpen().bits(0b11)        // compare repeatedly until true
                                     .loop_().bits(0)         // no loop
                                     .next().bits(0)         // no next command
@@ -146,6 +269,40 @@ impl<State> Adc<State> {
         self.raw.ctrl.modify(|_,w| { w.rstfifo0().set_bit().rstfifo1().set_bit() })
     }
 
+    /// Change the options of subsequent conversions.
+    pub fn set_conversion(&mut self, conversion: Conversion) {
+        self.config.conversion = conversion;
+    }
+
+    pub fn reference(&self) -> Reference {
+        self.config.reference
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.config.conversion.resolution
+    }
+
+    /// The value of a sample as returned by `read`, scaled to the resolution
+    pub fn to_code(&self, sample: u16) -> u16 {
+        sample >> self.resolution().shift()
+    }
+
+    /// The voltage of a sample as returned by `read`, relative to the reference
+    pub fn to_millivolts(&self, sample: u16) -> u32 {
+        self.to_code(sample) as u32 * self.reference().millivolts() as u32
+            / self.resolution().full_scale() as u32
+    }
+
+    /// The voltage of a comparator threshold, as `set_threshold` expects it
+    pub fn threshold_for_millivolts(&self, millivolts: u32) -> u16 {
+        let code = (millivolts * self.resolution().full_scale() as u32
+            / self.reference().millivolts() as u32)
+            .min(self.resolution().full_scale() as u32);
+        (code << self.resolution().shift()) as u16
+    }
+}
+
+impl<State> Adc<State> {
     pub fn configure(mut self, config: Config) -> Adc<State> {
         self.config = config;
         self
@@ -187,7 +344,10 @@ impl<State> Adc<State> {
         self.raw.cfg.write(|w| unsafe {
             w.pwren().set_bit()
             .pudly().bits(0x80)
-            .refsel().refsel_1()
+            .refsel().variant(match self.config.reference {
+                Reference::Vrefh { .. } => raw::adc0::cfg::REFSEL_A::REFSEL_0,
+                Reference::Vdda { .. } => raw::adc0::cfg::REFSEL_A::REFSEL_1,
+            })
             .pwrsel().pwrsel_3()
             .tprictrl().bits(0)
             .tres().clear_bit() // Do not resume interrupted captures
@@ -217,11 +377,17 @@ impl<State> Adc<State> {
         // This breaks adc when it's not being run from debugger for some reason
         // self.autocal();
 
-        self.arm_comparator_channel(3);
-        self.arm_normal_channel(3);
+        let mut adc = Adc {
+            raw: self.raw,
+            _state: init_state::Enabled(()),
+            config: self.config,
+        };
+
+        adc.arm_comparator_channel(3, Side::A);
+        adc.arm_normal_channel(3, Side::A);
 
         // Main trigger
-        self.raw.tctrl[ChannelType::Comparator as usize].write(|w| unsafe {
+        adc.raw.tctrl[ChannelType::Comparator as usize].write(|w| unsafe {
             w.hten().set_bit()
             .fifo_sel_a().fifo_sel_a_0()
             .fifo_sel_b().fifo_sel_b_0()
@@ -230,7 +396,7 @@ impl<State> Adc<State> {
         });
 
         // Cancel/resync trigger to main trigger
-        self.raw.tctrl[ChannelType::Cancel as usize].write(|w| unsafe {
+        adc.raw.tctrl[ChannelType::Cancel as usize].write(|w| unsafe {
             w.hten().set_bit()
             .fifo_sel_a().fifo_sel_a_0()
             .fifo_sel_b().fifo_sel_b_0()
@@ -240,7 +406,7 @@ impl<State> Adc<State> {
         });
 
         // Normal measurement trigger
-        self.raw.tctrl[ChannelType::Normal as usize].write(|w| unsafe {
+        adc.raw.tctrl[ChannelType::Normal as usize].write(|w| unsafe {
             w.hten().set_bit()
             .fifo_sel_a().fifo_sel_a_0()
             .fifo_sel_b().fifo_sel_b_0()
@@ -249,11 +415,7 @@ impl<State> Adc<State> {
         });
 
 
-        Adc {
-            raw: self.raw,
-            _state: init_state::Enabled(()),
-            config: Default::default(),
-        }
+        adc
     }
 
     pub fn disabled(mut self, syscon: &mut Syscon) -> Adc<init_state::Disabled> {
@@ -264,7 +426,7 @@ impl<State> Adc<State> {
         Adc {
             raw: self.raw,
             _state: init_state::Disabled,
-            config: Default::default(),
+            config: self.config,
         }
     }
 }
@@ -274,15 +436,24 @@ impl<State> Adc<State> {
 type Result<T> = core::result::Result<T, Underflow>;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Underflow;
 
-impl<State> Adc <State>
+impl core::fmt::Display for Underflow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("ADC result FIFO empty")
+    }
+}
+
+impl core::error::Error for Underflow {}
+
+impl Adc<init_state::Enabled>
 {
     // type Error = Underflow;
 
     // Read normal sample
-    pub fn read(&mut self, pin: & Pin<impl PinId, state::Analog<direction::Input>>) -> Result<u16> {
-        self.arm_normal_channel(pin.state.channel);
+    pub fn read<C: Channel>(&mut self, _pin: &C) -> Result<u16> {
+        self.arm_normal_channel(C::CHANNEL, C::SIDE);
 
         self.raw.swtrig.write(|w| unsafe {w.bits(1<<(ChannelType::Normal as usize))});
         while self.raw.fctrl[0].read().fcount().bits() == 0 {
@@ -294,4 +465,38 @@ impl<State> Adc <State>
         let sample = (result & 0xffff) as u16;
         Ok(sample)
     }
+
+    /// Like `read`, converted to millivolts according to the reference.
+    pub fn read_millivolts<C: Channel>(&mut self, pin: &C) -> Result<u32> {
+        let sample = self.read(pin)?;
+        Ok(self.to_millivolts(sample))
+    }
+}
+
+#[cfg(feature = "async")]
+impl Adc<init_state::Enabled>
+{
+    /// Like `read`, awaiting the FIFO watermark interrupt instead of polling.
+    ///
+    /// Needs `drivers::asynch::AdcInterruptHandler` bound to the ADC0 interrupt.
+    pub async fn read_async<C: Channel>(&mut self, _pin: &C) -> Result<u16> {
+        use crate::drivers::asynch;
+
+        self.arm_normal_channel(C::CHANNEL, C::SIDE);
+
+        self.raw.swtrig.write(|w| unsafe {w.bits(1<<(ChannelType::Normal as usize))});
+        let adc = &self.raw;
+        // watermark is 0, so the interrupt fires on the first result
+        asynch::wait_for(
+            &asynch::ADC_WAKER,
+            || adc.fctrl[0].read().fcount().bits() != 0,
+            || adc.ie.modify(|_, w| w.fwmie0().set_bit()),
+        ).await;
+        let result = self.raw.resfifo[0].read().bits();
+        if  (result & 0x80000000) == 0 {
+            return Err(Underflow);
+        }
+        let sample = (result & 0xffff) as u16;
+        Ok(sample)
+    }
 }
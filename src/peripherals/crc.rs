@@ -0,0 +1,61 @@
+use crate::{
+    drivers::crc::{Checksum, Config},
+    raw,
+    peripherals::syscon,
+    typestates::init_state,
+};
+
+crate::wrap_stateful_peripheral!(Crc, CRC_ENGINE);
+
+impl<State> core::ops::Deref for Crc<State> {
+    type Target = raw::crc_engine::RegisterBlock;
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+impl<State> Crc<State> {
+    pub fn enabled(mut self, syscon: &mut syscon::Syscon) -> Crc<init_state::Enabled> {
+        syscon.enable_clock(&mut self.raw);
+        syscon.reset(&mut self.raw);
+
+        Crc {
+            raw: self.raw,
+            _state: init_state::Enabled(()),
+        }
+    }
+
+    pub fn disabled(mut self, syscon: &mut syscon::Syscon) -> Crc<init_state::Disabled> {
+        syscon.disable_clock(&mut self.raw);
+
+        Crc {
+            raw: self.raw,
+            _state: init_state::Disabled,
+        }
+    }
+
+}
+
+impl Crc<init_state::Enabled> {
+
+    /// CRC-32, as used by Ethernet, zlib, PNG, ...
+    pub fn crc32<'a>(&'a mut self) -> Checksum<'a> {
+        Checksum::new(self, Config::CRC32)
+    }
+
+    /// CRC-16/CCITT-FALSE (polynomial 0x1021, seed 0xFFFF)
+    pub fn crc16_ccitt<'a>(&'a mut self) -> Checksum<'a> {
+        Checksum::new(self, Config::CRC16_CCITT)
+    }
+
+    /// CRC-16/IBM, also known as CRC-16/ARC (polynomial 0x8005, reflected)
+    pub fn crc16_ibm<'a>(&'a mut self) -> Checksum<'a> {
+        Checksum::new(self, Config::CRC16_IBM)
+    }
+
+    /// Any other combination of polynomial, seed, reflection and complement
+    pub fn custom<'a>(&'a mut self, config: Config) -> Checksum<'a> {
+        Checksum::new(self, config)
+    }
+
+}
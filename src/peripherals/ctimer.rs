@@ -2,7 +2,7 @@ use core::ops::Deref;
 use crate::{
     raw,
     peripherals::{
-        syscon::Syscon,
+        syscon::{ClockControl, Syscon},
     },
     typestates::{
         init_state,
@@ -19,7 +19,7 @@ pub type Ctimers = (
     Ctimer4,
 );
 
-pub trait Ctimer<State>: Deref<Target = raw::ctimer0::RegisterBlock> {}
+pub trait Ctimer<State>: Deref<Target = raw::ctimer0::RegisterBlock> + ClockControl {}
 
 macro_rules! ctimer {
     ($c_hal:ident, $c_pac:ident, $register:ident, $clock_input:ident) => {
@@ -34,6 +34,20 @@ macro_rules! ctimer {
     }
     impl Ctimer<init_state::Enabled> for $c_hal<init_state::Enabled> {}
 
+    impl<State> ClockControl for $c_hal<State> {
+        fn enable_clock(&self, s: &mut Syscon) {
+            self.raw.enable_clock(s)
+        }
+
+        fn disable_clock(&self, s: &mut Syscon) {
+            self.raw.disable_clock(s)
+        }
+
+        fn is_clock_enabled(&self, s: &Syscon) -> bool {
+            self.raw.is_clock_enabled(s)
+        }
+    }
+
 
     impl<State> $c_hal<State> {
         pub fn enabled(mut self, syscon: &mut Syscon, _token: ClocksSupport1MhzFroToken) -> $c_hal <init_state::Enabled> {
@@ -1,3 +1,6 @@
+use core::mem::ManuallyDrop;
+use core::ptr;
+
 use crate::{
     raw,
     peripherals::{
@@ -5,6 +8,7 @@ use crate::{
         adc::Adc,
         ctimer::Ctimer,
     },
+    drivers::crc::Checksum,
     typestates::{
         init_state,
     }
@@ -50,6 +54,288 @@ static mut DESCRIPTORS: Align512 = Align512(
     Empty!(),Empty!(),Empty!(),Empty!(),
 );
 
+// Reload descriptors, for CRC transfers of more than 1024 items
+const EMPTY: Descriptor = Empty!();
+static mut CRC_DESCRIPTORS: [Descriptor; 15] = [EMPTY; 15];
+
+/// Channel used to feed the CRC engine (it has no peripheral request)
+const CRC_CHANNEL: u8 = 20;
+
+/// Maximum number of transfers per descriptor
+const MAX_TRANSFERS: usize = 1024;
+
+/// Number of DMA0 channels
+const CHANNELS: usize = 23;
+
+// Reload descriptors of ring buffers, pointing to themselves
+static mut RING_DESCRIPTORS: [Descriptor; CHANNELS] = [EMPTY; CHANNELS];
+
+/// Peripheral which requests DMA transfers of received data
+pub trait RxRequest {
+    /// The channel the peripheral's request is wired to
+    const RX_CHANNEL: u8;
+
+    /// Address of the register holding received data
+    fn rx_data_address(&self) -> u32;
+
+    fn enable_rx_request(&mut self);
+
+    fn disable_rx_request(&mut self);
+}
+
+/// Peripheral which requests DMA transfers of data to transmit
+pub trait TxRequest {
+    /// The channel the peripheral's request is wired to
+    const TX_CHANNEL: u8;
+
+    /// Address of the register taking data to transmit
+    fn tx_data_address(&self) -> u32;
+
+    fn enable_tx_request(&mut self);
+
+    fn disable_tx_request(&mut self);
+}
+
+/// Unit of a DMA transfer
+pub trait Word: Copy + 'static {
+    /// XFERCFG.WIDTH
+    #[doc(hidden)]
+    const WIDTH: u32;
+}
+
+impl Word for u8 {
+    const WIDTH: u32 = 0;
+}
+
+impl Word for u16 {
+    const WIDTH: u32 = 1;
+}
+
+impl Word for u32 {
+    const WIDTH: u32 = 2;
+}
+
+/// Data was lost, as the DMA wrapped around the ring buffer before it was read
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Overrun;
+
+impl core::fmt::Display for Overrun {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("DMA ring buffer overrun")
+    }
+}
+
+impl core::error::Error for Overrun {}
+
+fn channel(number: u8) -> &'static raw::dma0::CHANNEL {
+    // channels are 0x10 apart
+    unsafe { &*(ptr::addr_of!((*raw::DMA0::ptr()).channel0) as *const u8)
+        .add(0x10 * number as usize)
+        .cast::<raw::dma0::CHANNEL>() }
+}
+
+/// Received data, continuously written into a buffer by the DMA.
+///
+/// The DMA wraps around at the end of the buffer, software keeps up by reading
+/// the data in between. `read` must be called at least once per wrap-around
+/// (e.g. on reaching a watermark), otherwise the overrun goes undetected.
+///
+/// ```ignore
+/// static mut BUFFER: [u8; 256] = [0; 256];
+///
+/// let (tx, rx) = serial.split();
+/// let mut rx = dma.ring_buffer(rx, unsafe { &mut *addr_of_mut!(BUFFER) });
+/// rx.set_watermark(64);
+/// loop {
+///     if rx.watermark_reached()? {
+///         let n = rx.read(&mut line)?;
+///         ...
+///     }
+/// }
+/// ```
+pub struct DmaRingBuffer<W: Word, P: RxRequest> {
+    source: P,
+    buffer: &'static mut [W],
+    /// total words read, and total wrap-arounds seen
+    read: u64,
+    laps: u64,
+    watermark: usize,
+}
+
+impl<W: Word, P: RxRequest> DmaRingBuffer<W, P> {
+    const BIT: u32 = 1 << P::RX_CHANNEL;
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The peripheral the data is received from
+    pub fn source(&self) -> &P {
+        &self.source
+    }
+
+    /// Total words the DMA has written so far
+    fn written(&mut self) -> u64 {
+        let dma = unsafe { &*raw::DMA0::ptr() };
+        let len = self.buffer.len() as u64;
+        // the flag must not change while reading the position
+        let (wrapped, position) = loop {
+            let before = dma.inta0.read().bits() & Self::BIT != 0;
+            let remaining = (channel(P::RX_CHANNEL).xfercfg.read().xfercount().bits() as u64 + 1) % 1024;
+            let after = dma.inta0.read().bits() & Self::BIT != 0;
+            if before == after {
+                break (after, (len - remaining) % len);
+            }
+        };
+        if wrapped {
+            dma.inta0.write(|w| unsafe { w.bits(Self::BIT) });
+            self.laps += 1;
+        }
+        self.laps * len + position
+    }
+
+    /// Number of words received and not yet read.
+    ///
+    /// On overrun, the received data is discarded.
+    pub fn available(&mut self) -> Result<usize, Overrun> {
+        let written = self.written();
+        let available = written - self.read;
+        if available > self.buffer.len() as u64 {
+            self.read = written;
+            return Err(Overrun);
+        }
+        Ok(available as usize)
+    }
+
+    /// Move received words into `buffer`, returning how many.
+    pub fn read(&mut self, buffer: &mut [W]) -> Result<usize, Overrun> {
+        let n = self.available()?.min(buffer.len());
+        let len = self.buffer.len();
+        let start = (self.read % len as u64) as usize;
+        for (i, word) in buffer[..n].iter_mut().enumerate() {
+            *word = unsafe { ptr::read_volatile(&self.buffer[(start + i) % len]) };
+        }
+        self.read += n as u64;
+        // the words read must not have been overwritten meanwhile
+        self.available()?;
+        Ok(n)
+    }
+
+    /// Number of available words at which `watermark_reached` is true.
+    pub fn set_watermark(&mut self, watermark: usize) {
+        self.watermark = watermark;
+    }
+
+    pub fn watermark_reached(&mut self) -> Result<bool, Overrun> {
+        Ok(self.available()? >= self.watermark)
+    }
+
+    /// Stop the DMA, returning the source and the buffer.
+    pub fn stop<State>(mut self, dma: &mut Dma<State>) -> (P, &'static mut [W]) {
+        self.source.disable_rx_request();
+        dma.raw.enableclr0.write(|w| unsafe { w.bits(Self::BIT) });
+        while dma.raw.busy0.read().bits() & Self::BIT != 0 {}
+        dma.raw.inta0.write(|w| unsafe { w.bits(Self::BIT) });
+        (self.source, self.buffer)
+    }
+}
+
+/// One-shot transfer between a buffer and a peripheral, started by `Dma::transmit`
+/// or `Dma::receive`.
+///
+/// Completion is polled with `is_done`, or signalled by the DMA interrupt if
+/// requested (e.g. as `events::Event::DmaComplete`); `wait` returns the buffer
+/// and the peripheral. Dropping the transfer instead aborts it, disabling the
+/// channel.
+///
+/// ```ignore
+/// static LOG: [u8; 512] = ...;
+///
+/// let transfer = dma.transmit(tx, &LOG, false);
+/// // ... do something else meanwhile
+/// let (_, tx) = transfer.wait();
+/// ```
+pub struct Transfer<B, P> {
+    buffer: B,
+    peripheral: P,
+    channel: u8,
+}
+
+impl<B, P> Transfer<B, P> {
+    pub fn is_done(&self) -> bool {
+        channel_done(self.channel)
+    }
+
+    /// Spin until the transfer is done, and acknowledge its interrupt flag,
+    /// returning the buffer and the peripheral.
+    fn finish(self) -> (B, P) {
+        while !self.is_done() {}
+        let dma = unsafe { &*raw::DMA0::ptr() };
+        dma.inta0.write(|w| unsafe { w.bits(1 << self.channel) });
+        let this = ManuallyDrop::new(self);
+        unsafe { (ptr::read(&this.buffer), ptr::read(&this.peripheral)) }
+    }
+}
+
+impl<B, P> Drop for Transfer<B, P> {
+    fn drop(&mut self) {
+        if self.is_done() {
+            return;
+        }
+        let dma = unsafe { &*raw::DMA0::ptr() };
+        let bit = 1 << self.channel;
+        dma.enableclr0.write(|w| unsafe { w.bits(bit) });
+        while dma.busy0.read().bits() & bit != 0 {}
+        dma.inta0.write(|w| unsafe { w.bits(bit) });
+    }
+}
+
+impl<W: Word, P: TxRequest> Transfer<&'static [W], P> {
+    /// Wait for the transfer to complete (the peripheral may still be sending the last words).
+    pub fn wait(self) -> (&'static [W], P) {
+        let (buffer, mut peripheral) = self.finish();
+        peripheral.disable_tx_request();
+        (buffer, peripheral)
+    }
+}
+
+impl<'a, 'c> Transfer<&'static [u8], &'a mut Checksum<'c>> {
+    /// Wait for the data to be fed into the CRC engine, after which `sum` is valid.
+    pub fn wait(self) -> (&'static [u8], &'a mut Checksum<'c>) {
+        self.finish()
+    }
+}
+
+impl<W: Word, P: RxRequest> Transfer<&'static mut [W], P> {
+    /// Wait for the buffer to be filled.
+    pub fn wait(self) -> (&'static mut [W], P) {
+        let (buffer, mut peripheral) = self.finish();
+        peripheral.disable_rx_request();
+        (buffer, peripheral)
+    }
+}
+
+fn channel_done(number: u8) -> bool {
+    let dma = unsafe { &*raw::DMA0::ptr() };
+    let bit = 1u32 << number;
+    (dma.active0.read().bits() | dma.busy0.read().bits()) & bit == 0
+}
+
+// XFERCFG of a single transfer of `count` words, see `configure_adc` for the meaning of the bits
+fn single_xfercfg<W: Word>(count: usize, increments: u32) -> u32 {
+    1                                       // cfgvalid
+    | (1 << 4)                              // setinta
+    | (W::WIDTH << 8)
+    | increments
+    | (((count - 1) as u32) << 16)
+}
+
+/// *src++, dst = FIFO
+const TO_PERIPHERAL: u32 = 1 << 12;
+/// src = FIFO, *dst++
+const FROM_PERIPHERAL: u32 = 1 << 14;
+
 crate::wrap_stateful_peripheral!(Dma, DMA0);
 
 impl<State> Dma<State> {
@@ -82,6 +368,8 @@ impl<State> Dma<State> {
     /// Configures DMA to write any new results from ADC FIFO 0
     /// to a user supplied array in circular fashion.  Runs continuously.
     /// Timer is reset at the end of each ADC DMA transaction
+    ///
+    /// For streaming without the timer, see `ring_buffer`.
     pub fn configure_adc(&mut self, adc: &mut Adc<init_state::Enabled>, timer: &mut impl Ctimer<init_state::Enabled>, recv_buf: &mut [u32]) {
         assert!(recv_buf.len() < 0x3FF);
 
@@ -192,4 +480,266 @@ impl<State> Dma<State> {
         // trigger
         self.raw.channel21.xfercfg.modify(|_,w| { w.swtrig().set_bit() });
     }
+
+    /// Stops the channels set up by `configure_adc`, so they can be reused.
+    pub fn release_adc(&mut self, adc: &mut Adc<init_state::Enabled>) {
+        adc.de.write(|w| w.fwmde0().clear_bit());
+        self.raw.enableclr0.write(|w| unsafe { w.bits((1 << 21) | (1 << 22)) });
+    }
+
+    /// Configures DMA to feed `data` into the CRC engine, using channel 20, and starts the transfer.
+    ///
+    /// The CPU is free while the checksum is being computed. The returned transfer
+    /// holds on to `crc` until it is done, which is also signaled by the DMA0 interrupt
+    /// if `interrupt` is set.
+    ///
+    /// Word-aligned data with a length that is a multiple of 4 is transferred in 32-bit words
+    /// (up to 64KB), anything else byte per byte (up to 16KB).
+    pub fn configure_crc<'a, 'c>(
+        &mut self,
+        crc: &'a mut Checksum<'c>,
+        data: &'static [u8],
+        interrupt: bool,
+    ) -> Transfer<&'static [u8], &'a mut Checksum<'c>> {
+        // `usize::is_multiple_of` needs Rust 1.87
+        #[allow(clippy::manual_is_multiple_of)]
+        let (width, step) = if (data.as_ptr() as usize) % 4 == 0 && data.len() % 4 == 0 {
+            (2, 4)
+        } else {
+            (0, 1)
+        };
+        let transfers = data.len() / step;
+        assert!(transfers > 0);
+        assert!(transfers <= MAX_TRANSFERS * (1 + 15));
+
+        let bit = 1u32 << CRC_CHANNEL;
+
+        // in case a previous transfer is still going on
+        self.raw.enableclr0.write(|w| unsafe { w.bits(bit) });
+        while self.raw.busy0.read().bits() & bit != 0 {}
+        self.raw.inta0.write(|w| unsafe { w.bits(bit) });
+
+        self.raw.channel20.cfg.write(|w| unsafe{
+            w
+            .periphreqen().clear_bit()      // memory to memory
+            .hwtrigen().clear_bit()         // Will software trigger
+            .trigburst().clear_bit()        // No need to burst
+            .chpriority().bits(7)           // 0 highest, 7 lowest
+        });
+
+        // XFERCFG of the n-th chunk, see `configure_adc` for the meaning of the bits
+        let xfercfg = |count: usize, last: bool| -> u32 {
+            1                               // cfgvalid
+            | ((!last as u32) << 1)         // reload next descriptor
+            | (((last && interrupt) as u32) << 4) // setinta
+            | (width << 8)
+            | (1 << 12)                     // *src++, dst = WR_DATA
+            | (((count - 1) as u32) << 16)
+        };
+
+        // WR_DATA, at offset 0x08
+        let dest_end_addr = (raw::CRC_ENGINE::ptr() as u32) + 0x08;
+        let chunks = transfers.div_ceil(MAX_TRANSFERS);
+
+        unsafe {
+            for i in 0..chunks {
+                let count = core::cmp::min(MAX_TRANSFERS, transfers - i * MAX_TRANSFERS);
+                let last = i == chunks - 1;
+                let source_end_addr = (data.as_ptr() as u32) + ((i * MAX_TRANSFERS + count - 1) * step) as u32;
+
+                // first descriptor lives in the channel's slot of the descriptor table
+                let descriptor: *mut Descriptor = if i == 0 {
+                    core::ptr::addr_of_mut!(DESCRIPTORS.20)
+                } else {
+                    core::ptr::addr_of_mut!(CRC_DESCRIPTORS[i - 1])
+                };
+                (*descriptor).transfer_config = xfercfg(count, last);
+                (*descriptor).source_end_addr = source_end_addr;
+                (*descriptor).dest_end_addr = dest_end_addr;
+                (*descriptor).next = if last {
+                    0
+                } else {
+                    core::ptr::addr_of!(CRC_DESCRIPTORS[i]) as u32
+                };
+            }
+
+            if interrupt {
+                self.raw.intenset0.write(|w| w.bits(bit));
+            } else {
+                self.raw.intenclr0.write(|w| w.bits(bit));
+            }
+
+            // enable the channel
+            self.raw.enableset0.write(|w| w.bits(bit));
+
+            // load the first chunk, and trigger
+            self.raw.channel20.xfercfg.write(|w| w.bits(xfercfg(core::cmp::min(MAX_TRANSFERS, transfers), chunks == 1) | (1 << 2)));
+        }
+
+        Transfer { buffer: data, peripheral: crc, channel: CRC_CHANNEL }
+    }
+
+    /// Start streaming received data of `source` into `buffer` (at most 1024 words).
+    ///
+    /// Uses the channel of the source's DMA request.
+    pub fn ring_buffer<W: Word, P: RxRequest>(&mut self, mut source: P, buffer: &'static mut [W]) -> DmaRingBuffer<W, P> {
+        let len = buffer.len();
+        assert!(len > 0 && len <= MAX_TRANSFERS);
+        let number = P::RX_CHANNEL as usize;
+        let bit = 1u32 << number;
+
+        self.raw.enableclr0.write(|w| unsafe { w.bits(bit) });
+        while self.raw.busy0.read().bits() & bit != 0 {}
+        self.raw.inta0.write(|w| unsafe { w.bits(bit) });
+
+        channel(P::RX_CHANNEL).cfg.write(|w| unsafe{
+            w
+            .periphreqen().set_bit()        // paced by the peripheral
+            .hwtrigen().clear_bit()
+            .chpriority().bits(1)           // 0 highest, 7 lowest
+        });
+
+        // see `configure_adc` for the meaning of the bits
+        let xfercfg = 1                     // cfgvalid
+            | (1 << 1)                      // reload the (same) descriptor
+            | (1 << 4)                      // setinta, counting wrap-arounds
+            | (W::WIDTH << 8)
+            | (1 << 14)                     // src = FIFO, *dst++
+            | (((len - 1) as u32) << 16);
+
+        unsafe {
+            let reload = ptr::addr_of_mut!(RING_DESCRIPTORS[number]);
+            (*reload).transfer_config = xfercfg;
+            (*reload).source_end_addr = source.rx_data_address();
+            (*reload).dest_end_addr = buffer.as_mut_ptr().add(len - 1) as u32;
+            (*reload).next = reload as u32;
+
+            let descriptor = (ptr::addr_of_mut!(DESCRIPTORS) as *mut Descriptor).add(number);
+            (*descriptor).transfer_config = 0;
+            (*descriptor).source_end_addr = (*reload).source_end_addr;
+            (*descriptor).dest_end_addr = (*reload).dest_end_addr;
+            (*descriptor).next = reload as u32;
+
+            self.raw.intenclr0.write(|w| w.bits(bit));
+            self.raw.enableset0.write(|w| w.bits(bit));
+            channel(P::RX_CHANNEL).xfercfg.write(|w| w.bits(xfercfg | (1 << 2)));
+        }
+        source.enable_rx_request();
+
+        DmaRingBuffer {
+            source,
+            buffer,
+            read: 0,
+            laps: 0,
+            watermark: 1,
+        }
+    }
+
+    /// Start a single transfer on channel `number`, paced by the peripheral's request.
+    fn start_paced(&mut self, number: u8, xfercfg: u32, source_end_addr: u32, dest_end_addr: u32, interrupt: bool) {
+        let bit = 1u32 << number;
+
+        self.raw.enableclr0.write(|w| unsafe { w.bits(bit) });
+        while self.raw.busy0.read().bits() & bit != 0 {}
+        self.raw.inta0.write(|w| unsafe { w.bits(bit) });
+
+        channel(number).cfg.write(|w| unsafe{
+            w
+            .periphreqen().set_bit()        // paced by the peripheral
+            .hwtrigen().clear_bit()
+            .chpriority().bits(1)           // 0 highest, 7 lowest
+        });
+
+        unsafe {
+            let descriptor = (ptr::addr_of_mut!(DESCRIPTORS) as *mut Descriptor).add(number as usize);
+            (*descriptor).transfer_config = 0;
+            (*descriptor).source_end_addr = source_end_addr;
+            (*descriptor).dest_end_addr = dest_end_addr;
+            (*descriptor).next = 0;
+
+            if interrupt {
+                self.raw.intenset0.write(|w| w.bits(bit));
+            } else {
+                self.raw.intenclr0.write(|w| w.bits(bit));
+            }
+            self.raw.enableset0.write(|w| w.bits(bit));
+            channel(number).xfercfg.write(|w| w.bits(xfercfg | (1 << 2)));
+        }
+    }
+
+    fn start_transmit<W: Word, P: TxRequest>(&mut self, destination: &mut P, words: &[W], interrupt: bool) {
+        assert!(!words.is_empty() && words.len() <= MAX_TRANSFERS);
+        let source_end_addr = unsafe { words.as_ptr().add(words.len() - 1) } as u32;
+        self.start_paced(
+            P::TX_CHANNEL,
+            single_xfercfg::<W>(words.len(), TO_PERIPHERAL),
+            source_end_addr,
+            destination.tx_data_address(),
+            interrupt,
+        );
+        destination.enable_tx_request();
+    }
+
+    fn start_receive<W: Word, P: RxRequest>(&mut self, source: &mut P, words: &mut [W], interrupt: bool) {
+        assert!(!words.is_empty() && words.len() <= MAX_TRANSFERS);
+        let dest_end_addr = unsafe { words.as_mut_ptr().add(words.len() - 1) } as u32;
+        self.start_paced(
+            P::RX_CHANNEL,
+            single_xfercfg::<W>(words.len(), FROM_PERIPHERAL),
+            source.rx_data_address(),
+            dest_end_addr,
+            interrupt,
+        );
+        source.enable_rx_request();
+    }
+
+    /// Start transmitting `buffer` (at most 1024 words) to `destination`, raising
+    /// the DMA interrupt on completion if `interrupt` is set.
+    ///
+    /// Uses the channel of the destination's DMA request.
+    pub fn transmit<W: Word, P: TxRequest>(&mut self, mut destination: P, buffer: &'static [W], interrupt: bool) -> Transfer<&'static [W], P> {
+        self.start_transmit(&mut destination, buffer, interrupt);
+        Transfer { buffer, peripheral: destination, channel: P::TX_CHANNEL }
+    }
+
+    /// Start filling `buffer` (at most 1024 words) from `source`, raising the DMA
+    /// interrupt on completion if `interrupt` is set.
+    ///
+    /// Uses the channel of the source's DMA request.
+    pub fn receive<W: Word, P: RxRequest>(&mut self, mut source: P, buffer: &'static mut [W], interrupt: bool) -> Transfer<&'static mut [W], P> {
+        self.start_receive(&mut source, buffer, interrupt);
+        Transfer { buffer, peripheral: source, channel: P::RX_CHANNEL }
+    }
+
+    /// Transmit all of `buffer` to `destination`, waiting for the DMA to complete.
+    pub fn write_all<W: Word, P: TxRequest>(&mut self, destination: &mut P, buffer: &[W]) {
+        for chunk in buffer.chunks(MAX_TRANSFERS) {
+            self.start_transmit(destination, chunk, false);
+            while !channel_done(P::TX_CHANNEL) {}
+        }
+        destination.disable_tx_request();
+    }
+
+    /// Fill `buffer` from `source`, waiting for the DMA to complete.
+    pub fn read_exact<W: Word, P: RxRequest>(&mut self, source: &mut P, buffer: &mut [W]) {
+        for chunk in buffer.chunks_mut(MAX_TRANSFERS) {
+            self.start_receive(source, chunk, false);
+            while !channel_done(P::RX_CHANNEL) {}
+        }
+        source.disable_rx_request();
+    }
+
+    /// Transmit `buffer` to `peripheral` while overwriting it with the words received
+    /// in return (full duplex, e.g. SPI), waiting for the DMA to complete.
+    pub fn transfer_in_place<W: Word, P: TxRequest + RxRequest>(&mut self, peripheral: &mut P, buffer: &mut [W]) {
+        for chunk in buffer.chunks_mut(MAX_TRANSFERS) {
+            // each word is sent before its reply overwrites it
+            self.start_receive(peripheral, chunk, false);
+            self.start_transmit(peripheral, chunk, false);
+            while !channel_done(P::RX_CHANNEL) {}
+        }
+        peripheral.disable_tx_request();
+        peripheral.disable_rx_request();
+    }
+
 }
@@ -1,7 +1,43 @@
+//! API for the Flexcomm interfaces
+//!
+//! A Flexcomm is enabled as one of its protocol personalities (USART, SPI, I2C
+//! or I2S), which locks the selection in PSELID until the Flexcomm is reset.
+//! Each `enabled_as_*` resets the Flexcomm first, so a personality can be torn
+//! down and the Flexcomm enabled as another at runtime, e.g. for a connector
+//! which is either a UART console or an SPI flash programming port:
+//!
+//! ```ignore
+//! // the drivers return the (disabled) Flexcomm and their pins
+//! let (fc, (sck, mosi, miso, cs)) = spi.teardown(&mut syscon);
+//! let (tx, rx) = (miso.into_unused_pin(&mut iocon), mosi.into_unused_pin(&mut iocon));
+//! let usart = fc.enabled_as_usart(&mut syscon, &clocks.support_flexcomm_token().unwrap());
+//! let serial = Serial::new(usart, (tx.into_usart2_tx_pin(&mut iocon), ...), config);
+//! ```
+//!
+//! Without going through a disabled Flexcomm, `free` stops a personality (letting
+//! it finish transmitting, and flushing its FIFOs) and returns the Flexcomm still
+//! clocked, to be used as another personality with `into_*`:
+//!
+//! ```ignore
+//! let (usart, pins) = debug_console.release();
+//! let spi = usart.free().into_spi(&mut syscon);
+//! ```
+//!
+//! The function clock is the 12 MHz FRO, unless another `ClockSource` is chosen
+//! with `enabled_as_*_clocked`; the drivers derive their bit rates from it:
+//!
+//! ```ignore
+//! let spi = fc.enabled_as_spi_clocked(&mut syscon, &token, ClockSource::Fro32Mhz);
+//! ```
+//!
+//! The interrupt of each Flexcomm can be shared by the interrupt-driven drivers
+//! through `dispatch`.
+
 use core::ops::Deref;
 
 use crate::{
-    raw,
+    raw::{self, flexcomm0::pselid::PERSEL_A},
+    time::Hertz,
     typestates::{
         init_state,
         ClocksSupportFlexcommToken,
@@ -14,9 +50,187 @@ use crate::{
             },
         },
     },
-    peripherals::syscon,
+    peripherals::{
+        dma,
+        syscon::{self, ClockControl},
+    },
 };
 
+pub mod fifo;
+pub use fifo::{Fifo, FifoConfig};
+
+pub mod dispatch;
+pub use dispatch::DispatchHandler;
+
+
+/// Protocol personality of a Flexcomm (PSELID.PERSEL)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Personality {
+    Usart,
+    Spi,
+    I2c,
+    I2s,
+}
+
+impl Personality {
+    fn from_pselid(pselid: &raw::flexcomm0::pselid::R) -> Option<Self> {
+        use raw::flexcomm0::pselid::PERSEL_A;
+        match pselid.persel().variant() {
+            Some(PERSEL_A::USART) => Some(Personality::Usart),
+            Some(PERSEL_A::SPI) => Some(Personality::Spi),
+            Some(PERSEL_A::I2C) => Some(Personality::I2c),
+            Some(PERSEL_A::I2S_TRANSMIT) | Some(PERSEL_A::I2S_RECEIVE) => Some(Personality::I2s),
+            _ => None,
+        }
+    }
+}
+
+/// The function clock of USART and I2C must not be faster
+const MAX_FREQUENCY_USART_I2C: Hertz = Hertz::from_raw(44_000_000);
+/// The function clock of SPI and I2S must not be faster
+const MAX_FREQUENCY_SPI_I2S: Hertz = Hertz::from_raw(48_000_000);
+
+/// Function clock of a Flexcomm (FCCLKSEL), from which the drivers derive
+/// their bit rates.
+///
+/// The fractional rate generator (FRG) following the selection is left at
+/// divide by one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockSource {
+    /// The 12 MHz FRO, the default
+    Fro12Mhz,
+    /// The 1 MHz FRO
+    Fro1Mhz,
+    /// The 96 MHz FRO divided by three, in FROHFDIV (shared by all Flexcomms)
+    Fro32Mhz,
+    /// The 96 MHz FRO, FROHFDIV dividing by one. Only the high-speed SPI is
+    /// fast enough to use it.
+    ///
+    /// FROHFDIV can't divide by one and three at once, so enabling a Flexcomm
+    /// with `Fro96Mhz` while another uses `Fro32Mhz`, or vice versa, panics.
+    Fro96Mhz,
+    /// The 32 kHz oscillator (FRO32K, or XTAL32K as selected in PMC RTCOSC32K),
+    /// which keeps running in deep-sleep. It must be powered, e.g. with
+    /// `Clocks::enable_32k_fro`. A USART uses its 32 kHz mode with it.
+    Osc32k,
+}
+
+impl ClockSource {
+    pub fn frequency(self) -> Hertz {
+        match self {
+            ClockSource::Fro12Mhz => Hertz::from_raw(12_000_000),
+            ClockSource::Fro1Mhz => Hertz::from_raw(1_000_000),
+            ClockSource::Fro32Mhz => Hertz::from_raw(32_000_000),
+            ClockSource::Fro96Mhz => Hertz::from_raw(96_000_000),
+            ClockSource::Osc32k => Hertz::from_raw(32_768),
+        }
+    }
+
+    fn sel(self) -> u8 {
+        match self {
+            ClockSource::Fro12Mhz => 2,
+            ClockSource::Fro1Mhz => 4,
+            ClockSource::Fro32Mhz | ClockSource::Fro96Mhz => 3,
+            ClockSource::Osc32k => 6,
+        }
+    }
+
+    fn from_sel(sel: u8) -> Option<Self> {
+        match sel {
+            2 => Some(ClockSource::Fro12Mhz),
+            4 => Some(ClockSource::Fro1Mhz),
+            // FROHFDIV tells the two apart
+            3 => {
+                let syscon = unsafe { &*raw::SYSCON::ptr() };
+                match syscon.frohfdiv.read().div().bits() {
+                    0 => Some(ClockSource::Fro96Mhz),
+                    2 => Some(ClockSource::Fro32Mhz),
+                    _ => None,
+                }
+            }
+            6 => Some(ClockSource::Osc32k),
+            _ => None,
+        }
+    }
+
+    /// Start the divider behind the selection for Flexcomm `index`, if any
+    fn prepare(self, syscon: &mut syscon::Syscon, index: usize) {
+        let div = match self {
+            ClockSource::Fro32Mhz => 2,
+            ClockSource::Fro96Mhz => 0,
+            _ => return,
+        };
+        let raw = &syscon.raw;
+        let selections = [
+            raw.fcclksel0().read().sel().bits(),
+            raw.fcclksel1().read().sel().bits(),
+            raw.fcclksel2().read().sel().bits(),
+            raw.fcclksel3().read().sel().bits(),
+            raw.fcclksel4().read().sel().bits(),
+            raw.fcclksel5().read().sel().bits(),
+            raw.fcclksel6().read().sel().bits(),
+            raw.fcclksel7().read().sel().bits(),
+            raw.hslspiclksel.read().sel().bits(),
+        ];
+        let shared = selections.iter().enumerate()
+            .any(|(other, &sel)| other != index && sel == self.sel());
+        if shared {
+            assert!(
+                raw.frohfdiv.read().div().bits() == div,
+                "FROHFDIV divides differently for another Flexcomm",
+            );
+            return;
+        }
+        syscon.raw.frohfdiv.modify(|_, w| unsafe { w.div().bits(div).halt().run().reset().released() });
+        while syscon.raw.frohfdiv.read().reqflag().is_ongoing() {}
+    }
+}
+
+/// Direction of a Flexcomm enabled as I2S (PSELID.PERSEL)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum I2sDirection {
+    Transmit,
+    Receive,
+}
+
+/// Implemented by the enabled personalities of the Flexcomms
+pub trait FunctionClock {
+    /// The clock selected with `enabled_as_*_clocked`, `None` if it was
+    /// selected elsewhere
+    fn clock_source(&self) -> Option<ClockSource>;
+
+    /// The frequency of the function clock, `None` if unknown
+    fn clock_frequency(&self) -> Option<Hertz> {
+        self.clock_source().map(ClockSource::frequency)
+    }
+}
+
+macro_rules! function_clock {
+    ($register_sel:ident, $($hal:ident),*) => {
+        $(
+            impl<State> FunctionClock for $hal<State> {
+                fn clock_source(&self) -> Option<ClockSource> {
+                    let syscon = unsafe { &*raw::SYSCON::ptr() };
+                    ClockSource::from_sel(syscon.$register_sel().read().sel().bits())
+                }
+            }
+        )*
+    }
+}
+
+/// An enabled personality of a Flexcomm, which can be torn down to enable another.
+pub trait Repurpose: Sized {
+    /// The disabled Flexcomm
+    type Flexcomm;
+
+    /// Consumes the personality, returns the disabled Flexcomm.
+    ///
+    /// The PSELID lock stays until the Flexcomm is enabled again, as that resets it.
+    fn teardown(self, syscon: &mut syscon::Syscon) -> Self::Flexcomm;
+}
 
 pub type Flexcomm = (
     Flexcomm0,
@@ -33,7 +247,7 @@ pub type Flexcomm = (
 macro_rules! flexcomm {
     ($fc_hal:ident, $i2c_hal:ident, $i2s_hal:ident, $spi_hal:ident, $usart_hal:ident,
      $fc_pac:ident, $i2c_pac:ident, $i2s_pac:ident, $spi_pac:ident, $usart_pac:ident,
-     $register_sel:ident
+     $register_sel:ident, $dma_rx:literal
     ) => {
         pub struct $fc_hal<State = init_state::Unknown> {
             pub(crate) raw_fc: raw::$fc_pac,
@@ -63,6 +277,48 @@ macro_rules! flexcomm {
 
         impl I2c for $i2c_hal {}
 
+        impl Repurpose for $i2c_hal {
+            type Flexcomm = $fc_hal<init_state::Disabled>;
+
+            fn teardown(self, syscon: &mut syscon::Syscon) -> Self::Flexcomm {
+                self.disabled(syscon)
+            }
+        }
+
+        impl $i2c_hal<init_state::Enabled> {
+            /// Stops the interface like `free`, returns the disabled Flexcomm
+            pub fn disabled(self, syscon: &mut syscon::Syscon) -> $fc_hal<init_state::Disabled> {
+                self.free().disabled(syscon)
+            }
+        }
+
+        impl<State> $i2c_hal<State> {
+            /// The raw PAC peripheral.
+            pub fn raw(&self) -> &raw::$i2c_pac {
+                &self.raw
+            }
+
+            /// Like `raw`, mutably.
+            pub fn raw_mut(&mut self) -> &mut raw::$i2c_pac {
+                &mut self.raw
+            }
+        }
+
+        // the clock is the Flexcomm's
+        impl<State> ClockControl for $i2c_hal<State> {
+            fn enable_clock(&self, s: &mut syscon::Syscon) {
+                self._raw_fc.enable_clock(s)
+            }
+
+            fn disable_clock(&self, s: &mut syscon::Syscon) {
+                self._raw_fc.disable_clock(s)
+            }
+
+            fn is_clock_enabled(&self, s: &syscon::Syscon) -> bool {
+                self._raw_fc.is_clock_enabled(s)
+            }
+        }
+
         pub struct $i2s_hal<State = init_state::Enabled> {
             pub(crate) _raw_fc: raw::$fc_pac,
             pub(crate) _raw_i2c: raw::$i2c_pac,
@@ -75,6 +331,49 @@ macro_rules! flexcomm {
 
         impl I2s for $i2s_hal {}
 
+        impl Repurpose for $i2s_hal {
+            type Flexcomm = $fc_hal<init_state::Disabled>;
+
+            fn teardown(self, syscon: &mut syscon::Syscon) -> Self::Flexcomm {
+                self.disabled(syscon)
+            }
+        }
+
+        impl dma::RxRequest for $i2s_hal {
+            const RX_CHANNEL: u8 = $dma_rx;
+
+            fn rx_data_address(&self) -> u32 {
+                &self.raw.fiford as *const _ as u32
+            }
+
+            fn enable_rx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmarx().enabled());
+            }
+
+            fn disable_rx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmarx().disabled());
+            }
+        }
+
+        impl $i2s_hal<init_state::Enabled> {
+            /// Stops the interface like `free`, returns the disabled Flexcomm
+            pub fn disabled(self, syscon: &mut syscon::Syscon) -> $fc_hal<init_state::Disabled> {
+                self.free().disabled(syscon)
+            }
+        }
+
+        impl<State> $i2s_hal<State> {
+            /// The raw PAC peripheral.
+            pub fn raw(&self) -> &raw::$i2s_pac {
+                &self.raw
+            }
+
+            /// Like `raw`, mutably.
+            pub fn raw_mut(&mut self) -> &mut raw::$i2s_pac {
+                &mut self.raw
+            }
+        }
+
         pub struct $spi_hal<State = init_state::Enabled> {
             pub(crate) _raw_fc: raw::$fc_pac,
             pub(crate) _raw_i2c: raw::$i2c_pac,
@@ -94,6 +393,81 @@ macro_rules! flexcomm {
 
         impl Spi for $spi_hal {}
 
+        impl Repurpose for $spi_hal {
+            type Flexcomm = $fc_hal<init_state::Disabled>;
+
+            fn teardown(self, syscon: &mut syscon::Syscon) -> Self::Flexcomm {
+                self.disabled(syscon)
+            }
+        }
+
+        impl dma::RxRequest for $spi_hal {
+            const RX_CHANNEL: u8 = $dma_rx;
+
+            fn rx_data_address(&self) -> u32 {
+                &self.raw.fiford as *const _ as u32
+            }
+
+            fn enable_rx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmarx().enabled());
+            }
+
+            fn disable_rx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmarx().disabled());
+            }
+        }
+
+        // the TX request is wired to the channel after the RX request
+        impl dma::TxRequest for $spi_hal {
+            const TX_CHANNEL: u8 = $dma_rx + 1;
+
+            fn tx_data_address(&self) -> u32 {
+                &self.raw.fifowr as *const _ as u32
+            }
+
+            fn enable_tx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmatx().enabled());
+            }
+
+            fn disable_tx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmatx().disabled());
+            }
+        }
+
+        impl $spi_hal<init_state::Enabled> {
+            /// Stops the interface like `free`, returns the disabled Flexcomm
+            pub fn disabled(self, syscon: &mut syscon::Syscon) -> $fc_hal<init_state::Disabled> {
+                self.free().disabled(syscon)
+            }
+        }
+
+        impl<State> $spi_hal<State> {
+            /// The raw PAC peripheral.
+            pub fn raw(&self) -> &raw::$spi_pac {
+                &self.raw
+            }
+
+            /// Like `raw`, mutably.
+            pub fn raw_mut(&mut self) -> &mut raw::$spi_pac {
+                &mut self.raw
+            }
+        }
+
+        // the clock is the Flexcomm's
+        impl<State> ClockControl for $spi_hal<State> {
+            fn enable_clock(&self, s: &mut syscon::Syscon) {
+                self._raw_fc.enable_clock(s)
+            }
+
+            fn disable_clock(&self, s: &mut syscon::Syscon) {
+                self._raw_fc.disable_clock(s)
+            }
+
+            fn is_clock_enabled(&self, s: &syscon::Syscon) -> bool {
+                self._raw_fc.is_clock_enabled(s)
+            }
+        }
+
         pub struct $usart_hal<State = init_state::Enabled> {
             pub(crate) _raw_fc: raw::$fc_pac,
             pub(crate) _raw_i2c: raw::$i2c_pac,
@@ -113,6 +487,81 @@ macro_rules! flexcomm {
 
         impl Usart for $usart_hal {}
 
+        impl Repurpose for $usart_hal {
+            type Flexcomm = $fc_hal<init_state::Disabled>;
+
+            fn teardown(self, syscon: &mut syscon::Syscon) -> Self::Flexcomm {
+                self.disabled(syscon)
+            }
+        }
+
+        impl dma::RxRequest for $usart_hal {
+            const RX_CHANNEL: u8 = $dma_rx;
+
+            fn rx_data_address(&self) -> u32 {
+                &self.raw.fiford as *const _ as u32
+            }
+
+            fn enable_rx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmarx().enabled());
+            }
+
+            fn disable_rx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmarx().disabled());
+            }
+        }
+
+        // the TX request is wired to the channel after the RX request
+        impl dma::TxRequest for $usart_hal {
+            const TX_CHANNEL: u8 = $dma_rx + 1;
+
+            fn tx_data_address(&self) -> u32 {
+                &self.raw.fifowr as *const _ as u32
+            }
+
+            fn enable_tx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmatx().enabled());
+            }
+
+            fn disable_tx_request(&mut self) {
+                self.raw.fifocfg.modify(|_, w| w.dmatx().disabled());
+            }
+        }
+
+        impl $usart_hal<init_state::Enabled> {
+            /// Stops the interface like `free`, returns the disabled Flexcomm
+            pub fn disabled(self, syscon: &mut syscon::Syscon) -> $fc_hal<init_state::Disabled> {
+                self.free().disabled(syscon)
+            }
+        }
+
+        impl<State> $usart_hal<State> {
+            /// The raw PAC peripheral.
+            pub fn raw(&self) -> &raw::$usart_pac {
+                &self.raw
+            }
+
+            /// Like `raw`, mutably.
+            pub fn raw_mut(&mut self) -> &mut raw::$usart_pac {
+                &mut self.raw
+            }
+        }
+
+        // the clock is the Flexcomm's
+        impl<State> ClockControl for $usart_hal<State> {
+            fn enable_clock(&self, s: &mut syscon::Syscon) {
+                self._raw_fc.enable_clock(s)
+            }
+
+            fn disable_clock(&self, s: &mut syscon::Syscon) {
+                self._raw_fc.disable_clock(s)
+            }
+
+            fn is_clock_enabled(&self, s: &syscon::Syscon) -> bool {
+                self._raw_fc.is_clock_enabled(s)
+            }
+        }
+
         impl core::convert::From<(raw::$fc_pac, raw::$i2c_pac, raw::$i2s_pac, raw::$spi_pac, raw::$usart_pac)> for $fc_hal {
             fn from(raw: (raw::$fc_pac, raw::$i2c_pac, raw::$i2s_pac, raw::$spi_pac, raw::$usart_pac)) -> Self {
                 $fc_hal::new(raw)
@@ -131,45 +580,163 @@ macro_rules! flexcomm {
                 }
             }
 
-            // pub unsafe fn steal() -> Self {
-            //     // seems a little wastefule to steal the full peripherals but ok..
-            //     Self::new(raw::Peripherals::steal().$pac_name)
-            // }
+            /// Take the peripheral, even if it is owned elsewhere.
+            ///
+            /// # Safety
+            ///
+            /// The caller must ensure the other instance is not used concurrently.
+            pub unsafe fn steal() -> Self {
+                let p = raw::Peripherals::steal();
+                Self::new((p.$fc_pac, p.$i2c_pac, p.$i2s_pac, p.$spi_pac, p.$usart_pac))
+            }
+        }
+
+        impl $i2c_hal {
+            /// Take the peripheral, assuming it was enabled as I2C before.
+            ///
+            /// Unlike `enabled_as_i2c`, this does not touch the hardware, so it is
+            /// suitable for fault handlers and panic paths.
+            ///
+            /// # Safety
+            ///
+            /// The caller must ensure the other instance is not used concurrently.
+            pub unsafe fn steal() -> Self {
+                let p = raw::Peripherals::steal();
+                $i2c_hal {
+                    _raw_fc: p.$fc_pac,
+                    raw: p.$i2c_pac,
+                    _raw_i2s: p.$i2s_pac,
+                    _raw_spi: p.$spi_pac,
+                    _raw_usart: p.$usart_pac,
+                    _state: init_state::Enabled(()),
+                }
+            }
+        }
+
+        impl $spi_hal {
+            /// Take the peripheral, assuming it was enabled as SPI before.
+            ///
+            /// Unlike `enabled_as_spi`, this does not touch the hardware, so it is
+            /// suitable for fault handlers and panic paths.
+            ///
+            /// # Safety
+            ///
+            /// The caller must ensure the other instance is not used concurrently.
+            pub unsafe fn steal() -> Self {
+                let p = raw::Peripherals::steal();
+                $spi_hal {
+                    _raw_fc: p.$fc_pac,
+                    _raw_i2c: p.$i2c_pac,
+                    _raw_i2s: p.$i2s_pac,
+                    raw: p.$spi_pac,
+                    _raw_usart: p.$usart_pac,
+                    _state: init_state::Enabled(()),
+                }
+            }
+        }
+
+        impl $usart_hal {
+            /// Take the peripheral, assuming it was enabled as USART before.
+            ///
+            /// Unlike `enabled_as_usart`, this does not touch the hardware, so it is
+            /// suitable for fault handlers and panic paths.
+            ///
+            /// # Safety
+            ///
+            /// The caller must ensure the other instance is not used concurrently.
+            pub unsafe fn steal() -> Self {
+                let p = raw::Peripherals::steal();
+                $usart_hal {
+                    _raw_fc: p.$fc_pac,
+                    _raw_i2c: p.$i2c_pac,
+                    _raw_i2s: p.$i2s_pac,
+                    _raw_spi: p.$spi_pac,
+                    raw: p.$usart_pac,
+                    _state: init_state::Enabled(()),
+                }
+            }
         }
 
         impl<State> $fc_hal<State> {
             pub fn release(self) -> (raw::$fc_pac, raw::$i2c_pac, raw::$i2s_pac, raw::$spi_pac, raw::$usart_pac) {
                 (self.raw_fc, self.raw_i2c, self.raw_i2s, self.raw_spi, self.raw_usart)
             }
+
+            /// The raw PAC peripherals.
+            pub fn raw(&self) -> (&raw::$fc_pac, &raw::$i2c_pac, &raw::$i2s_pac, &raw::$spi_pac, &raw::$usart_pac) {
+                (&self.raw_fc, &self.raw_i2c, &self.raw_i2s, &self.raw_spi, &self.raw_usart)
+            }
+
+            /// Like `raw`, mutably.
+            pub fn raw_mut(&mut self) -> (&mut raw::$fc_pac, &mut raw::$i2c_pac, &mut raw::$i2s_pac, &mut raw::$spi_pac, &mut raw::$usart_pac) {
+                (&mut self.raw_fc, &mut self.raw_i2c, &mut self.raw_i2s, &mut self.raw_spi, &mut self.raw_usart)
+            }
         }
 
-        impl $fc_hal {
+        impl<State> $fc_hal<State> {
+            /// The personality selected in PSELID, if any
+            pub fn personality(&self) -> Option<Personality> {
+                Personality::from_pselid(&self.raw_fc.pselid.read())
+            }
+
+            /// Is the personality locked in PSELID?
+            pub fn is_locked(&self) -> bool {
+                self.raw_fc.pselid.read().lock().is_locked()
+            }
+
             fn enabled(&mut self, syscon: &mut syscon::Syscon) {
+                // the lock in PSELID is only released by this reset
                 syscon.reset(&mut self.raw_fc);
                 syscon.enable_clock(&mut self.raw_fc);
             }
 
-            pub fn enabled_as_i2c(
-                mut self,
-                syscon: &mut syscon::Syscon,
-                _clocks_token: &ClocksSupportFlexcommToken,
-            ) -> $i2c_hal<init_state::Enabled> {
+            /// Consumes the Flexcomm, returns a disabled (un-clocked) one
+            pub fn disabled(mut self, syscon: &mut syscon::Syscon) -> $fc_hal<init_state::Disabled> {
+                syscon.disable_clock(&mut self.raw_fc);
+                syscon.raw.$register_sel().modify(|_, w| w.sel().enum_0x7()); // no clock
 
-                // The FRG output frequency must not be higher than 48 MHz for SPI and I2S
-                // and not higher than 44 MHz for USART and I2C.
-                //
-                // Currently, we just use the 12MHz clock
+                $fc_hal {
+                    raw_fc: self.raw_fc,
+                    raw_i2c: self.raw_i2c,
+                    raw_i2s: self.raw_i2s,
+                    raw_spi: self.raw_spi,
+                    raw_usart: self.raw_usart,
+                    _state: init_state::Disabled,
+                }
+            }
 
-                syscon.raw.$register_sel().modify(|_, w| w.sel().enum_0x2()); // Fro12MHz
+            /// Enable the Flexcomm with function clock `source`, selecting and locking `persel`
+            fn enabled_as(&mut self, syscon: &mut syscon::Syscon, source: ClockSource, persel: PERSEL_A) {
+                source.prepare(syscon, dispatch::instance(&self.raw_fc));
+                syscon.raw.$register_sel().modify(|_, w| w.sel().bits(source.sel()));
 
                 self.enabled(syscon);
 
                 self.raw_fc.pselid.modify(|_, w| w
-                    // select I2C function on corresponding FLEXCOMM
-                    .persel().i2c()
+                    .persel().variant(persel)
                     // lock it
                     .lock().locked()
                 );
+            }
+
+            /// Enable as I2C, clocked from the 12 MHz FRO
+            pub fn enabled_as_i2c(
+                self,
+                syscon: &mut syscon::Syscon,
+                clocks_token: &ClocksSupportFlexcommToken,
+            ) -> $i2c_hal<init_state::Enabled> {
+                self.enabled_as_i2c_clocked(syscon, clocks_token, ClockSource::Fro12Mhz)
+            }
+
+            /// Enable as I2C, clocked from `source` (at most 44 MHz)
+            pub fn enabled_as_i2c_clocked(
+                mut self,
+                syscon: &mut syscon::Syscon,
+                _clocks_token: &ClocksSupportFlexcommToken,
+                source: ClockSource,
+            ) -> $i2c_hal<init_state::Enabled> {
+                assert!(source.frequency() <= MAX_FREQUENCY_USART_I2C);
+                self.enabled_as(syscon, source, PERSEL_A::I2C);
                 assert!(self.raw_fc.pselid.read().i2cpresent().is_present());
 
                 $i2c_hal {
@@ -182,27 +749,50 @@ macro_rules! flexcomm {
                 }
             }
 
-            pub fn enabled_as_spi(
+            /// Enable as I2S transmitter or receiver, clocked from `source` (at most 48 MHz)
+            pub fn enabled_as_i2s(
                 mut self,
                 syscon: &mut syscon::Syscon,
                 _clocks_token: &ClocksSupportFlexcommToken,
-            ) -> $spi_hal<init_state::Enabled> {
-
-                // The FRG output frequency must not be higher than 48 MHz for SPI and I2S
-                // and not higher than 44 MHz for USART and I2C.
-                //
-                // Currently, we just use the 12MHz clock
+                source: ClockSource,
+                direction: I2sDirection,
+            ) -> $i2s_hal<init_state::Enabled> {
+                assert!(source.frequency() <= MAX_FREQUENCY_SPI_I2S);
+                let persel = match direction {
+                    I2sDirection::Transmit => PERSEL_A::I2S_TRANSMIT,
+                    I2sDirection::Receive => PERSEL_A::I2S_RECEIVE,
+                };
+                self.enabled_as(syscon, source, persel);
+                assert!(self.raw_fc.pselid.read().i2spresent().is_present());
 
-                syscon.raw.$register_sel().modify(|_, w| w.sel().enum_0x2()); // Fro12MHz
+                $i2s_hal {
+                    _raw_fc: self.raw_fc,
+                    _raw_i2c: self.raw_i2c,
+                    raw: self.raw_i2s,
+                    _raw_spi: self.raw_spi,
+                    _raw_usart: self.raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
 
-                self.enabled(syscon);
+            /// Enable as SPI, clocked from the 12 MHz FRO
+            pub fn enabled_as_spi(
+                self,
+                syscon: &mut syscon::Syscon,
+                clocks_token: &ClocksSupportFlexcommToken,
+            ) -> $spi_hal<init_state::Enabled> {
+                self.enabled_as_spi_clocked(syscon, clocks_token, ClockSource::Fro12Mhz)
+            }
 
-                self.raw_fc.pselid.modify(|_, w| w
-                    // select SPI function on corresponding FLEXCOMM
-                    .persel().spi()
-                    // lock it
-                    .lock().locked()
-                );
+            /// Enable as SPI, clocked from `source` (at most 48 MHz)
+            pub fn enabled_as_spi_clocked(
+                mut self,
+                syscon: &mut syscon::Syscon,
+                _clocks_token: &ClocksSupportFlexcommToken,
+                source: ClockSource,
+            ) -> $spi_hal<init_state::Enabled> {
+                assert!(source.frequency() <= MAX_FREQUENCY_SPI_I2S);
+                self.enabled_as(syscon, source, PERSEL_A::SPI);
                 assert!(self.raw_fc.pselid.read().spipresent().is_present());
 
                 $spi_hal {
@@ -215,29 +805,96 @@ macro_rules! flexcomm {
                 }
             }
 
+            /// Enable as USART, clocked from the 12 MHz FRO
             pub fn enabled_as_usart(
+                self,
+                syscon: &mut syscon::Syscon,
+                clocks_token: &ClocksSupportFlexcommToken,
+            ) -> $usart_hal<init_state::Enabled> {
+                self.enabled_as_usart_clocked(syscon, clocks_token, ClockSource::Fro12Mhz)
+            }
+
+            /// Enable as USART, clocked from `source` (at most 44 MHz)
+            pub fn enabled_as_usart_clocked(
                 mut self,
                 syscon: &mut syscon::Syscon,
                 _clocks_token: &ClocksSupportFlexcommToken,
+                source: ClockSource,
             ) -> $usart_hal<init_state::Enabled> {
+                assert!(source.frequency() <= MAX_FREQUENCY_USART_I2C);
+                self.enabled_as(syscon, source, PERSEL_A::USART);
+                assert!(self.raw_fc.pselid.read().usartpresent().is_present());
 
-                // The FRG output frequency must not be higher than 48 MHz for SPI and I2S
-                // and not higher than 44 MHz for USART and I2C.
-                //
-                // Currently, we just use the 12MHz clock
+                $usart_hal {
+                    _raw_fc: self.raw_fc,
+                    _raw_i2c: self.raw_i2c,
+                    _raw_i2s: self.raw_i2s,
+                    _raw_spi: self.raw_spi,
+                    raw: self.raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
+        }
 
-                syscon.raw.$register_sel().modify(|_, w| w.sel().enum_0x2()); // Fro12MHz
+        impl $fc_hal<init_state::Enabled> {
+            /// Select `persel`, without locking it, so the Flexcomm can be
+            /// repurposed again without a reset. Only resets the Flexcomm if
+            /// the personality was locked (by `enabled_as_*`).
+            fn repurposed(&mut self, syscon: &mut syscon::Syscon, persel: PERSEL_A, max_frequency: Hertz) {
+                let source = ClockSource::from_sel(syscon.raw.$register_sel().read().sel().bits());
+                assert!(source.map_or(true, |source| source.frequency() <= max_frequency));
+                if self.is_locked() {
+                    syscon.reset(&mut self.raw_fc);
+                }
+                self.raw_fc.pselid.modify(|_, w| w.persel().variant(persel).lock().unlocked());
+            }
 
-                self.enabled(syscon);
+            /// Use the Flexcomm freed by another personality as I2C, keeping its function clock.
+            pub fn into_i2c(mut self, syscon: &mut syscon::Syscon) -> $i2c_hal<init_state::Enabled> {
+                self.repurposed(syscon, PERSEL_A::I2C, MAX_FREQUENCY_USART_I2C);
+                $i2c_hal {
+                    _raw_fc: self.raw_fc,
+                    raw: self.raw_i2c,
+                    _raw_i2s: self.raw_i2s,
+                    _raw_spi: self.raw_spi,
+                    _raw_usart: self.raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
 
-                self.raw_fc.pselid.modify(|_, w| w
-                    // select USART function on corresponding FLEXCOMM
-                    .persel().usart()
-                    // lock it
-                    .lock().locked()
-                );
-                assert!(self.raw_fc.pselid.read().usartpresent().is_present());
+            /// Use the Flexcomm freed by another personality as I2S, keeping its function clock.
+            pub fn into_i2s(mut self, syscon: &mut syscon::Syscon, direction: I2sDirection) -> $i2s_hal<init_state::Enabled> {
+                let persel = match direction {
+                    I2sDirection::Transmit => PERSEL_A::I2S_TRANSMIT,
+                    I2sDirection::Receive => PERSEL_A::I2S_RECEIVE,
+                };
+                self.repurposed(syscon, persel, MAX_FREQUENCY_SPI_I2S);
+                $i2s_hal {
+                    _raw_fc: self.raw_fc,
+                    _raw_i2c: self.raw_i2c,
+                    raw: self.raw_i2s,
+                    _raw_spi: self.raw_spi,
+                    _raw_usart: self.raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
 
+            /// Use the Flexcomm freed by another personality as SPI, keeping its function clock.
+            pub fn into_spi(mut self, syscon: &mut syscon::Syscon) -> $spi_hal<init_state::Enabled> {
+                self.repurposed(syscon, PERSEL_A::SPI, MAX_FREQUENCY_SPI_I2S);
+                $spi_hal {
+                    _raw_fc: self.raw_fc,
+                    _raw_i2c: self.raw_i2c,
+                    _raw_i2s: self.raw_i2s,
+                    raw: self.raw_spi,
+                    _raw_usart: self.raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
+
+            /// Use the Flexcomm freed by another personality as USART, keeping its function clock.
+            pub fn into_usart(mut self, syscon: &mut syscon::Syscon) -> $usart_hal<init_state::Enabled> {
+                self.repurposed(syscon, PERSEL_A::USART, MAX_FREQUENCY_USART_I2C);
                 $usart_hal {
                     _raw_fc: self.raw_fc,
                     _raw_i2c: self.raw_i2c,
@@ -248,17 +905,109 @@ macro_rules! flexcomm {
                 }
             }
         }
+
+        impl $i2c_hal<init_state::Enabled> {
+            /// Finish the ongoing transaction (with a stop) and stop the I2C,
+            /// returning the Flexcomm still clocked, for `into_*`.
+            pub fn free(self) -> $fc_hal<init_state::Enabled> {
+                if self.raw.cfg.read().msten().is_enabled() {
+                    while self.raw.stat.read().mstpending().is_in_progress() {}
+                    if !self.raw.stat.read().mststate().is_idle() {
+                        self.raw.mstctl.write(|w| w.mststop().stop());
+                        while self.raw.stat.read().mstpending().is_in_progress() {}
+                    }
+                }
+                self.raw.cfg.modify(|_, w| w.msten().disabled().slven().disabled().monen().disabled());
+                $fc_hal {
+                    raw_fc: self._raw_fc,
+                    raw_i2c: self.raw,
+                    raw_i2s: self._raw_i2s,
+                    raw_spi: self._raw_spi,
+                    raw_usart: self._raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
+        }
+
+        impl $i2s_hal<init_state::Enabled> {
+            /// Stop the I2S, flushing its FIFO, returning the Flexcomm still clocked, for `into_*`.
+            pub fn free(self) -> $fc_hal<init_state::Enabled> {
+                self.raw.cfg1.modify(|_, w| w.mainenable().disabled());
+                self.raw.fifocfg.modify(|_, w| w
+                    .enabletx().disabled().enablerx().disabled()
+                    .emptytx().set_bit().emptyrx().set_bit()
+                );
+                $fc_hal {
+                    raw_fc: self._raw_fc,
+                    raw_i2c: self._raw_i2c,
+                    raw_i2s: self.raw,
+                    raw_spi: self._raw_spi,
+                    raw_usart: self._raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
+        }
+
+        impl $spi_hal<init_state::Enabled> {
+            /// Finish the ongoing transfer and stop the SPI, flushing its FIFOs,
+            /// returning the Flexcomm still clocked, for `into_*`.
+            pub fn free(self) -> $fc_hal<init_state::Enabled> {
+                if self.raw.cfg.read().enable().is_enabled() && self.raw.cfg.read().master().is_master_mode() {
+                    while self.raw.fifostat.read().txempty().bit_is_clear() {}
+                    while self.raw.stat.read().mstidle().bit_is_clear() {}
+                }
+                self.raw.cfg.modify(|_, w| w.enable().disabled());
+                self.raw.fifocfg.modify(|_, w| w
+                    .enabletx().disabled().enablerx().disabled()
+                    .emptytx().set_bit().emptyrx().set_bit()
+                );
+                $fc_hal {
+                    raw_fc: self._raw_fc,
+                    raw_i2c: self._raw_i2c,
+                    raw_i2s: self._raw_i2s,
+                    raw_spi: self.raw,
+                    raw_usart: self._raw_usart,
+                    _state: init_state::Enabled(()),
+                }
+            }
+        }
+
+        impl $usart_hal<init_state::Enabled> {
+            /// Finish transmitting and stop the USART, flushing its FIFOs,
+            /// returning the Flexcomm still clocked, for `into_*`.
+            pub fn free(self) -> $fc_hal<init_state::Enabled> {
+                if self.raw.cfg.read().enable().is_enabled() && self.raw.fifocfg.read().enabletx().is_enabled() {
+                    while self.raw.fifostat.read().txempty().bit_is_clear() {}
+                    while self.raw.stat.read().txidle().bit_is_clear() {}
+                }
+                self.raw.cfg.modify(|_, w| w.enable().disabled());
+                self.raw.fifocfg.modify(|_, w| w
+                    .enabletx().disabled().enablerx().disabled()
+                    .emptytx().set_bit().emptyrx().set_bit()
+                );
+                $fc_hal {
+                    raw_fc: self._raw_fc,
+                    raw_i2c: self._raw_i2c,
+                    raw_i2s: self._raw_i2s,
+                    raw_spi: self._raw_spi,
+                    raw_usart: self.raw,
+                    _state: init_state::Enabled(()),
+                }
+            }
+        }
+
+        function_clock!($register_sel, $i2c_hal, $i2s_hal, $spi_hal, $usart_hal);
     }
 }
 
-flexcomm!(Flexcomm0, I2c0, I2s0, Spi0, Usart0, FLEXCOMM0, I2C0, I2S0, SPI0, USART0, fcclksel0);
-flexcomm!(Flexcomm1, I2c1, I2s1, Spi1, Usart1, FLEXCOMM1, I2C1, I2S1, SPI1, USART1, fcclksel1);
-flexcomm!(Flexcomm2, I2c2, I2s2, Spi2, Usart2, FLEXCOMM2, I2C2, I2S2, SPI2, USART2, fcclksel2);
-flexcomm!(Flexcomm3, I2c3, I2s3, Spi3, Usart3, FLEXCOMM3, I2C3, I2S3, SPI3, USART3, fcclksel3);
-flexcomm!(Flexcomm4, I2c4, I2s4, Spi4, Usart4, FLEXCOMM4, I2C4, I2S4, SPI4, USART4, fcclksel4);
-flexcomm!(Flexcomm5, I2c5, I2s5, Spi5, Usart5, FLEXCOMM5, I2C5, I2S5, SPI5, USART5, fcclksel5);
-flexcomm!(Flexcomm6, I2c6, I2s6, Spi6, Usart6, FLEXCOMM6, I2C6, I2S6, SPI6, USART6, fcclksel6);
-flexcomm!(Flexcomm7, I2c7, I2s7, Spi7, Usart7, FLEXCOMM7, I2C7, I2S7, SPI7, USART7, fcclksel7);
+flexcomm!(Flexcomm0, I2c0, I2s0, Spi0, Usart0, FLEXCOMM0, I2C0, I2S0, SPI0, USART0, fcclksel0, 4);
+flexcomm!(Flexcomm1, I2c1, I2s1, Spi1, Usart1, FLEXCOMM1, I2C1, I2S1, SPI1, USART1, fcclksel1, 6);
+flexcomm!(Flexcomm2, I2c2, I2s2, Spi2, Usart2, FLEXCOMM2, I2C2, I2S2, SPI2, USART2, fcclksel2, 10);
+flexcomm!(Flexcomm3, I2c3, I2s3, Spi3, Usart3, FLEXCOMM3, I2C3, I2S3, SPI3, USART3, fcclksel3, 8);
+flexcomm!(Flexcomm4, I2c4, I2s4, Spi4, Usart4, FLEXCOMM4, I2C4, I2S4, SPI4, USART4, fcclksel4, 12);
+flexcomm!(Flexcomm5, I2c5, I2s5, Spi5, Usart5, FLEXCOMM5, I2C5, I2S5, SPI5, USART5, fcclksel5, 14);
+flexcomm!(Flexcomm6, I2c6, I2s6, Spi6, Usart6, FLEXCOMM6, I2C6, I2S6, SPI6, USART6, fcclksel6, 16);
+flexcomm!(Flexcomm7, I2c7, I2s7, Spi7, Usart7, FLEXCOMM7, I2C7, I2S7, SPI7, USART7, fcclksel7, 18);
 
 pub struct Flexcomm8<State = init_state::Unknown> {
     pub(crate) raw_fc: raw::FLEXCOMM8,
@@ -282,6 +1031,81 @@ impl Deref for Spi8 {
 
 impl Spi for Spi8 {}
 
+impl Repurpose for Spi8 {
+    type Flexcomm = Flexcomm8<init_state::Disabled>;
+
+    fn teardown(self, syscon: &mut syscon::Syscon) -> Self::Flexcomm {
+        self.disabled(syscon)
+    }
+}
+
+// the high-speed SPI requests DMA channels 2 (RX) and 3 (TX)
+impl dma::RxRequest for Spi8 {
+    const RX_CHANNEL: u8 = 2;
+
+    fn rx_data_address(&self) -> u32 {
+        &self.raw.fiford as *const _ as u32
+    }
+
+    fn enable_rx_request(&mut self) {
+        self.raw.fifocfg.modify(|_, w| w.dmarx().enabled());
+    }
+
+    fn disable_rx_request(&mut self) {
+        self.raw.fifocfg.modify(|_, w| w.dmarx().disabled());
+    }
+}
+
+impl dma::TxRequest for Spi8 {
+    const TX_CHANNEL: u8 = 3;
+
+    fn tx_data_address(&self) -> u32 {
+        &self.raw.fifowr as *const _ as u32
+    }
+
+    fn enable_tx_request(&mut self) {
+        self.raw.fifocfg.modify(|_, w| w.dmatx().enabled());
+    }
+
+    fn disable_tx_request(&mut self) {
+        self.raw.fifocfg.modify(|_, w| w.dmatx().disabled());
+    }
+}
+
+impl Spi8<init_state::Enabled> {
+    /// Stops the interface like `free`, returns the disabled Flexcomm
+    pub fn disabled(self, syscon: &mut syscon::Syscon) -> Flexcomm8<init_state::Disabled> {
+        self.free().disabled(syscon)
+    }
+}
+
+impl<State> Spi8<State> {
+    /// The raw PAC peripheral.
+    pub fn raw(&self) -> &raw::SPI8 {
+        &self.raw
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut raw::SPI8 {
+        &mut self.raw
+    }
+}
+
+// the clock is the Flexcomm's
+impl<State> ClockControl for Spi8<State> {
+    fn enable_clock(&self, s: &mut syscon::Syscon) {
+        self._raw_fc.enable_clock(s)
+    }
+
+    fn disable_clock(&self, s: &mut syscon::Syscon) {
+        self._raw_fc.disable_clock(s)
+    }
+
+    fn is_clock_enabled(&self, s: &syscon::Syscon) -> bool {
+        self._raw_fc.is_clock_enabled(s)
+    }
+}
+
 impl core::convert::From<(raw::FLEXCOMM8, raw::SPI8)> for Flexcomm8 {
     fn from(raw: (raw::FLEXCOMM8, raw::SPI8)) -> Self {
         Flexcomm8::new(raw)
@@ -296,34 +1120,81 @@ impl Flexcomm8 {
             _state: init_state::Unknown,
         }
     }
+
+    /// Take the peripheral, even if it is owned elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the other instance is not used concurrently.
+    pub unsafe fn steal() -> Self {
+        let p = raw::Peripherals::steal();
+        Self::new((p.FLEXCOMM8, p.SPI8))
+    }
 }
 
 impl<State> Flexcomm8<State> {
     pub fn release(self) -> (raw::FLEXCOMM8, raw::SPI8) {
         (self.raw_fc, self.raw_spi)
     }
+
+    /// The raw PAC peripherals.
+    pub fn raw(&self) -> (&raw::FLEXCOMM8, &raw::SPI8) {
+        (&self.raw_fc, &self.raw_spi)
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> (&mut raw::FLEXCOMM8, &mut raw::SPI8) {
+        (&mut self.raw_fc, &mut self.raw_spi)
+    }
 }
 
-impl Flexcomm8 {
+impl<State> Flexcomm8<State> {
+    /// The personality selected in PSELID, if any
+    pub fn personality(&self) -> Option<Personality> {
+        Personality::from_pselid(&self.raw_fc.pselid.read())
+    }
+
+    /// Is the personality locked in PSELID?
+    pub fn is_locked(&self) -> bool {
+        self.raw_fc.pselid.read().lock().is_locked()
+    }
+
     fn enabled(&mut self, syscon: &mut syscon::Syscon) {
+        // the lock in PSELID is only released by this reset
         syscon.reset(&mut self.raw_fc);
         syscon.enable_clock(&mut self.raw_fc);
     }
 
+    /// Consumes the Flexcomm, returns a disabled (un-clocked) one
+    pub fn disabled(mut self, syscon: &mut syscon::Syscon) -> Flexcomm8<init_state::Disabled> {
+        syscon.disable_clock(&mut self.raw_fc);
+        syscon.raw.hslspiclksel.modify(|_, w| w.sel().enum_0x7()); // no clock
+
+        Flexcomm8 {
+            raw_fc: self.raw_fc,
+            raw_spi: self.raw_spi,
+            _state: init_state::Disabled,
+        }
+    }
+
+    /// Enable as (high-speed) SPI, clocked from the 12 MHz FRO
     pub fn enabled_as_spi(
+        self,
+        syscon: &mut syscon::Syscon,
+        clocks_token: &ClocksSupportFlexcommToken,
+    ) -> Spi8<init_state::Enabled> {
+        self.enabled_as_spi_clocked(syscon, clocks_token, ClockSource::Fro12Mhz)
+    }
+
+    /// Enable as (high-speed) SPI, clocked from `source` (HSLSPICLKSEL)
+    pub fn enabled_as_spi_clocked(
         mut self,
         syscon: &mut syscon::Syscon,
         _clocks_token: &ClocksSupportFlexcommToken,
+        source: ClockSource,
     ) -> Spi8<init_state::Enabled> {
-
-        // NB: This is the high-speed SPI
-
-        // The FRG output frequency must not be higher than 48 MHz for SPI and I2S
-        // and not higher than 44 MHz for USART and I2C.
-        //
-        // Currently, we just use the 12MHz clock
-
-        syscon.raw.hslspiclksel.modify(|_, w| w.sel().enum_0x2()); // Fro12MHz
+        source.prepare(syscon, 8);
+        syscon.raw.hslspiclksel.modify(|_, w| w.sel().bits(source.sel()));
 
         self.enabled(syscon);
 
@@ -341,5 +1212,66 @@ impl Flexcomm8 {
             _state: init_state::Enabled(()),
         }
     }
+}
+
+impl Spi8<init_state::Enabled> {
+    /// Finish the ongoing transfer and stop the SPI, flushing its FIFOs,
+    /// returning the Flexcomm still clocked, for `into_spi`.
+    pub fn free(self) -> Flexcomm8<init_state::Enabled> {
+        if self.raw.cfg.read().enable().is_enabled() && self.raw.cfg.read().master().is_master_mode() {
+            while self.raw.fifostat.read().txempty().bit_is_clear() {}
+            while self.raw.stat.read().mstidle().bit_is_clear() {}
+        }
+        self.raw.cfg.modify(|_, w| w.enable().disabled());
+        self.raw.fifocfg.modify(|_, w| w
+            .enabletx().disabled().enablerx().disabled()
+            .emptytx().set_bit().emptyrx().set_bit()
+        );
+        Flexcomm8 {
+            raw_fc: self._raw_fc,
+            raw_spi: self.raw,
+            _state: init_state::Enabled(()),
+        }
+    }
+}
+
+impl Flexcomm8<init_state::Enabled> {
+    /// Use the freed Flexcomm as SPI again, keeping its function clock.
+    pub fn into_spi(mut self, syscon: &mut syscon::Syscon) -> Spi8<init_state::Enabled> {
+        if self.is_locked() {
+            syscon.reset(&mut self.raw_fc);
+        }
+        self.raw_fc.pselid.modify(|_, w| w.persel().spi().lock().unlocked());
+        Spi8 {
+            _raw_fc: self.raw_fc,
+            raw: self.raw_spi,
+            _state: init_state::Enabled(()),
+        }
+    }
+}
+
+impl<State> FunctionClock for Spi8<State> {
+    fn clock_source(&self) -> Option<ClockSource> {
+        let syscon = unsafe { &*raw::SYSCON::ptr() };
+        ClockSource::from_sel(syscon.hslspiclksel.read().sel().bits())
+    }
+}
 
+impl Spi8 {
+    /// Take the peripheral, assuming it was enabled as SPI before.
+    ///
+    /// Unlike `enabled_as_spi`, this does not touch the hardware, so it is
+    /// suitable for fault handlers and panic paths.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the other instance is not used concurrently.
+    pub unsafe fn steal() -> Self {
+        let p = raw::Peripherals::steal();
+        Spi8 {
+            _raw_fc: p.FLEXCOMM8,
+            raw: p.SPI8,
+            _state: init_state::Enabled(()),
+        }
+    }
 }
@@ -0,0 +1,135 @@
+//! Sharing the Flexcomm interrupts between drivers.
+//!
+//! Each Flexcomm has one interrupt vector, whatever its personality. Instead of
+//! writing the vectors by hand, bind `DispatchHandler`s to them once; the
+//! interrupt-driven drivers then register a handler for the Flexcomm they use,
+//! which is called with the Flexcomm's index:
+//!
+//! ```ignore
+//! hal::bind_interrupts!(struct Irqs {
+//!     FLEXCOMM0 => DispatchHandler<0>;
+//!     FLEXCOMM2 => DispatchHandler<2>;
+//! });
+//!
+//! dispatch::register(dispatch::instance(&usart), on_console_interrupt);
+//! // the waker plumbing of the async drivers fits as well
+//! dispatch::register(2, hal::drivers::asynch::on_flexcomm_interrupt);
+//!
+//! hal::interrupt::unmask::<DispatchHandler<0>>(Irqs);
+//! hal::interrupt::unmask::<DispatchHandler<2>>(Irqs);
+//! ```
+//!
+//! Registration can change at any time, e.g. when a Flexcomm is repurposed. The
+//! drivers use `try_register`, so they don't take over the interrupt of another
+//! driver, and unregister once done.
+
+use core::cell::Cell;
+use core::ops::Deref;
+
+use cortex_m::interrupt::{self, Mutex};
+
+use crate::{
+    interrupt::Handler,
+    raw::{self, Interrupt},
+};
+
+/// Number of Flexcomm interfaces
+pub const INSTANCES: usize = 9;
+
+/// A driver's interrupt logic, called with the index of the Flexcomm
+pub type InterruptFn = fn(usize);
+
+static HANDLERS: [Mutex<Cell<Option<InterruptFn>>>; INSTANCES] =
+    [const { Mutex::new(Cell::new(None)) }; INSTANCES];
+
+/// Address of the register blocks of Flexcomm `index`
+pub(crate) fn base(index: usize) -> usize {
+    (match index {
+        0 => raw::FLEXCOMM0::ptr(),
+        1 => raw::FLEXCOMM1::ptr(),
+        2 => raw::FLEXCOMM2::ptr(),
+        3 => raw::FLEXCOMM3::ptr(),
+        4 => raw::FLEXCOMM4::ptr(),
+        5 => raw::FLEXCOMM5::ptr(),
+        6 => raw::FLEXCOMM6::ptr(),
+        7 => raw::FLEXCOMM7::ptr(),
+        8 => raw::FLEXCOMM8::ptr(),
+        _ => panic!("there are only Flexcomm interfaces 0-8"),
+    }) as usize
+}
+
+/// Index of the Flexcomm interface, given the address of any of its register blocks
+pub(crate) fn index_of<T: ?Sized>(block: *const T) -> usize {
+    (0..INSTANCES).find(|&i| base(i) == block as *const u8 as usize).unwrap()
+}
+
+/// Index of the Flexcomm a personality (or raw register block) belongs to
+pub fn instance<P: Deref>(personality: &P) -> usize {
+    index_of(&**personality as *const _)
+}
+
+/// The interrupt vector of Flexcomm `index`
+pub const fn interrupt(index: usize) -> Interrupt {
+    match index {
+        0 => Interrupt::FLEXCOMM0,
+        1 => Interrupt::FLEXCOMM1,
+        2 => Interrupt::FLEXCOMM2,
+        3 => Interrupt::FLEXCOMM3,
+        4 => Interrupt::FLEXCOMM4,
+        5 => Interrupt::FLEXCOMM5,
+        6 => Interrupt::FLEXCOMM6,
+        7 => Interrupt::FLEXCOMM7,
+        8 => Interrupt::FLEXCOMM8,
+        _ => panic!("there are only Flexcomm interfaces 0-8"),
+    }
+}
+
+/// Call `handler` on interrupts of Flexcomm `index`, returning the handler it replaces.
+pub fn register(index: usize, handler: InterruptFn) -> Option<InterruptFn> {
+    interrupt::free(|cs| HANDLERS[index].borrow(cs).replace(Some(handler)))
+}
+
+/// Like `register`, but fails with the registered handler if there is one.
+pub fn try_register(index: usize, handler: InterruptFn) -> Result<(), InterruptFn> {
+    interrupt::free(|cs| {
+        let slot = HANDLERS[index].borrow(cs);
+        match slot.get() {
+            Some(registered) => Err(registered),
+            None => {
+                slot.set(Some(handler));
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Stop handling interrupts of Flexcomm `index`, returning the handler removed.
+pub fn unregister(index: usize) -> Option<InterruptFn> {
+    interrupt::free(|cs| HANDLERS[index].borrow(cs).take())
+}
+
+/// The handler registered for Flexcomm `index`
+pub fn registered(index: usize) -> Option<InterruptFn> {
+    interrupt::free(|cs| HANDLERS[index].borrow(cs).get())
+}
+
+/// Call the handler registered for Flexcomm `index`, if any.
+///
+/// The handler runs outside the critical section, so it can be preempted by
+/// interrupts of higher priority.
+pub fn dispatch(index: usize) {
+    if let Some(handler) = registered(index) {
+        handler(index);
+    }
+}
+
+/// Interrupt handler for Flexcomm interface `N` (0-8), calling the registered handler
+pub struct DispatchHandler<const N: usize>;
+
+impl<const N: usize> Handler for DispatchHandler<N> {
+    const INTERRUPT: Interrupt = interrupt(N);
+
+    unsafe fn on_interrupt() {
+        dispatch(N);
+    }
+}
@@ -0,0 +1,143 @@
+//! The FIFOs of the Flexcomm personalities (FIFOCFG, FIFOTRIG, FIFOSTAT).
+//!
+//! USART, SPI and I2S share the FIFO registers; I2C has none. The drivers apply
+//! a `FifoConfig`, and the personalities themselves can be configured with the
+//! `Fifo` trait:
+//!
+//! ```ignore
+//! // interrupt once the TX FIFO is half empty, or 4 bytes were received
+//! serial_config.fifo(FifoConfig::new().tx_level(Some(8)).rx_level(Some(3)))
+//! ```
+
+use crate::raw;
+
+/// The FIFOs hold this many entries
+pub const FIFO_DEPTH: u8 = 16;
+
+/// Configuration of the FIFOs of a Flexcomm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FifoConfig {
+    /// Enable the TX FIFO (needed to transmit)
+    pub tx: bool,
+    /// Enable the RX FIFO (needed to receive)
+    pub rx: bool,
+    /// Trigger when the TX FIFO holds at most this many entries (FIFOTRIG.TXLVL)
+    pub tx_level: Option<u8>,
+    /// Trigger when the RX FIFO holds more than this many entries (FIFOTRIG.RXLVL)
+    pub rx_level: Option<u8>,
+    /// Request DMA while the TX FIFO has room
+    pub dma_tx: bool,
+    /// Request DMA while the RX FIFO has data
+    pub dma_rx: bool,
+}
+
+impl FifoConfig {
+    /// Both FIFOs enabled, no level triggers, no DMA requests
+    pub fn new() -> Self {
+        Self {
+            tx: true,
+            rx: true,
+            tx_level: None,
+            rx_level: None,
+            dma_tx: false,
+            dma_rx: false,
+        }
+    }
+
+    pub fn tx(mut self, enabled: bool) -> Self {
+        self.tx = enabled;
+        self
+    }
+
+    pub fn rx(mut self, enabled: bool) -> Self {
+        self.rx = enabled;
+        self
+    }
+
+    /// At most `FIFO_DEPTH - 1`
+    pub fn tx_level(mut self, level: Option<u8>) -> Self {
+        self.tx_level = level;
+        self
+    }
+
+    /// At most `FIFO_DEPTH - 1`
+    pub fn rx_level(mut self, level: Option<u8>) -> Self {
+        self.rx_level = level;
+        self
+    }
+
+    pub fn dma_tx(mut self, enabled: bool) -> Self {
+        self.dma_tx = enabled;
+        self
+    }
+
+    pub fn dma_rx(mut self, enabled: bool) -> Self {
+        self.dma_rx = enabled;
+        self
+    }
+}
+
+impl Default for FifoConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implemented by the register blocks with FIFOs, so by the personalities
+/// dereferencing to them
+pub trait Fifo {
+    /// Apply `config`, keeping the contents of the FIFOs.
+    fn configure_fifo(&self, config: FifoConfig);
+
+    /// Discard the contents of the TX FIFO.
+    fn empty_tx_fifo(&self);
+
+    /// Discard the contents of the RX FIFO.
+    fn empty_rx_fifo(&self);
+
+    /// The number of entries in the TX and the RX FIFO
+    fn fifo_levels(&self) -> (u8, u8);
+}
+
+macro_rules! fifo {
+    ($($block:ty),*) => {
+        $(
+            impl Fifo for $block {
+                fn configure_fifo(&self, config: FifoConfig) {
+                    let tx_level = config.tx_level.unwrap_or(0);
+                    let rx_level = config.rx_level.unwrap_or(0);
+                    assert!(tx_level < FIFO_DEPTH && rx_level < FIFO_DEPTH);
+
+                    self.fifocfg.modify(|_, w| w
+                        .enabletx().bit(config.tx)
+                        .enablerx().bit(config.rx)
+                        .dmatx().bit(config.dma_tx)
+                        .dmarx().bit(config.dma_rx)
+                    );
+                    self.fifotrig.modify(|_, w| unsafe { w
+                        .txlvl().bits(tx_level)
+                        .txlvlena().bit(config.tx_level.is_some())
+                        .rxlvl().bits(rx_level)
+                        .rxlvlena().bit(config.rx_level.is_some())
+                    });
+                }
+
+                fn empty_tx_fifo(&self) {
+                    self.fifocfg.modify(|_, w| w.emptytx().set_bit());
+                }
+
+                fn empty_rx_fifo(&self) {
+                    self.fifocfg.modify(|_, w| w.emptyrx().set_bit());
+                }
+
+                fn fifo_levels(&self) -> (u8, u8) {
+                    let fifostat = self.fifostat.read();
+                    (fifostat.txlvl().bits(), fifostat.rxlvl().bits())
+                }
+            }
+        )*
+    }
+}
+
+fifo!(raw::usart0::RegisterBlock, raw::spi0::RegisterBlock, raw::i2s0::RegisterBlock);
@@ -39,7 +39,21 @@ impl Deref for Gint0 {
         &self.raw
     }
 }
-impl traits::Gint for Gint0 {}
+impl traits::Gint for Gint0 {
+    const INTERRUPT: raw::Interrupt = raw::Interrupt::GINT0;
+}
+
+impl<State: init_state::InitState> Gint0<State> {
+    /// The raw PAC peripheral.
+    pub fn raw(&self) -> &raw::GINT0 {
+        &self.raw
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut raw::GINT0 {
+        &mut self.raw
+    }
+}
 
 pub type EnabledGint0 = Gint0<init_state::Enabled>;
 
@@ -53,7 +67,21 @@ impl Deref for Gint1 {
         &self.raw
     }
 }
-impl traits::Gint for Gint1 {}
+impl traits::Gint for Gint1 {
+    const INTERRUPT: raw::Interrupt = raw::Interrupt::GINT1;
+}
+
+impl<State: init_state::InitState> Gint1<State> {
+    /// The raw PAC peripheral.
+    pub fn raw(&self) -> &raw::GINT1 {
+        &self.raw
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut raw::GINT1 {
+        &mut self.raw
+    }
+}
 
 pub type EnabledGint1 = Gint1<init_state::Enabled>;
 
@@ -62,6 +90,16 @@ impl<State: init_state::InitState> Gint<State> {
         (self.raw_gint0, self.raw_gint1)
     }
 
+    /// The raw PAC peripherals.
+    pub fn raw(&self) -> (&raw::GINT0, &raw::GINT1) {
+        (&self.raw_gint0, &self.raw_gint1)
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> (&mut raw::GINT0, &mut raw::GINT1) {
+        (&mut self.raw_gint0, &mut self.raw_gint1)
+    }
+
     pub fn enabled(
         mut self,
         syscon: &mut syscon::Syscon,
@@ -80,20 +118,22 @@ impl<State: init_state::InitState> Gint<State> {
     }
 }
 
-// impl (Gint0, Gint1) {
-//     pub fn disabled(
-//         mut self,
-//         syscon: &mut syscon::Syscon,
-//     ) -> Gint<init_state::Disabled> {
-//         syscon.disable_clock(&mut self);
-
-//         Gint {
-//             raw_gint0: self.0.raw,
-//             raw_gint1: self.1.raw,
-//             _state: init_state::Disabled,
-//         }
-//     }
-// }
+impl Gint<init_state::Disabled> {
+    /// Consumes the enabled parts, returns the disabled (un-clocked) peripheral
+    pub fn disabled(
+        parts: (Gint0, Gint1),
+        syscon: &mut syscon::Syscon,
+    ) -> Self {
+        let (mut gint0, mut gint1) = (parts.0.raw, parts.1.raw);
+        syscon.disable_clock(&mut (&mut gint0, &mut gint1));
+
+        Gint {
+            raw_gint0: gint0,
+            raw_gint1: gint1,
+            _state: init_state::Disabled,
+        }
+    }
+}
 
 impl From<(raw::GINT0, raw::GINT1)> for Gint {
     fn from(raw: (raw::GINT0, raw::GINT1)) -> Self {
@@ -1,7 +1,9 @@
-use crate::traits::digest::generic_array::GenericArray;
-
+#[cfg(feature = "crypto")]
 use crate::{
     drivers::{aes, Aes, AesKey, Sha1, Sha256},
+    traits::digest::generic_array::GenericArray,
+};
+use crate::{
     raw,
     peripherals::syscon,
     typestates::init_state,
@@ -38,6 +40,7 @@ impl<State> Hashcrypt<State> {
 
 }
 
+#[cfg(feature = "crypto")]
 impl Hashcrypt<init_state::Enabled> {
 
     /// SHA-1, as in RustCrypto  `digest` trait
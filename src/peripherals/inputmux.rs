@@ -1,10 +1,33 @@
+//! API for the input multiplexing (INPUTMUX)
+//!
+//! The INPUTMUX routes signals to the inputs of other peripherals: pins to the
+//! pin interrupts, trigger signals to the DMA channels, timer inputs to the
+//! CTIMER captures, and clocks to the frequency measurement (FREQME).
+//!
+//! Each input is a `Destination`, which determines the type of the sources it
+//! can be connected to:
+//!
+//! ```ignore
+//! let mut mux = board.inputmux.enabled(&mut board.syscon);
+//! mux.connect(PinInterrupt(Slot::Slot2), PinSource::of(&button));
+//! mux.connect(DmaTrigger(4), DmaTriggerSource::CtimerMatch0(1));
+//! mux.connect(FreqmeTarget, FreqmeSource::MainClock);
+//! ```
+//!
+//! The hardware triggers of the ADC are not routed here, they are fixed.
+//!
+//! The INPUTMUX peripheral is described in the user manual, chapter 16.
+
 use crate::{
     raw,
+    drivers::pins::Pin,
     peripherals::{
+        pint::Slot,
         syscon,
     },
     typestates::{
         init_state,
+        pin::PinId,
     }
 };
 
@@ -31,3 +54,230 @@ impl<State> InputMux<State> {
 
 }
 
+impl InputMux<init_state::Enabled> {
+    /// Connect `source` to the peripheral input `destination`.
+    pub fn connect<D: Destination>(&mut self, destination: D, source: D::Source) {
+        destination.connect(&self.raw, source);
+    }
+
+    /// Connect capture input `capture` (0..=3) of CTIMER`timer` to the timer
+    /// input multiplexer input `input` (CT_INP0..19 are inputs 0..19).
+    pub fn select_ctimer_capture(&mut self, timer: usize, capture: usize, input: u8) {
+        self.connect(
+            CtimerCapture { timer: timer as u8, capture: capture as u8 },
+            CaptureSource::Input(input),
+        );
+    }
+}
+
+/// A peripheral input routed by the INPUTMUX
+pub trait Destination {
+    /// What can be connected to this input
+    type Source: Copy;
+
+    /// Internal method
+    fn connect(self, mux: &raw::inputmux::RegisterBlock, source: Self::Source);
+}
+
+/// Pin interrupt (or pattern match) slot, PINTSEL
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PinInterrupt(pub Slot);
+
+/// A pin, as source for the pin interrupts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PinSource {
+    port: usize,
+    number: u8,
+}
+
+impl PinSource {
+    pub fn of<T: PinId, S: crate::typestates::pin::state::PinState>(_pin: &Pin<T, S>) -> Self {
+        PinSource { port: T::PORT, number: T::NUMBER }
+    }
+}
+
+impl Destination for PinInterrupt {
+    type Source = PinSource;
+
+    fn connect(self, mux: &raw::inputmux::RegisterBlock, source: PinSource) {
+        mux.pintsel[self.0 as usize].write(|w| unsafe {
+            w.intpin().bits((source.port << 5) as u8 | source.number)
+        });
+    }
+}
+
+/// Trigger input of DMA0 channel `n` (0..=22), DMA0_ITRIG_INMUX
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DmaTrigger(pub u8);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DmaTriggerSource {
+    /// Pin interrupt slot 0..=3
+    PinInterrupt(u8),
+    /// Match 0 of CTIMER 0..=4
+    CtimerMatch0(u8),
+    /// Match 1 of CTIMER 0..=4
+    CtimerMatch1(u8),
+    ComparatorOutput,
+    /// DMA0 output trigger 0..=3, see `DmaOutputTrigger`
+    OutputTrigger(u8),
+    /// DMA request 0..=1 of SCT0
+    Sct(u8),
+    Hashcrypt,
+}
+
+impl DmaTriggerSource {
+    fn number(self) -> u8 {
+        match self {
+            DmaTriggerSource::PinInterrupt(slot) => {
+                assert!(slot < 4);
+                slot
+            }
+            DmaTriggerSource::CtimerMatch0(timer) => {
+                assert!(timer < 5);
+                4 + 2 * timer
+            }
+            DmaTriggerSource::CtimerMatch1(timer) => {
+                assert!(timer < 5);
+                5 + 2 * timer
+            }
+            DmaTriggerSource::ComparatorOutput => 14,
+            DmaTriggerSource::OutputTrigger(n) => {
+                assert!(n < 4);
+                15 + n
+            }
+            DmaTriggerSource::Sct(n) => {
+                assert!(n < 2);
+                19 + n
+            }
+            DmaTriggerSource::Hashcrypt => 21,
+        }
+    }
+}
+
+impl Destination for DmaTrigger {
+    type Source = DmaTriggerSource;
+
+    fn connect(self, mux: &raw::inputmux::RegisterBlock, source: DmaTriggerSource) {
+        let number = source.number();
+        mux.dma0_itrig_inmux[self.0 as usize].write(|w| unsafe { w.inp().bits(number) });
+        mux.dma0_itrig_ena_set.write(|w| unsafe { w.bits(1 << number) });
+    }
+}
+
+/// DMA0 output trigger `n` (0..=3), which can feed back into `DmaTrigger`s
+/// to chain channels, DMA0_OTRIG_INMUX
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DmaOutputTrigger(pub u8);
+
+/// A DMA0 channel (0..=22), whose completion triggers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DmaChannel(pub u8);
+
+impl Destination for DmaOutputTrigger {
+    type Source = DmaChannel;
+
+    fn connect(self, mux: &raw::inputmux::RegisterBlock, source: DmaChannel) {
+        assert!(source.0 < 23);
+        mux.dma0_otrig_inmux[self.0 as usize].write(|w| unsafe { w.inp().bits(source.0) });
+    }
+}
+
+/// Capture input `capture` (0..=3) of CTIMER `timer` (0..=4), TIMERnCAPTSEL
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CtimerCapture {
+    pub timer: u8,
+    pub capture: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CaptureSource {
+    /// Timer input pin CT_INP0..19 (IOCON function of the pin)
+    Input(u8),
+    Usb0FrameToggle,
+    Usb1FrameToggle,
+    ComparatorOutput,
+    I2sSharedWs0,
+    I2sSharedWs1,
+}
+
+impl CaptureSource {
+    fn number(self) -> u32 {
+        match self {
+            CaptureSource::Input(n) => {
+                assert!(n < 20);
+                n as u32
+            }
+            CaptureSource::Usb0FrameToggle => 20,
+            CaptureSource::Usb1FrameToggle => 21,
+            CaptureSource::ComparatorOutput => 22,
+            CaptureSource::I2sSharedWs0 => 23,
+            CaptureSource::I2sSharedWs1 => 24,
+        }
+    }
+}
+
+impl Destination for CtimerCapture {
+    type Source = CaptureSource;
+
+    fn connect(self, mux: &raw::inputmux::RegisterBlock, source: CaptureSource) {
+        let bits = source.number();
+        let capture = self.capture as usize;
+        match self.timer {
+            0 => mux.timer0captsel[capture].write(|w| unsafe { w.bits(bits) }),
+            1 => mux.timer1captsel[capture].write(|w| unsafe { w.bits(bits) }),
+            2 => mux.timer2captsel[capture].write(|w| unsafe { w.bits(bits) }),
+            3 => mux.timer3captsel[capture].write(|w| unsafe { w.bits(bits) }),
+            4 => mux.timer4captsel[capture].write(|w| unsafe { w.bits(bits) }),
+            timer => panic!("no CTIMER{}", timer),
+        }
+    }
+}
+
+/// Reference clock of the frequency measurement, FREQMEAS_REF
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FreqmeReference;
+
+/// Measured clock of the frequency measurement, FREQMEAS_TARGET
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FreqmeTarget;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FreqmeSource {
+    ClkIn = 0,
+    Fro12Mhz = 1,
+    Fro1Mhz = 2,
+    Rtc32Khz = 3,
+    MainClock = 4,
+    Pio0_4 = 5,
+    Pio0_20 = 6,
+    Pio0_24 = 7,
+    Pio1_4 = 8,
+}
+
+impl Destination for FreqmeReference {
+    type Source = FreqmeSource;
+
+    fn connect(self, mux: &raw::inputmux::RegisterBlock, source: FreqmeSource) {
+        mux.freqmeas_ref.write(|w| unsafe { w.clkin().bits(source as u8) });
+    }
+}
+
+impl Destination for FreqmeTarget {
+    type Source = FreqmeSource;
+
+    fn connect(self, mux: &raw::inputmux::RegisterBlock, source: FreqmeSource) {
+        mux.freqmeas_target.write(|w| unsafe { w.clkin().bits(source as u8) });
+    }
+}
@@ -1,10 +1,12 @@
 use crate::{
     raw,
+    drivers::pins::Pull,
     peripherals::{
         syscon,
     },
     typestates::{
         init_state,
+        pin::PinType,
     }
 };
 
@@ -50,7 +52,187 @@ impl<State> Iocon<State> {
     }
 }
 
+// IOCON bits common to all pins
+const FUNC: u32 = 0xf;
+const MODE: u32 = 0x3 << 4;
+const MODE_PULL_UP: u32 = 0x2 << 4;
+const SLEW: u32 = 1 << 6;
+const INVERT: u32 = 1 << 7;
+const DIGIMODE: u32 = 1 << 8;
+const OD: u32 = 1 << 9;
+const ASW: u32 = 1 << 10;
+// IOCON bits of I2C pins (type I)
+const FILTEROFF: u32 = 1 << 12;
+
+/// Driver slew rate (IOCON SLEW)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Slew {
+    Standard,
+    Fast,
+}
+
+/// Implemented by `Digital` and `Analog`, the two kinds of `PinConfig`
+pub trait ConfigMode: Copy {
+    /// The DIGIMODE bit of configurations of this kind
+    #[doc(hidden)]
+    const DIGIMODE: u32;
+}
+
+/// Configuration of a pin with its digital input buffer enabled
+#[derive(Clone, Copy, Debug)]
+pub struct Digital;
+impl ConfigMode for Digital {
+    const DIGIMODE: u32 = DIGIMODE;
+}
+
+/// Configuration of a pin with its digital input buffer disabled
+#[derive(Clone, Copy, Debug)]
+pub struct Analog;
+impl ConfigMode for Analog {
+    const DIGIMODE: u32 = 0;
+}
+
+/// Electrical configuration of a pin, everything in IOCON except the function.
+///
+/// Applied with `Pin::configure`, or changed in part with `Pin::modify_config`.
+/// Digital pins (GPIO, special functions) take a `PinConfig<Digital>`, analog
+/// pins a `PinConfig<Analog>`, which can't enable the digital input buffer
+/// (DIGIMODE):
+///
+/// ```ignore
+/// button.configure(&mut iocon, PinConfig::new().pull(Pull::Up).invert(true));
+/// ```
+///
+/// The LPC55 has no drive strength setting; the glitch filter only exists on
+/// I2C pins (`PinType::I`), and the analog switch only on analog pins.
+#[derive(Clone, Copy, Debug)]
+pub struct PinConfig<M: ConfigMode = Digital> {
+    bits: u32,
+    _mode: M,
+}
+
+impl PinConfig<Digital> {
+    /// No pull resistor, standard slew rate, not inverted, push-pull, glitch filter on
+    pub fn new() -> Self {
+        Self { bits: DIGIMODE, _mode: Digital }
+    }
+
+    pub fn slew(self, slew: Slew) -> Self {
+        self.with(SLEW, slew == Slew::Fast)
+    }
+
+    /// Invert the input
+    pub fn invert(self, invert: bool) -> Self {
+        self.with(INVERT, invert)
+    }
+
+    pub fn open_drain(self, open_drain: bool) -> Self {
+        self.with(OD, open_drain)
+    }
+
+    /// Filter glitches below 10 ns on the input, only on I2C pins
+    pub fn glitch_filter(self, filter: bool) -> Self {
+        self.with(FILTEROFF, !filter)
+    }
+}
+
+impl Default for PinConfig<Digital> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PinConfig<Analog> {
+    /// No pull resistor, analog switch closed
+    pub fn analog() -> Self {
+        Self { bits: ASW, _mode: Analog }
+    }
+
+    /// Connect the pin to the analog peripherals (ADC, comparator)
+    pub fn analog_switch(self, close: bool) -> Self {
+        self.with(ASW, close)
+    }
+}
+
+impl<M: ConfigMode> PinConfig<M> {
+    pub fn pull(self, pull: Pull) -> Self {
+        let mode = match pull {
+            Pull::None => 0,
+            Pull::Down => 1,
+            Pull::Up => 2,
+            Pull::Repeater => 3,
+        };
+        Self { bits: (self.bits & !MODE) | (mode << 4), _mode: self._mode }
+    }
+
+    fn with(self, bit: u32, set: bool) -> Self {
+        let bits = if set { self.bits | bit } else { self.bits & !bit };
+        Self { bits, _mode: self._mode }
+    }
+}
+
+/// The IOCON bits a `PinConfig` sets on pins of type `pin_type`
+fn config_mask(pin_type: PinType) -> u32 {
+    let mask = MODE | SLEW | INVERT | DIGIMODE | OD;
+    match pin_type {
+        PinType::A => mask | ASW,
+        PinType::I => mask | FILTEROFF,
+        PinType::D => mask,
+    }
+}
+
 impl Iocon<init_state::Enabled> {
+    /// Route pin `PIOport_number` to its digital function `func`.
+    ///
+    /// The pin is reset to no pull-up/down, standard slew rate, not inverted, and not open drain.
+    /// All pin transitions go through here, rather than each carrying its own copy.
+    pub(crate) fn configure_pin(&mut self, port: usize, number: u8, func: u8) {
+        self.modify_pin(port, number, FUNC | MODE | SLEW | INVERT | DIGIMODE | OD, func as u32 | DIGIMODE);
+    }
+
+    /// Make the digital pin `PIOport_number` open drain, with the internal pull-up if `pull_up`.
+    pub(crate) fn configure_open_drain(&mut self, port: usize, number: u8, pull_up: bool) {
+        let mode = if pull_up { MODE_PULL_UP } else { 0 };
+        self.modify_pin(port, number, MODE | OD, mode | OD);
+    }
+
+    /// Configure pin `PIOport_number` as analog input, enabling its analog switch.
+    pub(crate) fn configure_analog_pin(&mut self, port: usize, number: u8) {
+        self.modify_pin(port, number, FUNC | MODE | SLEW | INVERT | DIGIMODE | OD | ASW, ASW);
+    }
+
+    /// Route pin `PIOport_number` to its digital function `func`, keeping the
+    /// rest of its configuration (only enabling the digital input buffer).
+    pub(crate) fn set_function(&mut self, port: usize, number: u8, func: u8) {
+        self.modify_pin(port, number, FUNC | DIGIMODE, func as u32 | DIGIMODE);
+    }
+
+    /// Apply `config` to pin `PIOport_number` of type `pin_type`, keeping its function.
+    pub(crate) fn apply_config<M: ConfigMode>(&mut self, port: usize, number: u8, pin_type: PinType, config: PinConfig<M>) {
+        let mask = config_mask(pin_type);
+        self.modify_pin(port, number, mask, config.bits & mask);
+    }
+
+    /// The current configuration of pin `PIOport_number` of type `pin_type`,
+    /// as a configuration of kind `M`.
+    pub(crate) fn read_config<M: ConfigMode>(&self, port: usize, number: u8, pin_type: PinType, mode: M) -> PinConfig<M> {
+        let bits = unsafe { self.pin_register(port, number).read_volatile() } & config_mask(pin_type);
+        PinConfig { bits: (bits & !DIGIMODE) | M::DIGIMODE, _mode: mode }
+    }
+
+    fn pin_register(&self, port: usize, number: u8) -> *mut u32 {
+        assert!(port < 2 && number < 32);
+        // the PIOx_y registers are contiguous, PIO1_0 following PIO0_31
+        let pio0_0 = &self.raw.pio0_0 as *const _ as *mut u32;
+        pio0_0.wrapping_add(32 * port + number as usize)
+    }
+
+    fn modify_pin(&mut self, port: usize, number: u8, mask: u32, bits: u32) {
+        let reg = self.pin_register(port, number);
+        unsafe { reg.write_volatile((reg.read_volatile() & !mask) | bits) };
+    }
+
     pub fn get_pio_0_8_config(&self) -> u32 {
         self.raw.pio0_8.read().bits()
     }
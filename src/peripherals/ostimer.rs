@@ -0,0 +1,94 @@
+//! API for the OS event timer (OSTIMER)
+//!
+//! A 42-bit counter driven by the 32 kHz oscillator, which keeps counting in
+//! all low power modes except deep power-down. This makes it the natural source
+//! of timestamps, e.g. for logging.
+//!
+//! With feature `defmt-timestamp`, the defmt timestamps are the time since the
+//! timer was enabled, in microseconds (zero before).
+//!
+//! The OSTIMER peripheral is described in the user manual, chapter 20.
+
+use crate::{
+    raw,
+    peripherals::{
+        pmc::Pmc,
+        syscon::Syscon,
+    },
+    typestates::{
+        init_state,
+        ClocksSupport32KhzFroToken,
+    },
+};
+
+crate::wrap_stateful_peripheral!(OsTimer, OSTIMER);
+
+/// Point in time of the OS event timer, in ticks of the 32 kHz oscillator
+pub type Instant = fugit::TimerInstantU64<32_768>;
+/// Time span of the OS event timer, in ticks of the 32 kHz oscillator
+pub type Duration = fugit::TimerDurationU64<32_768>;
+
+impl<State> OsTimer<State> {
+    pub fn enabled(
+        mut self,
+        pmc: &mut Pmc,
+        syscon: &mut Syscon,
+        _token: &ClocksSupport32KhzFroToken,
+    ) -> OsTimer<init_state::Enabled> {
+        pmc.raw.ostimer.modify(|_, w| w
+            .softreset().clear_bit()
+            .clockenable().set_bit()
+            .osc32kpd().clear_bit()
+        );
+        syscon.enable_clock(&mut self.raw);
+
+        OsTimer {
+            raw: self.raw,
+            _state: init_state::Enabled(()),
+        }
+    }
+
+    pub fn disabled(mut self, pmc: &mut Pmc, syscon: &mut Syscon) -> OsTimer<init_state::Disabled> {
+        syscon.disable_clock(&mut self.raw);
+        pmc.raw.ostimer.modify(|_, w| w.clockenable().clear_bit());
+
+        OsTimer {
+            raw: self.raw,
+            _state: init_state::Disabled,
+        }
+    }
+}
+
+impl OsTimer<init_state::Enabled> {
+    pub fn now(&self) -> Instant {
+        Instant::from_ticks(ticks(&self.raw))
+    }
+}
+
+fn ticks(ostimer: &raw::ostimer::RegisterBlock) -> u64 {
+    // the counter is Gray-coded
+    let low = ostimer.evtimerl.read().bits();
+    let high = ostimer.evtimerh.read().bits();
+    let mut ticks = ((high as u64) << 32) | low as u64;
+    let mut shift = 1;
+    while shift < 64 {
+        ticks ^= ticks >> shift;
+        shift <<= 1;
+    }
+    ticks
+}
+
+/// The current time, without access to the `OsTimer`.
+///
+/// Meant for timestamps, where the timer is out of reach. Zero if the timer
+/// is not enabled.
+pub fn now() -> Instant {
+    let syscon = unsafe { &*raw::SYSCON::ptr() };
+    if syscon.ahbclkctrl1.read().ostimer().is_disable() {
+        return Instant::from_ticks(0);
+    }
+    Instant::from_ticks(ticks(unsafe { &*raw::OSTIMER::ptr() }))
+}
+
+#[cfg(feature = "defmt-timestamp")]
+defmt::timestamp!("{=u64:us}", now().duration_since_epoch().to_micros());
@@ -10,6 +10,7 @@ use crate::{
 };
 
 #[derive(Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum KeyType {
     Sbkek = 0x00,
     User = 0x01,
@@ -20,6 +21,7 @@ pub enum KeyType {
 }
 
 #[derive(Copy,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(C)]
 pub struct IvCodePrinceRegion {
     pub keycode_header: u32,
@@ -27,6 +29,7 @@ pub struct IvCodePrinceRegion {
 }
 
 #[derive(Copy,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(C)]
 pub struct Cfpa {
     pub header: u32,
@@ -77,6 +80,7 @@ impl Cfpa {
 }
 
 #[derive(Copy,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(C)]
 pub struct Cmpa {
     pub boot_cfg: u32,
@@ -243,7 +247,7 @@ impl Pfr{
 
     pub fn enabled(mut self, clock_config: &Clocks) -> Result<Pfr<init_state::Enabled>, u32> {
 
-        self.flash_config = FlashConfig::new(clock_config.system_frequency.0/1000_000);
+        self.flash_config = FlashConfig::new(clock_config.system_frequency.to_MHz());
 
         let flash_init = Self::bootloader_api_tree().flash_driver.flash_init;
         let ffr_init = Self::bootloader_api_tree().flash_driver.ffr_init;
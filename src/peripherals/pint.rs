@@ -1,4 +1,5 @@
 use core::ops::Deref;
+use core::sync::atomic::{AtomicU8, Ordering};
 use crate::{
     raw,
     peripherals::{
@@ -6,7 +7,7 @@ use crate::{
         inputmux::InputMux,
     },
     drivers::{
-        pins::Pin,
+        pins::{Pin, SecurePin},
     },
     typestates::{
         init_state,
@@ -21,9 +22,13 @@ use crate::{
     },
 };
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Mode {
     RisingEdge,
     FallingEdge,
+    /// Rising and falling edges
+    BothEdges,
     ActiveLow,
     ActiveHigh,
 }
@@ -31,6 +36,7 @@ pub enum Mode {
 /// Bit position 0 - 7 indicating which of the 8 external interrupt positions to use
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Slot {
     Slot0 = 0,
     Slot1 = 1,
@@ -44,6 +50,10 @@ pub enum Slot {
 
 use Mode::*;
 
+/// Slots in use, by the application (`enable_interrupt`, `select_input`) or
+/// by the `Wait` implementation of input pins, one bit per slot
+static TAKEN_SLOTS: AtomicU8 = AtomicU8::new(0);
+
 impl<State> Deref for Pint<State> {
     type Target = raw::pint::RegisterBlock;
     fn deref(&self) -> &Self::Target {
@@ -87,69 +97,513 @@ impl Pint <init_state::Enabled> {
         slot: Slot,
         mode: Mode
     ){
+        TAKEN_SLOTS.fetch_or(1 << slot as u8, Ordering::Acquire);
+        configure_slot(&self.raw, &mux.raw, PIN::PORT, PIN::NUMBER, slot as u8, mode);
+    }
+
+    /// Disable the interrupt of `slot`, in all modes, and clear its detections.
+    pub fn disable_interrupt(&mut self, slot: Slot) {
+        disable_slot(&self.raw, slot as u8);
+        TAKEN_SLOTS.fetch_and(!(1 << slot as u8), Ordering::Release);
+    }
+
+    /// Is the interrupt of `slot` pending?
+    ///
+    /// For level interrupts, this is whether the pin is at the active level.
+    pub fn is_pending(&self, slot: Slot) -> bool {
+        self.raw.ist.read().pstat().bits() & (1 << slot as u8) != 0
+    }
+
+    /// Has a rising edge been detected in `slot` since the last `clear`?
+    ///
+    /// Edges are detected whether or not the interrupt is enabled for them.
+    pub fn rising_edge_detected(&self, slot: Slot) -> bool {
+        self.raw.rise.read().rdet().bits() & (1 << slot as u8) != 0
+    }
+
+    /// Has a falling edge been detected in `slot` since the last `clear`?
+    pub fn falling_edge_detected(&self, slot: Slot) -> bool {
+        self.raw.fall.read().fdet().bits() & (1 << slot as u8) != 0
+    }
+
+    /// Clear the edge detections of `slot`, typically in its interrupt handler.
+    ///
+    /// Does not touch level interrupts, where writing to IST would toggle the
+    /// active level; these stay pending while the pin is at the active level.
+    pub fn clear(&mut self, slot: Slot) {
+        clear_slot(&self.raw, slot as u8);
+    }
+}
+
+fn disable_slot(pint: &raw::pint::RegisterBlock, slot: u8) {
+    let bit = 1 << slot;
+    pint.cienr.write(|w| unsafe { w.cenrl().bits(bit) });
+    pint.cienf.write(|w| unsafe { w.cenaf().bits(bit) });
+    pint.isel.modify(|r, w| unsafe { w.pmode().bits(r.pmode().bits() & !bit) });
+    pint.rise.write(|w| unsafe { w.rdet().bits(bit) });
+    pint.fall.write(|w| unsafe { w.fdet().bits(bit) });
+}
+
+fn clear_slot(pint: &raw::pint::RegisterBlock, slot: u8) {
+    let bit = 1 << slot;
+    if pint.isel.read().pmode().bits() & bit == 0 {
+        pint.ist.write(|w| unsafe { w.pstat().bits(bit) });
+    }
+    pint.rise.write(|w| unsafe { w.rdet().bits(bit) });
+    pint.fall.write(|w| unsafe { w.fdet().bits(bit) });
+}
+
+/// Has an edge of `mode` been detected in the slot(s) of `bits`?
+#[cfg(feature = "async")]
+fn edge_detected(pint: &raw::pint::RegisterBlock, bits: u8, mode: Mode) -> bool {
+    let rising = pint.rise.read().rdet().bits() & bits != 0;
+    let falling = pint.fall.read().fdet().bits() & bits != 0;
+    match mode {
+        RisingEdge => rising,
+        FallingEdge => falling,
+        _ => rising || falling,
+    }
+}
+
+/// Slot of the secure pin interrupts, of which there are two
+#[repr(u8)]
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SecureSlot {
+    Slot0 = 0,
+    Slot1 = 1,
+}
+
+impl SecureSlot {
+    /// The interrupt of the slot (SEC_GPIO_INT0_IRQn)
+    pub fn interrupt(self) -> raw::Interrupt {
+        match self {
+            SecureSlot::Slot0 => raw::Interrupt::SEC_GPIO_INT0_IRQ0,
+            SecureSlot::Slot1 => raw::Interrupt::SEC_GPIO_INT0_IRQ1,
+        }
+    }
+}
+
+crate::wrap_stateful_peripheral!(SecPint, SECPINT);
+
+impl<State> SecPint<State> {
+    pub fn enabled(mut self, syscon: &mut syscon::Syscon) -> SecPint<init_state::Enabled> {
+        syscon.enable_clock(&mut self.raw);
+
+        SecPint {
+            raw: self.raw,
+            _state: init_state::Enabled(()),
+        }
+    }
+
+    pub fn disabled(mut self, syscon: &mut syscon::Syscon) -> SecPint<init_state::Disabled> {
+        syscon.disable_clock(&mut self.raw);
+
+        SecPint {
+            raw: self.raw,
+            _state: init_state::Disabled,
+        }
+    }
+}
+
+/// Same as `Pint`, for the two secure pin interrupts, on secure pins
+impl SecPint<init_state::Enabled> {
+    pub fn enable_interrupt<PIN: PinId>(
+        &mut self,
+        mux: &mut InputMux<init_state::Enabled>,
+        _pin: &SecurePin<PIN, direction::Input>,
+        slot: SecureSlot,
+        mode: Mode,
+    ) {
+        // secure pins are all in port 0
+        mux.raw.pintsecsel[slot as usize].write(|w| unsafe { w.intpin().bits(PIN::NUMBER) });
+        configure_mode(&self.raw, slot as u8, mode);
+    }
+
+    pub fn disable_interrupt(&mut self, slot: SecureSlot) {
+        disable_slot(&self.raw, slot as u8);
+    }
+
+    pub fn is_pending(&self, slot: SecureSlot) -> bool {
+        self.raw.ist.read().pstat().bits() & (1 << slot as u8) != 0
+    }
+
+    pub fn clear(&mut self, slot: SecureSlot) {
+        clear_slot(&self.raw, slot as u8);
+    }
+}
+
+/// Condition on an input of the pattern match engine (PMCFG.CFGx)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Condition {
+    /// Always true
+    Always = 0,
+    /// A rising edge was seen since the term last matched, or the pattern was set
+    StickyRising = 1,
+    /// A falling edge was seen since the term last matched, or the pattern was set
+    StickyFalling = 2,
+    /// Any edge was seen since the term last matched, or the pattern was set
+    StickyEdge = 3,
+    High = 4,
+    Low = 5,
+    /// Never true
+    Never = 6,
+    /// An edge occurs (for one clock cycle)
+    Edge = 7,
+}
+
+/// Boolean pattern over the 8 inputs selected via PINTSEL, for the pattern match engine.
+///
+/// A pattern is a sum of product terms, each an AND of conditions on inputs,
+/// taking one bit slice of the engine per condition. A term ending at slice `n`
+/// fires interrupt PIN_INT`n`:
+///
+/// ```ignore
+/// // button 0 low AND button 1 rising, on PIN_INT1
+/// let pattern = PatternMatch::new()
+///     .and(Slot::Slot0, Condition::Low)
+///     .and(Slot::Slot1, Condition::StickyRising)
+///     .end_term();
+/// pint.select_input(&mut mux, &button0, Slot::Slot0);
+/// pint.select_input(&mut mux, &button1, Slot::Slot1);
+/// pint.enable_pattern_match(&pattern, false);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PatternMatch {
+    sources: [u8; 8],
+    conditions: [u8; 8],
+    endpoints: u8,
+    slices: u8,
+}
+
+impl Default for PatternMatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PatternMatch {
+    pub const fn new() -> Self {
+        Self {
+            sources: [0; 8],
+            conditions: [Condition::Never as u8; 8],
+            endpoints: 0,
+            slices: 0,
+        }
+    }
+
+    /// AND `condition` on `input` to the current product term.
+    ///
+    /// Panics if all 8 slices are used.
+    pub fn and(mut self, input: Slot, condition: Condition) -> Self {
+        assert!(self.slices < 8, "pattern uses more than 8 slices");
+        self.sources[self.slices as usize] = input as u8;
+        self.conditions[self.slices as usize] = condition as u8;
+        self.slices += 1;
+        self
+    }
+
+    /// End the current product term, which fires the interrupt of its last slice.
+    ///
+    /// Panics if the term is empty.
+    pub fn end_term(mut self) -> Self {
+        assert!(self.slices > 0 && self.endpoints & (1 << (self.slices - 1)) == 0, "empty product term");
+        self.endpoints |= 1 << (self.slices - 1);
+        self
+    }
+
+    /// The interrupts fired by the terms ended so far, one bit per PIN_INT
+    pub fn interrupts(&self) -> u8 {
+        self.endpoints
+    }
 
-        // Enable pin as external interrupt for ext int source `slot`
+    fn pmsrc(&self) -> u32 {
+        self.sources.iter().enumerate()
+            .fold(0, |bits, (i, source)| bits | (*source as u32) << (8 + 3 * i))
+    }
+
+    fn pmcfg(&self) -> u32 {
+        // the slices after the last term form a term with slice 7 as
+        // (implicit) endpoint, which must never match
+        let conditions = self.conditions.iter().enumerate()
+            .fold(0, |bits, (i, condition)| bits | (*condition as u32) << (8 + 3 * i));
+        conditions | (self.endpoints & 0x7f) as u32
+    }
+}
+
+impl Pint<init_state::Enabled> {
+    /// Select `pin` as input `slot` (of the pin interrupts and the pattern match engine).
+    pub fn select_input<PIN: PinId>(
+        &mut self,
+        mux: &mut InputMux<init_state::Enabled>,
+        _pin: &Pin<PIN, state::Gpio<direction::Input>>,
+        slot: Slot,
+    ) {
+        TAKEN_SLOTS.fetch_or(1 << slot as u8, Ordering::Acquire);
         mux.raw.pintsel[slot as usize].write(|w| unsafe {
-            w
-            .intpin().bits( (PIN::PORT << 5) as u8 | (PIN::NUMBER) )
+            w.intpin().bits((PIN::PORT << 5) as u8 | PIN::NUMBER)
         });
+    }
 
-        let bit = 1 << (slot as u8);
+    /// Switch the interrupts from the pin interrupts to the terms of `pattern`.
+    ///
+    /// With `rxev`, a match also signals the CPU's RXEV (waking it from WFE).
+    pub fn enable_pattern_match(&mut self, pattern: &PatternMatch, rxev: bool) {
+        self.raw.pmsrc.write(|w| unsafe { w.bits(pattern.pmsrc()) });
+        // (re)writing the configuration clears the sticky conditions
+        self.raw.pmcfg.write(|w| unsafe { w.bits(pattern.pmcfg()) });
+        self.raw.pmctrl.modify(|_, w| w
+            .sel_pmatch().pattern_match()
+            .ena_rxev().bit(rxev)
+        );
+    }
 
-        // Clear respective slot bit (default rising)
-        self.raw.isel.modify(|r,w| unsafe {
-            w.pmode().bits(
-                r.pmode().bits() & (!bit)
-            )
-        });
+    /// Switch the interrupts back to the pin interrupts.
+    pub fn disable_pattern_match(&mut self) {
+        self.raw.pmctrl.modify(|_, w| w
+            .sel_pmatch().pin_interrupt()
+            .ena_rxev().disabled()
+        );
+    }
+
+    /// The current match of each product term, one bit per PIN_INT of its endpoint
+    pub fn pattern_matches(&self) -> u8 {
+        self.raw.pmctrl.read().pmat().bits()
+    }
+}
+
+/// Route pin `PIOport_number` to `slot` and enable its interrupt in `mode`.
+fn configure_slot(
+    pint: &raw::pint::RegisterBlock,
+    mux: &raw::inputmux::RegisterBlock,
+    port: usize,
+    number: u8,
+    slot: u8,
+    mode: Mode,
+) {
+    // Enable pin as external interrupt for ext int source `slot`
+    mux.pintsel[slot as usize].write(|w| unsafe {
+        w
+        .intpin().bits( (port << 5) as u8 | number )
+    });
+
+    configure_mode(pint, slot, mode);
+}
+
+/// Enable the interrupt of `slot` in `mode`, for PINT and SECPINT alike
+fn configure_mode(pint: &raw::pint::RegisterBlock, slot: u8, mode: Mode) {
+    let bit = 1 << slot;
+
+    // Clear respective slot bit (default rising)
+    pint.isel.modify(|r,w| unsafe {
+        w.pmode().bits(
+            r.pmode().bits() & (!bit)
+        )
+    });
+
+    match mode {
+        RisingEdge => {
+            // enable level/rising interrupt
+            pint.sienr.write(|w| unsafe {
+                w.setenrl().bits( bit )
+            });
+        }
+        FallingEdge => {
+            // enable falling interrupt
+            pint.sienf.write(|w| unsafe {
+                w.setenaf().bits( bit )
+            });
+        }
+        BothEdges => {
+            pint.sienr.write(|w| unsafe { w.setenrl().bits(bit) });
+            pint.sienf.write(|w| unsafe { w.setenaf().bits(bit) });
+        }
+        _ => {
+
+            // Make level interrupt
+            pint.isel.modify(|r,w| unsafe {
+                w.pmode().bits(
+                    r.pmode().bits() | bit
+                )
+            });
+
+            // enable level/rising interrupt
+            pint.sienr.write(|w| unsafe {
+                w.setenrl().bits( bit )
+            });
+
+            match mode {
+                ActiveHigh => {
+                    // Make level active high
+                    pint.sienf.write(|w| unsafe {
+                        w.setenaf().bits( bit )
+                    });
+                }
+                ActiveLow => {
+                    // Make level active low
+                    pint.cienf.write(|w| unsafe {
+                        w.cenaf().bits( bit )
+                    });
 
-        match mode {
-            RisingEdge => {
-                // enable level/rising interrupt
-                self.raw.sienr.write(|w| unsafe {
-                    w.setenrl().bits( bit )
-                });
-            }
-            FallingEdge => {
-                // enable falling interrupt
-                self.raw.sienf.write(|w| unsafe {
-                    w.setenaf().bits( bit )
-                });
-            }
-            _ => {
-
-                // Make level interrupt
-                self.raw.isel.modify(|r,w| unsafe {
-                    w.pmode().bits(
-                        r.pmode().bits() | bit
-                    )
-                });
-
-                // enable level/rising interrupt
-                self.raw.sienr.write(|w| unsafe {
-                    w.setenrl().bits( bit )
-                });
-
-                match mode {
-                    ActiveHigh => {
-                        // Make level active high
-                        self.raw.sienf.write(|w| unsafe {
-                            w.setenaf().bits( bit )
-                        });  
-                    }
-                    ActiveLow => {
-                        // Make level active low
-                        self.raw.cienf.write(|w| unsafe {
-                            w.cenaf().bits( bit )
-                        });  
-
-                    }
-                    _ => {}
                 }
+                _ => {}
+            }
+
+
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Pint <init_state::Enabled> {
+
+    /// Wait until `pin` triggers according to `mode`, using the external interrupt `slot`.
+    ///
+    /// The interrupt is disabled again once it fired. Needs
+    /// `drivers::asynch::PintInterruptHandler` bound to the PIN_INT`slot` interrupt.
+    pub async fn wait<PIN: PinId>(
+        &mut self,
+        mux: &mut InputMux<init_state::Enabled>,
+        pin: &Pin<PIN, state::Gpio<direction::Input>>,
+        slot: Slot,
+        mode: Mode,
+    ) {
+        use crate::drivers::asynch;
+
+        let bit = 1 << (slot as u8);
+        let edge = matches!(mode, RisingEdge | FallingEdge | BothEdges);
+        if edge {
+            // clear any earlier detection; in level mode, this would toggle the active level
+            clear_slot(&self.raw, slot as u8);
+        }
+        self.enable_interrupt(mux, pin, slot, mode);
+
+        let pint = &self.raw;
+        let gpio = unsafe { &*raw::GPIO::ptr() };
+        asynch::wait_for(
+            &asynch::PINT_WAKERS[slot as usize],
+            || if edge {
+                // IST only reports enabled interrupts, which the handler disabled,
+                // while RISE and FALL latch regardless
+                edge_detected(pint, bit, mode)
+            } else {
+                // IST doesn't latch levels, so check the pin
+                let high = gpio.pin[PIN::PORT].read().bits() & (1 << PIN::NUMBER) != 0;
+                high == (mode == ActiveHigh)
+            },
+            || if !edge {
+                // disabled by the interrupt handler
+                pint.sienr.write(|w| unsafe { w.setenrl().bits(bit) });
+            },
+        ).await;
+
+        if edge {
+            clear_slot(&self.raw, slot as u8);
+        }
+    }
+}
+
+/// The tasks waiting for a slot to free up, all woken when one does
+#[cfg(feature = "async")]
+static SLOT_WAITERS: crate::drivers::asynch::WakerList<8> = crate::drivers::asynch::WakerList::new();
 
+/// A slot taken for one `Wait` call, disabled and returned when dropped
+#[cfg(feature = "async")]
+struct WaitSlot(u8);
 
+#[cfg(feature = "async")]
+impl WaitSlot {
+    /// Take the highest free slot, so that applications using `Pint` directly
+    /// can stick to the low ones.
+    fn try_take() -> Option<Self> {
+        let mut taken = TAKEN_SLOTS.load(Ordering::Relaxed);
+        loop {
+            let slot = (0..8u8).rev().find(|slot| taken & (1 << slot) == 0)?;
+            match TAKEN_SLOTS.compare_exchange_weak(
+                taken, taken | (1 << slot), Ordering::Acquire, Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(WaitSlot(slot)),
+                Err(current) => taken = current,
             }
         }
+    }
 
+    /// Take a slot, waiting for one to free up if they are all taken.
+    async fn take() -> Self {
+        core::future::poll_fn(|cx| {
+            if let Some(slot) = Self::try_take() {
+                return core::task::Poll::Ready(slot);
+            }
+            SLOT_WAITERS.register(cx.waker());
+            match Self::try_take() {
+                Some(slot) => core::task::Poll::Ready(slot),
+                None => core::task::Poll::Pending,
+            }
+        }).await
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "async")]
+impl Drop for WaitSlot {
+    fn drop(&mut self) {
+        let pint = unsafe { &*raw::PINT::ptr() };
+        disable_slot(pint, self.0);
+        TAKEN_SLOTS.fetch_and(!(1 << self.0), Ordering::Release);
+        SLOT_WAITERS.wake_all();
+    }
+}
+
+/// Wait until pin `PIOport_number` triggers according to `modes` (both edges
+/// if given `RisingEdge` and `FallingEdge`), using a free pin interrupt slot.
+///
+/// This is what the `Wait` implementation of input pins builds on. It enables
+/// the PINT and INPUTMUX clocks itself, and needs `PintInterruptHandler`s
+/// bound to the PIN_INT interrupts of the slots taken, from 7 downwards.
+/// Slots the application uses are skipped; if all are taken, this waits
+/// for one to free up.
+#[cfg(feature = "async")]
+pub(crate) async fn wait_for_pin(port: usize, number: u8, modes: &[Mode]) {
+    use crate::drivers::asynch;
+
+    let syscon = unsafe { &*raw::SYSCON::ptr() };
+    cortex_m::interrupt::free(|_| {
+        syscon.ahbclkctrl0.modify(|_, w| w.pint().enable().mux().enable())
+    });
+    let pint = unsafe { &*raw::PINT::ptr() };
+    let mux = unsafe { &*raw::INPUTMUX::ptr() };
+    let gpio = unsafe { &*raw::GPIO::ptr() };
+
+    let slot = WaitSlot::take().await;
+    let bit = 1 << slot.0;
+    // clear any earlier edge detection of the slot
+    clear_slot(pint, slot.0);
+    for mode in modes {
+        configure_slot(pint, mux, port, number, slot.0, *mode);
+    }
+
+    // IST doesn't latch levels, and the interrupt handler disables the level
+    // interrupt, so levels are checked on the pin itself
+    let level = modes.iter().find_map(|mode| match mode {
+        ActiveHigh => Some(true),
+        ActiveLow => Some(false),
+        _ => None,
+    });
+    // IST only reports enabled interrupts, so edges are checked in RISE and FALL
+    let edges = match (modes.contains(&RisingEdge), modes.contains(&FallingEdge)) {
+        (true, false) => RisingEdge,
+        (false, true) => FallingEdge,
+        _ => BothEdges,
+    };
+    asynch::wait_for(
+        &asynch::PINT_WAKERS[slot.0 as usize],
+        || match level {
+            Some(high) => (gpio.pin[port].read().bits() & (1 << number) != 0) == high,
+            None => edge_detected(pint, bit, edges),
+        },
+        || if level.is_some() {
+            pint.sienr.write(|w| unsafe { w.setenrl().bits(bit) });
+        },
+    ).await;
+}
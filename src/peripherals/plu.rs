@@ -0,0 +1,157 @@
+use core::ops::Deref;
+use crate::{
+    raw,
+    peripherals::{
+        syscon,
+    },
+    typestates::{
+        init_state,
+        ClocksSupport1MhzFroToken,
+        ClocksSupport32KhzFroToken,
+    },
+};
+
+/// Glitch filter applied to the PLU outputs before they reach the wake-up interrupt
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Filter {
+    Bypass,
+    OneClock,
+    TwoClocks,
+    ThreeClocks,
+}
+
+/// Clock used by the glitch filter of the wake-up interrupt
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FilterClock {
+    Fro1Mhz,
+    Fro12Mhz,
+}
+
+/// Internal oscillator driving the PLU flip-flops via CLKOUT
+#[derive(Copy, Clone)]
+pub enum FlipFlopClock {
+    Fro1Mhz(ClocksSupport1MhzFroToken),
+    Osc32Khz(ClocksSupport32KhzFroToken),
+}
+
+impl<State> Deref for Plu<State> {
+    type Target = raw::plu::RegisterBlock;
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+crate::wrap_stateful_peripheral!(Plu, PLU);
+
+impl<State> Plu<State> {
+    pub fn enabled(mut self, syscon: &mut syscon::Syscon) -> Plu<init_state::Enabled> {
+        syscon.reset(&mut self.raw);
+        syscon.enable_clock(&mut self.raw);
+
+        Plu {
+            raw: self.raw,
+            _state: init_state::Enabled(()),
+        }
+    }
+
+    pub fn disabled(mut self, syscon: &mut syscon::Syscon) -> Plu<init_state::Disabled> {
+        syscon.disable_clock(&mut self.raw);
+
+        Plu {
+            raw: self.raw,
+            _state: init_state::Disabled,
+        }
+    }
+
+}
+
+impl Plu<init_state::Enabled> {
+
+    /// Current state of the 8 PLU outputs, bit `n` being `OUTPUT_n`.
+    pub fn outputs(&self) -> u8 {
+        self.raw.outputs.read().output_state().bits()
+    }
+
+    /// Route the PLU outputs selected by `mask` (bit `n` for `OUTPUT_n`) to the PLU interrupt.
+    ///
+    /// The interrupt fires when any of the selected outputs is high. With `latch` set,
+    /// it stays asserted until `clear_wakeup_interrupt` is called, so that a short
+    /// pulse is not lost while the core is waking up.
+    ///
+    /// The interrupt is a deep-sleep wake-up source, so the logic configured in
+    /// the LUTs can wake the part without any CPU involvement. It still has to be
+    /// unmasked in the NVIC (`raw::Interrupt::PLU`) to actually wake the core.
+    pub fn enable_wakeup_interrupt(&mut self, mask: u8, latch: bool) {
+        self.raw.wakeint_ctrl.modify(|_, w| unsafe {
+            w
+            .mask().bits(mask)
+            .latch_enable().bit(latch)
+        });
+    }
+
+    /// Stop routing any PLU output to the PLU interrupt.
+    pub fn disable_wakeup_interrupt(&mut self) {
+        self.raw.wakeint_ctrl.modify(|_, w| unsafe {
+            w
+            .mask().bits(0)
+            .latch_enable().clear_bit()
+        });
+        self.clear_wakeup_interrupt();
+    }
+
+    /// Clear the latched wake-up interrupt.
+    pub fn clear_wakeup_interrupt(&mut self) {
+        self.raw.wakeint_ctrl.modify(|_, w| w.intr_clear().set_bit());
+        self.raw.wakeint_ctrl.modify(|_, w| w.intr_clear().clear_bit());
+    }
+
+    /// Add a glitch filter between the PLU outputs and the wake-up interrupt.
+    ///
+    /// Anything other than `Filter::Bypass` needs the deglitch clock, which is enabled here.
+    pub fn set_wakeup_filter(&mut self, syscon: &mut syscon::Syscon, filter: Filter, clock: FilterClock) {
+        syscon.raw.clock_ctrl.modify(|_, w| w.plu_deglitch_clk_ena().bit(filter != Filter::Bypass));
+        self.raw.wakeint_ctrl.modify(|_, w| {
+            let w = match filter {
+                Filter::Bypass => w.filter_mode().bypass(),
+                Filter::OneClock => w.filter_mode().filter1clk(),
+                Filter::TwoClocks => w.filter_mode().filter2clk(),
+                Filter::ThreeClocks => w.filter_mode().filter3clk(),
+            };
+            match clock {
+                FilterClock::Fro1Mhz => w.filter_clksel().fro1mhz(),
+                FilterClock::Fro12Mhz => w.filter_clksel().fro12mhz(),
+            }
+        });
+    }
+
+    /// Clock the flip-flops of sequential PLU designs from whatever is applied to the PLU_CLKIN pin.
+    ///
+    /// This stops CLKOUT, in case it was previously used by `use_internal_flip_flop_clock`.
+    pub fn use_external_flip_flop_clock(&mut self, syscon: &mut syscon::Syscon) {
+        syscon.raw.clkoutsel.write(|w| w.sel().enum_0x7());  // no clock
+    }
+
+    /// Clock the flip-flops from a low-power internal oscillator, divided by `divider` (1-256).
+    ///
+    /// The flip-flops can only be clocked from the PLU_CLKIN pin, so the divided clock is
+    /// generated on CLKOUT, which must be connected to PLU_CLKIN (pin muxing and board wiring
+    /// are left to the application). Slow clocks are what glitch filters and frequency
+    /// dividers built in the LUTs typically need.
+    pub fn use_internal_flip_flop_clock(&mut self, syscon: &mut syscon::Syscon, source: FlipFlopClock, divider: u16) {
+        assert!((1..=256).contains(&divider));
+
+        syscon.raw.clkoutdiv.write(|w| w.halt().halt());
+        syscon.raw.clkoutsel.write(|w| match source {
+            FlipFlopClock::Fro1Mhz(_) => w.sel().enum_0x4(),
+            FlipFlopClock::Osc32Khz(_) => w.sel().enum_0x6(),
+        });
+        syscon.raw.clkoutdiv.write(|w| unsafe {
+            w
+            .div().bits((divider - 1) as u8)
+            .reset().released()
+            .halt().run()
+        });
+    }
+}
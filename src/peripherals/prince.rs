@@ -33,6 +33,21 @@ impl Prince {
     pub fn new(raw: raw::PRINCE) -> Self {
         Prince { raw , _state: init_state::Unknown }
     }
+}
+
+impl<State> Prince<State> {
+    /// The raw PAC peripheral.
+    pub fn raw(&self) -> &raw::PRINCE {
+        &self.raw
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> &mut raw::PRINCE {
+        &mut self.raw
+    }
+}
+
+impl Prince {
 
     // PRINCE doesn't actually get enabled or disabled,
     // but am using this pattern to enforce that random numbers get written to the mask registers.
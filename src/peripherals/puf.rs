@@ -24,6 +24,8 @@ impl PufStates for Enrolled {}
 
 /// PUF error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
     /// PUF Command could not start
     CommandFailedToStart,
@@ -32,6 +34,18 @@ pub enum Error {
     /// PUF Command is not allowed
     NotAllowed,
 }
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Error::CommandFailedToStart => "PUF command could not start",
+            Error::CommandFailed => "PUF command failed",
+            Error::NotAllowed => "PUF command not allowed",
+        })
+    }
+}
+
+impl core::error::Error for Error {}
 pub type Result<T> = core::result::Result<T, Error>;
 
 pub enum KeyDestination {
@@ -1,7 +1,10 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
 use crate::{
+    drivers::rng::Error,
     raw,
     peripherals::{
-        syscon::Syscon,
+        syscon::{Revision, Syscon},
     },
     typestates::{
         init_state,
@@ -17,6 +20,7 @@ crate::wrap_stateful_peripheral!(Rng, RNG);
 // }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(dead_code)]
 // not sure why this kind of thing is not in `svd2rust`?
 pub struct ModuleId {
@@ -29,13 +33,46 @@ pub struct ModuleId {
 impl Rng {
 }
 
+// DIEID.REV_ID, read once by `try_enabled`
+static REV_ID: AtomicU8 = AtomicU8::new(0);
+
+/// Largest maximal chi-squared value the online test may report, UM 48.14
+const REF_CHI_SQUARED: u8 = 2;
+/// Largest value of the 3-bit SHIFT4X
+const MAX_SHIFT4X: u8 = 7;
+/// Largest value of the 5-bit REFRESH_CNT
+const MAX_REFRESH_CNT: u8 = 31;
+/// Polls of the online test per SHIFT4X setting, before giving up
+const CHI_SQUARED_POLLS: u32 = 1_000_000;
+
 impl<State> Rng<State> {
-    pub fn enabled(mut self, syscon: &mut Syscon) -> Rng<init_state::Enabled> {
+    /// Enable the RNG, tuning its entropy source for the detected chip revision
+    /// until it passes the online test.
+    ///
+    /// Panics if the entropy source does not converge, see `try_enabled`.
+    pub fn enabled(self, syscon: &mut Syscon) -> Rng<init_state::Enabled> {
+        self.try_enabled(syscon).expect("RNG entropy source did not converge")
+    }
+
+    /// Enable the RNG, tuning its entropy source until it passes the online test.
+    ///
+    /// On error, the entropy source can't be trusted, and the RNG is left
+    /// disabled.
+    pub fn try_enabled(mut self, syscon: &mut Syscon) -> Result<Rng<init_state::Enabled>, Error> {
+        syscon.reset(&mut self.raw);
         syscon.enable_clock(&mut self.raw);
+        REV_ID.store(syscon.rev_id(), Ordering::Relaxed);
 
-        Rng {
+        let mut rng = Rng {
             raw: self.raw,
             _state: init_state::Enabled(()),
+        };
+        match rng.initialize_entropy() {
+            Ok(()) => Ok(rng),
+            Err(error) => {
+                rng.disabled(syscon);
+                Err(error)
+            }
         }
     }
 
@@ -51,69 +88,107 @@ impl<State> Rng<State> {
 }
 
 impl Rng<init_state::Enabled> {
-    /// DO NOT CALL - doesn't work yet
-    #[allow(dead_code, unreachable_code)]
-    fn initialize_entropy(&self) {
-        unimplemented!();
-
-        // NB: there are functional and operational differences between
-        // the A0 and A1 versions of the chip, see UM 48.14 (page 1033)
-        //
-        // A0/A1 refer to syscon.dieid.rev
-        //
-        // Here, we assume A1 (as maj.min = 3.2 seems to indicate this)
-        // TODO: check this is true for the lpcxpresso55s69
-        // TODO: check again when going into production
-
-        // poll ONLINE_TEST_VAL
-        let val = &self.raw.online_test_val.read();
-        #[allow(non_snake_case)]
-        let REF_CHI_SQUARED = 2;
-
-        // dbg!("shift4x is", self.raw.counter_cfg.read().shift4x().bits());
-        // let _: u8 =  self.raw.counter_cfg.read().shift4x().bits();
+    /// The silicon revision, which decides how the entropy source is operated
+    /// (UM 48.14): A0 without the online test, all later revisions with it.
+    pub fn chip_revision(&self) -> Revision {
+        Revision::from_rev_id(REV_ID.load(Ordering::Relaxed))
+    }
 
-        loop {
-            // activate CHI computing
-            // dbg!(self.raw.online_test_cfg.read().activate().bit());  // <-- false
-            self.raw
-                .online_test_cfg
-                .modify(|_, w| unsafe { w.data_sel().bits(4) });
-            self.raw
-                .online_test_cfg
-                .modify(|_, w| w.activate().set_bit());
-            // dbg!(self.raw.online_test_cfg.read().activate().bit());  // <-- true
-
-            // dbg!(val.min_chi_squared().bits());  // <-- 15
-            // dbg!(val.max_chi_squared().bits());  // <--  0
-
-            // TODO: this gets stuck
-            // unimplemented!("figure out how to make this not block");
-            while val.min_chi_squared().bits() > val.max_chi_squared().bits() {}
-
-            // dbg!("passed");
-
-            if val.max_chi_squared().bits() > REF_CHI_SQUARED {
-                // reset
-                self.raw
-                    .online_test_cfg
-                    .modify(|_, w| w.activate().clear_bit());
-                // increment SHIFT4X, which has bit width 3
-                // self.raw.counter_cfg.modify(|_, w| (w.shift4x().bits() as u8) + 1);
-                continue;
-            } else {
-                break;
+    /// Run the startup health check of the entropy source, UM 48.14.
+    ///
+    /// On A1 silicon, the online chi-squared test is activated, and the
+    /// accumulation (SHIFT4X) increased until the test's maximal chi-squared
+    /// value is acceptable. The test stays active afterwards.
+    ///
+    /// On A0 silicon, the online test is not usable, so the maximal
+    /// accumulation is set instead.
+    ///
+    /// Called by `enabled`; calling it again restarts the tuning.
+    pub fn initialize_entropy(&mut self) -> Result<(), Error> {
+        self.raw.online_test_cfg.modify(|_, w| w.activate().clear_bit());
+        if self.chip_revision() == Revision::A0 {
+            self.raw.counter_cfg.modify(|_, w| unsafe { w.shift4x().bits(MAX_SHIFT4X) });
+            return Ok(());
+        }
+
+        for shift4x in 0..=MAX_SHIFT4X {
+            // reset the test, and accumulate more
+            self.raw.online_test_cfg.modify(|_, w| w.activate().clear_bit());
+            self.raw.counter_cfg.modify(|_, w| unsafe { w.shift4x().bits(shift4x) });
+            self.raw.online_test_cfg.modify(|_, w| w.activate().set_bit());
+
+            if self.max_chi_squared()? <= REF_CHI_SQUARED {
+                return Ok(());
+            }
+        }
+        self.raw.online_test_cfg.modify(|_, w| w.activate().clear_bit());
+        Err(Error::HealthTest)
+    }
+
+    /// Have the entropy counters refreshed enough for the next word?
+    fn word_ready(&self) -> bool {
+        let refreshes = self.raw.counter_val.read().refresh_cnt().bits();
+        match self.chip_revision() {
+            // without the online test, wait for all counters to be refreshed
+            Revision::A0 => refreshes == MAX_REFRESH_CNT,
+            _ => refreshes != 0,
+        }
+    }
+
+    /// Maximal chi-squared value of the online test, once it is computed
+    fn max_chi_squared(&self) -> Result<u8, Error> {
+        for _ in 0..CHI_SQUARED_POLLS {
+            let val = self.raw.online_test_val.read();
+            // the minimum is above the maximum until the first value is computed
+            if val.min_chi_squared().bits() <= val.max_chi_squared().bits() {
+                return Ok(val.max_chi_squared().bits());
             }
         }
+        Err(Error::Timeout)
     }
 
     pub fn get_random_u32(&self) -> u32 {
-        for _ in 0..32 {
-            while self.raw.counter_val.read().refresh_cnt().bits() == 0 {
-                // dbg!("was not zero");
+        crate::instrumented!(RNG_WORD, {
+            while !self.word_ready() {}
+            self.raw.random_number.read().bits()
+        })
+    }
+
+    /// A random word, if the entropy counters refreshed since the last one.
+    ///
+    /// Fails with `HealthTest` if the online test (activated by `enabled` on A1)
+    /// currently rejects the entropy source.
+    pub fn try_get_random_u32(&self) -> nb::Result<u32, Error> {
+        if !self.word_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+        let val = self.raw.online_test_val.read();
+        if self.raw.online_test_cfg.read().activate().bit_is_set()
+            && val.min_chi_squared().bits() <= val.max_chi_squared().bits()
+            && val.max_chi_squared().bits() > REF_CHI_SQUARED
+        {
+            return Err(nb::Error::Other(Error::HealthTest));
+        }
+        Ok(self.raw.random_number.read().bits())
+    }
+
+    /// A random word, waiting at most `cycles` CPU cycles for the entropy counters.
+    ///
+    /// The wait is measured with the DWT cycle counter, which must be enabled,
+    /// otherwise this never times out.
+    pub fn get_random_u32_timeout(&self, cycles: u32) -> Result<u32, Error> {
+        let start = crate::get_cycle_count();
+        loop {
+            match self.try_get_random_u32() {
+                Ok(word) => return Ok(word),
+                Err(nb::Error::Other(error)) => return Err(error),
+                Err(nb::Error::WouldBlock) => {
+                    if crate::get_cycle_count().wrapping_sub(start) >= cycles {
+                        return Err(Error::Timeout);
+                    }
+                }
             }
         }
-        self.raw.random_number.read().bits()
     }
 
     /// random method to get some information about the RNG
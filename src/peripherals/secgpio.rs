@@ -0,0 +1,49 @@
+//! API for the secure GPIO (SECGPIO)
+//!
+//! A second GPIO block for the pins of port 0, only accessible from the secure
+//! world. Secure firmware moves pins to it with `Pin::into_secure`, which also
+//! blocks the non-secure GPIO from reading them (AHB_SECURE_CTRL SEC_GPIO_MASK0).
+//!
+//! The secure pin interrupts (SECPINT) are in `pint`.
+
+use crate::{
+    raw,
+    peripherals::syscon,
+    typestates::init_state,
+};
+
+crate::wrap_stateful_peripheral!(SecGpio, SECGPIO);
+
+impl<State> SecGpio<State> {
+    pub fn enabled(mut self, syscon: &mut syscon::Syscon) -> SecGpio<init_state::Enabled> {
+        syscon.enable_clock(&mut self.raw);
+
+        SecGpio {
+            raw: self.raw,
+            _state: init_state::Enabled(()),
+        }
+    }
+
+    pub fn disabled(mut self, syscon: &mut syscon::Syscon) -> SecGpio<init_state::Disabled> {
+        syscon.disable_clock(&mut self.raw);
+
+        SecGpio {
+            raw: self.raw,
+            _state: init_state::Disabled,
+        }
+    }
+}
+
+/// Block (or unblock) the non-secure GPIO from reading pin `PIO0_number`.
+pub(crate) fn set_blocked(number: u8, blocked: bool) {
+    let ahb_secure_ctrl = unsafe { &*raw::AHB_SECURE_CTRL::ptr() };
+    cortex_m::interrupt::free(|_| {
+        ahb_secure_ctrl.sec_gpio_mask0.modify(|r, w| unsafe {
+            w.bits(if blocked {
+                r.bits() & !(1 << number)
+            } else {
+                r.bits() | (1 << number)
+            })
+        })
+    });
+}
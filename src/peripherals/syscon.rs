@@ -27,8 +27,18 @@
 //     typestates::init_state,
 // };
 
+use crate::time::Megahertz;
+
 crate::wrap_always_on_peripheral!(Syscon, SYSCON);
 
+pub use raw::syscon::{AHBCLKCTRL0, AHBCLKCTRL1, AHBCLKCTRL2};
+
+// the clock control registers are shared between subsystems, and can be split
+// into the sets of bits of each via `RegMutProxy::split`
+crate::reg!(AHBCLKCTRL0, AHBCLKCTRL0, raw::SYSCON, ahbclkctrl0);
+crate::reg!(AHBCLKCTRL1, AHBCLKCTRL1, raw::SYSCON, ahbclkctrl1);
+crate::reg!(AHBCLKCTRL2, AHBCLKCTRL2, raw::SYSCON, ahbclkctrl2);
+
 impl Syscon {
     // TODO: relocate
     pub fn rev_id(&self) -> u8 {
@@ -39,6 +49,74 @@ impl Syscon {
     }
 }
 
+/// Silicon revision of the die (DIEID.REV_ID)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Revision {
+    /// "0A", e.g. on LPCXpresso55S69 boards revision A1
+    A0,
+    /// "1B", e.g. on LPCXpresso55S69 boards revision A2
+    B1,
+    Unknown(u8),
+}
+
+impl Revision {
+    pub(crate) fn from_rev_id(rev_id: u8) -> Self {
+        match rev_id {
+            0 => Revision::A0,
+            1 => Revision::B1,
+            other => Revision::Unknown(other),
+        }
+    }
+}
+
+/// The silicon revision of the die, see `Revision`
+pub fn revision() -> Revision {
+    Revision::from_rev_id(unsafe { &*raw::SYSCON::ptr() }.dieid.read().rev_id().bits())
+}
+
+/// Identification of the chip, as returned by `device_id`, read from its registers.
+///
+/// The fields of the part identification which tell the parts of a die apart are
+/// not documented. The part number, memory sizes and security features of the
+/// part the HAL is built for are in `crate::chip`, selected by Cargo feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceId {
+    /// The die (DIEID.MCO_NUM_IN_DIE_ID), 0x426B for the LPC55S6x family
+    pub die: u32,
+    pub revision: Revision,
+    /// Revision of the boot ROM (DEVICE_ID0.ROM_REV_MINOR)
+    pub rom_revision: u8,
+    /// The part identification (DEVICE_ID0), as read
+    pub part_id: u32,
+}
+
+impl DeviceId {
+    /// Is the die that of the LPC55S6x family, which the register definitions are for?
+    pub fn is_lpc55s6x(&self) -> bool {
+        self.die == 0x426B
+    }
+}
+
+/// Identify the chip, see `DeviceId`.
+pub fn device_id() -> DeviceId {
+    let syscon = unsafe { &*raw::SYSCON::ptr() };
+    let dieid = syscon.dieid.read();
+    let device_id0 = syscon.device_id0.read();
+    DeviceId {
+        die: dieid.mco_num_in_die_id().bits(),
+        revision: Revision::from_rev_id(dieid.rev_id().bits()),
+        rom_revision: device_id0.rom_rev_minor().bits(),
+        part_id: device_id0.bits(),
+    }
+}
+
+/// Disable the clock of a peripheral whose driver is dropped, without access to the `Syscon`.
+pub(crate) fn disable_clock_on_drop<P: ClockControl>(peripheral: &P) {
+    cortex_m::interrupt::free(|_| peripheral.disable_clock(&mut unsafe { Syscon::steal() }));
+}
+
 /// The main API for the SYSCON peripheral
 impl Syscon {
     /// Enables the clock for a peripheral or other hardware component
@@ -141,6 +219,126 @@ impl Syscon {
 
 }
 
+/// Use of the flash memory controller (FMC) line buffers
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FlashBuffers {
+    None,
+    One,
+    All,
+}
+
+/// Flash memory controller (FMC) acceleration settings (FMCCR)
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FlashAcceleration {
+    /// buffers used for instruction fetches
+    pub instruction_buffers: FlashBuffers,
+    /// buffers used for data accesses
+    pub data_buffers: FlashBuffers,
+    pub acceleration: bool,
+    pub prefetch: bool,
+}
+
+impl FlashAcceleration {
+    /// Flash is read directly, without buffering or prefetching
+    pub const DISABLED: FlashAcceleration = FlashAcceleration {
+        instruction_buffers: FlashBuffers::None,
+        data_buffers: FlashBuffers::None,
+        acceleration: false,
+        prefetch: false,
+    };
+
+    /// The reset configuration: all buffers, acceleration, no prefetch
+    pub const DEFAULT: FlashAcceleration = FlashAcceleration {
+        instruction_buffers: FlashBuffers::All,
+        data_buffers: FlashBuffers::All,
+        acceleration: true,
+        prefetch: false,
+    };
+
+    /// All buffers, acceleration and instruction prefetch
+    pub const FULL: FlashAcceleration = FlashAcceleration {
+        prefetch: true,
+        ..FlashAcceleration::DEFAULT
+    };
+
+    /// Recommended settings for a given system frequency.
+    ///
+    /// Up to 11 MHz, flash is read in one cycle, and acceleration only costs power.
+    /// From there on, the more wait states, the more buffering and prefetching pay off.
+    pub fn for_frequency(freq: Megahertz) -> FlashAcceleration {
+        match freq.to_MHz() {
+            0 ..= 11 => FlashAcceleration::DISABLED,
+            12 ..= 48 => FlashAcceleration::DEFAULT,
+            _ => FlashAcceleration::FULL,
+        }
+    }
+}
+
+impl Default for FlashAcceleration {
+    fn default() -> Self {
+        FlashAcceleration::DEFAULT
+    }
+}
+
+/// Flash memory controller (FMC) acceleration
+impl Syscon {
+    pub fn flash_acceleration(&self) -> FlashAcceleration {
+        let fmccr = self.raw.fmccr.read();
+        let buffers = |bits| match bits {
+            0 => FlashBuffers::None,
+            1 => FlashBuffers::One,
+            _ => FlashBuffers::All,
+        };
+        FlashAcceleration {
+            instruction_buffers: buffers(fmccr.fetchcfg().bits()),
+            data_buffers: buffers(fmccr.datacfg().bits()),
+            acceleration: fmccr.accel().is_enable(),
+            prefetch: fmccr.prefen().is_enable(),
+        }
+    }
+
+    /// Configures the FMC, flushing its buffers. The flash wait states are left as they are.
+    pub fn set_flash_acceleration(&mut self, config: FlashAcceleration) {
+        self.raw.fmccr.modify(|_, w| {
+            match config.instruction_buffers {
+                FlashBuffers::None => w.fetchcfg().nobuf(),
+                FlashBuffers::One => w.fetchcfg().onebuf(),
+                FlashBuffers::All => w.fetchcfg().allbuf(),
+            };
+            match config.data_buffers {
+                FlashBuffers::None => w.datacfg().nobuf(),
+                FlashBuffers::One => w.datacfg().onebuf(),
+                FlashBuffers::All => w.datacfg().allbuf(),
+            };
+            w
+            .accel().bit(config.acceleration)
+            .prefen().bit(config.prefetch)
+        });
+        self.flush_flash_buffers();
+    }
+
+    /// Invalidates the FMC buffers, which is needed after flash contents changed
+    /// that might have been buffered.
+    pub fn flush_flash_buffers(&mut self) {
+        self.raw.fmcflush.write(|w| w.flush().flush());
+    }
+
+    /// Runs `f` (typically, flash programming) with flash acceleration disabled,
+    /// restoring the previous settings with flushed buffers afterwards.
+    ///
+    /// Buffers and prefetches could otherwise serve stale data, or access flash
+    /// while it is being programmed.
+    pub fn without_flash_acceleration<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = self.flash_acceleration();
+        self.set_flash_acceleration(FlashAcceleration::DISABLED);
+        let result = f(self);
+        self.set_flash_acceleration(previous);
+        result
+    }
+}
+
 /// TODO: do this systematically
 /// By default, fro_12m is enabled in MAINCLKSELA
 impl Syscon {
@@ -315,6 +513,7 @@ impl_clock_control!(raw::CTIMER1, timer1, ahbclkctrl1);
 impl_clock_control!(raw::CTIMER2, timer2, ahbclkctrl1);
 impl_clock_control!(raw::CTIMER3, timer3, ahbclkctrl2);
 impl_clock_control!(raw::CTIMER4, timer4, ahbclkctrl2);
+impl_clock_control!(raw::CRC_ENGINE, crcgen, ahbclkctrl0);
 impl_clock_control!(raw::DMA0, dma0, ahbclkctrl0);
 impl_clock_control!(raw::FLASH, flash, ahbclkctrl0);
 impl_clock_control!(raw::FLEXCOMM0, fc0, ahbclkctrl1);
@@ -331,18 +530,20 @@ impl_clock_control!(raw::INPUTMUX, mux, ahbclkctrl0);
 impl_clock_control!(raw::IOCON, iocon, ahbclkctrl0);
 impl_clock_control!((&mut raw::GINT0, &mut raw::GINT1), gint, ahbclkctrl0);
 impl_clock_control!(raw::PINT, pint, ahbclkctrl0);
+impl_clock_control!(raw::PLU, plulut, ahbclkctrl2);
 
 impl_clock_control!(raw::USB0, usb0_dev, ahbclkctrl1);
 impl_clock_control!(raw::USBPHY, usb1_phy, ahbclkctrl2);
 impl_clock_control!(raw::USB1, usb1_dev, usb1_ram, ahbclkctrl2);
 impl_clock_control!(raw::USBFSH, usb0_hosts, ahbclkctrl2);  // well what about usb0_hostm?
 impl_clock_control!(raw::USBHSH, usb1_host, ahbclkctrl2);
+impl_clock_control!(raw::OSTIMER, ostimer, ahbclkctrl1);
 impl_clock_control!(raw::UTICK0, utick, ahbclkctrl1);
 
 impl_clock_control!(raw::ANACTRL, analog_ctrl, ahbclkctrl2);
 impl_clock_control!(raw::CASPER, casper, ahbclkctrl2);
-// there is no GPIO_SEC. what to do? create a PhantomData one?
-// impl_clock_control!(raw::GPIO_SEC, gpio_sec, ahbclkctrl2);
+impl_clock_control!(raw::SECGPIO, gpio_sec, ahbclkctrl2);
+impl_clock_control!(raw::SECPINT, gpio_sec_int, ahbclkctrl2);
 impl_clock_control!(raw::PUF, puf, ahbclkctrl2);
 impl_clock_control!(raw::RNG, rng, ahbclkctrl2);
 impl_clock_control!(raw::RTC, rtc, ahbclkctrl0);
@@ -409,6 +610,7 @@ macro_rules! impl_reset_control {
 // to be completed
 impl_reset_control!(raw::ADC0, adc_rst, presetctrl0);
 impl_reset_control!(raw::CASPER, casper_rst, presetctrl2);
+impl_reset_control!(raw::CRC_ENGINE, crcgen_rst, presetctrl0);
 impl_reset_control!(raw::CTIMER0, timer0_rst, presetctrl1);
 impl_reset_control!(raw::CTIMER1, timer1_rst, presetctrl1);
 impl_reset_control!(raw::CTIMER2, timer2_rst, presetctrl1);
@@ -425,6 +627,8 @@ impl_reset_control!(raw::FLEXCOMM6, fc6_rst, presetctrl1);
 impl_reset_control!(raw::FLEXCOMM7, fc7_rst, presetctrl1);
 impl_reset_control!(raw::FLEXCOMM8, hs_lspi_rst, presetctrl2);
 impl_reset_control!(raw::HASHCRYPT, hash_aes_rst, presetctrl2);
+impl_reset_control!(raw::PLU, plulut_rst, presetctrl2);
+impl_reset_control!(raw::RNG, rng_rst, presetctrl2);
 impl_reset_control!(raw::USB0, usb0_dev_rst, presetctrl1);
 impl_reset_control!(raw::USBHSH, usb1_host_rst, presetctrl2);
 impl_reset_control!(raw::USBPHY, usb1_phy_rst, presetctrl2);
@@ -61,6 +61,16 @@ impl<State: init_state::InitState, Mode: usbfs_mode::UsbfsMode> Usbfs<State, Mod
         (self.raw_fsd, self.raw_fsh)
     }
 
+    /// The raw PAC peripherals (device, host).
+    pub fn raw(&self) -> (&raw::USB0, &raw::USBFSH) {
+        (&self.raw_fsd, &self.raw_fsh)
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> (&mut raw::USB0, &mut raw::USBFSH) {
+        (&mut self.raw_fsd, &mut self.raw_fsh)
+    }
+
     pub fn enabled_as_device(
         mut self,
         anactrl: &mut anactrl::Anactrl,
@@ -114,6 +124,7 @@ impl<State: init_state::InitState, Mode: usbfs_mode::UsbfsMode> Usbfs<State, Mod
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(dead_code)]
 pub struct UsbFsDevInfo {
     maj_rev: u8,
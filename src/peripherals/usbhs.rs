@@ -1,5 +1,5 @@
 use core::ops::Deref;
-use embedded_time::duration::Extensions;
+use crate::time::DurationExtensions;
 
 use crate::raw;
 use crate::traits::wg::timer::CountDown;
@@ -66,6 +66,16 @@ impl<State: init_state::InitState, Mode: usbhs_mode::UsbhsMode> Usbhs<State, Mod
         (self.raw_hsd, self.raw_hsh)
     }
 
+    /// The raw PAC peripherals (PHY, device, host).
+    pub fn raw(&self) -> (&raw::USBPHY, &raw::USB1, &raw::USBHSH) {
+        (&self.raw_phy, &self.raw_hsd, &self.raw_hsh)
+    }
+
+    /// Like `raw`, mutably.
+    pub fn raw_mut(&mut self) -> (&mut raw::USBPHY, &mut raw::USB1, &mut raw::USBHSH) {
+        (&mut self.raw_phy, &mut self.raw_hsd, &mut self.raw_hsh)
+    }
+
 
     pub fn enabled_as_device(
         mut self,
@@ -100,7 +110,7 @@ impl<State: init_state::InitState, Mode: usbhs_mode::UsbhsMode> Usbhs<State, Mod
         pmc.power_on(&mut self.raw_phy);
 
         // Give long delay for PHY to be ready
-        timer.start(5000_u32.microseconds());
+        timer.start(5000_u32.micros());
         nb::block!(timer.wait()).ok();
 
         syscon.enable_clock(&mut self.raw_phy);
@@ -123,7 +133,7 @@ impl<State: init_state::InitState, Mode: usbhs_mode::UsbhsMode> Usbhs<State, Mod
         });
 
         // Must wait at least 15 us for pll-reg to stabilize
-        timer.start(15.microseconds());
+        timer.start(15.micros());
         nb::block!(timer.wait()).ok();
 
         self.raw_phy.pll_sic.modify(|_,w| {
@@ -161,6 +171,7 @@ impl<State: init_state::InitState, Mode: usbhs_mode::UsbhsMode> Usbhs<State, Mod
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(dead_code)]
 pub struct UsbHsDevInfo {
     maj_rev: u8,
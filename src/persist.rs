@@ -0,0 +1,276 @@
+//! Persisting panics and HardFaults across resets (feature `panic-persist`).
+//!
+//! The panic message or the fault registers are stored in SRAM which is not
+//! initialized at startup, protected by a CRC, after which the chip resets.
+//! A summary (kind and program counter) is also stored in the general purpose
+//! registers 4-7 of the RTC, which survive deep power-down (but not losing VBAT).
+//!
+//! With the feature enabled, this module provides the `#[panic_handler]`.
+//! HardFaults need to be forwarded from the application's handler:
+//!
+//! ```ignore
+//! #[exception]
+//! unsafe fn HardFault(frame: &ExceptionFrame) -> ! {
+//!     hal::persist::store_hard_fault(frame);
+//!     SCB::sys_reset()
+//! }
+//!
+//! // after reset
+//! if let Some(record) = hal::persist::take() {
+//!     rprintln!("{:?}: {} {:x?}", record.kind(), record.message(), record.registers);
+//! }
+//! ```
+//!
+//! With feature `fault-dump`, the HAL provides the HardFault handler instead,
+//! which also keeps a more detailed dump, see `fault`.
+
+use core::{
+    fmt,
+    mem::MaybeUninit,
+    ptr,
+};
+
+use cortex_m_rt::ExceptionFrame;
+
+use crate::raw;
+
+#[cfg(feature = "fault-dump")]
+pub mod fault;
+
+/// Capacity for panic messages, longer ones are truncated
+pub const MESSAGE_SIZE: usize = 256;
+
+const MAGIC: u32 = 0x5045_5253;
+/// Index of the first RTC general purpose register used for the summary
+const GPREG_FIRST: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Kind {
+    Panic = 1,
+    HardFault = 2,
+}
+
+impl Kind {
+    fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            1 => Some(Kind::Panic),
+            2 => Some(Kind::HardFault),
+            _ => None,
+        }
+    }
+}
+
+/// Registers at the time of a HardFault (all zero for panics)
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct Registers {
+    pub r0: u32,
+    pub r1: u32,
+    pub r2: u32,
+    pub r3: u32,
+    pub r12: u32,
+    pub lr: u32,
+    pub pc: u32,
+    pub xpsr: u32,
+    /// Configurable Fault Status Register
+    pub cfsr: u32,
+    /// HardFault Status Register
+    pub hfsr: u32,
+    /// MemManage Fault Address Register
+    pub mmfar: u32,
+    /// BusFault Address Register
+    pub bfar: u32,
+}
+
+/// A panic or HardFault of a previous run
+#[derive(Clone)]
+#[repr(C)]
+pub struct Record {
+    magic: u32,
+    kind: u32,
+    len: u32,
+    message: [u8; MESSAGE_SIZE],
+    pub registers: Registers,
+    crc: u32,
+}
+
+impl Record {
+    const EMPTY: Self = Record {
+        magic: MAGIC,
+        kind: 0,
+        len: 0,
+        message: [0; MESSAGE_SIZE],
+        registers: Registers {
+            r0: 0, r1: 0, r2: 0, r3: 0, r12: 0, lr: 0, pc: 0, xpsr: 0,
+            cfsr: 0, hfsr: 0, mmfar: 0, bfar: 0,
+        },
+        crc: 0,
+    };
+
+    pub fn kind(&self) -> Kind {
+        // verified by `take`
+        Kind::from_u32(self.kind).unwrap()
+    }
+
+    /// The panic message, possibly truncated (empty for HardFaults)
+    pub fn message(&self) -> &str {
+        let message = &self.message[..self.len as usize];
+        match core::str::from_utf8(message) {
+            Ok(message) => message,
+            // truncated within a character
+            Err(error) => core::str::from_utf8(&message[..error.valid_up_to()]).unwrap(),
+        }
+    }
+
+    fn checksum(&self) -> u32 {
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>() - core::mem::size_of::<u32>(),
+            )
+        };
+        crc32(bytes)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.magic == MAGIC
+            && Kind::from_u32(self.kind).is_some()
+            && self.len as usize <= MESSAGE_SIZE
+            && self.crc == self.checksum()
+    }
+}
+
+impl fmt::Write for Record {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let len = self.len as usize;
+        let n = s.len().min(MESSAGE_SIZE - len);
+        self.message[len..][..n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n as u32;
+        Ok(())
+    }
+}
+
+/// What remains in the RTC after SRAM was lost
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Summary {
+    pub kind: Kind,
+    /// Program counter of a HardFault, zero for panics
+    pub pc: u32,
+    /// CFSR of a HardFault, zero for panics
+    pub cfsr: u32,
+}
+
+#[link_section = ".uninit.lpc55-hal.persist"]
+static mut RECORD: MaybeUninit<Record> = MaybeUninit::uninit();
+
+fn crc32(bytes: &[u8]) -> u32 {
+    // bitwise, as the CRC engine may be in any state when panicking
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg()))
+    })
+}
+
+fn rtc() -> &'static raw::rtc::RegisterBlock {
+    // the RTC registers need the clock, regardless of the state of the application's `Rtc`
+    let syscon = unsafe { &*raw::SYSCON::ptr() };
+    syscon.ahbclkctrl0.modify(|_, w| w.rtc().enable());
+    unsafe { &*raw::RTC::ptr() }
+}
+
+fn store(record: &mut Record) {
+    record.crc = record.checksum();
+    unsafe { ptr::write_volatile(ptr::addr_of_mut!(RECORD) as *mut Record, record.clone()) };
+
+    let summary = [MAGIC ^ record.kind, record.registers.pc, record.registers.cfsr];
+    let rtc = rtc();
+    for (gpreg, value) in rtc.gpreg[GPREG_FIRST..].iter().zip(summary.iter()) {
+        gpreg.write(|w| unsafe { w.bits(*value) });
+    }
+    rtc.gpreg[GPREG_FIRST + 3].write(|w| unsafe { w.bits(crc32(&summary_bytes(&summary))) });
+}
+
+fn summary_bytes(summary: &[u32; 3]) -> [u8; 12] {
+    let mut bytes = [0; 12];
+    for (chunk, word) in bytes.chunks_mut(4).zip(summary.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+/// Store a panic, the message formatted from `info`.
+pub fn store_panic(info: &core::panic::PanicInfo) {
+    let mut record = Record::EMPTY;
+    record.kind = Kind::Panic as u32;
+    fmt::write(&mut record, format_args!("{}", info)).ok();
+    store(&mut record);
+}
+
+/// Store a HardFault, with the stacked registers and the fault status registers.
+pub fn store_hard_fault(frame: &ExceptionFrame) {
+    let scb = unsafe { &*cortex_m::peripheral::SCB::PTR };
+    let mut record = Record::EMPTY;
+    record.kind = Kind::HardFault as u32;
+    record.registers = Registers {
+        r0: frame.r0,
+        r1: frame.r1,
+        r2: frame.r2,
+        r3: frame.r3,
+        r12: frame.r12,
+        lr: frame.lr,
+        pc: frame.pc,
+        xpsr: frame.xpsr,
+        cfsr: scb.cfsr.read(),
+        hfsr: scb.hfsr.read(),
+        mmfar: scb.mmfar.read(),
+        bfar: scb.bfar.read(),
+    };
+    store(&mut record);
+}
+
+/// The record of the previous run, if any, removing it.
+///
+/// Also removes the summary from the RTC if the record is valid; otherwise it is
+/// kept for `take_summary`.
+pub fn take() -> Option<Record> {
+    let stored = ptr::addr_of_mut!(RECORD) as *mut Record;
+    let record = unsafe { ptr::read_volatile(stored) };
+    unsafe { ptr::write_volatile(ptr::addr_of_mut!((*stored).magic), 0) };
+    if record.is_valid() {
+        take_summary();
+        Some(record)
+    } else {
+        None
+    }
+}
+
+/// The summary of the previous failure kept in the RTC, if any, removing it.
+///
+/// Useful if SRAM was not retained, e.g. after deep power-down.
+pub fn take_summary() -> Option<Summary> {
+    let rtc = rtc();
+    let mut summary = [0u32; 3];
+    for (value, gpreg) in summary.iter_mut().zip(rtc.gpreg[GPREG_FIRST..].iter()) {
+        *value = gpreg.read().bits();
+    }
+    let crc = rtc.gpreg[GPREG_FIRST + 3].read().bits();
+    rtc.gpreg[GPREG_FIRST].write(|w| unsafe { w.bits(0) });
+
+    if crc != crc32(&summary_bytes(&summary)) {
+        return None;
+    }
+    Kind::from_u32(summary[0] ^ MAGIC).map(|kind| Summary {
+        kind,
+        pc: summary[1],
+        cfsr: summary[2],
+    })
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    cortex_m::interrupt::disable();
+    store_panic(info);
+    cortex_m::peripheral::SCB::sys_reset()
+}
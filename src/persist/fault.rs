@@ -0,0 +1,182 @@
+//! Crash dumps of faults (feature `fault-dump`).
+//!
+//! With the feature enabled, this module provides the HardFault handler. It
+//! stores a `Dump` (the stacked registers, the fault status registers of both
+//! security states and the top of the stack) in SRAM which is not initialized
+//! at startup, records the HardFault as `persist::store_hard_fault` does, and
+//! resets the chip:
+//!
+//! ```ignore
+//! // after reset
+//! if let Some(dump) = hal::persist::fault::take() {
+//!     rprintln!("{:?} at {:x}, stack {:x?}", dump.fault(), dump.registers.pc, dump.stack());
+//! }
+//! ```
+//!
+//! MemManage, BusFault and UsageFault are reported if they escalate to a
+//! HardFault, which they do unless enabled in SHCSR. Their cause is kept
+//! in CFSR, and that of SecureFaults in SFSR.
+
+use core::{
+    mem::MaybeUninit,
+    ptr,
+};
+
+use cortex_m_rt::{exception, ExceptionFrame};
+
+use super::{crc32, Registers};
+
+/// Number of stack words stored, starting after the exception frame
+pub const STACK_WORDS: usize = 32;
+
+const MAGIC: u32 = 0x4641_4c54;
+
+/// Secure Fault Status Register (SAU)
+const SFSR: *const u32 = 0xE000_EDE4 as *const u32;
+/// Secure Fault Address Register (SAU)
+const SFAR: *const u32 = 0xE000_EDE8 as *const u32;
+
+/// The fault which caused the HardFault, as told by the fault status registers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Fault {
+    /// MPU or execute-never violation, address in `mmfar` if valid
+    MemManage,
+    /// Bus error, address in `bfar` if valid
+    Bus,
+    /// Undefined instruction, invalid state, unaligned access or division by zero
+    Usage,
+    /// Security violation, e.g. a non-secure access to secure memory or a
+    /// branch to non-secure code without `BLXNS`; address in `sfar` if valid
+    Secure,
+    /// None of the above, e.g. a vector table read error
+    Other,
+}
+
+/// A fault of a previous run
+#[derive(Clone)]
+#[repr(C)]
+pub struct Dump {
+    magic: u32,
+    pub registers: Registers,
+    /// Secure Fault Status Register
+    pub sfsr: u32,
+    /// Secure Fault Address Register
+    pub sfar: u32,
+    /// Stack pointer after the exception frame was stacked
+    pub sp: u32,
+    /// Number of valid words in `stack`
+    stack_len: u32,
+    stack: [u32; STACK_WORDS],
+    crc: u32,
+}
+
+impl Dump {
+    pub fn fault(&self) -> Fault {
+        let cfsr = self.registers.cfsr;
+        if cfsr & 0xff != 0 {
+            Fault::MemManage
+        } else if cfsr & 0xff00 != 0 {
+            Fault::Bus
+        } else if cfsr & 0xffff_0000 != 0 {
+            Fault::Usage
+        } else if self.sfsr != 0 {
+            Fault::Secure
+        } else {
+            Fault::Other
+        }
+    }
+
+    /// The stack following the exception frame, fewer words if it ended
+    pub fn stack(&self) -> &[u32] {
+        &self.stack[..self.stack_len as usize]
+    }
+
+    fn checksum(&self) -> u32 {
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>() - core::mem::size_of::<u32>(),
+            )
+        };
+        crc32(bytes)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.magic == MAGIC
+            && self.stack_len as usize <= STACK_WORDS
+            && self.crc == self.checksum()
+    }
+}
+
+#[link_section = ".uninit.lpc55-hal.fault-dump"]
+static mut DUMP: MaybeUninit<Dump> = MaybeUninit::uninit();
+
+/// The words from `from` to the end of the contiguous SRAM (or its secure
+/// alias), so that a corrupted stack pointer can't cause another fault
+fn readable_words(from: usize) -> usize {
+    // the secure alias is 0x1000_0000 above
+    let len = crate::chip::SRAM_END - 0x2000_0000;
+    let ram = [0x2000_0000, 0x3000_0000].iter()
+        .find(|&&start| from >= start && from < start + len);
+    match ram {
+        Some(start) => (start + len - from) / 4,
+        None => 0,
+    }
+}
+
+/// Store a dump of the fault with exception frame `frame`.
+pub fn store(frame: &ExceptionFrame) {
+    let scb = unsafe { &*cortex_m::peripheral::SCB::PTR };
+    // the frame has eight words, more with floating point context (FPCA),
+    // which the stack snippet then starts with
+    let sp = frame as *const ExceptionFrame as usize + core::mem::size_of::<ExceptionFrame>();
+    let stack_len = readable_words(sp).min(STACK_WORDS);
+    let mut dump = Dump {
+        magic: MAGIC,
+        registers: Registers {
+            r0: frame.r0,
+            r1: frame.r1,
+            r2: frame.r2,
+            r3: frame.r3,
+            r12: frame.r12,
+            lr: frame.lr,
+            pc: frame.pc,
+            xpsr: frame.xpsr,
+            cfsr: scb.cfsr.read(),
+            hfsr: scb.hfsr.read(),
+            mmfar: scb.mmfar.read(),
+            bfar: scb.bfar.read(),
+        },
+        sfsr: unsafe { ptr::read_volatile(SFSR) },
+        sfar: unsafe { ptr::read_volatile(SFAR) },
+        sp: sp as u32,
+        stack_len: stack_len as u32,
+        stack: [0; STACK_WORDS],
+        crc: 0,
+    };
+    for (i, word) in dump.stack[..stack_len].iter_mut().enumerate() {
+        *word = unsafe { ptr::read_volatile((sp as *const u32).add(i)) };
+    }
+    dump.crc = dump.checksum();
+    unsafe { ptr::write_volatile(ptr::addr_of_mut!(DUMP) as *mut Dump, dump) };
+}
+
+/// The dump of the previous run, if any, removing it.
+pub fn take() -> Option<Dump> {
+    let stored = ptr::addr_of_mut!(DUMP) as *mut Dump;
+    let dump = unsafe { ptr::read_volatile(stored) };
+    unsafe { ptr::write_volatile(ptr::addr_of_mut!((*stored).magic), 0) };
+    if dump.is_valid() {
+        Some(dump)
+    } else {
+        None
+    }
+}
+
+#[exception]
+fn HardFault(frame: &ExceptionFrame) -> ! {
+    store(frame);
+    super::store_hard_fault(frame);
+    cortex_m::peripheral::SCB::sys_reset()
+}
@@ -0,0 +1,146 @@
+//! Entering low power modes, and detecting an attached debugger.
+//!
+//! In deep-sleep, the debug logic loses its clock and the debugger its
+//! connection to the core, which typically ends the debug session (and makes
+//! reflashing a chip that goes back to deep-sleep right after reset painful).
+//! The deep-sleep entry can therefore be told to settle for sleep while a
+//! debugger is attached:
+//!
+//! ```ignore
+//! loop {
+//!     // deep-sleep in the field, sleep on the bench
+//!     hal::power::deep_sleep(&mut scb, DebugPolicy::SleepWhenDebugging);
+//!     handle_wakeup();
+//! }
+//! ```
+//!
+//! Only interrupts enabled as wake-up sources in the SYSCON start logic
+//! (`enable_wakeup`) end deep-sleep; in sleep, any unmasked interrupt does.
+//!
+//! The pins that wake the chip from deep power-down are set up in `wakeup`.
+
+use cortex_m::peripheral::{DCB, SCB};
+
+use crate::raw::{self, Interrupt};
+
+pub mod wakeup;
+
+/// Offset of STARTERSET0/1 in SYSCON (not in the PAC)
+const STARTERSET_OFFSET: usize = 0x6A0;
+/// Offset of STARTERCLR0/1 in SYSCON (not in the PAC)
+const STARTERCLR_OFFSET: usize = 0x6C0;
+/// Offset of STARTER0/1 in SYSCON (not in the PAC)
+const STARTER_OFFSET: usize = 0x680;
+
+fn starter_register(offset: usize, interrupt: Interrupt) -> (*mut u32, u32) {
+    let number = interrupt as usize;
+    let register = (raw::SYSCON::ptr() as *mut u8).wrapping_add(offset + 4 * (number / 32)) as *mut u32;
+    (register, 1 << (number % 32))
+}
+
+/// Let `interrupt` wake the chip from deep-sleep (SYSCON STARTER).
+///
+/// The interrupt still needs to be unmasked in the NVIC to be taken.
+pub fn enable_wakeup(interrupt: Interrupt) {
+    let (register, bit) = starter_register(STARTERSET_OFFSET, interrupt);
+    unsafe { core::ptr::write_volatile(register, bit) };
+}
+
+/// Stop `interrupt` from waking the chip from deep-sleep.
+pub fn disable_wakeup(interrupt: Interrupt) {
+    let (register, bit) = starter_register(STARTERCLR_OFFSET, interrupt);
+    unsafe { core::ptr::write_volatile(register, bit) };
+}
+
+/// Does `interrupt` wake the chip from deep-sleep?
+pub fn is_wakeup_enabled(interrupt: Interrupt) -> bool {
+    let (register, bit) = starter_register(STARTER_OFFSET, interrupt);
+    unsafe { core::ptr::read_volatile(register) & bit != 0 }
+}
+
+/// Debug access to CPU0, as granted by debug authentication (SYSCON DEBUG_FEATURES)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DebugState {
+    /// A debugger enabled halting debug (DHCSR.C_DEBUGEN)
+    pub attached: bool,
+    /// Invasive debug (halting, breakpoints) is allowed
+    pub invasive: bool,
+    /// Non-invasive debug (trace) is allowed
+    pub non_invasive: bool,
+    /// Invasive debug of the secure world is allowed
+    pub secure_invasive: bool,
+    /// Non-invasive debug of the secure world is allowed
+    pub secure_non_invasive: bool,
+}
+
+/// The current debug state of CPU0.
+pub fn debug_state() -> DebugState {
+    let features = unsafe { &*raw::SYSCON::ptr() }.debug_features.read();
+    DebugState {
+        attached: DCB::is_debugger_attached(),
+        invasive: features.cpu0_dbgen().is_enable(),
+        non_invasive: features.cpu0_niden().is_enable(),
+        secure_invasive: features.cpu0_spiden().is_enable(),
+        secure_non_invasive: features.cpu0_spniden().is_enable(),
+    }
+}
+
+/// Is a debugger attached to CPU0?
+///
+/// True if halting debug is enabled (DHCSR.C_DEBUGEN), which debug
+/// authentication only permits if invasive debug is enabled in SYSCON.
+pub fn debugger_attached() -> bool {
+    let state = debug_state();
+    state.attached && state.invasive
+}
+
+/// Low power mode entered by `wait_for_interrupt`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SleepMode {
+    /// Only the CPU clock stops
+    Sleep,
+    /// Clocks stop and analog blocks power down, as configured in the PMC
+    DeepSleep,
+}
+
+/// What `deep_sleep` does while a debugger is attached
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DebugPolicy {
+    /// Enter deep-sleep regardless, losing the debug connection
+    Ignore,
+    /// Enter sleep instead, keeping the debug connection
+    SleepWhenDebugging,
+}
+
+/// Enter `mode` until the next interrupt (or event).
+pub fn wait_for_interrupt(scb: &mut SCB, mode: SleepMode) {
+    match mode {
+        SleepMode::Sleep => scb.clear_sleepdeep(),
+        SleepMode::DeepSleep => scb.set_sleepdeep(),
+    }
+    cortex_m::asm::dsb();
+    cortex_m::asm::wfi();
+    // later `wfi`s (e.g. of an executor) should not enter deep-sleep unexpectedly
+    scb.clear_sleepdeep();
+}
+
+/// Enter sleep until the next interrupt.
+pub fn sleep(scb: &mut SCB) {
+    wait_for_interrupt(scb, SleepMode::Sleep);
+}
+
+/// Enter deep-sleep until the next wake-up interrupt, or sleep if a debugger
+/// is attached and `policy` says so.
+///
+/// Returns the mode that was entered.
+pub fn deep_sleep(scb: &mut SCB, policy: DebugPolicy) -> SleepMode {
+    let mode = match policy {
+        DebugPolicy::SleepWhenDebugging if debugger_attached() => SleepMode::Sleep,
+        _ => SleepMode::DeepSleep,
+    };
+    wait_for_interrupt(scb, mode);
+    mode
+}
@@ -0,0 +1,113 @@
+//! Wake-up pins for deep power-down.
+//!
+//! Four pins can wake the chip from deep power-down: PIO1_1, PIO0_28, PIO1_18
+//! and PIO1_30 (wake-up I/O 0 to 3). Only these implement `WakeupPin`, so other
+//! pins are rejected at compile time:
+//!
+//! ```ignore
+//! let button = pins.pio1_18.into_gpio_pin(&mut iocon, &mut gpio).into_input();
+//! wakeup::enable(&button, Edge::Falling);
+//!
+//! // after the reset that ends deep power-down
+//! if wakeup::woken_by(&button) {
+//!     wakeup::clear_causes();
+//! }
+//! ```
+//!
+//! The pins need to be digital inputs (or unused, which is the same for IOCON).
+
+use crate::{
+    drivers::pins::{Pin, Pio},
+    raw,
+    typestates::pin::{state, PinId},
+};
+
+/// Offset of WAKEUPIOCTRL in PMC (not in the PAC)
+const WAKEUPIOCTRL_OFFSET: usize = 0x64;
+/// Edge bits of all four wake-up I/Os
+const EDGES: u32 = 0xff;
+/// Wake-up I/O enable
+const WAKEUPIO_ENABLE: u32 = 1 << 8;
+/// Wake-up I/O latches out of reset (clears WAKEIOCAUSE while low)
+const WAKEUPIO_RSTN: u32 = 1 << 9;
+
+/// Implemented by the pins that can wake the chip from deep power-down
+pub trait WakeupPin: PinId {
+    /// This is `N` for wake-up I/O `N`
+    const INDEX: u8;
+}
+
+impl WakeupPin for Pio<1, 1> { const INDEX: u8 = 0; }
+impl WakeupPin for Pio<0, 28> { const INDEX: u8 = 1; }
+impl WakeupPin for Pio<1, 18> { const INDEX: u8 = 2; }
+impl WakeupPin for Pio<1, 30> { const INDEX: u8 = 3; }
+
+/// Pin transition that wakes the chip
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+}
+
+impl Edge {
+    fn bits(self, index: u8) -> u32 {
+        let bits = match self {
+            Edge::Rising => 0b01,
+            Edge::Falling => 0b10,
+            Edge::Both => 0b11,
+        };
+        bits << (2 * index)
+    }
+}
+
+fn wakeupioctrl() -> *mut u32 {
+    (raw::PMC::ptr() as *mut u8).wrapping_add(WAKEUPIOCTRL_OFFSET) as *mut u32
+}
+
+fn modify(f: impl FnOnce(u32) -> u32) {
+    cortex_m::interrupt::free(|_| unsafe {
+        let register = wakeupioctrl();
+        core::ptr::write_volatile(register, f(core::ptr::read_volatile(register)));
+    });
+}
+
+/// Let `pin` wake the chip from deep power-down on `edge`.
+pub fn enable<T: WakeupPin, S: state::PinState>(_pin: &Pin<T, S>, edge: Edge) {
+    modify(|ctrl| {
+        (ctrl & !Edge::Both.bits(T::INDEX)) | edge.bits(T::INDEX) | WAKEUPIO_ENABLE | WAKEUPIO_RSTN
+    });
+}
+
+/// Stop `pin` from waking the chip from deep power-down.
+///
+/// Wake-up I/O is turned off altogether once no pin is left.
+pub fn disable<T: WakeupPin, S: state::PinState>(_pin: &Pin<T, S>) {
+    modify(|ctrl| {
+        let ctrl = ctrl & !Edge::Both.bits(T::INDEX);
+        if ctrl & EDGES == 0 {
+            ctrl & !WAKEUPIO_ENABLE
+        } else {
+            ctrl
+        }
+    });
+}
+
+/// The wake-up I/Os that caused the last wake-up from deep power-down, as bits
+/// `WakeupPin::INDEX` (PMC WAKEIOCAUSE).
+pub fn causes() -> u8 {
+    let pmc = unsafe { &*raw::PMC::ptr() };
+    (pmc.wakeiocause.read().bits() & 0xf) as u8
+}
+
+/// Did `pin` cause the last wake-up from deep power-down?
+pub fn woken_by<T: WakeupPin, S: state::PinState>(_pin: &Pin<T, S>) -> bool {
+    causes() & (1 << T::INDEX) != 0
+}
+
+/// Clear the wake-up causes, so the next wake-up can be told apart.
+pub fn clear_causes() {
+    modify(|ctrl| ctrl & !WAKEUPIO_RSTN);
+    modify(|ctrl| ctrl | WAKEUPIO_RSTN);
+}
@@ -0,0 +1,345 @@
+//! Sharing drivers between thread context and interrupt handlers.
+//!
+//! A `Shared` is meant to be a `static`, holding a driver once it is moved in.
+//! Access happens within a critical section, so an interrupt handler never
+//! observes the driver half-way through a thread-context operation.
+//!
+//! ```ignore
+//! static TIMER: Shared<Timer<Ctimer1<Enabled>>> = Shared::new();
+//!
+//! // thread context
+//! TIMER.put(Timer::new(ctimer));
+//! TIMER.lock(|timer| timer.start(Microseconds::from_ticks(500)));
+//!
+//! #[interrupt]
+//! fn CTIMER1() {
+//!     TIMER.lock(|timer| timer.cancel().ok());
+//! }
+//! ```
+//!
+//! For drivers which `split()` into parts, typically only one part is needed in
+//! the interrupt handler, while the application keeps the other one:
+//!
+//! ```ignore
+//! static RX: Shared<Rx<Usart0<Enabled>>> = Shared::new();
+//!
+//! let tx = RX.split(serial.split());
+//! ```
+//!
+//! An I2C or SPI bus can be shared by the drivers of several devices via
+//! `I2cBus` and `SpiBus`, which hand out one proxy per device driver:
+//!
+//! ```ignore
+//! static I2C: I2cBus<I2cMaster<...>> = I2cBus::new();
+//!
+//! I2C.put(I2cMaster::new(i2c, (scl, sda), 400.kHz()));
+//! let mut display = Ssd1306::new(I2C.acquire());
+//! let mut sensor = Sht3x::new(I2C.acquire());
+//! ```
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self, CriticalSection, Mutex};
+
+use crate::traits::wg::blocking::{i2c, spi};
+
+/// A driver, shared between thread context and interrupt handlers
+pub struct Shared<T> {
+    inner: Mutex<RefCell<Option<T>>>,
+}
+
+impl<T> Shared<T> {
+    pub const fn new() -> Self {
+        Self { inner: Mutex::new(RefCell::new(None)) }
+    }
+
+    /// Move the driver in, returning the previous one (if any).
+    pub fn put(&self, driver: T) -> Option<T> {
+        interrupt::free(|cs| self.inner.borrow(cs).replace(Some(driver)))
+    }
+
+    /// Move the driver out again.
+    pub fn take(&self) -> Option<T> {
+        interrupt::free(|cs| self.inner.borrow(cs).take())
+    }
+
+    pub fn is_some(&self) -> bool {
+        interrupt::free(|cs| self.inner.borrow(cs).borrow().is_some())
+    }
+
+    /// Run `f` on the driver in a critical section, if the driver was moved in.
+    pub fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        interrupt::free(|cs| self.with(cs, f))
+    }
+
+    /// Run `f` on the driver, if it was moved in, with interrupts enabled.
+    ///
+    /// Only moving the driver out and back in happens in critical sections.
+    /// Meanwhile, the driver is missing to `lock`, `take` and other `lease`s.
+    pub fn lease<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let mut driver = self.take()?;
+        let result = f(&mut driver);
+        self.put(driver);
+        Some(result)
+    }
+
+    /// Like `lock`, within an existing critical section.
+    ///
+    /// # Panics
+    ///
+    /// If called recursively from `f`.
+    pub fn with<R>(&self, cs: &CriticalSection, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.inner.borrow(cs).borrow_mut().as_mut().map(f)
+    }
+
+    /// Move the second part of a `split()` driver in, returning the first one.
+    ///
+    /// The previous driver, if any, is dropped.
+    pub fn split<A>(&self, parts: (A, T)) -> A {
+        let (other, part) = parts;
+        self.put(part);
+        other
+    }
+
+    /// Move the first part of a `split()` driver in, returning the second one.
+    ///
+    /// The previous driver, if any, is dropped.
+    pub fn split_first<B>(&self, parts: (T, B)) -> B {
+        let (part, other) = parts;
+        self.put(part);
+        other
+    }
+}
+
+impl<T> Default for Shared<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error of a transaction via an `I2cBus` or `SpiBus`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusError<E> {
+    /// The bus driver is in use by a preempted transaction, or was not moved in
+    Busy,
+    /// Error of the bus driver
+    Bus(E),
+}
+
+/// An I2C bus, shared by the drivers of several devices.
+///
+/// Each transaction leases the bus driver (see `Shared::lease`), so interrupts
+/// are served during transactions. A transaction which preempts another one,
+/// from an interrupt handler of higher priority, fails with `BusError::Busy`
+/// and can be retried once the preempted one completed.
+pub struct I2cBus<I2C> {
+    bus: Shared<I2C>,
+}
+
+impl<I2C> I2cBus<I2C> {
+    pub const fn new() -> Self {
+        Self { bus: Shared::new() }
+    }
+
+    /// Move the bus driver in, returning the previous one (if any).
+    pub fn put(&self, i2c: I2C) -> Option<I2C> {
+        self.bus.put(i2c)
+    }
+
+    /// Move the bus driver out again.
+    pub fn take(&self) -> Option<I2C> {
+        self.bus.take()
+    }
+
+    /// A handle to the bus, for one device driver.
+    ///
+    /// Transactions via the proxy fail with `BusError::Busy` if the bus driver was
+    /// not moved in, or is busy.
+    pub fn acquire(&self) -> I2cProxy<'_, I2C> {
+        I2cProxy { bus: &self.bus }
+    }
+}
+
+impl<I2C> Default for I2cBus<I2C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle to an `I2cBus`, implementing the I2C traits
+pub struct I2cProxy<'a, I2C> {
+    bus: &'a Shared<I2C>,
+}
+
+impl<I2C> I2cProxy<'_, I2C> {
+    fn lock<E>(&self, f: impl FnOnce(&mut I2C) -> Result<(), E>) -> Result<(), BusError<E>> {
+        self.bus.lease(f).ok_or(BusError::Busy)?.map_err(BusError::Bus)
+    }
+}
+
+impl<I2C: i2c::Write> i2c::Write for I2cProxy<'_, I2C> {
+    type Error = BusError<I2C::Error>;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write(address, bytes))
+    }
+}
+
+impl<I2C: i2c::Read> i2c::Read for I2cProxy<'_, I2C> {
+    type Error = BusError<I2C::Error>;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.read(address, buffer))
+    }
+}
+
+impl<I2C: i2c::WriteRead> i2c::WriteRead for I2cProxy<'_, I2C> {
+    type Error = BusError<I2C::Error>;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write_read(address, bytes, buffer))
+    }
+}
+
+/// An SPI bus, shared by the drivers of several devices.
+///
+/// Like `I2cBus`, each transfer (or, for a `SpiDevice`, each transaction
+/// including chip select) leases the bus driver, and fails with `BusError::Busy`
+/// if it preempted another one.
+pub struct SpiBus<SPI> {
+    bus: Shared<SPI>,
+}
+
+impl<SPI> SpiBus<SPI> {
+    pub const fn new() -> Self {
+        Self { bus: Shared::new() }
+    }
+
+    /// Move the bus driver in, returning the previous one (if any).
+    pub fn put(&self, spi: SPI) -> Option<SPI> {
+        self.bus.put(spi)
+    }
+
+    /// Move the bus driver out again.
+    pub fn take(&self) -> Option<SPI> {
+        self.bus.take()
+    }
+
+    /// A handle to the bus, for one device driver which handles chip select itself.
+    ///
+    /// Transfers via the proxy fail with `BusError::Busy` if the bus driver was not
+    /// moved in, or is busy.
+    pub fn acquire(&self) -> SpiProxy<'_, SPI> {
+        SpiProxy { bus: &self.bus }
+    }
+
+    /// A handle to the bus for one device, asserting `cs` (active low) during its transactions.
+    ///
+    /// Implements the embedded-hal 1.0 `SpiDevice`, like the devices of `embedded-hal-bus`.
+    #[cfg(feature = "eh1")]
+    pub fn device<CS>(&self, cs: CS) -> SpiDevice<'_, SPI, CS> {
+        SpiDevice { bus: &self.bus, cs }
+    }
+}
+
+impl<SPI> Default for SpiBus<SPI> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle to an `SpiBus`, implementing the blocking SPI traits
+pub struct SpiProxy<'a, SPI> {
+    bus: &'a Shared<SPI>,
+}
+
+impl<SPI> SpiProxy<'_, SPI> {
+    fn lock<R, E>(&self, f: impl FnOnce(&mut SPI) -> Result<R, E>) -> Result<R, BusError<E>> {
+        self.bus.lease(f).ok_or(BusError::Busy)?.map_err(BusError::Bus)
+    }
+}
+
+impl<SPI: spi::Transfer<u8>> spi::Transfer<u8> for SpiProxy<'_, SPI> {
+    type Error = BusError<SPI::Error>;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        self.lock(move |bus| bus.transfer(words))
+    }
+}
+
+impl<SPI: spi::Write<u8>> spi::Write<u8> for SpiProxy<'_, SPI> {
+    type Error = BusError<SPI::Error>;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write(words))
+    }
+}
+
+/// Handle to an `SpiBus` for one device, with its chip select
+#[cfg(feature = "eh1")]
+pub struct SpiDevice<'a, SPI, CS> {
+    bus: &'a Shared<SPI>,
+    cs: CS,
+}
+
+#[cfg(feature = "eh1")]
+mod eh1_impls {
+    use core::convert::Infallible;
+
+    use crate::traits::eh1::{
+        digital::OutputPin,
+        i2c::{self, I2c, Operation as I2cOperation},
+        spi::{self, Operation, SpiBus},
+    };
+    use super::{BusError, I2cProxy, SpiDevice};
+
+    impl<E: i2c::Error> i2c::Error for BusError<E> {
+        fn kind(&self) -> i2c::ErrorKind {
+            match self {
+                BusError::Busy => i2c::ErrorKind::Other,
+                BusError::Bus(error) => error.kind(),
+            }
+        }
+    }
+
+    impl<E: spi::Error> spi::Error for BusError<E> {
+        fn kind(&self) -> spi::ErrorKind {
+            match self {
+                BusError::Busy => spi::ErrorKind::Other,
+                BusError::Bus(error) => error.kind(),
+            }
+        }
+    }
+
+    impl<I2C: i2c::ErrorType> i2c::ErrorType for I2cProxy<'_, I2C> {
+        type Error = BusError<I2C::Error>;
+    }
+
+    impl<I2C: I2c> I2c for I2cProxy<'_, I2C> {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [I2cOperation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.lock(|bus| bus.transaction(address, operations))
+        }
+    }
+
+    impl<SPI: spi::ErrorType, CS> spi::ErrorType for SpiDevice<'_, SPI, CS> {
+        type Error = BusError<SPI::Error>;
+    }
+
+    impl<SPI, CS> spi::SpiDevice<u8> for SpiDevice<'_, SPI, CS>
+    where
+        SPI: SpiBus<u8>,
+        CS: OutputPin<Error = Infallible>,
+    {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+            let cs = &mut self.cs;
+            self.bus.lease(|bus| crate::drivers::spi::transaction(bus, cs, operations))
+                .ok_or(BusError::Busy)?
+                .map_err(BusError::Bus)
+        }
+    }
+}
@@ -1,12 +1,22 @@
-//! This HAL now uses `embedded-time`.
+//! This HAL uses `fugit` for time and frequency types.
+//!
+//! All of them are `u32`-based, with the unit in the type, so mixing up
+//! e.g. kHz and Hz or ms and us is a compile error:
+//!
+//! ```ignore
+//! let speed: Hertz = 400.kHz();
+//! timer.start(Microseconds::millis(5));
+//! ```
 
-pub use embedded_time::{
-    duration::{Seconds, Milliseconds, Microseconds, Nanoseconds},
-    rate::{
-        Baud, Kilobaud, Megabaud,
-        Hertz, Kilohertz, Megahertz,
-    },
+pub use fugit::{
+    SecsDurationU32 as Seconds,
+    MillisDurationU32 as Milliseconds,
+    MicrosDurationU32 as Microseconds,
+    NanosDurationU32 as Nanoseconds,
+    HertzU32 as Hertz,
+    KilohertzU32 as Kilohertz,
+    MegahertzU32 as Megahertz,
 };
 
-pub use embedded_time::duration::Extensions as DurationExtensions;
-pub use embedded_time::rate::Extensions as RateExtensions;
+pub use fugit::ExtU32 as DurationExtensions;
+pub use fugit::RateExtU32 as RateExtensions;
@@ -1,6 +1,8 @@
 use core::ops::Deref;
 
+#[cfg(feature = "crypto")]
 pub use cipher;
+#[cfg(feature = "crypto")]
 pub use digest;
 
 // TODO: is this renaming confusing?
@@ -11,6 +13,21 @@ pub use digest;
 // Would be worth being more explicit.
 pub use embedded_hal as wg;
 
+/// The 1.0 release of `embedded-hal`, implemented next to
+/// the 0.2 traits if feature `eh1` is enabled.
+#[cfg(feature = "eh1")]
+pub use embedded_hal_1 as eh1;
+
+/// The `embedded-io` traits, implemented by the serial halves if feature `io` is enabled
+#[cfg(feature = "io")]
+pub use embedded_io as io;
+
+/// The async `embedded-io` traits, implemented if features `io` and `async` are enabled
+#[cfg(all(feature = "io", feature = "async"))]
+pub use embedded_io_async as io_async;
+
+/// The `rand_core` traits, implemented by `Rng` if feature `rand_core` is enabled
+#[cfg(feature = "rand_core")]
 pub use rand_core;
 
 // TODO: Add more as needed,
@@ -57,10 +74,28 @@ pub mod reg_proxy {
         fn get() -> *const [Self::Target];
     }
 
+    /// Implemented for a set of bits of a register, which `RegFieldProxy` can proxy
+    ///
+    /// Use the `reg_field!` macro to implement this trait.
+    ///
+    /// # Safety
+    ///
+    /// The `Target` of the register must be a 32-bit register.
+    pub unsafe trait RegField {
+        /// The register containing the bits
+        type Reg: Reg;
+
+        /// The bits owned by the proxy
+        const MASK: u32;
+    }
+
 }
 
 // maybe put in submodule?
-pub trait Gint: Deref<Target = crate::raw::gint0::RegisterBlock> {}
+pub trait Gint: Deref<Target = crate::raw::gint0::RegisterBlock> {
+    /// The interrupt of this group
+    const INTERRUPT: crate::raw::Interrupt;
+}
 
 
 pub mod aligned;
@@ -5,6 +5,8 @@ use generic_array::{
 
 /// Flash operation error
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
     /// Flash controller is not done yet
     Busy,
@@ -26,6 +28,19 @@ pub enum Error {
 //     Busy
 // }
 
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Error::Busy => "flash controller busy",
+            Error::Illegal => "illegal flash command",
+            Error::EccError => "flash ECC error",
+            Error::Failure => "flash command failed",
+        })
+    }
+}
+
+impl core::error::Error for Error {}
+
 /// A type alias for the result of a Flash operation.
 pub type Result = core::result::Result<(), Error>;
 
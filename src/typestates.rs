@@ -32,6 +32,7 @@ pub mod pin;
 pub mod main_clock {
 
     #[derive(Copy, Clone, Debug, PartialEq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub enum MainClock {
         // Unknown,
         Fro12Mhz,
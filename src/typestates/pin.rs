@@ -20,6 +20,7 @@ pub trait PinId {
     const TYPE: PinType;
 }
 
+#[derive(Clone, Copy)]
 pub enum PinType {
     /// Digital only
     D, // igitalOnly,
@@ -55,6 +56,7 @@ pub mod state {
         pub(crate) pin: RegClusterProxy<raw::gpio::PIN>,
         pub(crate) set: RegClusterProxy<raw::gpio::SET>,
         pub(crate) clr: RegClusterProxy<raw::gpio::CLR>,
+        pub(crate) not: RegClusterProxy<raw::gpio::NOT>,
 
         pub(crate) _direction: D,
     }
@@ -79,7 +81,10 @@ pub mod state {
 pub mod gpio {
     pub mod direction {
         /// Implemented by types that indicate GPIO pin direction
-        pub trait Direction {}
+        pub trait Direction {
+            /// The pin must stay open drain
+            const OPEN_DRAIN: bool = false;
+        }
 
         pub struct Unknown;
         impl Direction for Unknown {}
@@ -96,6 +101,22 @@ pub mod gpio {
         pub struct AnalogOutput;
         impl Direction for AnalogOutput {}
 
+        /// Output which only drives low, and reads back the line
+        pub struct OpenDrain;
+        impl Direction for OpenDrain {
+            const OPEN_DRAIN: bool = true;
+        }
+
+        /// Implemented by directions that drive the pin
+        pub trait Drive: Direction {}
+        impl Drive for Output {}
+        impl Drive for OpenDrain {}
+
+        /// Implemented by directions that read the pin
+        pub trait Sense: Direction {}
+        impl Sense for Input {}
+        impl Sense for OpenDrain {}
+
         pub trait NotInput: Direction {}
         impl NotInput for Unknown {}
         impl NotInput for Output {}
@@ -105,6 +126,8 @@ pub mod gpio {
         impl NotOutput for Input {}
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub enum Level {
         Low,
         High,
@@ -114,3 +137,5 @@ pub mod gpio {
 pub mod function;
 
 pub mod flexcomm;
+
+pub mod signal;
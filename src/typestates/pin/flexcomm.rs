@@ -5,18 +5,25 @@
 use core::ops::Deref;
 
 use crate::{
+    drivers::pins::Pin,
     raw,
+    peripherals::{
+        flexcomm::FunctionClock,
+        syscon::ClockControl,
+    },
 };
 
 use super::{
+    function::Function,
+    state::Special,
     PinId,
     PinType,
 };
 
-pub trait I2c: Deref<Target = raw::i2c0::RegisterBlock> {}
+pub trait I2c: Deref<Target = raw::i2c0::RegisterBlock> + ClockControl + FunctionClock {}
 pub trait I2s {}
-pub trait Spi: Deref<Target = raw::spi0::RegisterBlock> {}
-pub trait Usart: Deref<Target = raw::usart0::RegisterBlock> {}
+pub trait Spi: Deref<Target = raw::spi0::RegisterBlock> + ClockControl + FunctionClock {}
+pub trait Usart: Deref<Target = raw::usart0::RegisterBlock> + ClockControl + FunctionClock {}
 
 
 /// I2C serial clock
@@ -33,6 +40,9 @@ pub trait I2sSdaPin<PIO, I2S> where PIO: PinId, I2S: I2s {}
 /// I2S master clock
 pub trait I2sMclkPin<PIO, I2S> where PIO: PinId, I2S: I2s {}
 
+/// Hardware chip select (SSEL) of an SPI
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ChipSelect {
     Chip0,
     Chip1,
@@ -134,7 +144,28 @@ for (SCK, MOSI, MISO, CS) where
     const CS: ChipSelect = CS::CS;
 }
 
-pub trait UsartPins<PIO1: PinId, PIO2: PinId, USART: Usart> {}
+pub trait I2sPins<PIO1: PinId, PIO2: PinId, PIO3: PinId, I2S: I2s> {}
+
+impl<PIO1, PIO2, PIO3, I2S, SCK, WS, SDA> I2sPins<PIO1, PIO2, PIO3, I2S> for (SCK, WS, SDA)
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    PIO3: PinId,
+    I2S: I2s,
+    SCK: I2sSckPin<PIO1, I2S>,
+    WS: I2sWsPin<PIO2, I2S>,
+    SDA: I2sSdaPin<PIO3, I2S>,
+{}
+
+
+/// The pins of a USART: `(TX, RX)`, `(TX, RX, RTS, CTS)` for hardware flow
+/// control, or `(TX, RX, RTS)` for RS-485 with RTS as driver enable
+pub trait UsartPins<PIO1: PinId, PIO2: PinId, USART: Usart> {
+    /// RTS and CTS are connected
+    const FLOW_CONTROL: bool = false;
+    /// RTS enables the driver of an RS-485 transceiver
+    const DRIVER_ENABLE: bool = false;
+}
 
 impl<PIO1, PIO2, USART, TX, RX> UsartPins<PIO1, PIO2, USART> for (TX, RX)
 where
@@ -145,6 +176,40 @@ where
     RX: UsartRxPin<PIO2, USART>,
 {}
 
+// the RTS and CTS pin types are spelled out, so their PIOs are constrained
+impl<PIO1, PIO2, PIO3, PIO4, USART, TX, RX, F3, F4> UsartPins<PIO1, PIO2, USART>
+for (TX, RX, Pin<PIO3, Special<F3>>, Pin<PIO4, Special<F4>>)
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    PIO3: PinId,
+    PIO4: PinId,
+    USART: Usart,
+    TX: UsartTxPin<PIO1, USART>,
+    RX: UsartRxPin<PIO2, USART>,
+    F3: Function,
+    F4: Function,
+    Pin<PIO3, Special<F3>>: UsartRtsPin<PIO3, USART>,
+    Pin<PIO4, Special<F4>>: UsartCtsPin<PIO4, USART>,
+{
+    const FLOW_CONTROL: bool = true;
+}
+
+impl<PIO1, PIO2, PIO3, USART, TX, RX, F3> UsartPins<PIO1, PIO2, USART>
+for (TX, RX, Pin<PIO3, Special<F3>>)
+where
+    PIO1: PinId,
+    PIO2: PinId,
+    PIO3: PinId,
+    USART: Usart,
+    TX: UsartTxPin<PIO1, USART>,
+    RX: UsartRxPin<PIO2, USART>,
+    F3: Function,
+    Pin<PIO3, Special<F3>>: UsartRtsPin<PIO3, USART>,
+{
+    const DRIVER_ENABLE: bool = true;
+}
+
 // Note: Pio0_12 can be both: into_i2c_3_scl_pin() and into_i2c_6_scl_pin() [alt1 vs alt7]
 //
 // pin.into_I2C3_SCL_pin()
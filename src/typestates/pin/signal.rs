@@ -0,0 +1,37 @@
+//! Markers for the Flexcomm signals a pin can be routed to.
+//!
+//! Named after the corresponding pin traits in `flexcomm`. A pin type
+//! implementing `Route<P, S>` can carry signal `S` of peripheral `P`, so
+//! routing a pin to a signal it can't carry is a compile error:
+//!
+//! ```ignore
+//! let tx = pins.pio0_27.into_usart_tx::<Usart2>(&mut iocon);
+//! // does not compile, PIO0_27 can't be USART2's RX
+//! let rx = pins.pio0_27.into_usart_rx::<Usart2>(&mut iocon);
+//! ```
+
+use super::{function::Function, PinId};
+
+/// Implemented by pins which can carry signal `S` of peripheral `P`
+pub trait Route<P, S>: PinId {
+    /// The IOCON function selecting the signal
+    const FUNC: u8;
+    type Function: Function;
+    /// Constructs the function typestate
+    fn function() -> Self::Function;
+}
+
+pub struct UsartTxPin;
+pub struct UsartRxPin;
+pub struct UsartRtsPin;
+pub struct UsartCtsPin;
+pub struct UsartSclkPin;
+pub struct SpiSckPin;
+pub struct SpiMosiPin;
+pub struct SpiMisoPin;
+pub struct SpiCsPin;
+pub struct I2cSclPin;
+pub struct I2cSdaPin;
+pub struct I2sSckPin;
+pub struct I2sSdaPin;
+pub struct I2sWsPin;
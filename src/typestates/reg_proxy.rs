@@ -1,11 +1,16 @@
 use core::{
     marker::PhantomData,
+    mem,
     ops::Deref,
+    ptr,
 };
 
+use cortex_m::interrupt;
+
 use crate::traits::reg_proxy::{
     Reg,
     RegCluster,
+    RegField,
 };
 
 pub struct RegProxy<T: Reg> {
@@ -48,6 +53,54 @@ impl<T: RegCluster> RegClusterProxy<T> {
             _marker: PhantomData,
         }
     }
+
+    /// The register at `index`, if within the cluster
+    pub fn get(&self, index: usize) -> Option<&T::Target> {
+        (**self).get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, T::Target> {
+        (**self).iter()
+    }
+
+    fn ptr(&self, index: usize) -> *mut u32 {
+        const { assert!(mem::size_of::<T::Target>() == 4, "not a cluster of 32-bit registers") };
+        &self[index] as *const T::Target as *mut u32
+    }
+
+    /// Read the register at `index`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn read_bits(&self, index: usize) -> u32 {
+        unsafe { ptr::read_volatile(self.ptr(index)) }
+    }
+
+    /// Write `bits` to the register at `index`, in one write without reading first.
+    ///
+    /// This is how the GPIO-style SET/CLR/NOT clusters are meant to be used:
+    /// only the pins whose bits are set are affected.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn write_bits(&self, index: usize, bits: u32) {
+        unsafe { ptr::write_volatile(self.ptr(index), bits) }
+    }
+}
+
+impl<'a, T: RegCluster> IntoIterator for &'a RegClusterProxy<T> {
+    type Item = &'a T::Target;
+    type IntoIter = core::slice::Iter<'a, T::Target>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 unsafe impl<T> Send for RegClusterProxy<T> where T: RegCluster {}
@@ -59,3 +112,120 @@ impl<T: RegCluster> Deref for RegClusterProxy<T> {
         unsafe { &*T::get() }
     }
 }
+
+/// Exclusive proxy for a register
+///
+/// Unlike `RegProxy`, only one instance may exist per register, and access
+/// requires `&mut self`, so two driver components can't race their `modify`
+/// calls. Registers shared between subsystems can be `split` into disjoint sets
+/// of bits, each owned by a `RegFieldProxy`:
+///
+/// ```ignore
+/// use hal::peripherals::syscon::AHBCLKCTRL2;
+///
+/// hal::reg_field!(UsbClocks, AHBCLKCTRL2, 0x0000_0070);
+/// hal::reg_field!(HashClocks, AHBCLKCTRL2, 0x0004_0000);
+///
+/// let ahbclkctrl2: RegMutProxy<AHBCLKCTRL2> = unsafe { RegMutProxy::new() };
+/// let (usb_clocks, hash_clocks) = ahbclkctrl2.split::<UsbClocks, HashClocks>();
+/// ```
+pub struct RegMutProxy<T: Reg> {
+    _marker: PhantomData<*const T>,
+}
+
+impl<T: Reg> RegMutProxy<T> {
+    /// Create the proxy object
+    ///
+    /// # Safety
+    ///
+    /// No other proxy of the register may exist, and the register must not be
+    /// accessed through its peripheral while the proxy is alive.
+    pub unsafe fn new() -> Self {
+        RegMutProxy {
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&mut self) -> &T::Target {
+        // see `RegProxy`
+        unsafe { &*T::get() }
+    }
+
+    /// Split the register into two disjoint sets of bits, owned separately.
+    pub fn split<A, B>(self) -> (RegFieldProxy<A>, RegFieldProxy<B>)
+    where
+        A: RegField<Reg = T>,
+        B: RegField<Reg = T>,
+    {
+        const { assert!(A::MASK & B::MASK == 0, "the sets of bits overlap") };
+        unsafe { (RegFieldProxy::new(), RegFieldProxy::new()) }
+    }
+}
+
+unsafe impl<T> Send for RegMutProxy<T> where T: Reg {}
+
+/// Exclusive proxy for a set of bits of a register
+///
+/// Other bits of the register may be owned elsewhere, therefore modifications
+/// are done in a critical section, leaving bits outside of `F::MASK` untouched.
+/// Not suitable for registers with write-one-to-clear bits.
+pub struct RegFieldProxy<F: RegField> {
+    _marker: PhantomData<*const F>,
+}
+
+impl<F: RegField> RegFieldProxy<F> {
+    /// Create the proxy object
+    ///
+    /// # Safety
+    ///
+    /// No other proxy of the bits may exist, and the bits must not be accessed
+    /// through their peripheral while the proxy is alive.
+    pub unsafe fn new() -> Self {
+        RegFieldProxy {
+            _marker: PhantomData,
+        }
+    }
+
+    fn ptr() -> *mut u32 {
+        const { assert!(mem::size_of::<<F::Reg as Reg>::Target>() == 4, "not a 32-bit register") };
+        F::Reg::get() as *mut u32
+    }
+
+    /// The owned bits of the register, other bits read as zero
+    pub fn read(&self) -> u32 {
+        unsafe { ptr::read_volatile(Self::ptr()) & F::MASK }
+    }
+
+    /// Read-modify-write the owned bits, changes to other bits are ignored.
+    pub fn modify(&mut self, f: impl FnOnce(u32) -> u32) {
+        let reg = Self::ptr();
+        interrupt::free(|_| unsafe {
+            let value = ptr::read_volatile(reg);
+            let bits = f(value & F::MASK) & F::MASK;
+            ptr::write_volatile(reg, (value & !F::MASK) | bits);
+        })
+    }
+
+    pub fn set_bits(&mut self, bits: u32) {
+        self.modify(|value| value | bits)
+    }
+
+    pub fn clear_bits(&mut self, bits: u32) {
+        self.modify(|value| value & !bits)
+    }
+
+    /// Split the bits further into two disjoint subsets, owned separately.
+    pub fn split<A, B>(self) -> (RegFieldProxy<A>, RegFieldProxy<B>)
+    where
+        A: RegField<Reg = F::Reg>,
+        B: RegField<Reg = F::Reg>,
+    {
+        const {
+            assert!(A::MASK & B::MASK == 0, "the sets of bits overlap");
+            assert!((A::MASK | B::MASK) & !F::MASK == 0, "not a subset of the bits");
+        };
+        unsafe { (RegFieldProxy::new(), RegFieldProxy::new()) }
+    }
+}
+
+unsafe impl<F> Send for RegFieldProxy<F> where F: RegField {}